@@ -7,12 +7,17 @@
 // - Cache performance
 // - Configuration parsing performance
 // - Data source manager operations
+// - Pool manager registry contention under concurrent tool calls
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
 use mysql_mcp_server::cache::TtlCache;
-use mysql_mcp_server::config::{DataSourceConfig, PoolConfig, ServerConfig};
+use mysql_mcp_server::config::{DataSourceConfig, ServerConfig};
 use mysql_mcp_server::manager::DataSourceManager;
+use mysql_mcp_server::pool::{get_or_create_pool_manager, ConnectionPoolManager};
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::RwLock;
 
 // Benchmark cache operations
 fn bench_cache_operations(c: &mut Criterion) {
@@ -118,13 +123,10 @@ fn bench_manager_operations(c: &mut Criterion) {
         .map(|i| DataSourceConfig {
             key: format!("db_{}", i),
             name: format!("Database {}", i),
-            host: "localhost".to_string(),
-            port: 3306,
             username: "user".to_string(),
             password: "pass".to_string(),
-            databases: vec![],
-            pool_config: PoolConfig::default(),
             permission: mysql_mcp_server::config::Permission::Query,
+            ..Default::default()
         })
         .collect();
     
@@ -178,6 +180,109 @@ fn bench_manager_operations(c: &mut Criterion) {
     group.finish();
 }
 
+// Benchmark concurrent tool calls against the shared pool manager registry
+//
+// Each call looks up the pool manager for one of 10 data sources and then
+// "runs a query" (a 1ms sleep, so no MySQL server is needed). The
+// `write_lock_held` variant keeps the registry write lock for the whole call,
+// as every tool used to; `registry_lookup` only holds the lock for the lookup,
+// so the simulated queries overlap instead of running one after another.
+fn bench_pool_registry_contention(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pool_registry");
+    group.sample_size(20);
+
+    let query_latency = Duration::from_millis(1);
+    let configs: Vec<DataSourceConfig> = (0..10)
+        .map(|i| DataSourceConfig {
+            key: format!("db_{}", i),
+            name: format!("Database {}", i),
+            username: "user".to_string(),
+            password: "pass".to_string(),
+            permission: mysql_mcp_server::config::Permission::Query,
+            ..Default::default()
+        })
+        .collect();
+
+    for concurrency in [10, 50].iter() {
+        group.bench_with_input(
+            BenchmarkId::new("write_lock_held", concurrency),
+            concurrency,
+            |b, &concurrency| {
+                let rt = tokio::runtime::Runtime::new().unwrap();
+                let manager = Arc::new(rt.block_on(DataSourceManager::new(configs.clone())).unwrap());
+                let pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>> =
+                    Arc::new(RwLock::new(HashMap::new()));
+
+                b.to_async(&rt).iter(|| {
+                    let manager = manager.clone();
+                    let pool_managers = pool_managers.clone();
+                    async move {
+                        let tasks: Vec<_> = (0..concurrency)
+                            .map(|i| {
+                                let manager = manager.clone();
+                                let pool_managers = pool_managers.clone();
+                                tokio::spawn(async move {
+                                    let key = format!("db_{}", i % 10);
+                                    let mut guard = pool_managers.write().await;
+                                    if !guard.contains_key(&key) {
+                                        let config = manager.get_source(&key).unwrap();
+                                        let pool_manager =
+                                            ConnectionPoolManager::new((*config).clone()).await.unwrap();
+                                        guard.insert(key.clone(), pool_manager);
+                                    }
+                                    black_box(guard.get(&key));
+                                    tokio::time::sleep(query_latency).await;
+                                })
+                            })
+                            .collect();
+                        for task in tasks {
+                            task.await.unwrap();
+                        }
+                    }
+                });
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("registry_lookup", concurrency),
+            concurrency,
+            |b, &concurrency| {
+                let rt = tokio::runtime::Runtime::new().unwrap();
+                let manager = Arc::new(rt.block_on(DataSourceManager::new(configs.clone())).unwrap());
+                let pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>> =
+                    Arc::new(RwLock::new(HashMap::new()));
+
+                b.to_async(&rt).iter(|| {
+                    let manager = manager.clone();
+                    let pool_managers = pool_managers.clone();
+                    async move {
+                        let tasks: Vec<_> = (0..concurrency)
+                            .map(|i| {
+                                let manager = manager.clone();
+                                let pool_managers = pool_managers.clone();
+                                tokio::spawn(async move {
+                                    let key = format!("db_{}", i % 10);
+                                    let pool_manager =
+                                        get_or_create_pool_manager(&pool_managers, &manager, &key)
+                                            .await
+                                            .unwrap();
+                                    black_box(pool_manager);
+                                    tokio::time::sleep(query_latency).await;
+                                })
+                            })
+                            .collect();
+                        for task in tasks {
+                            task.await.unwrap();
+                        }
+                    }
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
 // Benchmark different cache sizes
 fn bench_cache_sizes(c: &mut Criterion) {
     let mut group = c.benchmark_group("cache_sizes");
@@ -218,6 +323,7 @@ criterion_group!(
     bench_cache_operations,
     bench_config_parsing,
     bench_manager_operations,
-    bench_cache_sizes
+    bench_cache_sizes,
+    bench_pool_registry_contention
 );
 criterion_main!(benches);
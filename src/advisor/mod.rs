@@ -0,0 +1,832 @@
+//! Index suggestions from the shape of queries
+//!
+//! `mysql_suggest_indexes` reads which columns a query filters on, joins on
+//! and sorts by, and compares them with the indexes of each table:
+//!
+//! - columns compared for equality (`=`, `<=>`, `IN`, `IS NULL`) and join
+//!   columns lead a candidate index, in the order they appear
+//! - then the first range condition (`<`, `>`, `BETWEEN`, `LIKE 'prefix%'`),
+//!   or, for a single-table query without one, the `ORDER BY` / `GROUP BY`
+//!   columns so rows come out sorted
+//!
+//! A candidate is dropped if an existing index already starts with it. The
+//! analysis works on tokens, not a full parse: conditions wrapped in functions
+//! or expressions cannot use an index anyway and are skipped. Suggestions are
+//! advice only and never executed.
+
+use crate::explain::{PlanNode, RedFlag};
+use crate::sql::{self, Token, TokenKind};
+use crate::tools::{Index, TableSchema};
+use serde::Serialize;
+
+/// Most columns proposed for one index
+const MAX_INDEX_COLUMNS: usize = 5;
+
+/// Longest identifier MySQL accepts
+const MAX_IDENTIFIER_LENGTH: usize = 64;
+
+/// Words that end a table reference or cannot be an alias
+const NON_ALIAS_KEYWORDS: &[&str] = &[
+    "WHERE", "JOIN", "INNER", "LEFT", "RIGHT", "OUTER", "CROSS", "NATURAL", "STRAIGHT_JOIN",
+    "ON", "USING", "GROUP", "ORDER", "LIMIT", "HAVING", "UNION", "EXCEPT", "INTERSECT", "FOR",
+    "LOCK", "WINDOW", "SET", "USE", "FORCE", "IGNORE", "PARTITION", "AS", "VALUES", "SELECT",
+    "INTO", "FULL",
+];
+
+/// Words that look like column names but are values
+const VALUE_KEYWORDS: &[&str] = &[
+    "NULL", "TRUE", "FALSE", "DEFAULT", "CURRENT_DATE", "CURRENT_TIME", "CURRENT_TIMESTAMP",
+    "CURRENT_USER", "LOCALTIME", "LOCALTIMESTAMP", "UTC_DATE", "UTC_TIME", "UTC_TIMESTAMP",
+    "INTERVAL", "AND", "OR", "NOT", "XOR", "IS", "IN", "LIKE", "BETWEEN", "CASE", "WHEN",
+    "THEN", "ELSE", "END", "EXISTS", "ANY", "ALL", "SOME", "ASC", "DESC", "BINARY", "COLLATE",
+    "DISTINCT", "SELECT",
+];
+
+/// A table named in a query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableRef {
+    /// Database the name is qualified with, if any
+    pub database: Option<String>,
+    pub name: String,
+    pub alias: Option<String>,
+}
+
+/// How a query uses a column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColumnUsage {
+    /// Compared with `=`, `<=>`, `IN` or `IS NULL`
+    Equality,
+    /// Compared with another table's column
+    Join,
+    /// Compared with `<`, `>`, `BETWEEN`, `LIKE 'prefix%'` or `IS NOT NULL`
+    Range,
+    OrderBy,
+    GroupBy,
+}
+
+/// A column reference found in a query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnRef {
+    /// Table name or alias the column is qualified with, if any
+    pub qualifier: Option<String>,
+    pub column: String,
+    pub usage: ColumnUsage,
+}
+
+/// The tables of a query and the columns that could use an index
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryShape {
+    pub tables: Vec<TableRef>,
+    pub columns: Vec<ColumnRef>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Clause {
+    Other,
+    Tables,
+    Condition,
+    OrderBy,
+    GroupBy,
+}
+
+impl QueryShape {
+    /// Find the tables and indexable column uses of the first statement
+    pub fn parse(statement: &str) -> Self {
+        let tokens: Vec<Token> = sql::tokenize(statement)
+            .into_iter()
+            .take_while(|t| !t.is_punct(';'))
+            .collect();
+        let mut shape = QueryShape::default();
+        let mut clause = Clause::Other;
+        let mut i = 0;
+
+        while i < tokens.len() {
+            let token = &tokens[i];
+            let previous_is = |keyword: &str| i > 0 && tokens[i - 1].is_keyword(keyword);
+            let next_is = |keyword: &str| tokens.get(i + 1).is_some_and(|t| t.is_keyword(keyword));
+
+            if token.kind == TokenKind::Word {
+                let keyword = token.text.to_ascii_uppercase();
+                let table_list = match keyword.as_str() {
+                    "FROM" | "JOIN" | "STRAIGHT_JOIN" => true,
+                    // UPDATE t, not ON DUPLICATE KEY UPDATE or FOR UPDATE
+                    "UPDATE" => !previous_is("KEY") && !previous_is("FOR"),
+                    _ => false,
+                };
+                if table_list {
+                    i = shape.read_tables(&tokens, i + 1);
+                    clause = Clause::Tables;
+                    continue;
+                }
+
+                let next_clause = match keyword.as_str() {
+                    "WHERE" | "ON" => Some(Clause::Condition),
+                    "USING" if tokens.get(i + 1).is_some_and(|t| t.is_punct('(')) => {
+                        i = shape.read_using(&tokens, i + 2);
+                        continue;
+                    }
+                    "ORDER" if next_is("BY") => Some(Clause::OrderBy),
+                    "GROUP" if next_is("BY") => Some(Clause::GroupBy),
+                    "SELECT" | "HAVING" | "LIMIT" | "SET" | "UNION" | "WINDOW" | "FOR"
+                    | "VALUES" | "INTO" => Some(Clause::Other),
+                    _ => None,
+                };
+                if let Some(next_clause) = next_clause {
+                    // Step over the BY of ORDER BY and GROUP BY
+                    i += if matches!(next_clause, Clause::OrderBy | Clause::GroupBy) { 2 } else { 1 };
+                    clause = next_clause;
+                    continue;
+                }
+            }
+
+            let Some((qualifier, column, end)) = column_ref(&tokens, i) else {
+                i += 1;
+                continue;
+            };
+            let usage = match clause {
+                Clause::Condition => condition_usage(&tokens, i, end),
+                Clause::OrderBy => Some(ColumnUsage::OrderBy),
+                Clause::GroupBy => Some(ColumnUsage::GroupBy),
+                Clause::Tables | Clause::Other => None,
+            };
+            if let Some(usage) = usage {
+                shape.columns.push(ColumnRef {
+                    qualifier,
+                    column,
+                    usage,
+                });
+            }
+            i = end;
+        }
+
+        shape
+    }
+
+    /// Read a comma-separated list of table references starting at `i`
+    fn read_tables(&mut self, tokens: &[Token], mut i: usize) -> usize {
+        loop {
+            // Derived tables are subqueries, read like the rest of the statement
+            let Some(first) = tokens.get(i).and_then(|t| name(t)) else {
+                return i;
+            };
+            let mut table = TableRef {
+                database: None,
+                name: first,
+                alias: None,
+            };
+            i += 1;
+            if tokens.get(i).is_some_and(|t| t.is_punct('.')) {
+                if let Some(second) = tokens.get(i + 1).and_then(|t| name(t)) {
+                    table.database = Some(std::mem::replace(&mut table.name, second));
+                    i += 2;
+                }
+            }
+            if tokens.get(i).is_some_and(|t| t.is_keyword("AS")) {
+                i += 1;
+            }
+            if let Some(alias) = tokens.get(i).and_then(|t| name(t)) {
+                table.alias = Some(alias);
+                i += 1;
+            }
+            self.tables.push(table);
+
+            if !tokens.get(i).is_some_and(|t| t.is_punct(',')) {
+                return i;
+            }
+            i += 1;
+        }
+    }
+
+    /// Read the columns of `USING (a, b)`, joined on by name
+    fn read_using(&mut self, tokens: &[Token], mut i: usize) -> usize {
+        while let Some(token) = tokens.get(i) {
+            if token.is_punct(')') {
+                return i + 1;
+            }
+            if let Some(column) = token.identifier() {
+                self.columns.push(ColumnRef {
+                    qualifier: None,
+                    column,
+                    usage: ColumnUsage::Join,
+                });
+            }
+            i += 1;
+        }
+        i
+    }
+
+    /// The columns of `table` the query uses, resolving aliases and unqualified
+    /// names against the table's columns
+    fn columns_of(&self, table: &TableRef, schema: &TableSchema) -> Vec<(String, ColumnUsage)> {
+        let names_table = |qualifier: &str| {
+            qualifier.eq_ignore_ascii_case(&table.name)
+                || table
+                    .alias
+                    .as_deref()
+                    .is_some_and(|alias| alias.eq_ignore_ascii_case(qualifier))
+        };
+
+        self.columns
+            .iter()
+            .filter(|column| column.qualifier.as_deref().is_none_or(names_table))
+            .filter_map(|column| {
+                schema
+                    .columns
+                    .iter()
+                    .find(|c| c.name.eq_ignore_ascii_case(&column.column))
+                    .map(|c| (c.name.clone(), column.usage))
+            })
+            .collect()
+    }
+}
+
+/// An unquoted or quoted name
+fn name(token: &Token) -> Option<String> {
+    match token.kind {
+        TokenKind::Word
+            if NON_ALIAS_KEYWORDS.contains(&token.text.to_ascii_uppercase().as_str()) =>
+        {
+            None
+        }
+        _ => token.identifier(),
+    }
+}
+
+/// A column reference starting at `i`: `[[db.]table.]column`, not a function call
+fn column_ref(tokens: &[Token], i: usize) -> Option<(Option<String>, String, usize)> {
+    let is_name = |index: usize| {
+        tokens.get(index).is_some_and(|t| {
+            t.kind == TokenKind::QuotedIdent
+                || (t.kind == TokenKind::Word
+                    && !VALUE_KEYWORDS.contains(&t.text.to_ascii_uppercase().as_str()))
+        })
+    };
+    if !is_name(i) || (i > 0 && tokens[i - 1].is_punct('.')) {
+        return None;
+    }
+
+    let mut parts = vec![tokens[i].identifier()?];
+    let mut end = i + 1;
+    while tokens.get(end).is_some_and(|t| t.is_punct('.')) && is_name(end + 1) {
+        parts.push(tokens[end + 1].identifier()?);
+        end += 2;
+    }
+    if tokens.get(end).is_some_and(|t| t.is_punct('(')) {
+        return None;
+    }
+
+    let column = parts.pop()?;
+    Some((parts.pop(), column, end))
+}
+
+/// How a condition uses the column reference at `start..end`, if it can use an index
+fn condition_usage(tokens: &[Token], start: usize, end: usize) -> Option<ColumnUsage> {
+    let punct = |index: usize, ch: char| tokens.get(index).is_some_and(|t| t.is_punct(ch));
+    let keyword = |index: usize, word: &str| tokens.get(index).is_some_and(|t| t.is_keyword(word));
+
+    // Operator after the column: a = ..., a <=> ..., a < ..., a IN (...)
+    let (operator, operand) = if punct(end, '<') && punct(end + 1, '=') && punct(end + 2, '>') {
+        (Some("="), end + 3)
+    } else if (punct(end, '<') || punct(end, '>') || punct(end, '!')) && punct(end + 1, '=') {
+        (tokens.get(end).map(|t| t.text), end + 2)
+    } else if punct(end, '<') && punct(end + 1, '>') {
+        (Some("!"), end + 2)
+    } else if punct(end, '=') || punct(end, '<') || punct(end, '>') {
+        (tokens.get(end).map(|t| t.text), end + 1)
+    } else if keyword(end, "IN") || keyword(end, "BETWEEN") || keyword(end, "LIKE") {
+        (Some(tokens[end].text), end + 1)
+    } else if keyword(end, "IS") {
+        let not = keyword(end + 1, "NOT");
+        (Some(if not { "IS NOT" } else { "IS" }), end + 1)
+    } else {
+        (None, end)
+    };
+
+    if let Some(operator) = operator {
+        let other_is_column = column_ref(tokens, operand)
+            .is_some_and(|(_, _, after)| !tokens.get(after).is_some_and(|t| t.is_punct('.')));
+        return match operator.to_ascii_uppercase().as_str() {
+            "=" if other_is_column => Some(ColumnUsage::Join),
+            "=" | "IN" | "IS" => Some(ColumnUsage::Equality),
+            "<" | ">" | "<=" | ">=" | "BETWEEN" | "IS NOT" => Some(ColumnUsage::Range),
+            // A leading wildcard cannot use an index
+            "LIKE" => tokens
+                .get(operand)
+                .filter(|t| {
+                    t.kind != TokenKind::String
+                        || !t.text[1..].starts_with('%') && !t.text[1..].starts_with('_')
+                })
+                .map(|_| ColumnUsage::Range),
+            _ => None,
+        };
+    }
+
+    // Column on the right: ... = a, ... < a
+    let before = start.checked_sub(1)?;
+    if punct(before, '=') {
+        let operator_start = if before > 0 && (punct(before - 1, '<') || punct(before - 1, '>') || punct(before - 1, '!')) {
+            before - 1
+        } else {
+            before
+        };
+        if operator_start < before {
+            return match tokens[operator_start].text {
+                "<" | ">" => Some(ColumnUsage::Range),
+                _ => None,
+            };
+        }
+        // Joins are recorded from their left side
+        return (!column_before(tokens, before)).then_some(ColumnUsage::Equality);
+    }
+    if punct(before, '<') || punct(before, '>') {
+        return Some(ColumnUsage::Range);
+    }
+    None
+}
+
+/// Whether a column reference ends right before `index`
+fn column_before(tokens: &[Token], index: usize) -> bool {
+    let Some(mut start) = index.checked_sub(1) else {
+        return false;
+    };
+    while start >= 2 && tokens[start - 1].is_punct('.') {
+        start -= 2;
+    }
+    column_ref(tokens, start).is_some_and(|(_, _, end)| end == index)
+}
+
+/// A proposed index
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexSuggestion {
+    pub table: String,
+    pub columns: Vec<String>,
+    /// `CREATE INDEX` statement for the index; never executed
+    pub ddl: String,
+    /// Which conditions the columns serve
+    pub reason: String,
+    /// An existing index this one makes redundant, since it starts with its columns
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaces: Option<String>,
+    /// How the table is read today, from EXPLAIN
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_access: Option<String>,
+    /// Number of analysed queries this index would serve
+    pub queries: usize,
+}
+
+/// Propose an index on `table` for a query, unless an existing one serves it
+pub fn suggest_index(
+    shape: &QueryShape,
+    table: &TableRef,
+    schema: &TableSchema,
+    database: &str,
+) -> Option<IndexSuggestion> {
+    let used = shape.columns_of(table, schema);
+    let indexable = |name: &str| {
+        schema
+            .columns
+            .iter()
+            .find(|c| c.name == name)
+            .is_some_and(|c| {
+                let data_type = c.data_type.to_lowercase();
+                !["text", "blob", "json", "geometry", "point", "polygon", "linestring"]
+                    .iter()
+                    .any(|t| data_type.contains(t))
+            })
+    };
+    let columns_with = |usages: &[ColumnUsage]| -> Vec<String> {
+        let mut columns: Vec<String> = Vec::new();
+        for (column, usage) in &used {
+            if usages.contains(usage) && !columns.contains(column) && indexable(column) {
+                columns.push(column.clone());
+            }
+        }
+        columns
+    };
+
+    let equality = columns_with(&[ColumnUsage::Equality, ColumnUsage::Join]);
+    let range = columns_with(&[ColumnUsage::Range])
+        .into_iter()
+        .find(|column| !equality.contains(column));
+    let mut reasons = Vec::new();
+    if !equality.is_empty() {
+        reasons.push(format!("equality on {}", equality.join(", ")));
+    }
+
+    let mut columns = equality.clone();
+    if let Some(range) = range {
+        reasons.push(format!("range on {}", range));
+        columns.push(range);
+    } else if shape.tables.len() == 1 {
+        // Reading in index order saves a sort, but only if every sort column is in the index
+        for (usage, label) in [
+            (ColumnUsage::OrderBy, "order by"),
+            (ColumnUsage::GroupBy, "group by"),
+        ] {
+            let all = shape.columns.iter().filter(|c| c.usage == usage).count();
+            let resolved = used.iter().filter(|(column, u)| *u == usage && indexable(column)).count();
+            let sorted: Vec<String> = columns_with(&[usage])
+                .into_iter()
+                .filter(|column| !equality.contains(column))
+                .collect();
+            if !sorted.is_empty() && resolved == all {
+                reasons.push(format!("{} {}", label, sorted.join(", ")));
+                columns.extend(sorted);
+                break;
+            }
+        }
+    }
+    columns.truncate(MAX_INDEX_COLUMNS);
+    if columns.is_empty() {
+        return None;
+    }
+
+    let existing = btree_indexes(schema);
+    if existing
+        .iter()
+        .any(|index| serves(&index.columns, &columns, equality.len()))
+    {
+        return None;
+    }
+
+    let replaces = existing
+        .iter()
+        .filter(|index| !index.unique && index.name != "PRIMARY")
+        .find(|index| is_prefix(&index.columns, &columns))
+        .map(|index| index.name.clone());
+    let name = index_name(&schema.table_name, &columns);
+    let ddl = format!(
+        "CREATE INDEX {} ON {}.{} ({})",
+        sql::quote_identifier(&name),
+        sql::quote_identifier(database),
+        sql::quote_identifier(&schema.table_name),
+        columns
+            .iter()
+            .map(|column| sql::quote_identifier(column))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    Some(IndexSuggestion {
+        table: schema.table_name.clone(),
+        columns,
+        ddl,
+        reason: reasons.join("; "),
+        replaces,
+        current_access: None,
+        queries: 1,
+    })
+}
+
+/// Whether an index with `existing` columns serves a lookup on `wanted`, whose
+/// first `equality` columns may come in any order
+fn serves(existing: &[String], wanted: &[String], equality: usize) -> bool {
+    if existing.len() < wanted.len() {
+        return false;
+    }
+    let same = |a: &String, b: &String| a.eq_ignore_ascii_case(b);
+    let equality = equality.min(wanted.len());
+    wanted[..equality]
+        .iter()
+        .all(|column| existing[..equality].iter().any(|e| same(e, column)))
+        && existing[equality..wanted.len()]
+            .iter()
+            .zip(&wanted[equality..])
+            .all(|(e, w)| same(e, w))
+}
+
+fn is_prefix(prefix: &[String], columns: &[String]) -> bool {
+    prefix.len() <= columns.len()
+        && prefix
+            .iter()
+            .zip(columns)
+            .all(|(a, b)| a.eq_ignore_ascii_case(b))
+}
+
+/// `idx_<table>_<columns>`, shortened to fit MySQL's identifier limit
+fn index_name(table: &str, columns: &[String]) -> String {
+    let mut name = format!("idx_{}_{}", table, columns.join("_"));
+    if name.len() > MAX_IDENTIFIER_LENGTH {
+        let mut end = MAX_IDENTIFIER_LENGTH;
+        while !name.is_char_boundary(end) {
+            end -= 1;
+        }
+        name.truncate(end);
+    }
+    name
+}
+
+/// B-tree indexes of a table, including the primary key
+fn btree_indexes(schema: &TableSchema) -> Vec<Index> {
+    let mut indexes: Vec<Index> = schema
+        .primary_key
+        .iter()
+        .map(|columns| Index {
+            name: "PRIMARY".to_string(),
+            columns: columns.clone(),
+            unique: true,
+            index_type: "BTREE".to_string(),
+        })
+        .collect();
+    indexes.extend(schema.indexes.iter().cloned());
+    indexes.retain(|index| {
+        matches!(index.index_type.to_uppercase().as_str(), "BTREE" | "")
+            && !index.columns.is_empty()
+            && index.columns.iter().all(|column| !column.is_empty())
+    });
+    indexes.sort_by(|a, b| a.name.cmp(&b.name));
+    indexes
+}
+
+/// An index that another index makes unnecessary
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RedundantIndex {
+    pub table: String,
+    pub index: String,
+    pub columns: Vec<String>,
+    /// The index that covers it
+    pub covered_by: String,
+    /// Same columns in the same order, rather than a left prefix
+    pub duplicate: bool,
+    /// `DROP INDEX` statement for the index; never executed
+    pub ddl: String,
+}
+
+/// Find indexes whose columns are the same as, or a left prefix of, another's
+///
+/// Unique indexes are kept unless another unique index has the same columns,
+/// since they enforce a constraint.
+pub fn redundant_indexes(schema: &TableSchema, database: &str) -> Vec<RedundantIndex> {
+    let indexes = btree_indexes(schema);
+    let mut redundant: Vec<RedundantIndex> = Vec::new();
+
+    for index in indexes.iter().filter(|index| index.name != "PRIMARY") {
+        let covering = indexes.iter().find(|other| {
+            if other.name == index.name
+                || redundant.iter().any(|r| r.index == other.name)
+                || !is_prefix(&index.columns, &other.columns)
+            {
+                return false;
+            }
+            let duplicate = index.columns.len() == other.columns.len();
+            if index.unique {
+                return duplicate && other.unique;
+            }
+            // Of two identical indexes, keep a unique one or the first by name
+            !duplicate || other.unique || other.name == "PRIMARY" || other.name < index.name
+        });
+        if let Some(other) = covering {
+            redundant.push(RedundantIndex {
+                table: schema.table_name.clone(),
+                index: index.name.clone(),
+                columns: index.columns.clone(),
+                covered_by: other.name.clone(),
+                duplicate: index.columns.len() == other.columns.len(),
+                ddl: format!(
+                    "DROP INDEX {} ON {}.{}",
+                    sql::quote_identifier(&index.name),
+                    sql::quote_identifier(database),
+                    sql::quote_identifier(&schema.table_name)
+                ),
+            });
+        }
+    }
+
+    redundant
+}
+
+/// An index the server has not used since it started, from `sys.schema_unused_indexes`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnusedIndex {
+    pub table: String,
+    pub index: String,
+}
+
+/// How the plan reads `table`, as `<access type> on <key>`
+///
+/// EXPLAIN names tables by their alias, so both are tried.
+pub fn plan_access(plan: &PlanNode, table: &TableRef) -> Option<String> {
+    let names_table = |name: &str| {
+        name.eq_ignore_ascii_case(table.alias.as_deref().unwrap_or(&table.name))
+    };
+    if let (Some(name), Some(access_type)) = (&plan.table, &plan.access_type) {
+        if names_table(name) {
+            return Some(match &plan.key {
+                Some(key) => format!("{} on {}", access_type, key),
+                None => access_type.clone(),
+            });
+        }
+    }
+    plan.children.iter().find_map(|child| plan_access(child, table))
+}
+
+/// Result of `mysql_suggest_indexes`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IndexAdvice {
+    pub queries_analyzed: usize,
+    pub suggestions: Vec<IndexSuggestion>,
+    pub redundant_indexes: Vec<RedundantIndex>,
+    pub unused_indexes: Vec<UnusedIndex>,
+    /// Red flags from the query's plan, when a single query is analysed
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub plan_red_flags: Vec<RedFlag>,
+    /// Tables or checks that were skipped, and why
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+impl IndexAdvice {
+    /// Add a suggestion, counting it once per query that asks for the same index
+    pub fn add_suggestion(&mut self, suggestion: IndexSuggestion) {
+        match self
+            .suggestions
+            .iter_mut()
+            .find(|s| s.table == suggestion.table && s.columns == suggestion.columns)
+        {
+            Some(existing) => existing.queries += suggestion.queries,
+            None => self.suggestions.push(suggestion),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::ColumnSchema;
+
+    fn table(name: &str, columns: &[(&str, &str)], primary_key: &[&str], indexes: &[(&str, &[&str], bool)]) -> TableSchema {
+        TableSchema {
+            table_name: name.to_string(),
+            columns: columns
+                .iter()
+                .map(|(name, data_type)| ColumnSchema {
+                    name: name.to_string(),
+                    data_type: data_type.to_string(),
+                    nullable: true,
+                    default_value: None,
+                    comment: None,
+                    extra: None,
+                })
+                .collect(),
+            primary_key: (!primary_key.is_empty())
+                .then(|| primary_key.iter().map(|c| c.to_string()).collect()),
+            foreign_keys: vec![],
+            indexes: indexes
+                .iter()
+                .map(|(name, columns, unique)| Index {
+                    name: name.to_string(),
+                    columns: columns.iter().map(|c| c.to_string()).collect(),
+                    unique: *unique,
+                    index_type: "BTREE".to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    fn orders() -> TableSchema {
+        table(
+            "orders",
+            &[
+                ("id", "bigint"),
+                ("customer_id", "bigint"),
+                ("status", "varchar(20)"),
+                ("created_at", "datetime"),
+                ("note", "text"),
+            ],
+            &["id"],
+            &[("idx_customer", &["customer_id"], false)],
+        )
+    }
+
+    fn uses(shape: &QueryShape) -> Vec<(Option<&str>, &str, ColumnUsage)> {
+        shape
+            .columns
+            .iter()
+            .map(|c| (c.qualifier.as_deref(), c.column.as_str(), c.usage))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_tables_and_columns() {
+        let shape = QueryShape::parse(
+            "SELECT o.id, c.name FROM shop.orders AS o JOIN customers c ON c.id = o.customer_id \
+             WHERE o.status = ? AND o.created_at >= '2024-01-01' AND DATE(o.created_at) = ? \
+             AND c.name LIKE '%x' AND c.email LIKE 'a%' AND `o`.`id` IN (1, 2) ORDER BY o.created_at DESC LIMIT 10",
+        );
+        assert_eq!(
+            shape.tables,
+            vec![
+                TableRef {
+                    database: Some("shop".to_string()),
+                    name: "orders".to_string(),
+                    alias: Some("o".to_string()),
+                },
+                TableRef {
+                    database: None,
+                    name: "customers".to_string(),
+                    alias: Some("c".to_string()),
+                },
+            ]
+        );
+        assert_eq!(
+            uses(&shape),
+            vec![
+                (Some("c"), "id", ColumnUsage::Join),
+                (Some("o"), "status", ColumnUsage::Equality),
+                (Some("o"), "created_at", ColumnUsage::Range),
+                (Some("c"), "email", ColumnUsage::Range),
+                (Some("o"), "id", ColumnUsage::Equality),
+                (Some("o"), "created_at", ColumnUsage::OrderBy),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_digest_text_and_right_hand_columns() {
+        let shape = QueryShape::parse(
+            "SELECT * FROM `orders` , `items` WHERE ? = `orders` . `status` AND `items` . `order_id` = `orders` . `id` AND ? < `created_at` GROUP BY `status`",
+        );
+        assert_eq!(shape.tables.len(), 2);
+        assert_eq!(
+            uses(&shape),
+            vec![
+                (Some("orders"), "status", ColumnUsage::Equality),
+                (Some("items"), "order_id", ColumnUsage::Join),
+                (None, "created_at", ColumnUsage::Range),
+                (None, "status", ColumnUsage::GroupBy),
+            ]
+        );
+
+        let shape = QueryShape::parse("UPDATE orders SET status = 'x' WHERE customer_id = 5");
+        assert_eq!(shape.tables[0].name, "orders");
+        assert_eq!(uses(&shape), vec![(None, "customer_id", ColumnUsage::Equality)]);
+    }
+
+    #[test]
+    fn test_suggest_composite_index() {
+        let shape = QueryShape::parse(
+            "SELECT * FROM orders WHERE status = 'open' AND customer_id = 3 AND created_at > NOW() - INTERVAL 1 DAY",
+        );
+        let suggestion = suggest_index(&shape, &shape.tables[0], &orders(), "shop").unwrap();
+        assert_eq!(suggestion.columns, ["status", "customer_id", "created_at"]);
+        assert_eq!(
+            suggestion.ddl,
+            "CREATE INDEX `idx_orders_status_customer_id_created_at` ON `shop`.`orders` (`status`, `customer_id`, `created_at`)"
+        );
+        assert_eq!(suggestion.reason, "equality on status, customer_id; range on created_at");
+        assert_eq!(suggestion.replaces, None);
+
+        // Sorting follows the equality columns when there is no range
+        let shape = QueryShape::parse("SELECT * FROM orders WHERE customer_id = 3 ORDER BY created_at");
+        let suggestion = suggest_index(&shape, &shape.tables[0], &orders(), "shop").unwrap();
+        assert_eq!(suggestion.columns, ["customer_id", "created_at"]);
+        assert_eq!(suggestion.replaces.as_deref(), Some("idx_customer"));
+    }
+
+    #[test]
+    fn test_no_suggestion_when_an_index_serves_the_query() {
+        for query in [
+            "SELECT * FROM orders WHERE customer_id = 3",
+            "SELECT * FROM orders WHERE id = 3",
+            "SELECT * FROM orders WHERE note = 'x'",
+            "SELECT * FROM orders WHERE UPPER(status) = 'OPEN'",
+            "SELECT * FROM orders",
+        ] {
+            let shape = QueryShape::parse(query);
+            assert_eq!(suggest_index(&shape, &shape.tables[0], &orders(), "shop"), None, "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_redundant_indexes() {
+        let schema = table(
+            "t",
+            &[("a", "int"), ("b", "int"), ("c", "int")],
+            &["a"],
+            &[
+                ("idx_a", &["a"], false),
+                ("idx_ab", &["a", "b"], false),
+                ("idx_ab2", &["a", "b"], false),
+                ("uq_b", &["b"], true),
+                ("idx_b_c", &["b", "c"], false),
+                ("idx_c", &["c"], false),
+            ],
+        );
+        let redundant = redundant_indexes(&schema, "db");
+        let found: Vec<(&str, &str, bool)> = redundant
+            .iter()
+            .map(|r| (r.index.as_str(), r.covered_by.as_str(), r.duplicate))
+            .collect();
+        assert_eq!(
+            found,
+            vec![("idx_a", "PRIMARY", true), ("idx_ab2", "idx_ab", true)]
+        );
+        assert_eq!(redundant[0].ddl, "DROP INDEX `idx_a` ON `db`.`t`");
+
+        let schema = table("t", &[("a", "int"), ("b", "int")], &[], &[("idx_a", &["a"], false), ("idx_a_b", &["a", "b"], false)]);
+        let redundant = redundant_indexes(&schema, "db");
+        assert_eq!(redundant.len(), 1);
+        assert_eq!(redundant[0].covered_by, "idx_a_b");
+        assert!(!redundant[0].duplicate);
+    }
+}
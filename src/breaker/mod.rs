@@ -0,0 +1,243 @@
+//! Per data source circuit breaker
+//!
+//! A data source that keeps refusing connections would otherwise make every
+//! tool call wait out the pool's `connection_timeout`. After enough connection
+//! failures within the window the circuit opens and calls fail straight away.
+//! Once the cool-down has passed the circuit is half-open: a single call is let
+//! through as a probe, and its outcome closes or reopens the circuit.
+
+use crate::config::CircuitBreakerConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// State of a circuit breaker
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Calls go through
+    #[default]
+    Closed,
+    /// Calls fail fast until the cool-down has passed
+    Open,
+    /// A single probe call is let through
+    HalfOpen,
+}
+
+/// Circuit breaker state as reported by the list and stats tools
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CircuitInfo {
+    pub state: CircuitState,
+    /// Connection failures within the window
+    pub recent_failures: usize,
+    /// Seconds until an open circuit lets a probe through
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_in_secs: Option<u64>,
+}
+
+/// Circuit breaker for a single data source
+///
+/// All methods take the current time so that transitions can be tested
+/// without waiting.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: CircuitState,
+    // Times of the connection failures since the last success, oldest first
+    failures: VecDeque<Instant>,
+    opened_at: Option<Instant>,
+    // When the half-open probe was let through; a probe that never reports back
+    // (e.g. its call was rejected before running) is replaced after a cool-down
+    probe_started: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            state: CircuitState::Closed,
+            failures: VecDeque::new(),
+            opened_at: None,
+            probe_started: None,
+        }
+    }
+
+    /// Current state
+    pub fn state(&self) -> CircuitState {
+        self.state
+    }
+
+    /// Check whether a call may go through
+    ///
+    /// An open circuit whose cool-down has passed turns half-open and lets this
+    /// call through as its probe; further calls are refused until it reports.
+    pub fn try_acquire(&mut self, now: Instant) -> bool {
+        let cool_down = self.config.cool_down();
+        match self.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => {
+                if self.opened_at.is_some_and(|opened| now.duration_since(opened) < cool_down) {
+                    return false;
+                }
+                self.state = CircuitState::HalfOpen;
+                self.probe_started = Some(now);
+                true
+            }
+            CircuitState::HalfOpen => {
+                if self.probe_started.is_some_and(|started| now.duration_since(started) < cool_down) {
+                    return false;
+                }
+                self.probe_started = Some(now);
+                true
+            }
+        }
+    }
+
+    /// Record a call that reached the server, closing the circuit
+    pub fn record_success(&mut self) {
+        self.state = CircuitState::Closed;
+        self.failures.clear();
+        self.opened_at = None;
+        self.probe_started = None;
+    }
+
+    /// Record a connection failure; returns true if it opened the circuit
+    pub fn record_failure(&mut self, now: Instant) -> bool {
+        match self.state {
+            CircuitState::Closed => {
+                self.failures.push_back(now);
+                self.expire_failures(now);
+                if self.failures.len() < self.config.failure_threshold as usize {
+                    return false;
+                }
+            }
+            // Calls started before the circuit opened may still be failing
+            CircuitState::Open => return false,
+            // The probe failed
+            CircuitState::HalfOpen => {}
+        }
+
+        self.state = CircuitState::Open;
+        self.opened_at = Some(now);
+        self.probe_started = None;
+        true
+    }
+
+    /// State for reporting
+    pub fn info(&self, now: Instant) -> CircuitInfo {
+        let window = self.config.window();
+        let recent_failures = self
+            .failures
+            .iter()
+            .filter(|failure| now.duration_since(**failure) < window)
+            .count();
+        let retry_in_secs = match (self.state, self.opened_at) {
+            (CircuitState::Open, Some(opened)) => Some(
+                self.config
+                    .cool_down()
+                    .saturating_sub(now.duration_since(opened))
+                    .as_secs(),
+            ),
+            _ => None,
+        };
+
+        CircuitInfo {
+            state: self.state,
+            recent_failures,
+            retry_in_secs,
+        }
+    }
+
+    fn expire_failures(&mut self, now: Instant) {
+        let window = self.config.window();
+        while self
+            .failures
+            .front()
+            .is_some_and(|failure| now.duration_since(*failure) >= window)
+        {
+            self.failures.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 3,
+            window_secs: 60,
+            cool_down_secs: 30,
+        })
+    }
+
+    #[test]
+    fn test_opens_after_threshold_within_window() {
+        let mut breaker = breaker();
+        let start = Instant::now();
+
+        assert!(!breaker.record_failure(start));
+        assert!(!breaker.record_failure(start + Duration::from_secs(10)));
+        assert!(breaker.record_failure(start + Duration::from_secs(20)));
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.try_acquire(start + Duration::from_secs(21)));
+
+        let info = breaker.info(start + Duration::from_secs(21));
+        assert_eq!(info.recent_failures, 3);
+        assert_eq!(info.retry_in_secs, Some(29));
+    }
+
+    #[test]
+    fn test_failures_outside_window_or_before_success_do_not_count() {
+        let mut breaker = breaker();
+        let start = Instant::now();
+
+        breaker.record_failure(start);
+        breaker.record_failure(start + Duration::from_secs(1));
+        assert!(!breaker.record_failure(start + Duration::from_secs(61)));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        breaker.record_success();
+        assert!(!breaker.record_failure(start + Duration::from_secs(62)));
+        assert_eq!(breaker.info(start + Duration::from_secs(62)).recent_failures, 1);
+    }
+
+    #[test]
+    fn test_half_open_lets_one_probe_through() {
+        let mut breaker = breaker();
+        let start = Instant::now();
+        for _ in 0..3 {
+            breaker.record_failure(start);
+        }
+
+        let after_cool_down = start + Duration::from_secs(30);
+        assert!(breaker.try_acquire(after_cool_down));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(!breaker.try_acquire(after_cool_down));
+
+        // A failed probe reopens the circuit for another cool-down
+        assert!(breaker.record_failure(after_cool_down + Duration::from_secs(1)));
+        assert!(!breaker.try_acquire(after_cool_down + Duration::from_secs(2)));
+
+        // A successful probe closes it
+        assert!(breaker.try_acquire(after_cool_down + Duration::from_secs(31)));
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.try_acquire(after_cool_down + Duration::from_secs(31)));
+    }
+
+    #[test]
+    fn test_lost_probe_is_replaced_after_cool_down() {
+        let mut breaker = breaker();
+        let start = Instant::now();
+        for _ in 0..3 {
+            breaker.record_failure(start);
+        }
+
+        assert!(breaker.try_acquire(start + Duration::from_secs(30)));
+        assert!(!breaker.try_acquire(start + Duration::from_secs(59)));
+        assert!(breaker.try_acquire(start + Duration::from_secs(60)));
+    }
+}
@@ -74,6 +74,31 @@ where
         entries.insert(key, CacheEntry::new(value));
     }
 
+    /// Get a value from the cache, inserting one made by `make` if it is
+    /// missing or expired
+    ///
+    /// Unlike `get` followed by `insert`, concurrent callers all get the same value.
+    pub async fn get_or_insert_with(&self, key: K, make: impl FnOnce() -> V) -> V {
+        let mut entries = self.entries.write().await;
+
+        if let Some(entry) = entries.get(&key) {
+            if !entry.is_expired(self.ttl) {
+                return entry.value.clone();
+            }
+        }
+
+        // If at capacity, remove oldest entry
+        if entries.len() >= self.max_capacity && !entries.contains_key(&key) {
+            if let Some(oldest_key) = self.find_oldest_key(&entries) {
+                entries.remove(&oldest_key);
+            }
+        }
+
+        let value = make();
+        entries.insert(key, CacheEntry::new(value.clone()));
+        value
+    }
+
     /// Remove a specific key from the cache
     pub async fn remove(&self, key: &K) {
         let mut entries = self.entries.write().await;
@@ -187,6 +212,19 @@ mod tests {
         assert!(cache.get(&"key4".to_string()).await.is_some());
     }
 
+    #[tokio::test]
+    async fn test_cache_get_or_insert_with() {
+        let cache = TtlCache::new(Duration::from_millis(100), 10);
+
+        assert_eq!(cache.get_or_insert_with("key1", || 1).await, 1);
+        // The stored value wins until it expires
+        assert_eq!(cache.get_or_insert_with("key1", || 2).await, 1);
+
+        sleep(Duration::from_millis(150)).await;
+        assert_eq!(cache.get_or_insert_with("key1", || 3).await, 3);
+        assert_eq!(cache.len().await, 1);
+    }
+
     #[tokio::test]
     async fn test_cache_clear() {
         let cache = TtlCache::new(Duration::from_secs(60), 10);
@@ -1,6 +1,9 @@
+use crate::error::RetryConfig;
+use crate::types::{self, TypeMapping};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Permission levels for data source access
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -53,6 +56,7 @@ pub struct DataSourceConfig {
     /// MySQL password (loaded from env var or config)
     pub password: String,
     /// List of allowed databases (empty means all)
+    /// Entries may use `*` / `?` wildcards (`app_*`) or be a regex wrapped in slashes (`/^tenant_[0-9]+$/`)
     #[serde(default)]
     pub databases: Vec<String>,
     /// Connection pool configuration
@@ -61,6 +65,49 @@ pub struct DataSourceConfig {
     /// Permission level for this data source
     #[serde(default)]
     pub permission: Permission,
+    /// Query timeout in seconds for this data source (defaults to the server's `query_timeout_secs`)
+    #[serde(default)]
+    pub query_timeout_secs: Option<u64>,
+    /// Maximum number of rows a query returns (defaults to the server's `max_rows`)
+    #[serde(default)]
+    pub max_rows: Option<usize>,
+    /// Maximum size of the rows a query returns (defaults to the server's `max_result_bytes`)
+    #[serde(default)]
+    pub max_result_bytes: Option<usize>,
+    /// Allow administrative tools such as `mysql_kill`, independently of `permission`
+    #[serde(default)]
+    pub admin: bool,
+    /// The server's slow query log, for `mysql_top_queries` where performance_schema is off;
+    /// must be readable by this process
+    #[serde(default)]
+    pub slow_query_log: Option<PathBuf>,
+    /// Circuit breaker configuration
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+}
+
+/// A data source on the default MySQL port of localhost, with no key,
+/// credentials or database restrictions and default settings otherwise
+impl Default for DataSourceConfig {
+    fn default() -> Self {
+        Self {
+            key: String::new(),
+            name: String::new(),
+            host: "localhost".to_string(),
+            port: 3306,
+            username: String::new(),
+            password: String::new(),
+            databases: Vec::new(),
+            pool_config: PoolConfig::default(),
+            permission: Permission::default(),
+            query_timeout_secs: None,
+            max_rows: None,
+            max_result_bytes: None,
+            admin: false,
+            slow_query_log: None,
+            circuit_breaker: CircuitBreakerConfig::default(),
+        }
+    }
 }
 
 /// Connection pool configuration
@@ -139,6 +186,54 @@ fn default_max_lifetime() -> u64 {
     1500
 }
 
+/// Circuit breaker configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitBreakerConfig {
+    /// Connection failures within the window, with no successful call between
+    /// them, that open the circuit
+    #[serde(default = "default_breaker_failure_threshold")]
+    pub failure_threshold: u32,
+    /// Window in seconds in which failures are counted
+    #[serde(default = "default_breaker_window")]
+    pub window_secs: u64,
+    /// Seconds an open circuit fails calls before letting a probe call through
+    #[serde(default = "default_breaker_cool_down")]
+    pub cool_down_secs: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: default_breaker_failure_threshold(),
+            window_secs: default_breaker_window(),
+            cool_down_secs: default_breaker_cool_down(),
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.window_secs)
+    }
+
+    pub fn cool_down(&self) -> Duration {
+        Duration::from_secs(self.cool_down_secs)
+    }
+}
+
+fn default_breaker_failure_threshold() -> u32 {
+    5
+}
+
+fn default_breaker_window() -> u64 {
+    60
+}
+
+fn default_breaker_cool_down() -> u64 {
+    // Longer than connection_timeout, so a probe settles before the next one
+    30
+}
+
 /// Server configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
@@ -147,15 +242,308 @@ pub struct ServerConfig {
     /// Query timeout in seconds
     #[serde(default = "default_query_timeout")]
     pub query_timeout_secs: u64,
+    /// Upper bound in seconds for per-data-source and per-call query timeouts
+    #[serde(default = "default_max_query_timeout")]
+    pub max_query_timeout_secs: u64,
+    /// Maximum number of rows a query returns, and the cap for all overrides
+    #[serde(default = "default_max_rows")]
+    pub max_rows: usize,
+    /// Maximum size in bytes of the rows a query returns, measured as JSON,
+    /// and the cap for all overrides
+    #[serde(default = "default_max_result_bytes")]
+    pub max_result_bytes: usize,
     /// Stream chunk size (number of rows)
     #[serde(default = "default_stream_chunk_size")]
     pub stream_chunk_size: usize,
+    /// Interactive transaction configuration
+    #[serde(default)]
+    pub transactions: TransactionConfig,
+    /// Query result stream configuration
+    #[serde(default)]
+    pub streams: StreamConfig,
+    /// Result value type mapping configuration
+    #[serde(default)]
+    pub types: TypeConfig,
+    /// Query result export configuration
+    #[serde(default)]
+    pub export: ExportConfig,
+    /// Bulk import configuration
+    #[serde(default)]
+    pub import: ImportConfig,
+    /// Background health check configuration
+    #[serde(default)]
+    pub health: HealthConfig,
+    /// Retries of connection attempts and operations that are safe to repeat
+    #[serde(default)]
+    pub retry: RetryConfig,
+}
+
+/// Limits on the rows a query returns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResultLimits {
+    /// Maximum number of rows
+    pub max_rows: usize,
+    /// Maximum size in bytes of the rows, measured as JSON
+    pub max_result_bytes: usize,
+}
+
+/// Interactive transaction configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionConfig {
+    /// Idle time in seconds after which an open transaction is rolled back
+    #[serde(default = "default_transaction_idle_timeout")]
+    pub idle_timeout_secs: u64,
+    /// Maximum number of open transactions per data source
+    #[serde(default = "default_max_open_transactions")]
+    pub max_open_per_datasource: usize,
+}
+
+impl Default for TransactionConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout_secs: default_transaction_idle_timeout(),
+            max_open_per_datasource: default_max_open_transactions(),
+        }
+    }
+}
+
+impl TransactionConfig {
+    pub fn idle_timeout(&self) -> Duration {
+        Duration::from_secs(self.idle_timeout_secs)
+    }
+}
+
+fn default_transaction_idle_timeout() -> u64 {
+    // Open transactions hold locks and a pinned connection, so keep this short
+    60
+}
+
+fn default_max_open_transactions() -> usize {
+    // Leaves most of the default pool (15 connections) for regular queries
+    5
+}
+
+/// Query result stream configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamConfig {
+    /// Idle time in seconds after which an open stream is closed
+    #[serde(default = "default_stream_idle_timeout")]
+    pub idle_timeout_secs: u64,
+    /// Maximum number of open streams reading from the server
+    #[serde(default = "default_max_open_streams")]
+    pub max_open: usize,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout_secs: default_stream_idle_timeout(),
+            max_open: default_max_open_streams(),
+        }
+    }
+}
+
+impl StreamConfig {
+    pub fn idle_timeout(&self) -> Duration {
+        Duration::from_secs(self.idle_timeout_secs)
+    }
+}
+
+fn default_stream_idle_timeout() -> u64 {
+    // Open streams keep a query running on the server
+    60
+}
+
+fn default_max_open_streams() -> usize {
+    // Each open stream holds a connection; the limit spans all data sources
+    10
+}
+
+/// Result value type mapping configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeConfig {
+    /// Time zone TIMESTAMP values are returned in, `UTC` or an offset such as `+02:00`
+    #[serde(default = "default_timestamp_time_zone")]
+    pub timestamp_time_zone: String,
+}
+
+impl Default for TypeConfig {
+    fn default() -> Self {
+        Self {
+            timestamp_time_zone: default_timestamp_time_zone(),
+        }
+    }
+}
+
+impl TypeConfig {
+    pub fn type_mapping(&self) -> Result<TypeMapping, ConfigError> {
+        types::parse_time_zone(&self.timestamp_time_zone)
+            .map(TypeMapping::new)
+            .ok_or_else(|| {
+                ConfigError::ValidationError(format!(
+                    "types.timestamp_time_zone must be UTC or an offset such as +02:00, got '{}'",
+                    self.timestamp_time_zone
+                ))
+            })
+    }
+}
+
+fn default_timestamp_time_zone() -> String {
+    // Connections use UTC as their session time zone
+    "UTC".to_string()
+}
+
+/// Query result export configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportConfig {
+    /// Directory export files are written to; exports are disabled without it
+    #[serde(default)]
+    pub directory: Option<PathBuf>,
+    /// Maximum size in bytes of an export file
+    #[serde(default = "default_max_export_file_bytes")]
+    pub max_file_bytes: u64,
+    /// Time limit in seconds for an export, and the cap for per-call timeouts
+    #[serde(default = "default_export_timeout")]
+    pub timeout_secs: u64,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            directory: None,
+            max_file_bytes: default_max_export_file_bytes(),
+            timeout_secs: default_export_timeout(),
+        }
+    }
+}
+
+impl ExportConfig {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+}
+
+fn default_max_export_file_bytes() -> u64 {
+    // 1 GiB
+    1024 * 1024 * 1024
+}
+
+fn default_export_timeout() -> u64 {
+    // Exports are for results too large to return, so they may run long
+    3600
+}
+
+/// Bulk import configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportConfig {
+    /// Directory import files are read from; imports are disabled without it
+    #[serde(default)]
+    pub directory: Option<PathBuf>,
+    /// Maximum size in bytes of an import file as stored
+    #[serde(default = "default_max_import_file_bytes")]
+    pub max_file_bytes: u64,
+    /// Maximum number of rows per INSERT statement
+    #[serde(default = "default_import_batch_rows")]
+    pub batch_rows: usize,
+    /// Time limit in seconds for an import, and the cap for per-call timeouts
+    #[serde(default = "default_import_timeout")]
+    pub timeout_secs: u64,
+}
+
+impl Default for ImportConfig {
+    fn default() -> Self {
+        Self {
+            directory: None,
+            max_file_bytes: default_max_import_file_bytes(),
+            batch_rows: default_import_batch_rows(),
+            timeout_secs: default_import_timeout(),
+        }
+    }
+}
+
+impl ImportConfig {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+}
+
+fn default_max_import_file_bytes() -> u64 {
+    // 1 GiB
+    1024 * 1024 * 1024
+}
+
+fn default_import_batch_rows() -> usize {
+    // Keeps statements well below max_allowed_packet for typical rows
+    500
+}
+
+fn default_import_timeout() -> u64 {
+    // The whole file loads in one transaction
+    3600
+}
+
+/// Background health check configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthConfig {
+    /// Seconds between health checks of each data source
+    #[serde(default = "default_health_interval")]
+    pub interval_secs: u64,
+    /// Consecutive failed checks after which a data source is marked unavailable
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+    /// Time limit in seconds for connecting and running a check
+    #[serde(default = "default_probe_timeout")]
+    pub probe_timeout_secs: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: default_health_interval(),
+            failure_threshold: default_failure_threshold(),
+            probe_timeout_secs: default_probe_timeout(),
+        }
+    }
+}
+
+impl HealthConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs)
+    }
+
+    pub fn probe_timeout(&self) -> Duration {
+        Duration::from_secs(self.probe_timeout_secs)
+    }
+}
+
+fn default_health_interval() -> u64 {
+    30
+}
+
+fn default_failure_threshold() -> u32 {
+    // A single dropped packet should not take a data source offline
+    3
+}
+
+fn default_probe_timeout() -> u64 {
+    5
 }
 
 impl ServerConfig {
     pub fn query_timeout(&self) -> Duration {
         Duration::from_secs(self.query_timeout_secs)
     }
+
+    pub fn max_query_timeout(&self) -> Duration {
+        Duration::from_secs(self.max_query_timeout_secs)
+    }
+
+    pub fn result_limits(&self) -> ResultLimits {
+        ResultLimits {
+            max_rows: self.max_rows,
+            max_result_bytes: self.max_result_bytes,
+        }
+    }
     
     /// Validate and filter data sources, keeping only valid ones
     /// Invalid data sources are logged and skipped
@@ -191,6 +579,21 @@ fn default_query_timeout() -> u64 {
     30
 }
 
+fn default_max_query_timeout() -> u64 {
+    // Long enough for reporting queries, short enough to bound a runaway one
+    300
+}
+
+fn default_max_rows() -> usize {
+    // Plenty for interactive use; larger result sets should be paged or streamed
+    10_000
+}
+
+fn default_max_result_bytes() -> usize {
+    // 5 MiB of JSON
+    5 * 1024 * 1024
+}
+
 fn default_stream_chunk_size() -> usize {
     // Optimized: Increased from 1000 to 1500 for better throughput
     // Reduces overhead while maintaining reasonable memory usage
@@ -267,6 +670,130 @@ impl ServerConfig {
         if self.data_sources.is_empty() {
             return Err(ConfigError::ValidationError("No data sources configured".to_string()));
         }
+
+        if self.query_timeout_secs == 0 {
+            return Err(ConfigError::ValidationError(
+                "query_timeout_secs must be greater than 0".to_string()
+            ));
+        }
+
+        if self.query_timeout_secs > self.max_query_timeout_secs {
+            return Err(ConfigError::ValidationError(format!(
+                "query_timeout_secs ({}) exceeds max_query_timeout_secs ({})",
+                self.query_timeout_secs, self.max_query_timeout_secs
+            )));
+        }
+
+        if self.max_rows == 0 {
+            return Err(ConfigError::ValidationError(
+                "max_rows must be greater than 0".to_string()
+            ));
+        }
+
+        if self.max_result_bytes == 0 {
+            return Err(ConfigError::ValidationError(
+                "max_result_bytes must be greater than 0".to_string()
+            ));
+        }
+
+        if self.transactions.idle_timeout_secs == 0 {
+            return Err(ConfigError::ValidationError(
+                "transactions.idle_timeout_secs must be greater than 0".to_string()
+            ));
+        }
+
+        if self.streams.idle_timeout_secs == 0 {
+            return Err(ConfigError::ValidationError(
+                "streams.idle_timeout_secs must be greater than 0".to_string()
+            ));
+        }
+
+        if self.streams.max_open == 0 {
+            return Err(ConfigError::ValidationError(
+                "streams.max_open must be greater than 0".to_string()
+            ));
+        }
+
+        self.types.type_mapping()?;
+
+        if self.export.max_file_bytes == 0 {
+            return Err(ConfigError::ValidationError(
+                "export.max_file_bytes must be greater than 0".to_string()
+            ));
+        }
+
+        if self.export.timeout_secs == 0 {
+            return Err(ConfigError::ValidationError(
+                "export.timeout_secs must be greater than 0".to_string()
+            ));
+        }
+
+        if self.export.directory.as_ref().is_some_and(|dir| dir.as_os_str().is_empty()) {
+            return Err(ConfigError::ValidationError(
+                "export.directory must not be empty".to_string()
+            ));
+        }
+
+        if self.import.max_file_bytes == 0 {
+            return Err(ConfigError::ValidationError(
+                "import.max_file_bytes must be greater than 0".to_string()
+            ));
+        }
+
+        if self.import.batch_rows == 0 {
+            return Err(ConfigError::ValidationError(
+                "import.batch_rows must be greater than 0".to_string()
+            ));
+        }
+
+        if self.import.timeout_secs == 0 {
+            return Err(ConfigError::ValidationError(
+                "import.timeout_secs must be greater than 0".to_string()
+            ));
+        }
+
+        if self.import.directory.as_ref().is_some_and(|dir| dir.as_os_str().is_empty()) {
+            return Err(ConfigError::ValidationError(
+                "import.directory must not be empty".to_string()
+            ));
+        }
+
+        if self.health.interval_secs == 0 {
+            return Err(ConfigError::ValidationError(
+                "health.interval_secs must be greater than 0".to_string()
+            ));
+        }
+
+        if self.health.failure_threshold == 0 {
+            return Err(ConfigError::ValidationError(
+                "health.failure_threshold must be greater than 0".to_string()
+            ));
+        }
+
+        if self.health.probe_timeout_secs == 0 {
+            return Err(ConfigError::ValidationError(
+                "health.probe_timeout_secs must be greater than 0".to_string()
+            ));
+        }
+
+        if self.retry.max_attempts == 0 {
+            return Err(ConfigError::ValidationError(
+                "retry.max_attempts must be greater than 0".to_string()
+            ));
+        }
+
+        if self.retry.backoff_multiplier < 1.0 {
+            return Err(ConfigError::ValidationError(
+                "retry.backoff_multiplier must be at least 1.0".to_string()
+            ));
+        }
+
+        if self.retry.initial_backoff_ms > self.retry.max_backoff_ms {
+            return Err(ConfigError::ValidationError(format!(
+                "retry.initial_backoff_ms ({}) exceeds retry.max_backoff_ms ({})",
+                self.retry.initial_backoff_ms, self.retry.max_backoff_ms
+            )));
+        }
         
         // Check for duplicate keys
         let mut keys = std::collections::HashSet::new();
@@ -279,6 +806,33 @@ impl ServerConfig {
             
             // Validate data source
             ds.validate()?;
+
+            if let Some(timeout) = ds.query_timeout_secs {
+                if timeout > self.max_query_timeout_secs {
+                    return Err(ConfigError::ValidationError(format!(
+                        "Data source '{}': query_timeout_secs ({}) exceeds max_query_timeout_secs ({})",
+                        ds.key, timeout, self.max_query_timeout_secs
+                    )));
+                }
+            }
+
+            if let Some(max_rows) = ds.max_rows {
+                if max_rows > self.max_rows {
+                    return Err(ConfigError::ValidationError(format!(
+                        "Data source '{}': max_rows ({}) exceeds the server's max_rows ({})",
+                        ds.key, max_rows, self.max_rows
+                    )));
+                }
+            }
+
+            if let Some(max_result_bytes) = ds.max_result_bytes {
+                if max_result_bytes > self.max_result_bytes {
+                    return Err(ConfigError::ValidationError(format!(
+                        "Data source '{}': max_result_bytes ({}) exceeds the server's max_result_bytes ({})",
+                        ds.key, max_result_bytes, self.max_result_bytes
+                    )));
+                }
+            }
         }
         
         Ok(())
@@ -321,11 +875,106 @@ impl DataSourceConfig {
             ));
         }
         
+        // Validate database allowlist patterns
+        self.database_allowlist()?;
+        
+        // Validate query timeout override
+        if self.query_timeout_secs == Some(0) {
+            return Err(ConfigError::ValidationError(
+                format!("Data source '{}': query_timeout_secs must be greater than 0", self.key)
+            ));
+        }
+
+        // Validate result limit overrides
+        if self.max_rows == Some(0) {
+            return Err(ConfigError::ValidationError(
+                format!("Data source '{}': max_rows must be greater than 0", self.key)
+            ));
+        }
+
+        if self.max_result_bytes == Some(0) {
+            return Err(ConfigError::ValidationError(
+                format!("Data source '{}': max_result_bytes must be greater than 0", self.key)
+            ));
+        }
+        
         // Validate pool config
         self.pool_config.validate(&self.key)?;
+
+        // Validate circuit breaker config
+        self.circuit_breaker.validate(&self.key)?;
         
         Ok(())
     }
+
+    /// Get the query timeout override of this data source
+    pub fn query_timeout(&self) -> Option<Duration> {
+        self.query_timeout_secs.map(Duration::from_secs)
+    }
+
+    /// Compile the `databases` allowlist of this data source
+    pub fn database_allowlist(&self) -> Result<DatabaseAllowlist, ConfigError> {
+        DatabaseAllowlist::new(&self.databases).map_err(|e| {
+            ConfigError::ValidationError(format!("Data source '{}': {}", self.key, e))
+        })
+    }
+}
+
+/// Compiled database allowlist
+///
+/// Every entry must match the whole database name. Plain entries support the
+/// `*` and `?` wildcards, entries wrapped in slashes are regular expressions.
+/// An empty allowlist allows every database.
+#[derive(Debug, Clone, Default)]
+pub struct DatabaseAllowlist {
+    patterns: Vec<Regex>,
+}
+
+impl DatabaseAllowlist {
+    /// Compile a list of database patterns
+    pub fn new(entries: &[String]) -> Result<Self, String> {
+        let patterns = entries
+            .iter()
+            .map(|entry| {
+                let source = match entry
+                    .strip_prefix('/')
+                    .and_then(|rest| rest.strip_suffix('/'))
+                {
+                    Some(regex) => format!("^(?:{})$", regex),
+                    None => glob_to_regex(entry),
+                };
+                Regex::new(&source)
+                    .map_err(|e| format!("invalid database pattern '{}': {}", entry, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { patterns })
+    }
+
+    /// Check if the allowlist restricts databases at all
+    pub fn is_unrestricted(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Check if a database name is allowed
+    pub fn allows(&self, database: &str) -> bool {
+        self.is_unrestricted() || self.patterns.iter().any(|p| p.is_match(database))
+    }
+}
+
+/// Translate a `*` / `?` wildcard pattern into an anchored regex
+fn glob_to_regex(pattern: &str) -> String {
+    let mut regex = String::with_capacity(pattern.len() + 2);
+    regex.push('^');
+    for c in pattern.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    regex.push('$');
+    regex
 }
 
 impl PoolConfig {
@@ -350,6 +999,31 @@ impl PoolConfig {
     }
 }
 
+impl CircuitBreakerConfig {
+    /// Validate circuit breaker configuration
+    pub fn validate(&self, datasource_key: &str) -> Result<(), ConfigError> {
+        if self.failure_threshold == 0 {
+            return Err(ConfigError::ValidationError(
+                format!("Data source '{}': circuit_breaker.failure_threshold must be > 0", datasource_key)
+            ));
+        }
+
+        if self.window_secs == 0 {
+            return Err(ConfigError::ValidationError(
+                format!("Data source '{}': circuit_breaker.window_secs must be > 0", datasource_key)
+            ));
+        }
+
+        if self.cool_down_secs == 0 {
+            return Err(ConfigError::ValidationError(
+                format!("Data source '{}': circuit_breaker.cool_down_secs must be > 0", datasource_key)
+            ));
+        }
+
+        Ok(())
+    }
+}
+
 /// Configuration error types
 #[derive(Debug, thiserror::Error)]
 pub enum ConfigError {
@@ -396,10 +1070,37 @@ mod tests {
         let config = ServerConfig {
             data_sources: vec![],
             query_timeout_secs: default_query_timeout(),
+            max_query_timeout_secs: default_max_query_timeout(),
+            max_rows: default_max_rows(),
+            max_result_bytes: default_max_result_bytes(),
             stream_chunk_size: default_stream_chunk_size(),
+            transactions: TransactionConfig::default(),
+            streams: StreamConfig::default(),
+            types: TypeConfig::default(),
+            export: ExportConfig::default(),
+            import: ImportConfig::default(),
+            health: HealthConfig::default(),
+            retry: RetryConfig::default(),
         };
         assert_eq!(config.query_timeout(), Duration::from_secs(30));
+        assert_eq!(config.max_query_timeout(), Duration::from_secs(300));
+        assert_eq!(config.max_rows, 10_000);
+        assert_eq!(config.max_result_bytes, 5 * 1024 * 1024);
         assert_eq!(config.stream_chunk_size, 1500); // Optimized from 1000
+        assert_eq!(config.transactions.idle_timeout(), Duration::from_secs(60));
+        assert_eq!(config.transactions.max_open_per_datasource, 5);
+        assert_eq!(config.streams.idle_timeout(), Duration::from_secs(60));
+        assert_eq!(config.streams.max_open, 10);
+        assert_eq!(config.types.timestamp_time_zone, "UTC");
+        assert_eq!(config.export.directory, None);
+        assert_eq!(config.export.max_file_bytes, 1024 * 1024 * 1024);
+        assert_eq!(config.export.timeout(), Duration::from_secs(3600));
+        assert_eq!(config.import.directory, None);
+        assert_eq!(config.import.batch_rows, 500);
+        assert_eq!(config.import.timeout(), Duration::from_secs(3600));
+        assert_eq!(config.health.interval(), Duration::from_secs(30));
+        assert_eq!(config.health.failure_threshold, 3);
+        assert_eq!(config.health.probe_timeout(), Duration::from_secs(5));
     }
     
     #[test]
@@ -407,13 +1108,9 @@ mod tests {
         let ds = DataSourceConfig {
             key: "".to_string(),
             name: "Test".to_string(),
-            host: "localhost".to_string(),
-            port: 3306,
             username: "user".to_string(),
             password: "pass".to_string(),
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: crate::config::Permission::default(),
+            ..Default::default()
         };
         
         assert!(ds.validate().is_err());
@@ -425,12 +1122,9 @@ mod tests {
             key: "test".to_string(),
             name: "Test".to_string(),
             host: "".to_string(),
-            port: 3306,
             username: "user".to_string(),
             password: "pass".to_string(),
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: crate::config::Permission::default(),
+            ..Default::default()
         };
         
         assert!(ds.validate().is_err());
@@ -441,13 +1135,10 @@ mod tests {
         let ds = DataSourceConfig {
             key: "test".to_string(),
             name: "Test".to_string(),
-            host: "localhost".to_string(),
             port: 0,
             username: "user".to_string(),
             password: "pass".to_string(),
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: crate::config::Permission::default(),
+            ..Default::default()
         };
         
         assert!(ds.validate().is_err());
@@ -458,13 +1149,9 @@ mod tests {
         let ds = DataSourceConfig {
             key: "test".to_string(),
             name: "Test".to_string(),
-            host: "localhost".to_string(),
-            port: 3306,
             username: "".to_string(),
             password: "pass".to_string(),
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: crate::config::Permission::default(),
+            ..Default::default()
         };
         
         assert!(ds.validate().is_err());
@@ -475,13 +1162,9 @@ mod tests {
         let ds = DataSourceConfig {
             key: "test".to_string(),
             name: "Test".to_string(),
-            host: "localhost".to_string(),
-            port: 3306,
             username: "user".to_string(),
             password: "".to_string(),
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: crate::config::Permission::default(),
+            ..Default::default()
         };
         
         assert!(ds.validate().is_err());
@@ -492,18 +1175,69 @@ mod tests {
         let ds = DataSourceConfig {
             key: "test".to_string(),
             name: "Test".to_string(),
-            host: "localhost".to_string(),
-            port: 3306,
             username: "user".to_string(),
             password: "pass".to_string(),
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: crate::config::Permission::default(),
+            ..Default::default()
         };
         
         assert!(ds.validate().is_ok());
     }
     
+    #[test]
+    fn test_datasource_validation_invalid_database_pattern() {
+        let ds = DataSourceConfig {
+            key: "test".to_string(),
+            name: "Test".to_string(),
+            username: "user".to_string(),
+            password: "pass".to_string(),
+            databases: vec!["/app_(/".to_string()],
+            ..Default::default()
+        };
+        
+        assert!(ds.validate().is_err());
+    }
+    
+    #[test]
+    fn test_database_allowlist_empty_allows_all() {
+        let allowlist = DatabaseAllowlist::new(&[]).unwrap();
+        assert!(allowlist.is_unrestricted());
+        assert!(allowlist.allows("anything"));
+    }
+    
+    #[test]
+    fn test_database_allowlist_exact_and_glob() {
+        let allowlist = DatabaseAllowlist::new(&[
+            "reporting".to_string(),
+            "app_*".to_string(),
+            "shard_?".to_string(),
+        ])
+        .unwrap();
+        
+        assert!(allowlist.allows("reporting"));
+        assert!(allowlist.allows("app_"));
+        assert!(allowlist.allows("app_orders"));
+        assert!(allowlist.allows("shard_1"));
+        assert!(!allowlist.allows("shard_10"));
+        assert!(!allowlist.allows("reporting_old"));
+        assert!(!allowlist.allows("my_app_orders"));
+        assert!(!allowlist.allows("mysql"));
+    }
+    
+    #[test]
+    fn test_database_allowlist_escapes_regex_characters_in_globs() {
+        let allowlist = DatabaseAllowlist::new(&["app.db".to_string()]).unwrap();
+        assert!(allowlist.allows("app.db"));
+        assert!(!allowlist.allows("appxdb"));
+    }
+    
+    #[test]
+    fn test_database_allowlist_regex() {
+        let allowlist = DatabaseAllowlist::new(&["/tenant_[0-9]+/".to_string()]).unwrap();
+        assert!(allowlist.allows("tenant_42"));
+        assert!(!allowlist.allows("tenant_42_archive"));
+        assert!(!allowlist.allows("tenant_x"));
+    }
+    
     #[test]
     fn test_pool_config_validation_zero_max_connections() {
         let pool_config = PoolConfig {
@@ -541,7 +1275,17 @@ mod tests {
         let config = ServerConfig {
             data_sources: vec![],
             query_timeout_secs: 30,
+            max_query_timeout_secs: 300,
+            max_rows: 10_000,
+            max_result_bytes: 5_242_880,
             stream_chunk_size: 1000,
+            transactions: TransactionConfig::default(),
+            streams: StreamConfig::default(),
+            types: TypeConfig::default(),
+            export: ExportConfig::default(),
+            import: ImportConfig::default(),
+            health: HealthConfig::default(),
+            retry: RetryConfig::default(),
         };
         
         assert!(config.validate().is_err());
@@ -552,31 +1296,34 @@ mod tests {
         let ds1 = DataSourceConfig {
             key: "test".to_string(),
             name: "Test 1".to_string(),
-            host: "localhost".to_string(),
-            port: 3306,
             username: "user".to_string(),
             password: "pass".to_string(),
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: crate::config::Permission::default(),
+            ..Default::default()
         };
         
         let ds2 = DataSourceConfig {
             key: "test".to_string(),
             name: "Test 2".to_string(),
-            host: "localhost".to_string(),
             port: 3307,
             username: "user".to_string(),
             password: "pass".to_string(),
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: crate::config::Permission::default(),
+            ..Default::default()
         };
         
         let config = ServerConfig {
             data_sources: vec![ds1, ds2],
             query_timeout_secs: 30,
+            max_query_timeout_secs: 300,
+            max_rows: 10_000,
+            max_result_bytes: 5_242_880,
             stream_chunk_size: 1000,
+            transactions: TransactionConfig::default(),
+            streams: StreamConfig::default(),
+            types: TypeConfig::default(),
+            export: ExportConfig::default(),
+            import: ImportConfig::default(),
+            health: HealthConfig::default(),
+            retry: RetryConfig::default(),
         };
         
         assert!(config.validate().is_err());
@@ -587,21 +1334,303 @@ mod tests {
         let ds = DataSourceConfig {
             key: "test".to_string(),
             name: "Test".to_string(),
-            host: "localhost".to_string(),
-            port: 3306,
             username: "user".to_string(),
             password: "pass".to_string(),
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: crate::config::Permission::default(),
+            ..Default::default()
         };
         
         let config = ServerConfig {
             data_sources: vec![ds],
             query_timeout_secs: 30,
+            max_query_timeout_secs: 300,
+            max_rows: 10_000,
+            max_result_bytes: 5_242_880,
+            stream_chunk_size: 1000,
+            transactions: TransactionConfig::default(),
+            streams: StreamConfig::default(),
+            types: TypeConfig::default(),
+            export: ExportConfig::default(),
+            import: ImportConfig::default(),
+            health: HealthConfig::default(),
+            retry: RetryConfig::default(),
+        };
+        
+        assert!(config.validate().is_ok());
+    }
+    
+    #[test]
+    fn test_server_config_validation_query_timeouts() {
+        let ds = DataSourceConfig {
+            key: "test".to_string(),
+            name: "Test".to_string(),
+            username: "user".to_string(),
+            password: "pass".to_string(),
+            query_timeout_secs: Some(120),
+            ..Default::default()
+        };
+        
+        let mut config = ServerConfig {
+            data_sources: vec![ds],
+            query_timeout_secs: 30,
+            max_query_timeout_secs: 300,
+            max_rows: 10_000,
+            max_result_bytes: 5_242_880,
             stream_chunk_size: 1000,
+            transactions: TransactionConfig::default(),
+            streams: StreamConfig::default(),
+            types: TypeConfig::default(),
+            export: ExportConfig::default(),
+            import: ImportConfig::default(),
+            health: HealthConfig::default(),
+            retry: RetryConfig::default(),
         };
+        assert!(config.validate().is_ok());
+        assert_eq!(config.data_sources[0].query_timeout(), Some(Duration::from_secs(120)));
         
+        // A data source override may not exceed the maximum
+        config.max_query_timeout_secs = 60;
+        assert!(config.validate().is_err());
+        
+        // Neither may the server default
+        config.data_sources[0].query_timeout_secs = None;
+        config.query_timeout_secs = 90;
+        assert!(config.validate().is_err());
+        
+        config.query_timeout_secs = 30;
+        config.data_sources[0].query_timeout_secs = Some(0);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_server_config_validation_result_limits() {
+        let ds = DataSourceConfig {
+            key: "test".to_string(),
+            name: "Test".to_string(),
+            username: "user".to_string(),
+            password: "pass".to_string(),
+            max_rows: Some(500),
+            max_result_bytes: Some(65_536),
+            ..Default::default()
+        };
+
+        let mut config = ServerConfig {
+            data_sources: vec![ds],
+            query_timeout_secs: 30,
+            max_query_timeout_secs: 300,
+            max_rows: 10_000,
+            max_result_bytes: 5_242_880,
+            stream_chunk_size: 1000,
+            transactions: TransactionConfig::default(),
+            streams: StreamConfig::default(),
+            types: TypeConfig::default(),
+            export: ExportConfig::default(),
+            import: ImportConfig::default(),
+            health: HealthConfig::default(),
+            retry: RetryConfig::default(),
+        };
+        assert!(config.validate().is_ok());
+
+        // Data source overrides may not exceed the server's limits
+        config.max_rows = 100;
+        assert!(config.validate().is_err());
+        config.max_rows = 10_000;
+        config.max_result_bytes = 1024;
+        assert!(config.validate().is_err());
+        config.max_result_bytes = 5_242_880;
+
+        config.data_sources[0].max_rows = Some(0);
+        assert!(config.validate().is_err());
+        config.data_sources[0].max_rows = None;
+        config.data_sources[0].max_result_bytes = Some(0);
+        assert!(config.validate().is_err());
+
+        config.data_sources[0].max_result_bytes = None;
+        config.max_rows = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_type_config_time_zone() {
+        let mut types = TypeConfig::default();
+        assert_eq!(types.type_mapping().unwrap(), TypeMapping::default());
+
+        types.timestamp_time_zone = "+05:30".to_string();
+        assert_eq!(
+            types.type_mapping().unwrap().timestamp_offset().local_minus_utc(),
+            5 * 3600 + 30 * 60
+        );
+
+        types.timestamp_time_zone = "Europe/Paris".to_string();
+        assert!(matches!(
+            types.type_mapping(),
+            Err(ConfigError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_export_config() {
+        let config: ServerConfig = toml::from_str(
+            r#"
+            [[data_sources]]
+            key = "test"
+            name = "Test"
+            host = "localhost"
+            port = 3306
+            username = "user"
+            password = "pass"
+
+            [export]
+            directory = "/var/lib/mysql-mcp/exports"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.export.directory,
+            Some(PathBuf::from("/var/lib/mysql-mcp/exports"))
+        );
+        assert_eq!(config.export.timeout_secs, 3600);
+        assert!(config.validate().is_ok());
+
+        let mut invalid = config.clone();
+        invalid.export.max_file_bytes = 0;
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = config.clone();
+        invalid.export.timeout_secs = 0;
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = config;
+        invalid.export.directory = Some(PathBuf::new());
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_import_config() {
+        let config: ServerConfig = toml::from_str(
+            r#"
+            [[data_sources]]
+            key = "test"
+            name = "Test"
+            host = "localhost"
+            port = 3306
+            username = "user"
+            password = "pass"
+
+            [import]
+            directory = "/var/lib/mysql-mcp/imports"
+            batch_rows = 1000
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.import.directory,
+            Some(PathBuf::from("/var/lib/mysql-mcp/imports"))
+        );
+        assert_eq!(config.import.batch_rows, 1000);
+        assert!(config.validate().is_ok());
+
+        let mut invalid = config.clone();
+        invalid.import.batch_rows = 0;
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = config;
+        invalid.import.timeout_secs = 0;
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_health_config() {
+        let config: ServerConfig = toml::from_str(
+            r#"
+            [[data_sources]]
+            key = "test"
+            name = "Test"
+            host = "localhost"
+            port = 3306
+            username = "user"
+            password = "pass"
+
+            [health]
+            interval_secs = 10
+            failure_threshold = 5
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.health.interval(), Duration::from_secs(10));
+        assert_eq!(config.health.failure_threshold, 5);
+        assert_eq!(config.health.probe_timeout(), Duration::from_secs(5));
         assert!(config.validate().is_ok());
+
+        let mut invalid = config.clone();
+        invalid.health.interval_secs = 0;
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = config;
+        invalid.health.failure_threshold = 0;
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_circuit_breaker_config() {
+        let config: ServerConfig = toml::from_str(
+            r#"
+            [[data_sources]]
+            key = "test"
+            name = "Test"
+            host = "localhost"
+            port = 3306
+            username = "user"
+            password = "pass"
+
+            [data_sources.circuit_breaker]
+            cool_down_secs = 10
+            "#,
+        )
+        .unwrap();
+        let breaker = &config.data_sources[0].circuit_breaker;
+        assert_eq!(breaker.failure_threshold, 5);
+        assert_eq!(breaker.window(), Duration::from_secs(60));
+        assert_eq!(breaker.cool_down(), Duration::from_secs(10));
+        assert!(config.validate().is_ok());
+
+        let mut invalid = config.clone();
+        invalid.data_sources[0].circuit_breaker.failure_threshold = 0;
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = config;
+        invalid.data_sources[0].circuit_breaker.window_secs = 0;
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_retry_config() {
+        let config: ServerConfig = toml::from_str(
+            r#"
+            [[data_sources]]
+            key = "test"
+            name = "Test"
+            host = "localhost"
+            port = 3306
+            username = "user"
+            password = "pass"
+
+            [retry]
+            max_attempts = 5
+            initial_backoff_ms = 200
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.retry.max_attempts, 5);
+        assert_eq!(config.retry.backoff_duration(0), Duration::from_millis(200));
+        assert_eq!(config.retry.max_backoff_ms, 4000);
+        assert!(config.validate().is_ok());
+
+        let mut invalid = config.clone();
+        invalid.retry.max_attempts = 0;
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = config;
+        invalid.retry.initial_backoff_ms = 5000;
+        assert!(invalid.validate().is_err());
     }
 }
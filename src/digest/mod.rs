@@ -0,0 +1,528 @@
+//! Statement digests: normalised SQL with its run statistics
+//!
+//! `mysql_top_queries` reads `performance_schema.events_statements_summary_by_digest`,
+//! where MySQL has already grouped statements by their normalised text. Where
+//! performance_schema is off, the slow query log is parsed instead and its
+//! statements are grouped here: literals become `?` and lists of them
+//! collapse to `(...)`, so `WHERE id IN (1, 2)` and `WHERE id IN (7)` count
+//! as the same query.
+
+use crate::error::{McpError, Result};
+use crate::sql::{self, TokenKind};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::str::FromStr;
+
+/// How `mysql_top_queries` ranks digests
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TopQueriesOrder {
+    /// Total time spent running the statement
+    #[default]
+    TotalLatency,
+    RowsExamined,
+    Errors,
+}
+
+impl TopQueriesOrder {
+    /// Column of `events_statements_summary_by_digest` to sort by
+    pub fn column(&self) -> &'static str {
+        match self {
+            TopQueriesOrder::TotalLatency => "SUM_TIMER_WAIT",
+            TopQueriesOrder::RowsExamined => "SUM_ROWS_EXAMINED",
+            TopQueriesOrder::Errors => "SUM_ERRORS",
+        }
+    }
+
+    /// Sort digests, highest first
+    pub fn sort(&self, digests: &mut [DigestStats]) {
+        match self {
+            TopQueriesOrder::TotalLatency => {
+                digests.sort_by(|a, b| b.total_latency_ms.total_cmp(&a.total_latency_ms))
+            }
+            TopQueriesOrder::RowsExamined => {
+                digests.sort_by_key(|d| std::cmp::Reverse(d.rows_examined))
+            }
+            TopQueriesOrder::Errors => {
+                digests.sort_by_key(|d| std::cmp::Reverse(d.errors.unwrap_or(0)))
+            }
+        }
+    }
+}
+
+impl FromStr for TopQueriesOrder {
+    type Err = McpError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "total_latency" => Ok(TopQueriesOrder::TotalLatency),
+            "rows_examined" => Ok(TopQueriesOrder::RowsExamined),
+            "errors" => Ok(TopQueriesOrder::Errors),
+            _ => Err(McpError::InvalidStatement(format!(
+                "Unknown order_by '{}'; expected total_latency, rows_examined or errors",
+                s
+            ))),
+        }
+    }
+}
+
+/// Where `mysql_top_queries` reads statement statistics from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestSource {
+    /// performance_schema, or the slow query log if that is off or empty
+    #[default]
+    Auto,
+    PerformanceSchema,
+    SlowLog,
+}
+
+impl FromStr for DigestSource {
+    type Err = McpError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "auto" => Ok(DigestSource::Auto),
+            "performance_schema" => Ok(DigestSource::PerformanceSchema),
+            "slow_log" => Ok(DigestSource::SlowLog),
+            _ => Err(McpError::InvalidStatement(format!(
+                "Unknown source '{}'; expected auto, performance_schema or slow_log",
+                s
+            ))),
+        }
+    }
+}
+
+/// Result of `mysql_top_queries`
+#[derive(Debug, Clone, Serialize)]
+pub struct TopQueries {
+    /// Where the statistics came from: `performance_schema` or `slow_log`
+    pub source: DigestSource,
+    pub order_by: TopQueriesOrder,
+    pub queries: Vec<DigestStats>,
+    /// Why the slow query log was used instead of performance_schema
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+/// Statistics of one normalised statement
+///
+/// Times are in milliseconds. Counters the source does not record are `None`:
+/// the slow query log has no errors or warnings, and p95 needs MySQL 8.0.3 or
+/// later with performance_schema.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DigestStats {
+    /// performance_schema's digest hash
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+    pub schema: Option<String>,
+    /// Normalised statement text
+    pub query: String,
+    pub count: u64,
+    pub total_latency_ms: f64,
+    pub avg_latency_ms: f64,
+    pub p95_latency_ms: Option<f64>,
+    pub max_latency_ms: f64,
+    pub rows_examined: u64,
+    pub avg_rows_examined: f64,
+    pub rows_sent: u64,
+    pub avg_rows_sent: f64,
+    pub errors: Option<u64>,
+    pub warnings: Option<u64>,
+    /// Runs that used no index
+    pub no_index_used: Option<u64>,
+    pub first_seen: Option<String>,
+    pub last_seen: Option<String>,
+}
+
+/// Words after which a `(` is spaced, as opposed to a function call
+const SPACED_BEFORE_PAREN: &[&str] = &[
+    "IN", "VALUES", "VALUE", "AS", "ON", "USING", "FROM", "JOIN", "WHERE", "AND", "OR", "NOT",
+    "EXISTS", "SELECT", "UNION", "ALL", "ANY", "SOME", "INTO", "SET", "BY",
+];
+
+/// Normalise a statement: literals become `?`, literal lists `(...)`, and
+/// whitespace and comments are dropped
+pub fn normalize(statement: &str) -> String {
+    let tokens = sql::tokenize(statement);
+    let mut pieces: Vec<String> = Vec::new();
+
+    for (i, token) in tokens.iter().enumerate() {
+        let piece = match token.kind {
+            TokenKind::String
+            | TokenKind::Number
+            | TokenKind::Placeholder
+            | TokenKind::NamedPlaceholder => "?".to_string(),
+            // Sign of a negative literal: `= -5`
+            TokenKind::Punct('-' | '+')
+                if tokens.get(i + 1).is_some_and(|t| t.kind == TokenKind::Number)
+                    && (i == 0
+                        || matches!(tokens[i - 1].kind, TokenKind::Punct(c) if c != ')')
+                        || tokens[i - 1].kind == TokenKind::Word
+                            && SPACED_BEFORE_PAREN
+                                .contains(&tokens[i - 1].text.to_ascii_uppercase().as_str())) =>
+            {
+                continue
+            }
+            TokenKind::Punct(';') if i + 1 == tokens.len() => continue,
+            _ => token.text.to_string(),
+        };
+        pieces.push(piece);
+
+        // (?, ?, ?) -> (...), then (...), (...) -> (...)
+        if pieces.last().is_some_and(|p| p == ")") {
+            if let Some(open) = pieces.iter().rposition(|p| p == "(") {
+                let inner = &pieces[open + 1..pieces.len() - 1];
+                let literals = !inner.is_empty()
+                    && inner
+                        .iter()
+                        .enumerate()
+                        .all(|(j, p)| if j % 2 == 0 { p == "?" || p == "NULL" } else { p == "," })
+                    && inner.len() % 2 == 1;
+                if literals {
+                    pieces.truncate(open);
+                    pieces.push("(...)".to_string());
+                }
+            }
+            let n = pieces.len();
+            if n >= 3 && pieces[n - 1] == "(...)" && pieces[n - 2] == "," && pieces[n - 3] == "(...)" {
+                pieces.truncate(n - 2);
+            }
+        }
+    }
+
+    let mut text = String::new();
+    let mut previous: Option<&str> = None;
+    for piece in &pieces {
+        if let Some(previous) = previous {
+            let tight = matches!(piece.as_str(), "," | ")" | "." | ";")
+                || matches!(previous, "(" | "." | "@")
+                || (piece.starts_with('(')
+                    && !SPACED_BEFORE_PAREN.contains(&previous.to_ascii_uppercase().as_str())
+                    && previous
+                        .chars()
+                        .next()
+                        .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '`'));
+            if !tight {
+                text.push(' ');
+            }
+        }
+        text.push_str(piece);
+        previous = Some(piece);
+    }
+    text
+}
+
+/// Key under which statements are grouped: the normalised text, case-insensitively
+fn group_key(normalized: &str) -> String {
+    normalized.to_lowercase()
+}
+
+/// A statement from the slow query log
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SlowLogEntry {
+    /// When the statement finished, from `# Time:`
+    pub time: Option<String>,
+    /// Default database, from the last `use` before it
+    pub database: Option<String>,
+    pub query_time_secs: f64,
+    pub lock_time_secs: f64,
+    pub rows_sent: u64,
+    pub rows_examined: u64,
+    pub statement: String,
+}
+
+/// Reads entries of a MySQL slow query log
+///
+/// Each entry is a `# Time:`, `# User@Host:` and `# Query_time:` header,
+/// optional `use db;` and `SET timestamp=...;` lines, and the statement,
+/// which may span lines. Lines the server writes when it starts are skipped.
+pub struct SlowLogReader<R> {
+    lines: std::io::Lines<R>,
+    database: Option<String>,
+    header: Option<SlowLogEntry>,
+    time: Option<String>,
+}
+
+impl<R: BufRead> SlowLogReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+            database: None,
+            header: None,
+            time: None,
+        }
+    }
+
+    /// The entry collected so far, if it has a statement
+    fn take_entry(&mut self) -> Option<SlowLogEntry> {
+        let mut entry = self.header.take()?;
+        let statement = entry.statement.trim().trim_end_matches(';').trim_end();
+        if statement.is_empty() {
+            return None;
+        }
+        entry.statement = statement.to_string();
+        Some(entry)
+    }
+}
+
+impl<R: BufRead> Iterator for SlowLogReader<R> {
+    type Item = std::io::Result<SlowLogEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next() {
+                Some(Ok(line)) => line,
+                Some(Err(e)) => return Some(Err(e)),
+                None => return self.take_entry().map(Ok),
+            };
+
+            if let Some(header) = line.strip_prefix("# ") {
+                if let Some(time) = header.strip_prefix("Time:") {
+                    let entry = self.take_entry();
+                    self.time = Some(time.trim().to_string());
+                    if let Some(entry) = entry {
+                        return Some(Ok(entry));
+                    }
+                } else if header.starts_with("Query_time:") {
+                    let entry = self.take_entry();
+                    self.header = Some(parse_query_time(header, self.time.take()));
+                    if let Some(entry) = entry {
+                        return Some(Ok(entry));
+                    }
+                } else if header.starts_with("User@Host:") {
+                    if let Some(entry) = self.take_entry() {
+                        return Some(Ok(entry));
+                    }
+                }
+                continue;
+            }
+
+            // Written when the server starts, possibly in the middle of the file
+            if line.contains(", Version: ")
+                || line.starts_with("Tcp port:")
+                || line.starts_with("Time ") && line.contains("Command")
+            {
+                continue;
+            }
+
+            let trimmed = line.trim();
+            let lower = trimmed.to_ascii_lowercase();
+            if lower.starts_with("use ") && trimmed.ends_with(';') {
+                let database = trimmed[4..trimmed.len() - 1].trim().trim_matches('`');
+                self.database = Some(database.replace("``", "`"));
+                continue;
+            }
+            if lower.starts_with("set timestamp=") {
+                continue;
+            }
+
+            if let Some(entry) = self.header.as_mut() {
+                if entry.statement.is_empty() {
+                    entry.database = self.database.clone();
+                } else {
+                    entry.statement.push('\n');
+                }
+                entry.statement.push_str(&line);
+            }
+        }
+    }
+}
+
+/// Parse `Query_time: 1.5  Lock_time: 0.1 Rows_sent: 1  Rows_examined: 10`
+fn parse_query_time(header: &str, time: Option<String>) -> SlowLogEntry {
+    let mut entry = SlowLogEntry {
+        time,
+        ..Default::default()
+    };
+    let mut words = header.split_whitespace();
+    while let Some(key) = words.next() {
+        let Some(value) = words.next() else {
+            break;
+        };
+        match key {
+            "Query_time:" => entry.query_time_secs = value.parse().unwrap_or(0.0),
+            "Lock_time:" => entry.lock_time_secs = value.parse().unwrap_or(0.0),
+            "Rows_sent:" => entry.rows_sent = value.parse().unwrap_or(0),
+            "Rows_examined:" => entry.rows_examined = value.parse().unwrap_or(0),
+            _ => {}
+        }
+    }
+    entry
+}
+
+/// Nearest-rank 95th percentile
+pub fn p95(values: &mut [f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let rank = (values.len() as f64 * 0.95).ceil() as usize;
+    Some(values[rank.clamp(1, values.len()) - 1])
+}
+
+/// Groups slow log entries by normalised statement
+#[derive(Debug, Default)]
+pub struct SlowLogAggregator {
+    groups: HashMap<(Option<String>, String), (DigestStats, Vec<f64>)>,
+}
+
+impl SlowLogAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, entry: &SlowLogEntry) {
+        let query = normalize(&entry.statement);
+        let key = (entry.database.clone(), group_key(&query));
+        let latency_ms = entry.query_time_secs * 1000.0;
+
+        let (stats, latencies) = self.groups.entry(key).or_insert_with(|| {
+            (
+                DigestStats {
+                    digest: None,
+                    schema: entry.database.clone(),
+                    query,
+                    count: 0,
+                    total_latency_ms: 0.0,
+                    avg_latency_ms: 0.0,
+                    p95_latency_ms: None,
+                    max_latency_ms: 0.0,
+                    rows_examined: 0,
+                    avg_rows_examined: 0.0,
+                    rows_sent: 0,
+                    avg_rows_sent: 0.0,
+                    errors: None,
+                    warnings: None,
+                    no_index_used: None,
+                    first_seen: entry.time.clone(),
+                    last_seen: None,
+                },
+                Vec::new(),
+            )
+        });
+        stats.count += 1;
+        stats.total_latency_ms += latency_ms;
+        stats.max_latency_ms = stats.max_latency_ms.max(latency_ms);
+        stats.rows_examined += entry.rows_examined;
+        stats.rows_sent += entry.rows_sent;
+        if entry.time.is_some() {
+            stats.first_seen = stats.first_seen.take().or_else(|| entry.time.clone());
+            stats.last_seen = entry.time.clone();
+        }
+        latencies.push(latency_ms);
+    }
+
+    /// Statistics of every statement seen, in no particular order
+    pub fn finish(self) -> Vec<DigestStats> {
+        self.groups
+            .into_values()
+            .map(|(mut stats, mut latencies)| {
+                let count = stats.count.max(1) as f64;
+                stats.avg_latency_ms = stats.total_latency_ms / count;
+                stats.avg_rows_examined = stats.rows_examined as f64 / count;
+                stats.avg_rows_sent = stats.rows_sent as f64 / count;
+                stats.p95_latency_ms = p95(&mut latencies);
+                stats
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SLOW_LOG: &str = "\
+/usr/sbin/mysqld, Version: 8.0.36 (MySQL Community Server - GPL). started with:
+Tcp port: 3306  Unix socket: /var/run/mysqld/mysqld.sock
+Time                 Id Command    Argument
+# Time: 2024-05-01T10:00:00.000000Z
+# User@Host: app[app] @ localhost []  Id:    12
+# Query_time: 2.000000  Lock_time: 0.000100 Rows_sent: 1  Rows_examined: 50000
+use shop;
+SET timestamp=1714557600;
+SELECT * FROM orders
+WHERE customer_id = 42;
+# Time: 2024-05-01T10:00:05.000000Z
+# User@Host: app[app] @ localhost []  Id:    13
+# Query_time: 4.000000  Lock_time: 0.000000 Rows_sent: 0  Rows_examined: 70000
+SET timestamp=1714557605;
+select * from orders where customer_id = 7;
+# Time: 2024-05-01T10:00:06.000000Z
+# User@Host: app[app] @ localhost []  Id:    12
+# Query_time: 0.500000  Lock_time: 0.000000 Rows_sent: 3  Rows_examined: 3
+SET timestamp=1714557606;
+SELECT id FROM items WHERE id IN (1, 2, 3);
+";
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize("SELECT * FROM t WHERE a = 'x' AND b > -5.5 AND c IN (1, 2, 3) LIMIT 10;"),
+            "SELECT * FROM t WHERE a = ? AND b > ? AND c IN (...) LIMIT ?"
+        );
+        assert_eq!(
+            normalize("INSERT INTO t (a, b) VALUES (1, 'x'), (2, NULL)"),
+            "INSERT INTO t(a, b) VALUES (...)"
+        );
+        assert_eq!(
+            normalize("select count(*), `t`.a from t /* comment */ where a-1 = ?"),
+            "select count(*), `t`.a from t where a - ? = ?"
+        );
+    }
+
+    #[test]
+    fn test_slow_log_reader() {
+        let entries: Vec<SlowLogEntry> = SlowLogReader::new(SLOW_LOG.as_bytes())
+            .collect::<std::io::Result<_>>()
+            .unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(
+            entries[0],
+            SlowLogEntry {
+                time: Some("2024-05-01T10:00:00.000000Z".to_string()),
+                database: Some("shop".to_string()),
+                query_time_secs: 2.0,
+                lock_time_secs: 0.0001,
+                rows_sent: 1,
+                rows_examined: 50000,
+                statement: "SELECT * FROM orders\nWHERE customer_id = 42".to_string(),
+            }
+        );
+        // The database carries over until the next `use`
+        assert_eq!(entries[2].database.as_deref(), Some("shop"));
+    }
+
+    #[test]
+    fn test_slow_log_aggregation() {
+        let mut aggregator = SlowLogAggregator::new();
+        for entry in SlowLogReader::new(SLOW_LOG.as_bytes()) {
+            aggregator.add(&entry.unwrap());
+        }
+        let mut digests = aggregator.finish();
+        TopQueriesOrder::TotalLatency.sort(&mut digests);
+
+        assert_eq!(digests.len(), 2);
+        let orders = &digests[0];
+        assert_eq!(orders.query, "SELECT * FROM orders WHERE customer_id = ?");
+        assert_eq!(orders.count, 2);
+        assert_eq!(orders.total_latency_ms, 6000.0);
+        assert_eq!(orders.avg_latency_ms, 3000.0);
+        assert_eq!(orders.p95_latency_ms, Some(4000.0));
+        assert_eq!(orders.avg_rows_examined, 60000.0);
+        assert_eq!(orders.first_seen.as_deref(), Some("2024-05-01T10:00:00.000000Z"));
+        assert_eq!(orders.last_seen.as_deref(), Some("2024-05-01T10:00:05.000000Z"));
+        assert_eq!(orders.errors, None);
+        assert_eq!(digests[1].query, "SELECT id FROM items WHERE id IN (...)");
+    }
+
+    #[test]
+    fn test_p95() {
+        assert_eq!(p95(&mut []), None);
+        assert_eq!(p95(&mut [3.0]), Some(3.0));
+        let mut values: Vec<f64> = (1..=100).rev().map(f64::from).collect();
+        assert_eq!(p95(&mut values), Some(95.0));
+    }
+}
@@ -0,0 +1,728 @@
+//! Query plans from `EXPLAIN FORMAT=JSON` and `EXPLAIN ANALYZE`
+//!
+//! `mysql_explain` parses the server's JSON plan into a tree of [`PlanNode`]s,
+//! one per operation (query block, join, sort, table access, subquery), and
+//! checks it against a few rules for common causes of slow queries, reported
+//! as [`RedFlag`]s. With `analyze`, the statement is also run under
+//! `EXPLAIN ANALYZE` and its timed plan parsed into [`AnalyzeNode`]s.
+
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::sync::OnceLock;
+
+/// Row estimate from which scans and sorts are reported as warnings
+pub const LARGE_TABLE_ROWS: f64 = 10_000.0;
+
+/// Rows read from which a low `filtered` percentage is reported
+const FILTER_MIN_ROWS: f64 = 1_000.0;
+
+/// `filtered` percentage below which a table access reads mostly rows it discards
+const LOW_FILTERED_PERCENT: f64 = 10.0;
+
+/// Factor by which an estimate may be off before it is reported
+const MISESTIMATE_FACTOR: f64 = 10.0;
+
+/// Keys holding objects that are not operations
+const NON_OPERATION_KEYS: &[&str] = &["cost_info", "windows"];
+
+/// One operation of a query plan
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PlanNode {
+    /// `query_block`, `table`, `nested_loop`, `ordering_operation`, ...
+    pub operation: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub select_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table: Option<String>,
+    /// `system`, `const`, `eq_ref`, `ref`, `range`, `index`, `ALL`, ...
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_type: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub possible_keys: Vec<String>,
+    /// The index chosen
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub used_key_parts: Vec<String>,
+    /// Estimated rows read per scan of the table
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rows_examined: Option<f64>,
+    /// Estimated rows joined with the previous tables
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rows_produced: Option<f64>,
+    /// Estimated percentage of rows read that the table's condition keeps
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filtered: Option<f64>,
+    /// Cost of the query block, or of the join up to and including this table
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub using_temporary: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub using_filesort: bool,
+    /// Reads only the index, not the rows
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub using_index: bool,
+    /// `hash join`, `Block Nested Loop`, ...: the join does not use an index
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub using_join_buffer: Option<String>,
+    /// A subquery evaluated again for every row of the outer query
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub dependent: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<PlanNode>,
+}
+
+impl PlanNode {
+    /// Parse the document returned by `EXPLAIN FORMAT=JSON`
+    pub fn from_json(plan: &Value) -> Self {
+        match plan.get("query_block") {
+            Some(Value::Object(block)) => parse_operation("query_block", block),
+            _ => match plan {
+                Value::Object(root) => parse_operation("plan", root),
+                _ => PlanNode {
+                    operation: "plan".to_string(),
+                    ..Default::default()
+                },
+            },
+        }
+    }
+
+    /// Largest row estimate of this operation and those below it
+    fn max_rows(&self) -> Option<f64> {
+        self.children
+            .iter()
+            .filter_map(PlanNode::max_rows)
+            .chain(self.rows_produced)
+            .chain(self.rows_examined)
+            .reduce(f64::max)
+    }
+
+    fn visit<'a>(&'a self, f: &mut impl FnMut(&'a PlanNode)) {
+        f(self);
+        for child in &self.children {
+            child.visit(f);
+        }
+    }
+}
+
+fn parse_operation(operation: &str, object: &Map<String, Value>) -> PlanNode {
+    let text = |key: &str| object.get(key).and_then(Value::as_str).map(str::to_string);
+    let flag = |key: &str| object.get(key).and_then(Value::as_bool).unwrap_or(false);
+    let strings = |key: &str| -> Vec<String> {
+        object
+            .get(key)
+            .and_then(Value::as_array)
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let cost_info = object.get("cost_info");
+    let cost = |key: &str| cost_info.and_then(|info| number(info.get(key)?));
+
+    let mut children = Vec::new();
+    for (key, value) in object {
+        if NON_OPERATION_KEYS.contains(&key.as_str()) {
+            continue;
+        }
+        match value {
+            Value::Object(child) => children.push(parse_child(key, child)),
+            Value::Array(items) => {
+                let items: Vec<PlanNode> = items
+                    .iter()
+                    .filter_map(Value::as_object)
+                    .map(|item| parse_child(key, item))
+                    .collect();
+                if !items.is_empty() {
+                    children.push(PlanNode {
+                        operation: key.clone(),
+                        children: items,
+                        ..Default::default()
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let windows_filesort = object
+        .get("windows")
+        .and_then(Value::as_array)
+        .is_some_and(|windows| {
+            windows
+                .iter()
+                .any(|window| window.get("using_filesort") == Some(&Value::Bool(true)))
+        });
+
+    PlanNode {
+        // Later JSON plan versions name each operation themselves
+        operation: text("operation").unwrap_or_else(|| operation.to_string()),
+        select_id: object.get("select_id").and_then(Value::as_u64),
+        table: text("table_name").or_else(|| text("alias")),
+        access_type: text("access_type"),
+        possible_keys: strings("possible_keys"),
+        key: text("key").or_else(|| text("index_name")),
+        used_key_parts: strings("used_key_parts"),
+        rows_examined: ["rows_examined_per_scan", "rows", "estimated_rows"]
+            .iter()
+            .find_map(|key| number(object.get(*key)?)),
+        rows_produced: object.get("rows_produced_per_join").and_then(number),
+        filtered: object.get("filtered").and_then(number),
+        cost: cost("query_cost")
+            .or_else(|| cost("prefix_cost"))
+            .or_else(|| ["cost", "estimated_total_cost"].iter().find_map(|key| number(object.get(*key)?))),
+        using_temporary: flag("using_temporary_table") || flag("using_temporary"),
+        using_filesort: flag("using_filesort") || windows_filesort,
+        using_index: flag("using_index"),
+        using_join_buffer: text("using_join_buffer"),
+        dependent: flag("dependent"),
+        condition: text("attached_condition"),
+        message: text("message"),
+        children,
+    }
+}
+
+/// Parse an object under `key`, unwrapping `{"table": {...}}` and the like
+fn parse_child(key: &str, object: &Map<String, Value>) -> PlanNode {
+    match object.iter().next() {
+        Some((inner, Value::Object(child))) if object.len() == 1 => parse_operation(inner, child),
+        _ => parse_operation(key, object),
+    }
+}
+
+/// A number, or a string holding one as MySQL writes costs
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// One operation of an `EXPLAIN ANALYZE` plan
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AnalyzeNode {
+    /// `Table scan on t`, `Filter: (t.a > 1)`, `Sort: t.b`, ...
+    pub operation: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_cost: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_rows: Option<f64>,
+    /// Milliseconds until the first row, averaged over loops
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_row_ms: Option<f64>,
+    /// Milliseconds until the last row, averaged over loops
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_row_ms: Option<f64>,
+    /// Rows returned, averaged over loops
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual_rows: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub loops: Option<u64>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub never_executed: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<AnalyzeNode>,
+}
+
+impl AnalyzeNode {
+    /// Parse the tree returned by `EXPLAIN ANALYZE`
+    ///
+    /// Each operation is a line starting with `->`, indented by four spaces
+    /// per level. Lines that are not operations continue the one before.
+    pub fn parse(text: &str) -> Option<Self> {
+        static ESTIMATE: OnceLock<Regex> = OnceLock::new();
+        static ACTUAL: OnceLock<Regex> = OnceLock::new();
+        let estimate = ESTIMATE.get_or_init(|| {
+            Regex::new(r"\(cost=([\d.e+]+)(?:\.\.[\d.e+]+)? rows=([\d.e+]+)\)").unwrap()
+        });
+        let actual = ACTUAL.get_or_init(|| {
+            Regex::new(r"\(actual time=([\d.e+]+)\.\.([\d.e+]+) rows=([\d.e+]+) loops=(\d+)\)")
+                .unwrap()
+        });
+
+        // Nodes still open, with their depth; each is attached to the one
+        // before it once a line at its depth or above closes it
+        let mut open: Vec<(usize, AnalyzeNode)> = Vec::new();
+        let close = |open: &mut Vec<(usize, AnalyzeNode)>, depth: usize| {
+            while open.len() > 1 && open.last().is_some_and(|(d, _)| *d >= depth) {
+                let (_, node) = open.pop().unwrap();
+                open.last_mut().unwrap().1.children.push(node);
+            }
+        };
+
+        for line in text.lines() {
+            let trimmed = line.trim_start();
+            let Some(rest) = trimmed.strip_prefix("->") else {
+                if let Some((_, node)) = open.last_mut() {
+                    if !trimmed.is_empty() {
+                        node.operation.push(' ');
+                        node.operation.push_str(trimmed);
+                    }
+                }
+                continue;
+            };
+            let depth = (line.len() - trimmed.len()) / 4;
+            let rest = rest.trim();
+
+            let operation_end = [" (cost=", " (actual time=", " (never executed)"]
+                .iter()
+                .filter_map(|marker| rest.find(marker))
+                .min()
+                .unwrap_or(rest.len());
+            let estimate = estimate.captures(rest);
+            let actual = actual.captures(rest);
+            let parse = |captures: &Option<regex::Captures>, index: usize| {
+                captures.as_ref().and_then(|c| c[index].parse::<f64>().ok())
+            };
+            let node = AnalyzeNode {
+                operation: rest[..operation_end].trim().to_string(),
+                estimated_cost: parse(&estimate, 1),
+                estimated_rows: parse(&estimate, 2),
+                first_row_ms: parse(&actual, 1),
+                last_row_ms: parse(&actual, 2),
+                actual_rows: parse(&actual, 3),
+                loops: actual.as_ref().and_then(|c| c[4].parse().ok()),
+                never_executed: rest.contains("(never executed)"),
+                children: Vec::new(),
+            };
+
+            if open.is_empty() {
+                open.push((depth, node));
+            } else {
+                close(&mut open, depth.max(1));
+                open.push((depth.max(1), node));
+            }
+        }
+
+        close(&mut open, 1);
+        open.pop().map(|(_, node)| node)
+    }
+
+    fn visit<'a>(&'a self, f: &mut impl FnMut(&'a AnalyzeNode)) {
+        f(self);
+        for child in &self.children {
+            child.visit(f);
+        }
+    }
+}
+
+/// Kind of problem found in a plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedFlagKind {
+    /// Reads every row of a table
+    FullTableScan,
+    /// Reads every entry of an index
+    FullIndexScan,
+    /// Sorts rows instead of reading them in index order
+    Filesort,
+    /// Builds an internal temporary table
+    TemporaryTable,
+    /// Indexes could serve the table but none was chosen
+    UnusedIndex,
+    /// Joins without an index, through a join buffer
+    JoinBuffer,
+    /// A subquery runs again for every outer row
+    DependentSubquery,
+    /// Reads many rows only to discard most of them
+    LowFiltered,
+    /// `EXPLAIN ANALYZE` found far more or fewer rows than estimated
+    RowMisestimate,
+}
+
+/// How much a problem is likely to matter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+}
+
+/// A likely cause of a slow query, found by a rule
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RedFlag {
+    pub kind: RedFlagKind,
+    pub severity: Severity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table: Option<String>,
+    pub message: String,
+}
+
+/// Check a plan for common causes of slow queries, warnings first
+pub fn red_flags(plan: &PlanNode, analyze: Option<&AnalyzeNode>) -> Vec<RedFlag> {
+    let mut flags = Vec::new();
+    let size = |rows: Option<f64>| {
+        if rows.is_some_and(|rows| rows >= LARGE_TABLE_ROWS) {
+            Severity::Warning
+        } else {
+            Severity::Info
+        }
+    };
+
+    plan.visit(&mut |node| {
+        let table = node.table.clone();
+        let name = table.as_deref().unwrap_or("a table");
+        let mut flag = |kind, severity, message: String| {
+            flags.push(RedFlag {
+                kind,
+                severity,
+                table: table.clone(),
+                message,
+            })
+        };
+
+        match node.access_type.as_deref() {
+            Some("ALL") => {
+                let hint = if node.possible_keys.is_empty() {
+                    "; no index matches its conditions"
+                } else {
+                    ""
+                };
+                flag(
+                    RedFlagKind::FullTableScan,
+                    size(node.rows_examined),
+                    format!("Full scan of {}{}{}", name, rows_text(node.rows_examined), hint),
+                );
+            }
+            Some("index") => flag(
+                RedFlagKind::FullIndexScan,
+                size(node.rows_examined),
+                format!(
+                    "Full scan of index {} on {}{}",
+                    node.key.as_deref().unwrap_or("?"),
+                    name,
+                    rows_text(node.rows_examined)
+                ),
+            ),
+            _ => {}
+        }
+
+        if node.key.is_none() && !node.possible_keys.is_empty() {
+            flag(
+                RedFlagKind::UnusedIndex,
+                Severity::Warning,
+                format!(
+                    "Indexes {} could serve {} but none was used",
+                    node.possible_keys.join(", "),
+                    name
+                ),
+            );
+        }
+
+        if node.using_filesort {
+            let rows = node.max_rows();
+            flag(
+                RedFlagKind::Filesort,
+                size(rows),
+                format!("Rows are sorted without an index{}", rows_text(rows)),
+            );
+        }
+
+        if node.using_temporary {
+            let rows = node.max_rows();
+            flag(
+                RedFlagKind::TemporaryTable,
+                size(rows),
+                format!("An internal temporary table is built{}", rows_text(rows)),
+            );
+        }
+
+        if let Some(buffer) = &node.using_join_buffer {
+            flag(
+                RedFlagKind::JoinBuffer,
+                Severity::Warning,
+                format!("{} is joined without an index ({})", name, buffer),
+            );
+        }
+
+        if node.dependent {
+            flag(
+                RedFlagKind::DependentSubquery,
+                Severity::Warning,
+                "A dependent subquery runs again for every row of the outer query".to_string(),
+            );
+        }
+
+        if let (Some(filtered), Some(rows)) = (node.filtered, node.rows_examined) {
+            if filtered < LOW_FILTERED_PERCENT && rows >= FILTER_MIN_ROWS {
+                flag(
+                    RedFlagKind::LowFiltered,
+                    Severity::Info,
+                    format!(
+                        "Only {}% of the {} rows read from {} are kept; an index on the filtered columns may help",
+                        filtered, rows, name
+                    ),
+                );
+            }
+        }
+    });
+
+    if let Some(analyze) = analyze {
+        analyze.visit(&mut |node| {
+            let (Some(estimated), Some(actual)) = (node.estimated_rows, node.actual_rows) else {
+                return;
+            };
+            let larger = estimated.max(actual);
+            let smaller = estimated.min(actual).max(1.0);
+            if larger >= FILTER_MIN_ROWS && larger / smaller >= MISESTIMATE_FACTOR {
+                flags.push(RedFlag {
+                    kind: RedFlagKind::RowMisestimate,
+                    severity: Severity::Info,
+                    table: None,
+                    message: format!(
+                        "{}: estimated {} rows, found {}; ANALYZE TABLE may refresh the statistics",
+                        node.operation, estimated, actual
+                    ),
+                });
+            }
+        });
+    }
+
+    flags.sort_by_key(|flag| std::cmp::Reverse(flag.severity));
+    flags
+}
+
+fn rows_text(rows: Option<f64>) -> String {
+    rows.map(|rows| format!(" (about {} rows)", rows))
+        .unwrap_or_default()
+}
+
+/// Check whether a server version supports `EXPLAIN ANALYZE` (MySQL 8.0.18+)
+pub fn supports_analyze(version: &str) -> bool {
+    if version.to_lowercase().contains("mariadb") {
+        return false;
+    }
+    let numbers: Vec<u32> = version
+        .split(|c: char| !c.is_ascii_digit())
+        .take(3)
+        .map(|part| part.parse().unwrap_or(0))
+        .collect();
+    match numbers.as_slice() {
+        [major, minor, patch] => (*major, *minor, *patch) >= (8, 0, 18),
+        _ => false,
+    }
+}
+
+/// Result of `mysql_explain`
+#[derive(Debug, Clone, Serialize)]
+pub struct ExplainResult {
+    /// Estimated cost of the whole statement
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_cost: Option<f64>,
+    pub red_flags: Vec<RedFlag>,
+    pub plan: PlanNode,
+    /// The timed plan, with `analyze`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub analyze: Option<AnalyzeNode>,
+}
+
+impl ExplainResult {
+    /// Build the result from the output of `EXPLAIN FORMAT=JSON` and, if it
+    /// ran, `EXPLAIN ANALYZE`
+    pub fn new(plan: &Value, analyze: Option<&str>) -> Self {
+        let plan = PlanNode::from_json(plan);
+        let analyze = analyze.and_then(AnalyzeNode::parse);
+        Self {
+            total_cost: plan.cost,
+            red_flags: red_flags(&plan, analyze.as_ref()),
+            plan,
+            analyze,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn join_plan() -> Value {
+        json!({
+            "query_block": {
+                "select_id": 1,
+                "cost_info": {"query_cost": "25230.40"},
+                "ordering_operation": {
+                    "using_temporary_table": true,
+                    "using_filesort": true,
+                    "nested_loop": [
+                        {
+                            "table": {
+                                "table_name": "o",
+                                "access_type": "ALL",
+                                "possible_keys": ["idx_customer"],
+                                "rows_examined_per_scan": 50000,
+                                "rows_produced_per_join": 2500,
+                                "filtered": "5.00",
+                                "cost_info": {"read_cost": "100.0", "prefix_cost": "5100.00"},
+                                "attached_condition": "(`shop`.`o`.`status` = 'open')"
+                            }
+                        },
+                        {
+                            "table": {
+                                "table_name": "c",
+                                "access_type": "eq_ref",
+                                "possible_keys": ["PRIMARY"],
+                                "key": "PRIMARY",
+                                "used_key_parts": ["id"],
+                                "rows_examined_per_scan": 1,
+                                "rows_produced_per_join": 2500,
+                                "filtered": "100.00",
+                                "using_index": true,
+                                "cost_info": {"prefix_cost": "7600.00"}
+                            }
+                        }
+                    ]
+                },
+                "attached_subqueries": [
+                    {
+                        "dependent": true,
+                        "cacheable": false,
+                        "query_block": {
+                            "select_id": 2,
+                            "table": {
+                                "table_name": "i",
+                                "access_type": "index",
+                                "key": "idx_order",
+                                "rows_examined_per_scan": 20,
+                                "using_join_buffer": "hash join"
+                            }
+                        }
+                    }
+                ]
+            }
+        })
+    }
+
+    #[test]
+    fn test_plan_tree() {
+        let plan = PlanNode::from_json(&join_plan());
+        assert_eq!(plan.operation, "query_block");
+        assert_eq!(plan.select_id, Some(1));
+        assert_eq!(plan.cost, Some(25230.4));
+        assert_eq!(plan.children.len(), 2);
+
+        let subqueries = &plan.children[0];
+        assert_eq!(subqueries.operation, "attached_subqueries");
+        assert!(subqueries.children[0].dependent);
+        assert_eq!(subqueries.children[0].children[0].select_id, Some(2));
+
+        let ordering = &plan.children[1];
+        assert_eq!(ordering.operation, "ordering_operation");
+        assert!(ordering.using_filesort && ordering.using_temporary);
+
+        let join = &ordering.children[0];
+        assert_eq!(join.operation, "nested_loop");
+        let orders = &join.children[0];
+        assert_eq!(orders.operation, "table");
+        assert_eq!(orders.table.as_deref(), Some("o"));
+        assert_eq!(orders.access_type.as_deref(), Some("ALL"));
+        assert_eq!(orders.possible_keys, ["idx_customer"]);
+        assert_eq!(orders.key, None);
+        assert_eq!(orders.rows_examined, Some(50000.0));
+        assert_eq!(orders.filtered, Some(5.0));
+        assert_eq!(orders.cost, Some(5100.0));
+        let customers = &join.children[1];
+        assert_eq!(customers.key.as_deref(), Some("PRIMARY"));
+        assert_eq!(customers.used_key_parts, ["id"]);
+        assert!(customers.using_index);
+    }
+
+    #[test]
+    fn test_red_flags() {
+        let plan = PlanNode::from_json(&join_plan());
+        let flags = red_flags(&plan, None);
+        let kinds: Vec<RedFlagKind> = flags.iter().map(|flag| flag.kind).collect();
+
+        for kind in [
+            RedFlagKind::FullTableScan,
+            RedFlagKind::FullIndexScan,
+            RedFlagKind::Filesort,
+            RedFlagKind::TemporaryTable,
+            RedFlagKind::UnusedIndex,
+            RedFlagKind::JoinBuffer,
+            RedFlagKind::DependentSubquery,
+            RedFlagKind::LowFiltered,
+        ] {
+            assert!(kinds.contains(&kind), "{:?} not flagged in {:?}", kind, kinds);
+        }
+
+        let scan = flags
+            .iter()
+            .find(|flag| flag.kind == RedFlagKind::FullTableScan)
+            .unwrap();
+        assert_eq!(scan.severity, Severity::Warning);
+        assert_eq!(scan.table.as_deref(), Some("o"));
+        let index_scan = flags
+            .iter()
+            .find(|flag| flag.kind == RedFlagKind::FullIndexScan)
+            .unwrap();
+        assert_eq!(index_scan.severity, Severity::Info);
+
+        // Warnings come first
+        assert!(flags.windows(2).all(|pair| pair[0].severity >= pair[1].severity));
+    }
+
+    #[test]
+    fn test_good_plan_has_no_red_flags() {
+        let plan = PlanNode::from_json(&json!({
+            "query_block": {
+                "select_id": 1,
+                "cost_info": {"query_cost": "1.00"},
+                "table": {
+                    "table_name": "users",
+                    "access_type": "const",
+                    "possible_keys": ["PRIMARY"],
+                    "key": "PRIMARY",
+                    "rows_examined_per_scan": 1,
+                    "filtered": "100.00"
+                }
+            }
+        }));
+        assert_eq!(plan.children[0].table.as_deref(), Some("users"));
+        assert!(red_flags(&plan, None).is_empty());
+    }
+
+    #[test]
+    fn test_analyze_tree() {
+        let text = "-> Sort: o.created_at  (cost=5.2 rows=20) (actual time=0.9..0.95 rows=15000 loops=1)\n    -> Filter: (o.status = 'open')  (cost=2.25 rows=20) (actual time=0.05..0.8 rows=15000 loops=1)\n        -> Table scan on o  (cost=2.25 rows=200) (actual time=0.04..0.6 rows=20000 loops=1)\n    -> Index lookup on c using PRIMARY (id=o.customer_id)  (cost=0.25 rows=1) (never executed)\n";
+        let root = AnalyzeNode::parse(text).unwrap();
+        assert_eq!(root.operation, "Sort: o.created_at");
+        assert_eq!(root.estimated_cost, Some(5.2));
+        assert_eq!(root.estimated_rows, Some(20.0));
+        assert_eq!(root.first_row_ms, Some(0.9));
+        assert_eq!(root.last_row_ms, Some(0.95));
+        assert_eq!(root.actual_rows, Some(15000.0));
+        assert_eq!(root.loops, Some(1));
+        assert_eq!(root.children.len(), 2);
+        assert_eq!(root.children[0].children[0].operation, "Table scan on o");
+        assert!(root.children[1].never_executed);
+        assert_eq!(root.children[1].actual_rows, None);
+
+        let plan = PlanNode::from_json(&json!({"query_block": {"select_id": 1}}));
+        let flags = red_flags(&plan, Some(&root));
+        assert_eq!(flags.len(), 3);
+        assert!(flags.iter().all(|flag| flag.kind == RedFlagKind::RowMisestimate));
+    }
+
+    #[test]
+    fn test_supports_analyze() {
+        assert!(supports_analyze("8.0.18"));
+        assert!(supports_analyze("8.0.35-0ubuntu0.22.04.1"));
+        assert!(supports_analyze("8.4.0"));
+        assert!(!supports_analyze("8.0.17"));
+        assert!(!supports_analyze("5.7.44-log"));
+        assert!(!supports_analyze("10.11.6-MariaDB"));
+        assert!(!supports_analyze(""));
+    }
+}
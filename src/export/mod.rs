@@ -0,0 +1,923 @@
+//! Query result export to files
+//!
+//! `mysql_export` writes the rows of a query to a file in the configured export
+//! directory instead of returning them. Rows are written as they are read, in
+//! batches, so an export never holds the whole result in memory.
+//!
+//! - `csv`: header row and RFC 4180 quoting, as the `csv` result format
+//! - `ndjson`: one JSON object per row, as the `ndjson` result format
+//! - `parquet`: one optional column per result column; integers as `INT64`,
+//!   floating point as `DOUBLE`, binary and geometry columns as raw bytes and
+//!   everything else as UTF-8 strings in its JSON text form (`DECIMAL` stays
+//!   exact)
+//!
+//! CSV and NDJSON files are compressed as a whole with gzip or zstd; Parquet
+//! files compress their column chunks instead, so they stay readable by Parquet
+//! tools.
+//!
+//! A file is written under a temporary name next to its destination and only
+//! renamed into place once complete, so a failed or cancelled export leaves
+//! nothing behind.
+
+use crate::error::{McpError, Result};
+use crate::format::{self, ResultFormat};
+use crate::tools::{ColumnMetadata, QueryRow};
+use crate::types::ColumnKind;
+use base64::Engine;
+use parquet::basic::{LogicalType, Repetition, Type as PhysicalType};
+use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::types::Type as SchemaType;
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Rows per Parquet row group
+const ROW_GROUP_ROWS: usize = 10_000;
+
+/// File format of an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = McpError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "ndjson" | "jsonl" => Ok(ExportFormat::Ndjson),
+            "parquet" => Ok(ExportFormat::Parquet),
+            _ => Err(McpError::InvalidStatement(format!(
+                "Unknown export format '{}'; expected csv, ndjson or parquet",
+                s
+            ))),
+        }
+    }
+}
+
+/// Compression of an export file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Compression {
+    type Err = McpError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "none" => Ok(Compression::None),
+            "gzip" | "gz" => Ok(Compression::Gzip),
+            "zstd" | "zst" => Ok(Compression::Zstd),
+            _ => Err(McpError::InvalidStatement(format!(
+                "Unknown compression '{}'; expected none, gzip or zstd",
+                s
+            ))),
+        }
+    }
+}
+
+/// What to write and where
+#[derive(Debug, Clone)]
+pub struct ExportTarget {
+    /// Destination, inside the export directory
+    pub path: PathBuf,
+    pub format: ExportFormat,
+    pub compression: Compression,
+    /// Replace an existing file at `path`
+    pub overwrite: bool,
+    /// Maximum size of the file in bytes
+    pub max_bytes: u64,
+}
+
+/// A completed export
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportedFile {
+    pub path: String,
+    pub format: ExportFormat,
+    pub compression: Compression,
+    pub rows: u64,
+    /// Size of the file in bytes
+    pub bytes: u64,
+    /// SHA-256 of the file, hex encoded
+    pub sha256: String,
+}
+
+/// Resolve a file name relative to the export directory
+///
+/// Only plain relative paths are accepted; missing subdirectories are created.
+/// Symbolic links inside the directory may not lead out of it, and nothing is
+/// created before that has been checked.
+pub fn resolve_path(root: &Path, name: &str) -> Result<PathBuf> {
+    let relative = Path::new(name);
+    let file_name = match relative.file_name() {
+        Some(file_name) if is_plain_relative(name) => file_name,
+        _ => {
+            return Err(McpError::InvalidStatement(format!(
+                "Export file '{}' must be a relative path inside the export directory, without '.' or '..'",
+                name
+            )))
+        }
+    };
+
+    let root = root.canonicalize().map_err(|e| {
+        McpError::ConfigurationError(format!(
+            "Export directory '{}' is not accessible: {}",
+            root.display(),
+            e
+        ))
+    })?;
+    let parent = root.join(relative.parent().unwrap_or(Path::new("")));
+    // The deepest directory that exists decides where missing ones would be created
+    let existing = parent
+        .ancestors()
+        .find(|dir| dir.symlink_metadata().is_ok())
+        .unwrap_or(&root);
+    inside_root(&root, existing, name)?;
+    fs::create_dir_all(&parent).map_err(io_error)?;
+    let parent = inside_root(&root, &parent, name)?;
+
+    Ok(parent.join(file_name))
+}
+
+/// Canonicalize a path and check that it is inside the export directory `root`
+fn inside_root(root: &Path, path: &Path, name: &str) -> Result<PathBuf> {
+    let path = path.canonicalize().map_err(io_error)?;
+    if !path.starts_with(root) {
+        return Err(McpError::PermissionDenied(format!(
+            "Export file '{}' resolves outside the export directory",
+            name
+        )));
+    }
+    Ok(path)
+}
+
+/// Whether a file name is a relative path of plain components, without `.`, `..`
+/// or a trailing separator
+pub(crate) fn is_plain_relative(name: &str) -> bool {
+    !name.is_empty()
+        && !name.ends_with('/')
+        && Path::new(name)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Write the rows received from `rows` to `target`
+///
+/// Each message is a batch of rows; `None` marks the end of the result. If the
+/// sender goes away before that, the export is abandoned and no file is left.
+/// Blocks, so it belongs on a blocking task.
+pub fn write_file(
+    target: ExportTarget,
+    columns: Vec<ColumnMetadata>,
+    mut rows: mpsc::Receiver<Option<Vec<QueryRow>>>,
+) -> Result<ExportedFile> {
+    let mut writer = ExportWriter::create(target, columns)?;
+    loop {
+        match rows.blocking_recv() {
+            Some(Some(batch)) => writer.write_rows(&batch)?,
+            Some(None) => return writer.finish(),
+            None => {
+                return Err(McpError::QueryExecutionError(
+                    "Export stopped before the result ended".to_string(),
+                ))
+            }
+        }
+    }
+}
+
+/// Writes rows to a temporary file that becomes the export once finished
+///
+/// Dropping an unfinished writer removes the temporary file.
+pub struct ExportWriter {
+    target: ExportTarget,
+    temp_path: PathBuf,
+    columns: Vec<ColumnMetadata>,
+    body: Option<Body>,
+    rows: u64,
+}
+
+enum Body {
+    Text {
+        out: Encoder,
+        format: ResultFormat,
+        header_pending: bool,
+    },
+    Parquet {
+        writer: SerializedFileWriter<Counter>,
+        kinds: Vec<ParquetKind>,
+        buffer: Vec<QueryRow>,
+    },
+}
+
+impl ExportWriter {
+    /// Start an export, failing if the destination exists and may not be replaced
+    pub fn create(target: ExportTarget, columns: Vec<ColumnMetadata>) -> Result<Self> {
+        if !target.overwrite && target.path.exists() {
+            return Err(McpError::InvalidStatement(format!(
+                "Export file '{}' already exists; pass overwrite: true to replace it",
+                target.path.display()
+            )));
+        }
+
+        let file_name = target
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let temp_path =
+            target
+                .path
+                .with_file_name(format!(".{}.{}.partial", file_name, uuid::Uuid::new_v4()));
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)
+            .map_err(io_error)?;
+        let counter = Counter::new(BufWriter::new(file), target.max_bytes);
+
+        let mut writer = Self {
+            target,
+            temp_path,
+            columns,
+            body: None,
+            rows: 0,
+        };
+        writer.body = Some(writer.open_body(counter)?);
+        Ok(writer)
+    }
+
+    fn open_body(&self, counter: Counter) -> Result<Body> {
+        let format = match self.target.format {
+            ExportFormat::Csv => ResultFormat::Csv,
+            ExportFormat::Ndjson => ResultFormat::Ndjson,
+            ExportFormat::Parquet => return self.open_parquet(counter),
+        };
+        let out = match self.target.compression {
+            Compression::None => Encoder::Plain(counter),
+            Compression::Gzip => Encoder::Gzip(flate2::write::GzEncoder::new(
+                counter,
+                flate2::Compression::default(),
+            )),
+            Compression::Zstd => Encoder::Zstd(zstd::Encoder::new(counter, 0).map_err(io_error)?),
+        };
+        Ok(Body::Text {
+            out,
+            format,
+            header_pending: true,
+        })
+    }
+
+    fn open_parquet(&self, counter: Counter) -> Result<Body> {
+        let kinds: Vec<ParquetKind> = self
+            .columns
+            .iter()
+            .map(|column| ParquetKind::of(&column.data_type))
+            .collect();
+        let schema = parquet_schema(&self.columns, &kinds)?;
+        let compression = match self.target.compression {
+            Compression::None => parquet::basic::Compression::UNCOMPRESSED,
+            Compression::Gzip => parquet::basic::Compression::GZIP(Default::default()),
+            Compression::Zstd => parquet::basic::Compression::ZSTD(Default::default()),
+        };
+        let properties = WriterProperties::builder()
+            .set_compression(compression)
+            .build();
+        let writer = SerializedFileWriter::new(counter, schema, Arc::new(properties))
+            .map_err(parquet_error)?;
+        Ok(Body::Parquet {
+            writer,
+            kinds,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Append rows to the export
+    pub fn write_rows(&mut self, rows: &[QueryRow]) -> Result<()> {
+        let columns = &self.columns;
+        match self.body.as_mut() {
+            Some(Body::Text {
+                out,
+                format,
+                header_pending,
+            }) => {
+                if let Some(formatter) = format.formatter() {
+                    let text = formatter.format(columns, rows, *header_pending);
+                    out.write_all(text.as_bytes()).map_err(io_error)?;
+                }
+                *header_pending = false;
+            }
+            Some(Body::Parquet {
+                writer,
+                kinds,
+                buffer,
+            }) => {
+                buffer.extend_from_slice(rows);
+                while buffer.len() >= ROW_GROUP_ROWS {
+                    let group: Vec<QueryRow> = buffer.drain(..ROW_GROUP_ROWS).collect();
+                    write_row_group(writer, columns, kinds, &group)?;
+                }
+            }
+            None => {}
+        }
+        self.rows += rows.len() as u64;
+        Ok(())
+    }
+
+    /// Complete the file and move it into place
+    pub fn finish(mut self) -> Result<ExportedFile> {
+        // Write the header of an empty CSV export too
+        self.write_rows(&[])?;
+
+        let counter = match self.body.take() {
+            Some(Body::Text { out, .. }) => out.finish().map_err(io_error)?,
+            Some(Body::Parquet {
+                mut writer,
+                kinds,
+                buffer,
+            }) => {
+                if !buffer.is_empty() {
+                    write_row_group(&mut writer, &self.columns, &kinds, &buffer)?;
+                }
+                writer.into_inner().map_err(parquet_error)?
+            }
+            None => unreachable!("export body is only taken when finishing"),
+        };
+        let (bytes, sha256) = counter.finish().map_err(io_error)?;
+
+        if !self.target.overwrite && self.target.path.exists() {
+            return Err(McpError::InvalidStatement(format!(
+                "Export file '{}' was created while exporting; pass overwrite: true to replace it",
+                self.target.path.display()
+            )));
+        }
+        fs::rename(&self.temp_path, &self.target.path).map_err(io_error)?;
+
+        Ok(ExportedFile {
+            path: self.target.path.display().to_string(),
+            format: self.target.format,
+            compression: self.target.compression,
+            rows: self.rows,
+            bytes,
+            sha256,
+        })
+    }
+}
+
+impl Drop for ExportWriter {
+    fn drop(&mut self) {
+        // Closes the file first; after a successful rename this finds nothing
+        self.body = None;
+        let _ = fs::remove_file(&self.temp_path);
+    }
+}
+
+/// Compression of a text export
+enum Encoder {
+    Plain(Counter),
+    Gzip(flate2::write::GzEncoder<Counter>),
+    Zstd(zstd::Encoder<'static, Counter>),
+}
+
+impl Encoder {
+    fn finish(self) -> io::Result<Counter> {
+        match self {
+            Encoder::Plain(counter) => Ok(counter),
+            Encoder::Gzip(encoder) => encoder.finish(),
+            Encoder::Zstd(encoder) => encoder.finish(),
+        }
+    }
+}
+
+impl Write for Encoder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Encoder::Plain(out) => out.write(buf),
+            Encoder::Gzip(out) => out.write(buf),
+            Encoder::Zstd(out) => out.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Encoder::Plain(out) => out.flush(),
+            Encoder::Gzip(out) => out.flush(),
+            Encoder::Zstd(out) => out.flush(),
+        }
+    }
+}
+
+/// Counts and hashes the bytes written to the file, enforcing its size limit
+struct Counter {
+    file: BufWriter<File>,
+    hasher: Sha256,
+    bytes: u64,
+    max_bytes: u64,
+}
+
+impl Counter {
+    fn new(file: BufWriter<File>, max_bytes: u64) -> Self {
+        Self {
+            file,
+            hasher: Sha256::new(),
+            bytes: 0,
+            max_bytes,
+        }
+    }
+
+    /// Flush the file and return its size and SHA-256
+    fn finish(mut self) -> io::Result<(u64, String)> {
+        self.file.flush()?;
+        self.file.get_ref().sync_all()?;
+        Ok((self.bytes, format!("{:x}", self.hasher.finalize())))
+    }
+}
+
+impl Write for Counter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.bytes + buf.len() as u64 > self.max_bytes {
+            return Err(io::Error::other(format!(
+                "export exceeds the maximum file size of {} bytes",
+                self.max_bytes
+            )));
+        }
+        let written = self.file.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.bytes += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// How a column is stored in Parquet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParquetKind {
+    Int,
+    /// Unsigned values stored in `INT64` with an unsigned logical type
+    UnsignedInt,
+    Double,
+    Bytes,
+    Utf8,
+}
+
+impl ParquetKind {
+    fn of(data_type: &str) -> Self {
+        match ColumnKind::from_type_name(data_type) {
+            ColumnKind::Int | ColumnKind::Boolean | ColumnKind::Year => ParquetKind::Int,
+            ColumnKind::UnsignedInt | ColumnKind::Bit => ParquetKind::UnsignedInt,
+            ColumnKind::Float | ColumnKind::Double => ParquetKind::Double,
+            ColumnKind::BinaryString | ColumnKind::Geometry => ParquetKind::Bytes,
+            _ => ParquetKind::Utf8,
+        }
+    }
+}
+
+fn parquet_schema(columns: &[ColumnMetadata], kinds: &[ParquetKind]) -> Result<Arc<SchemaType>> {
+    let names = format::column_keys(columns);
+    let fields = names
+        .iter()
+        .zip(kinds)
+        .map(|(name, kind)| {
+            let (physical, logical) = match kind {
+                ParquetKind::Int => (
+                    PhysicalType::INT64,
+                    Some(LogicalType::Integer {
+                        bit_width: 64,
+                        is_signed: true,
+                    }),
+                ),
+                ParquetKind::UnsignedInt => (
+                    PhysicalType::INT64,
+                    Some(LogicalType::Integer {
+                        bit_width: 64,
+                        is_signed: false,
+                    }),
+                ),
+                ParquetKind::Double => (PhysicalType::DOUBLE, None),
+                ParquetKind::Bytes => (PhysicalType::BYTE_ARRAY, None),
+                ParquetKind::Utf8 => (PhysicalType::BYTE_ARRAY, Some(LogicalType::String)),
+            };
+            SchemaType::primitive_type_builder(name, physical)
+                .with_repetition(Repetition::OPTIONAL)
+                .with_logical_type(logical)
+                .build()
+                .map(Arc::new)
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(parquet_error)?;
+
+    SchemaType::group_type_builder("schema")
+        .with_fields(fields)
+        .build()
+        .map(Arc::new)
+        .map_err(parquet_error)
+}
+
+fn write_row_group(
+    writer: &mut SerializedFileWriter<Counter>,
+    columns: &[ColumnMetadata],
+    kinds: &[ParquetKind],
+    rows: &[QueryRow],
+) -> Result<()> {
+    let mut group = writer.next_row_group().map_err(parquet_error)?;
+    let mut index = 0;
+    while let Some(mut column) = group.next_column().map_err(parquet_error)? {
+        let values = rows
+            .iter()
+            .map(|row| row.values.get(index).unwrap_or(&Value::Null));
+        let levels: Vec<i16> = values.clone().map(|v| i16::from(!v.is_null())).collect();
+        let present = values.filter(|v| !v.is_null());
+        let name = &columns[index].name;
+
+        match kinds[index] {
+            ParquetKind::Int | ParquetKind::UnsignedInt => {
+                let data = present
+                    .map(|value| integer(value).ok_or_else(|| mismatch(name, "an integer", value)))
+                    .collect::<Result<Vec<i64>>>()?;
+                column
+                    .typed::<Int64Type>()
+                    .write_batch(&data, Some(&levels), None)
+                    .map_err(parquet_error)?;
+            }
+            ParquetKind::Double => {
+                let data = present
+                    .map(|value| {
+                        value
+                            .as_f64()
+                            .ok_or_else(|| mismatch(name, "a number", value))
+                    })
+                    .collect::<Result<Vec<f64>>>()?;
+                column
+                    .typed::<DoubleType>()
+                    .write_batch(&data, Some(&levels), None)
+                    .map_err(parquet_error)?;
+            }
+            ParquetKind::Bytes => {
+                let data = present
+                    .map(|value| {
+                        bytes(value)
+                            .map(ByteArray::from)
+                            .ok_or_else(|| mismatch(name, "binary data", value))
+                    })
+                    .collect::<Result<Vec<ByteArray>>>()?;
+                column
+                    .typed::<ByteArrayType>()
+                    .write_batch(&data, Some(&levels), None)
+                    .map_err(parquet_error)?;
+            }
+            ParquetKind::Utf8 => {
+                let data: Vec<ByteArray> = present
+                    .map(|value| match value {
+                        Value::String(s) => ByteArray::from(s.as_str()),
+                        other => ByteArray::from(other.to_string().into_bytes()),
+                    })
+                    .collect();
+                column
+                    .typed::<ByteArrayType>()
+                    .write_batch(&data, Some(&levels), None)
+                    .map_err(parquet_error)?;
+            }
+        }
+
+        column.close().map_err(parquet_error)?;
+        index += 1;
+    }
+    group.close().map_err(parquet_error)?;
+    Ok(())
+}
+
+/// An integer value as stored in `INT64`; unsigned values above `i64::MAX` keep their bits
+fn integer(value: &Value) -> Option<i64> {
+    match value {
+        Value::Bool(b) => Some(i64::from(*b)),
+        Value::Number(n) => n.as_i64().or_else(|| n.as_u64().map(|n| n as i64)),
+        _ => None,
+    }
+}
+
+/// Bytes of a binary value, a string or tagged base64
+fn bytes(value: &Value) -> Option<Vec<u8>> {
+    match value {
+        Value::String(s) => Some(s.as_bytes().to_vec()),
+        Value::Object(tagged) if tagged.get("type")? == "binary" => {
+            base64::engine::general_purpose::STANDARD
+                .decode(tagged.get("value")?.as_str()?)
+                .ok()
+        }
+        _ => None,
+    }
+}
+
+fn mismatch(column: &str, expected: &str, value: &Value) -> McpError {
+    McpError::QueryExecutionError(format!(
+        "Column '{}' holds {} that cannot be exported as {}",
+        column, value, expected
+    ))
+}
+
+fn io_error(error: io::Error) -> McpError {
+    McpError::QueryExecutionError(format!("Export failed: {}", error))
+}
+
+fn parquet_error(error: parquet::errors::ParquetError) -> McpError {
+    McpError::QueryExecutionError(format!("Export failed: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use serde_json::json;
+    use std::io::Read;
+
+    fn columns() -> Vec<ColumnMetadata> {
+        [
+            ("id", "BIGINT UNSIGNED"),
+            ("name", "VARCHAR"),
+            ("price", "DECIMAL"),
+            ("data", "BLOB"),
+        ]
+        .iter()
+        .map(|(name, data_type)| ColumnMetadata {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            nullable: true,
+            ..Default::default()
+        })
+        .collect()
+    }
+
+    fn rows() -> Vec<QueryRow> {
+        vec![
+            QueryRow {
+                values: vec![
+                    json!(u64::MAX),
+                    json!("a,b"),
+                    json!("1.50"),
+                    json!({"type": "binary", "value": "3q2+7w=="}),
+                ],
+            },
+            QueryRow {
+                values: vec![json!(2), Value::Null, json!("0.00"), json!("text")],
+            },
+        ]
+    }
+
+    fn target(
+        dir: &Path,
+        name: &str,
+        format: ExportFormat,
+        compression: Compression,
+    ) -> ExportTarget {
+        ExportTarget {
+            path: dir.join(name),
+            format,
+            compression,
+            overwrite: false,
+            max_bytes: 1024 * 1024,
+        }
+    }
+
+    fn export(target: ExportTarget) -> Result<ExportedFile> {
+        let mut writer = ExportWriter::create(target, columns())?;
+        writer.write_rows(&rows()[..1])?;
+        writer.write_rows(&rows()[1..])?;
+        writer.finish()
+    }
+
+    #[test]
+    fn test_resolve_path_stays_in_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+
+        assert_eq!(
+            resolve_path(&root, "out.csv").unwrap(),
+            root.join("out.csv")
+        );
+        assert_eq!(
+            resolve_path(&root, "a/b/out.csv").unwrap(),
+            root.join("a/b/out.csv")
+        );
+        assert!(root.join("a/b").is_dir());
+
+        for name in [
+            "",
+            "/etc/passwd",
+            "../out.csv",
+            "a/../../out.csv",
+            "./out.csv",
+            "a/",
+        ] {
+            assert!(
+                matches!(
+                    resolve_path(&root, name),
+                    Err(McpError::InvalidStatement(_))
+                ),
+                "{:?} was accepted",
+                name
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_path_rejects_symlink_out_of_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("link")).unwrap();
+
+        assert!(matches!(
+            resolve_path(dir.path(), "link/out.csv"),
+            Err(McpError::PermissionDenied(_))
+        ));
+
+        // Missing directories behind the link are not created outside the root
+        assert!(matches!(
+            resolve_path(dir.path(), "link/a/b/out.csv"),
+            Err(McpError::PermissionDenied(_))
+        ));
+        assert_eq!(fs::read_dir(outside.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_export_csv_gzip() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = export(target(
+            dir.path(),
+            "out.csv.gz",
+            ExportFormat::Csv,
+            Compression::Gzip,
+        ))
+        .unwrap();
+
+        assert_eq!(file.rows, 2);
+        let compressed = fs::read(dir.path().join("out.csv.gz")).unwrap();
+        assert_eq!(file.bytes, compressed.len() as u64);
+        assert_eq!(file.sha256, format!("{:x}", Sha256::digest(&compressed)));
+
+        let mut text = String::new();
+        flate2::read::GzDecoder::new(&compressed[..])
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(
+            text,
+            "id,name,price,data\n18446744073709551615,\"a,b\",1.50,\"{\"\"type\"\":\"\"binary\"\",\"\"value\"\":\"\"3q2+7w==\"\"}\"\n2,,0.00,text\n"
+        );
+        // Only the export is left in the directory
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_export_ndjson_zstd() {
+        let dir = tempfile::tempdir().unwrap();
+        export(target(
+            dir.path(),
+            "out.ndjson.zst",
+            ExportFormat::Ndjson,
+            Compression::Zstd,
+        ))
+        .unwrap();
+
+        let text =
+            zstd::decode_all(File::open(dir.path().join("out.ndjson.zst")).unwrap()).unwrap();
+        let lines: Vec<Value> = String::from_utf8(text)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[1],
+            json!({"id": 2, "name": null, "price": "0.00", "data": "text"})
+        );
+    }
+
+    #[test]
+    fn test_export_parquet() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = export(target(
+            dir.path(),
+            "out.parquet",
+            ExportFormat::Parquet,
+            Compression::Zstd,
+        ))
+        .unwrap();
+        assert_eq!(file.rows, 2);
+
+        let reader =
+            SerializedFileReader::new(File::open(dir.path().join("out.parquet")).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+        let rows: Vec<_> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap())
+            .collect();
+        assert_eq!(
+            rows[0].to_string(),
+            "{id: 18446744073709551615, name: \"a,b\", price: \"1.50\", data: [222, 173, 190, 239]}"
+        );
+        assert_eq!(
+            rows[1].to_string(),
+            "{id: 2, name: null, price: \"0.00\", data: [116, 101, 120, 116]}"
+        );
+    }
+
+    #[test]
+    fn test_export_existing_file_needs_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("out.csv"), "old").unwrap();
+
+        let result = export(target(
+            dir.path(),
+            "out.csv",
+            ExportFormat::Csv,
+            Compression::None,
+        ));
+        assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+
+        let mut replace = target(dir.path(), "out.csv", ExportFormat::Csv, Compression::None);
+        replace.overwrite = true;
+        export(replace).unwrap();
+        assert!(fs::read_to_string(dir.path().join("out.csv"))
+            .unwrap()
+            .starts_with("id,"));
+    }
+
+    #[test]
+    fn test_export_over_size_limit_leaves_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut small = target(dir.path(), "out.csv", ExportFormat::Csv, Compression::None);
+        small.max_bytes = 10;
+
+        assert!(export(small).is_err());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_abandoned_export_leaves_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let (sender, receiver) = mpsc::channel(1);
+        sender.blocking_send(Some(rows())).unwrap();
+        drop(sender);
+
+        let result = write_file(
+            target(dir.path(), "out.csv", ExportFormat::Csv, Compression::None),
+            columns(),
+            receiver,
+        );
+        assert!(result.is_err());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,487 @@
+//! Result encodings for query rows
+//!
+//! `mysql_query`, the stream tools and tabular resources return rows as pretty
+//! JSON by default. The `format` argument selects a more compact encoding:
+//!
+//! - `json_compact`: `[{"id":1,"name":"a"}]`
+//! - `json_columnar`: `{"id":[1],"name":["a"]}`
+//! - `csv`, `tsv`: RFC 4180 quoting with `,` or tab as the delimiter
+//! - `markdown`: a GitHub Markdown table
+//! - `ndjson`: one JSON object per line
+//!
+//! Objects are keyed by column name, with `_2`, `_3`, … appended to repeated
+//! names. In CSV and TSV, `NULL` is an empty field and an empty string is `""`;
+//! Markdown tables show `NULL`. Values that are JSON arrays or objects (`SET`,
+//! `JSON` and tagged binary columns) are written as compact JSON.
+
+use crate::error::{McpError, Result};
+use crate::tools::{ColumnMetadata, QueryRow};
+use serde::Serialize;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// Encoding of a result returned by a tool or resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResultFormat {
+    /// The tool's full response as pretty JSON
+    #[default]
+    Json,
+    JsonCompact,
+    JsonColumnar,
+    Csv,
+    Tsv,
+    Markdown,
+    Ndjson,
+}
+
+impl ResultFormat {
+    /// All formats, in the order they are documented
+    pub const ALL: [ResultFormat; 7] = [
+        ResultFormat::Json,
+        ResultFormat::JsonCompact,
+        ResultFormat::JsonColumnar,
+        ResultFormat::Csv,
+        ResultFormat::Tsv,
+        ResultFormat::Markdown,
+        ResultFormat::Ndjson,
+    ];
+
+    /// Name of the format as accepted by the `format` argument
+    pub fn name(&self) -> &'static str {
+        match self {
+            ResultFormat::Json => "json",
+            ResultFormat::JsonCompact => "json_compact",
+            ResultFormat::JsonColumnar => "json_columnar",
+            ResultFormat::Csv => "csv",
+            ResultFormat::Tsv => "tsv",
+            ResultFormat::Markdown => "markdown",
+            ResultFormat::Ndjson => "ndjson",
+        }
+    }
+
+    /// Formatter for the rows, or `None` for the full JSON response
+    pub fn formatter(&self) -> Option<&'static dyn ResultFormatter> {
+        match self {
+            ResultFormat::Json => None,
+            ResultFormat::JsonCompact => Some(&JsonCompactFormatter),
+            ResultFormat::JsonColumnar => Some(&JsonColumnarFormatter),
+            ResultFormat::Csv => Some(&CSV),
+            ResultFormat::Tsv => Some(&TSV),
+            ResultFormat::Markdown => Some(&MarkdownFormatter),
+            ResultFormat::Ndjson => Some(&NdjsonFormatter),
+        }
+    }
+
+    /// MIME type of the formatted output
+    pub fn mime_type(&self) -> &'static str {
+        self.formatter()
+            .map_or("application/json", |formatter| formatter.mime_type())
+    }
+}
+
+impl fmt::Display for ResultFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ResultFormat {
+    type Err = McpError;
+
+    /// Parse a format name, ignoring case
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim().to_lowercase();
+        ResultFormat::ALL
+            .into_iter()
+            .find(|format| format.name() == name)
+            .ok_or_else(|| {
+                McpError::InvalidStatement(format!(
+                    "Unknown format '{}'; expected one of {}",
+                    s,
+                    ResultFormat::ALL.map(|format| format.name()).join(", ")
+                ))
+            })
+    }
+}
+
+/// Encodes result rows as text
+pub trait ResultFormatter: Send + Sync {
+    /// MIME type of the output
+    fn mime_type(&self) -> &'static str;
+
+    /// Encode rows of a result with the given columns
+    ///
+    /// `header` is false for the chunks following the first one of a stream,
+    /// so that formats with a header row can be concatenated; formats that
+    /// cannot be read without their header ignore it.
+    fn format(&self, columns: &[ColumnMetadata], rows: &[QueryRow], header: bool) -> String;
+}
+
+/// Array of objects keyed by column
+pub struct JsonCompactFormatter;
+
+impl ResultFormatter for JsonCompactFormatter {
+    fn mime_type(&self) -> &'static str {
+        "application/json"
+    }
+
+    fn format(&self, columns: &[ColumnMetadata], rows: &[QueryRow], _header: bool) -> String {
+        let keys = column_keys(columns);
+        let mut out = String::from("[");
+        for (i, row) in rows.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            write_object(&mut out, &keys, &row.values);
+        }
+        out.push(']');
+        out
+    }
+}
+
+/// Object of value arrays keyed by column
+pub struct JsonColumnarFormatter;
+
+impl ResultFormatter for JsonColumnarFormatter {
+    fn mime_type(&self) -> &'static str {
+        "application/json"
+    }
+
+    fn format(&self, columns: &[ColumnMetadata], rows: &[QueryRow], _header: bool) -> String {
+        let keys = column_keys(columns);
+        let mut out = String::from("{");
+        for (i, key) in keys.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            write_json(&mut out, key);
+            out.push_str(":[");
+            for (j, row) in rows.iter().enumerate() {
+                if j > 0 {
+                    out.push(',');
+                }
+                write_json(&mut out, row.values.get(i).unwrap_or(&Value::Null));
+            }
+            out.push(']');
+        }
+        out.push('}');
+        out
+    }
+}
+
+/// One object keyed by column per line
+pub struct NdjsonFormatter;
+
+impl ResultFormatter for NdjsonFormatter {
+    fn mime_type(&self) -> &'static str {
+        "application/x-ndjson"
+    }
+
+    fn format(&self, columns: &[ColumnMetadata], rows: &[QueryRow], _header: bool) -> String {
+        let keys = column_keys(columns);
+        let mut out = String::new();
+        for row in rows {
+            write_object(&mut out, &keys, &row.values);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Delimiter-separated values with a header row
+pub struct DelimitedFormatter {
+    delimiter: char,
+    mime_type: &'static str,
+}
+
+static CSV: DelimitedFormatter = DelimitedFormatter {
+    delimiter: ',',
+    mime_type: "text/csv",
+};
+
+static TSV: DelimitedFormatter = DelimitedFormatter {
+    delimiter: '\t',
+    mime_type: "text/tab-separated-values",
+};
+
+impl DelimitedFormatter {
+    /// Append a field, quoted if it holds the delimiter, a quote or a line break
+    fn write_field(&self, out: &mut String, field: &str) {
+        if field.contains([self.delimiter, '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+
+    fn write_record<'a>(&self, out: &mut String, fields: impl Iterator<Item = Option<Cow<'a, str>>>) {
+        for (i, field) in fields.enumerate() {
+            if i > 0 {
+                out.push(self.delimiter);
+            }
+            match field {
+                // Tell an empty string apart from NULL
+                Some(field) if field.is_empty() => out.push_str("\"\""),
+                Some(field) => self.write_field(out, &field),
+                None => {}
+            }
+        }
+        out.push('\n');
+    }
+}
+
+impl ResultFormatter for DelimitedFormatter {
+    fn mime_type(&self) -> &'static str {
+        self.mime_type
+    }
+
+    fn format(&self, columns: &[ColumnMetadata], rows: &[QueryRow], header: bool) -> String {
+        let mut out = String::new();
+        if header && !columns.is_empty() {
+            self.write_record(
+                &mut out,
+                columns.iter().map(|column| Some(Cow::from(column.name.as_str()))),
+            );
+        }
+        for row in rows {
+            self.write_record(&mut out, row.values.iter().map(text));
+        }
+        out
+    }
+}
+
+/// GitHub Markdown table
+pub struct MarkdownFormatter;
+
+impl MarkdownFormatter {
+    fn write_row<'a>(out: &mut String, cells: impl Iterator<Item = Cow<'a, str>>) {
+        out.push('|');
+        for cell in cells {
+            out.push(' ');
+            for c in cell.chars() {
+                match c {
+                    '|' => out.push_str("\\|"),
+                    '\n' => out.push_str("<br>"),
+                    '\r' => {}
+                    c => out.push(c),
+                }
+            }
+            out.push_str(" |");
+        }
+        out.push('\n');
+    }
+}
+
+impl ResultFormatter for MarkdownFormatter {
+    fn mime_type(&self) -> &'static str {
+        "text/markdown"
+    }
+
+    /// Every chunk gets the header, as a table cannot do without it
+    fn format(&self, columns: &[ColumnMetadata], rows: &[QueryRow], _header: bool) -> String {
+        let mut out = String::new();
+        if columns.is_empty() {
+            return out;
+        }
+        Self::write_row(
+            &mut out,
+            columns.iter().map(|column| Cow::from(column.name.as_str())),
+        );
+        Self::write_row(&mut out, columns.iter().map(|_| Cow::from("---")));
+        for row in rows {
+            Self::write_row(
+                &mut out,
+                row.values
+                    .iter()
+                    .map(|value| text(value).unwrap_or(Cow::from("NULL"))),
+            );
+        }
+        out
+    }
+}
+
+/// Turn records into result rows, taking the named fields as columns
+///
+/// Lets lists that are not query results, such as those of resources, use the
+/// same formatters. Missing fields are `NULL`.
+pub fn tabulate<T: Serialize>(fields: &[&str], records: &[T]) -> (Vec<ColumnMetadata>, Vec<QueryRow>) {
+    let columns = fields
+        .iter()
+        .map(|field| ColumnMetadata {
+            name: field.to_string(),
+            nullable: true,
+            ..Default::default()
+        })
+        .collect();
+    let rows = records
+        .iter()
+        .map(|record| {
+            let record = serde_json::to_value(record).unwrap_or(Value::Null);
+            QueryRow {
+                values: fields
+                    .iter()
+                    .map(|field| record.get(field).cloned().unwrap_or(Value::Null))
+                    .collect(),
+            }
+        })
+        .collect();
+    (columns, rows)
+}
+
+/// Object keys for the columns, made unique by numbering repeated names
+pub(crate) fn column_keys(columns: &[ColumnMetadata]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    columns
+        .iter()
+        .map(|column| {
+            let mut key = column.name.clone();
+            let mut n = 1;
+            while !seen.insert(key.clone()) {
+                n += 1;
+                key = format!("{}_{}", column.name, n);
+            }
+            key
+        })
+        .collect()
+}
+
+/// Append an object in column order, which `serde_json::Map` does not keep
+fn write_object(out: &mut String, keys: &[String], values: &[Value]) {
+    out.push('{');
+    for (i, key) in keys.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_json(out, key);
+        out.push(':');
+        write_json(out, values.get(i).unwrap_or(&Value::Null));
+    }
+    out.push('}');
+}
+
+fn write_json<T: Serialize + ?Sized>(out: &mut String, value: &T) {
+    // Serializing strings and JSON values cannot fail
+    out.push_str(&serde_json::to_string(value).unwrap_or_default());
+}
+
+/// Text of a value for tabular formats, `None` for `NULL`
+fn text(value: &Value) -> Option<Cow<'_, str>> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(Cow::from(s.as_str())),
+        other => Some(Cow::from(other.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn columns(names: &[&str]) -> Vec<ColumnMetadata> {
+        names
+            .iter()
+            .map(|name| ColumnMetadata {
+                name: name.to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn rows(rows: Vec<Vec<Value>>) -> Vec<QueryRow> {
+        rows.into_iter().map(|values| QueryRow { values }).collect()
+    }
+
+    #[test]
+    fn test_parse_format() {
+        for format in ResultFormat::ALL {
+            assert_eq!(format.name().parse::<ResultFormat>().unwrap(), format);
+        }
+        assert_eq!("CSV".parse::<ResultFormat>().unwrap(), ResultFormat::Csv);
+        assert!(matches!(
+            "xml".parse::<ResultFormat>(),
+            Err(McpError::InvalidStatement(_))
+        ));
+    }
+
+    #[test]
+    fn test_csv_quoting() {
+        let out = ResultFormat::Csv.formatter().unwrap().format(
+            &columns(&["id", "note"]),
+            &rows(vec![
+                vec![json!(1), json!("plain")],
+                vec![json!(2), json!("a, \"quoted\"\nline")],
+                vec![json!(3), json!("")],
+                vec![json!(4), Value::Null],
+            ]),
+            true,
+        );
+        assert_eq!(
+            out,
+            "id,note\n1,plain\n2,\"a, \"\"quoted\"\"\nline\"\n3,\"\"\n4,\n"
+        );
+    }
+
+    #[test]
+    fn test_tsv_without_header() {
+        let out = ResultFormat::Tsv.formatter().unwrap().format(
+            &columns(&["a", "b"]),
+            &rows(vec![vec![json!("x\ty"), json!(["r", "g"])]]),
+            false,
+        );
+        assert_eq!(out, "\"x\ty\"\t\"[\"\"r\"\",\"\"g\"\"]\"\n");
+    }
+
+    #[test]
+    fn test_markdown_escaping() {
+        let out = ResultFormat::Markdown.formatter().unwrap().format(
+            &columns(&["id", "note"]),
+            &rows(vec![vec![json!(1), json!("a|b\nc")], vec![json!(2), Value::Null]]),
+            false,
+        );
+        assert_eq!(
+            out,
+            "| id | note |\n| --- | --- |\n| 1 | a\\|b<br>c |\n| 2 | NULL |\n"
+        );
+    }
+
+    #[test]
+    fn test_json_formats_keep_column_order() {
+        let columns = columns(&["z", "a", "z"]);
+        let rows = rows(vec![vec![json!(1), json!("x"), json!(true)]]);
+
+        let compact = ResultFormat::JsonCompact
+            .formatter()
+            .unwrap()
+            .format(&columns, &rows, true);
+        assert_eq!(compact, r#"[{"z":1,"a":"x","z_2":true}]"#);
+
+        let columnar = ResultFormat::JsonColumnar
+            .formatter()
+            .unwrap()
+            .format(&columns, &rows, true);
+        assert_eq!(columnar, r#"{"z":[1],"a":["x"],"z_2":[true]}"#);
+
+        let ndjson = ResultFormat::Ndjson
+            .formatter()
+            .unwrap()
+            .format(&columns, &rows, true);
+        assert_eq!(ndjson, "{\"z\":1,\"a\":\"x\",\"z_2\":true}\n");
+    }
+
+    #[test]
+    fn test_tabulate() {
+        let (columns, rows) = tabulate(
+            &["name", "size"],
+            &[json!({"size": 3, "name": "t", "extra": 1}), json!({"name": "u"})],
+        );
+        assert_eq!(columns.len(), 2);
+        assert_eq!(rows[0].values, vec![json!("t"), json!(3)]);
+        assert_eq!(rows[1].values, vec![json!("u"), Value::Null]);
+    }
+}
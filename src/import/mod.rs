@@ -0,0 +1,1346 @@
+//! Bulk loading of CSV and NDJSON files into a table
+//!
+//! `mysql_import` reads a file from the configured import directory and inserts
+//! its rows with multi-row `INSERT` statements. The file's columns are matched
+//! to the table's by name, ignoring case, and every value is checked against
+//! its column's type before anything is written:
+//!
+//! - `csv`: a header row of column names, then RFC 4180 records; an empty
+//!   unquoted field is `NULL` and `""` is an empty string, as written by the
+//!   `csv` result format and `mysql_export`
+//! - `ndjson`: one JSON object per line, keyed by column name; the keys of the
+//!   first object are the columns imported and missing keys are `NULL`
+//!
+//! Values in the forms the server returns are accepted back: `DECIMAL` as exact
+//! strings, `SET` as arrays, `TIMESTAMP` in RFC 3339, binary data as
+//! `{"type": "binary", "value": "<base64>"}`. Files ending in `.gz` or `.zst`
+//! are decompressed while reading.
+
+use crate::error::{McpError, Result};
+use crate::export::{self, Compression};
+use crate::params::{self, SqlParam};
+use crate::sql;
+use crate::tools::ColumnSchema;
+use base64::Engine;
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+use tokio::sync::mpsc;
+
+/// Most placeholders MySQL accepts in one prepared statement
+const MAX_PLACEHOLDERS: usize = 65_535;
+
+/// Approximate size of the values in one `INSERT`, kept well below the default
+/// `max_allowed_packet`
+const MAX_BATCH_BYTES: usize = 4 * 1024 * 1024;
+
+/// Validation problems reported in detail; further ones are only counted
+pub const MAX_REPORTED_ISSUES: usize = 20;
+
+/// File format of an import
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl ImportFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ImportFormat::Csv => "csv",
+            ImportFormat::Ndjson => "ndjson",
+        }
+    }
+}
+
+impl fmt::Display for ImportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ImportFormat {
+    type Err = McpError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "csv" => Ok(ImportFormat::Csv),
+            "ndjson" | "jsonl" => Ok(ImportFormat::Ndjson),
+            _ => Err(McpError::InvalidStatement(format!(
+                "Unknown import format '{}'; expected csv or ndjson",
+                s
+            ))),
+        }
+    }
+}
+
+/// What to do with rows whose primary or unique key already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnDuplicate {
+    /// Fail the import
+    #[default]
+    Error,
+    /// Keep the existing row
+    Ignore,
+    /// Overwrite the existing row's other columns with the imported values
+    Update,
+}
+
+impl FromStr for OnDuplicate {
+    type Err = McpError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "error" => Ok(OnDuplicate::Error),
+            "ignore" => Ok(OnDuplicate::Ignore),
+            "update" => Ok(OnDuplicate::Update),
+            _ => Err(McpError::InvalidStatement(format!(
+                "Unknown on_duplicate mode '{}'; expected error, ignore or update",
+                s
+            ))),
+        }
+    }
+}
+
+/// Format and compression of a file, from its extension
+///
+/// `orders.csv.gz` is gzip-compressed CSV; the format is `None` if the
+/// extension does not name one.
+pub fn detect_format(name: &str) -> (Option<ImportFormat>, Compression) {
+    let lower = name.to_lowercase();
+    let (stem, compression) = if let Some(stem) = lower.strip_suffix(".gz") {
+        (stem, Compression::Gzip)
+    } else if let Some(stem) = lower.strip_suffix(".zst") {
+        (stem, Compression::Zstd)
+    } else {
+        (lower.as_str(), Compression::None)
+    };
+
+    let format = match Path::new(stem).extension().and_then(|ext| ext.to_str()) {
+        Some("csv") => Some(ImportFormat::Csv),
+        Some("ndjson" | "jsonl") => Some(ImportFormat::Ndjson),
+        _ => None,
+    };
+    (format, compression)
+}
+
+/// Resolve the name of an existing file in the import directory
+///
+/// Only plain relative paths are accepted, and symbolic links may not lead out
+/// of the directory.
+pub fn resolve_path(root: &Path, name: &str) -> Result<PathBuf> {
+    if !export::is_plain_relative(name) {
+        return Err(McpError::InvalidStatement(format!(
+            "Import file '{}' must be a relative path inside the import directory, without '.' or '..'",
+            name
+        )));
+    }
+
+    let root = root.canonicalize().map_err(|e| {
+        McpError::ConfigurationError(format!(
+            "Import directory '{}' is not accessible: {}",
+            root.display(),
+            e
+        ))
+    })?;
+    let path = root.join(name).canonicalize().map_err(|e| {
+        McpError::InvalidStatement(format!("Import file '{}' cannot be opened: {}", name, e))
+    })?;
+    if !path.starts_with(&root) {
+        return Err(McpError::PermissionDenied(format!(
+            "Import file '{}' resolves outside the import directory",
+            name
+        )));
+    }
+    if !path.is_file() {
+        return Err(McpError::InvalidStatement(format!(
+            "Import file '{}' is not a regular file",
+            name
+        )));
+    }
+
+    Ok(path)
+}
+
+/// A value read from an import file
+#[derive(Debug, Clone, PartialEq)]
+pub enum Field {
+    Null,
+    /// A CSV field
+    Text(String),
+    /// A non-null NDJSON value
+    Json(Value),
+}
+
+/// A row read from an import file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    /// Line the row starts on, counting from 1
+    pub line: u64,
+    /// Values in file column order, or why the row could not be read
+    pub fields: std::result::Result<Vec<Field>, String>,
+}
+
+/// Reads the rows of an import file
+pub struct RecordReader {
+    source: Box<dyn BufRead + Send>,
+    format: ImportFormat,
+    columns: Vec<String>,
+    line: u64,
+    /// An NDJSON row read while looking for the columns
+    pending: Option<Record>,
+}
+
+impl RecordReader {
+    /// Open a file and read its columns
+    pub fn open(path: &Path, format: ImportFormat, compression: Compression) -> Result<Self> {
+        let file = File::open(path).map_err(io_error)?;
+        let source: Box<dyn Read + Send> = match compression {
+            Compression::None => Box::new(file),
+            Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(file)),
+            Compression::Zstd => Box::new(zstd::Decoder::new(file).map_err(io_error)?),
+        };
+        Self::new(Box::new(BufReader::new(source)), format)
+    }
+
+    fn new(source: Box<dyn BufRead + Send>, format: ImportFormat) -> Result<Self> {
+        let mut reader = Self {
+            source,
+            format,
+            columns: Vec::new(),
+            line: 0,
+            pending: None,
+        };
+
+        match format {
+            ImportFormat::Csv => {
+                let header = reader.read_csv()?.ok_or_else(empty_file)?;
+                reader.columns = header
+                    .fields
+                    .map_err(McpError::InvalidStatement)?
+                    .into_iter()
+                    .map(|field| match field {
+                        Field::Text(name) => name.trim_start_matches('\u{feff}').to_string(),
+                        _ => String::new(),
+                    })
+                    .collect();
+            }
+            ImportFormat::Ndjson => {
+                let (line, first) = loop {
+                    let (line, text) = reader.read_line()?.ok_or_else(empty_file)?;
+                    if !text.trim().is_empty() {
+                        break (line, text);
+                    }
+                };
+                match serde_json::from_str::<Value>(first.trim_start_matches('\u{feff}')) {
+                    Ok(Value::Object(object)) => {
+                        reader.columns = object.keys().cloned().collect();
+                        reader.pending = Some(reader.ndjson_record(line, &first));
+                    }
+                    _ => {
+                        return Err(McpError::InvalidStatement(format!(
+                            "Line {}: the first line of an NDJSON file must be a JSON object",
+                            line
+                        )))
+                    }
+                }
+            }
+        }
+
+        Ok(reader)
+    }
+
+    /// Names of the file's columns, in file order
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Read the next row, or `None` at the end of the file
+    pub fn next_record(&mut self) -> Result<Option<Record>> {
+        if let Some(record) = self.pending.take() {
+            return Ok(Some(record));
+        }
+
+        match self.format {
+            ImportFormat::Csv => loop {
+                let Some(mut record) = self.read_csv()? else {
+                    return Ok(None);
+                };
+                // Blank lines are a NULL only in a single-column file
+                if record.fields == Ok(vec![Field::Null]) && self.columns.len() > 1 {
+                    continue;
+                }
+                if let Ok(fields) = &record.fields {
+                    if fields.len() != self.columns.len() {
+                        record.fields = Err(format!(
+                            "expected {} fields, found {}",
+                            self.columns.len(),
+                            fields.len()
+                        ));
+                    }
+                }
+                return Ok(Some(record));
+            },
+            ImportFormat::Ndjson => loop {
+                let Some((line, text)) = self.read_line()? else {
+                    return Ok(None);
+                };
+                if !text.trim().is_empty() {
+                    return Ok(Some(self.ndjson_record(line, &text)));
+                }
+            },
+        }
+    }
+
+    fn read_line(&mut self) -> Result<Option<(u64, String)>> {
+        let mut text = String::new();
+        if self.source.read_line(&mut text).map_err(io_error)? == 0 {
+            return Ok(None);
+        }
+        self.line += 1;
+        Ok(Some((self.line, text)))
+    }
+
+    /// Read one CSV record, which may span lines inside quoted fields
+    fn read_csv(&mut self) -> Result<Option<Record>> {
+        let Some((line, mut text)) = self.read_line()? else {
+            return Ok(None);
+        };
+
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut in_quotes = false;
+        loop {
+            let mut chars = text.chars().peekable();
+            while let Some(c) = chars.next() {
+                if in_quotes {
+                    match c {
+                        '"' if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        '"' => in_quotes = false,
+                        c => field.push(c),
+                    }
+                    continue;
+                }
+                match c {
+                    '"' if field.is_empty() && !quoted => {
+                        quoted = true;
+                        in_quotes = true;
+                    }
+                    ',' => fields.push(csv_field(&mut field, &mut quoted)),
+                    '\r' if chars.peek() == Some(&'\n') => {}
+                    '\n' => break,
+                    c => field.push(c),
+                }
+            }
+            if !in_quotes {
+                fields.push(csv_field(&mut field, &mut quoted));
+                return Ok(Some(Record {
+                    line,
+                    fields: Ok(fields),
+                }));
+            }
+            text = match self.read_line()? {
+                Some((_, next)) => next,
+                None => {
+                    return Err(McpError::InvalidStatement(format!(
+                        "Line {}: quoted field is not closed before the end of the file",
+                        line
+                    )))
+                }
+            };
+        }
+    }
+
+    fn ndjson_record(&self, line: u64, text: &str) -> Record {
+        let fields = match serde_json::from_str::<Value>(text.trim_start_matches('\u{feff}')) {
+            Ok(Value::Object(mut object)) => {
+                let fields = self
+                    .columns
+                    .iter()
+                    .map(|column| match object.remove(column) {
+                        None | Some(Value::Null) => Field::Null,
+                        Some(value) => Field::Json(value),
+                    })
+                    .collect();
+                match object.keys().next() {
+                    Some(key) => Err(format!(
+                        "key '{}' is not among the columns of the first line",
+                        key
+                    )),
+                    None => Ok(fields),
+                }
+            }
+            Ok(_) => Err("expected a JSON object".to_string()),
+            Err(e) => Err(format!("invalid JSON: {}", e)),
+        };
+        Record { line, fields }
+    }
+}
+
+fn csv_field(field: &mut String, quoted: &mut bool) -> Field {
+    let value = std::mem::take(field);
+    let was_quoted = std::mem::replace(quoted, false);
+    if value.is_empty() && !was_quoted {
+        Field::Null
+    } else {
+        Field::Text(value)
+    }
+}
+
+fn empty_file() -> McpError {
+    McpError::InvalidStatement("Import file is empty".to_string())
+}
+
+/// A row that cannot be imported
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImportIssue {
+    /// Line of the file the row starts on
+    pub line: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<String>,
+    pub message: String,
+}
+
+/// How the columns of a file map to a table
+#[derive(Debug, Clone)]
+pub struct ImportPlan {
+    columns: Vec<PlannedColumn>,
+}
+
+#[derive(Debug, Clone)]
+struct PlannedColumn {
+    /// Name as defined in the table
+    name: String,
+    column_type: ColumnType,
+    accepts_null: bool,
+}
+
+impl ImportPlan {
+    /// Match the columns of a file to those of a table
+    ///
+    /// Every file column must exist in the table and may appear only once, and
+    /// table columns left out must be nullable or have a default.
+    pub fn new(file_columns: &[String], table_columns: &[ColumnSchema]) -> Result<Self> {
+        let mut seen = HashSet::new();
+        let mut unknown = Vec::new();
+        let mut columns = Vec::with_capacity(file_columns.len());
+
+        for name in file_columns {
+            if name.is_empty() {
+                return Err(McpError::InvalidStatement(
+                    "Import file has a column without a name".to_string(),
+                ));
+            }
+            if !seen.insert(name.to_lowercase()) {
+                return Err(McpError::InvalidStatement(format!(
+                    "Import file has column '{}' more than once",
+                    name
+                )));
+            }
+            let Some(column) = table_columns
+                .iter()
+                .find(|column| column.name.eq_ignore_ascii_case(name))
+            else {
+                unknown.push(name.as_str());
+                continue;
+            };
+            if is_generated(column) {
+                return Err(McpError::InvalidStatement(format!(
+                    "Column '{}' is generated and cannot be imported",
+                    column.name
+                )));
+            }
+            columns.push(PlannedColumn {
+                name: column.name.clone(),
+                column_type: ColumnType::parse(&column.data_type),
+                accepts_null: column.nullable || is_auto_increment(column),
+            });
+        }
+
+        if !unknown.is_empty() {
+            return Err(McpError::InvalidStatement(format!(
+                "Import file has columns the table does not: {}",
+                unknown.join(", ")
+            )));
+        }
+
+        let missing: Vec<&str> = table_columns
+            .iter()
+            .filter(|column| {
+                !seen.contains(&column.name.to_lowercase())
+                    && !column.nullable
+                    && column.default_value.is_none()
+                    && !is_auto_increment(column)
+                    && !is_generated(column)
+            })
+            .map(|column| column.name.as_str())
+            .collect();
+        if !missing.is_empty() {
+            return Err(McpError::InvalidStatement(format!(
+                "Import file lacks columns that have no default: {}",
+                missing.join(", ")
+            )));
+        }
+
+        Ok(Self { columns })
+    }
+
+    /// Table names of the imported columns, in file order
+    pub fn column_names(&self) -> Vec<String> {
+        self.columns.iter().map(|column| column.name.clone()).collect()
+    }
+
+    /// Most rows one statement may insert
+    pub fn max_batch_rows(&self, batch_rows: usize) -> usize {
+        batch_rows.min(MAX_PLACEHOLDERS / self.columns.len().max(1)).max(1)
+    }
+
+    /// Check a row and convert it to bind values
+    pub fn convert(&self, record: &Record) -> std::result::Result<Vec<SqlParam>, ImportIssue> {
+        let issue = |column: Option<&str>, message: String| ImportIssue {
+            line: record.line,
+            column: column.map(str::to_string),
+            message,
+        };
+        let fields = record
+            .fields
+            .as_ref()
+            .map_err(|message| issue(None, message.clone()))?;
+
+        self.columns
+            .iter()
+            .zip(fields)
+            .map(|(column, field)| match field {
+                Field::Null if column.accepts_null => Ok(SqlParam::Null),
+                Field::Null => Err(issue(Some(&column.name), "column does not accept NULL".to_string())),
+                field => column
+                    .column_type
+                    .convert(field)
+                    .map_err(|message| issue(Some(&column.name), message)),
+            })
+            .collect()
+    }
+
+    /// `INSERT` statement for `rows` rows
+    ///
+    /// With [`OnDuplicate::Update`] the columns outside the primary key are
+    /// overwritten; if there are none, or with [`OnDuplicate::Ignore`], existing
+    /// rows are kept. Unlike `INSERT IGNORE`, neither hides other errors.
+    pub fn insert_sql(
+        &self,
+        table: &str,
+        rows: usize,
+        on_duplicate: OnDuplicate,
+        primary_key: &[String],
+    ) -> String {
+        let names: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| sql::quote_identifier(&column.name))
+            .collect();
+        let row = format!("({})", vec!["?"; names.len()].join(", "));
+        let mut statement = format!(
+            "INSERT INTO {} ({}) VALUES {}",
+            sql::quote_identifier(table),
+            names.join(", "),
+            vec![row.as_str(); rows].join(", ")
+        );
+
+        let updated: Vec<String> = match on_duplicate {
+            OnDuplicate::Error => return statement,
+            OnDuplicate::Ignore => Vec::new(),
+            OnDuplicate::Update => self
+                .columns
+                .iter()
+                .filter(|column| {
+                    !primary_key
+                        .iter()
+                        .any(|key| key.eq_ignore_ascii_case(&column.name))
+                })
+                .map(|column| {
+                    let name = sql::quote_identifier(&column.name);
+                    format!("{} = VALUES({})", name, name)
+                })
+                .collect(),
+        };
+        let assignments = if updated.is_empty() {
+            format!("{} = {}", names[0], names[0])
+        } else {
+            updated.join(", ")
+        };
+        statement.push_str(" ON DUPLICATE KEY UPDATE ");
+        statement.push_str(&assignments);
+        statement
+    }
+}
+
+fn is_auto_increment(column: &ColumnSchema) -> bool {
+    column
+        .extra
+        .as_deref()
+        .is_some_and(|extra| extra.to_lowercase().contains("auto_increment"))
+}
+
+fn is_generated(column: &ColumnSchema) -> bool {
+    column.extra.as_deref().is_some_and(|extra| {
+        let extra = extra.to_uppercase();
+        extra.contains("VIRTUAL GENERATED") || extra.contains("STORED GENERATED")
+    })
+}
+
+/// Rows converted for one `INSERT`
+#[derive(Debug)]
+pub struct ImportBatch {
+    /// Lines of the first and last row
+    pub lines: (u64, u64),
+    pub rows: Vec<Vec<SqlParam>>,
+}
+
+/// What reading a file found
+#[derive(Debug, Default)]
+pub struct ReadSummary {
+    pub rows: u64,
+    /// The first [`MAX_REPORTED_ISSUES`] problems
+    pub issues: Vec<ImportIssue>,
+    pub issue_count: u64,
+}
+
+/// Read, check and convert the rows of a file, sending them on in batches
+///
+/// Without a sender (a dry run) every row is checked; otherwise reading stops
+/// at the first invalid row, or when the receiver goes away. Blocks, so it
+/// belongs on a blocking task.
+pub fn read_batches(
+    mut reader: RecordReader,
+    plan: &ImportPlan,
+    batch_rows: usize,
+    sender: Option<mpsc::Sender<ImportBatch>>,
+) -> Result<ReadSummary> {
+    let batch_rows = plan.max_batch_rows(batch_rows);
+    let mut summary = ReadSummary::default();
+    let mut batch = ImportBatch {
+        lines: (0, 0),
+        rows: Vec::new(),
+    };
+    let mut batch_bytes = 0;
+
+    while let Some(record) = reader.next_record()? {
+        summary.rows += 1;
+        let row = match plan.convert(&record) {
+            Ok(row) => row,
+            Err(issue) => {
+                summary.issue_count += 1;
+                if summary.issues.len() < MAX_REPORTED_ISSUES {
+                    summary.issues.push(issue);
+                }
+                if sender.is_some() {
+                    return Ok(summary);
+                }
+                continue;
+            }
+        };
+        let Some(sender) = &sender else {
+            continue;
+        };
+
+        if batch.rows.is_empty() {
+            batch.lines.0 = record.line;
+        }
+        batch.lines.1 = record.line;
+        batch_bytes += row.iter().map(param_size).sum::<usize>();
+        batch.rows.push(row);
+
+        if batch.rows.len() >= batch_rows || batch_bytes >= MAX_BATCH_BYTES {
+            let full = std::mem::replace(
+                &mut batch,
+                ImportBatch {
+                    lines: (0, 0),
+                    rows: Vec::new(),
+                },
+            );
+            batch_bytes = 0;
+            if sender.blocking_send(full).is_err() {
+                return Ok(summary);
+            }
+        }
+    }
+
+    if let Some(sender) = &sender {
+        if !batch.rows.is_empty() {
+            let _ = sender.blocking_send(batch);
+        }
+    }
+    Ok(summary)
+}
+
+fn param_size(param: &SqlParam) -> usize {
+    match param {
+        SqlParam::String(s) | SqlParam::Decimal(s) => s.len(),
+        SqlParam::Binary(bytes) => bytes.len(),
+        _ => 8,
+    }
+}
+
+/// The type of a table column, parsed from `information_schema.COLUMNS.COLUMN_TYPE`
+#[derive(Debug, Clone, PartialEq)]
+enum ColumnType {
+    Integer { min: i128, max: i128 },
+    Bit(u32),
+    Decimal { precision: u32, scale: u32, unsigned: bool },
+    Float,
+    Date,
+    DateTime,
+    Time,
+    Year,
+    /// Maximum length in characters
+    Char(u64),
+    /// Maximum length in bytes
+    Text(u64),
+    /// Maximum length in bytes
+    Binary(u64),
+    Enum(Vec<String>),
+    Set(Vec<String>),
+    Json,
+    Spatial,
+    Other,
+}
+
+impl ColumnType {
+    fn parse(column_type: &str) -> Self {
+        let lower = column_type.trim().to_lowercase();
+        let unsigned = lower.contains(" unsigned");
+        let (base, args) = match lower.find('(') {
+            Some(open) => (
+                &lower[..open],
+                lower[open + 1..lower.rfind(')').unwrap_or(lower.len())].to_string(),
+            ),
+            None => (
+                lower.split_whitespace().next().unwrap_or(""),
+                String::new(),
+            ),
+        };
+        let numbers: Vec<u64> = args
+            .split(',')
+            .filter_map(|n| n.trim().parse().ok())
+            .collect();
+        let length = |default: u64| numbers.first().copied().unwrap_or(default);
+        let integer = |bits: u32| {
+            if unsigned {
+                ColumnType::Integer {
+                    min: 0,
+                    max: (1i128 << bits) - 1,
+                }
+            } else {
+                ColumnType::Integer {
+                    min: -(1i128 << (bits - 1)),
+                    max: (1i128 << (bits - 1)) - 1,
+                }
+            }
+        };
+
+        match base {
+            "tinyint" | "bool" | "boolean" => integer(8),
+            "smallint" => integer(16),
+            "mediumint" => integer(24),
+            "int" | "integer" => integer(32),
+            "bigint" => integer(64),
+            "bit" => ColumnType::Bit(length(1) as u32),
+            "decimal" | "numeric" | "dec" | "fixed" => ColumnType::Decimal {
+                precision: length(10) as u32,
+                scale: numbers.get(1).copied().unwrap_or(0) as u32,
+                unsigned,
+            },
+            "float" | "double" | "real" => ColumnType::Float,
+            "date" => ColumnType::Date,
+            "datetime" | "timestamp" => ColumnType::DateTime,
+            "time" => ColumnType::Time,
+            "year" => ColumnType::Year,
+            "char" | "varchar" => ColumnType::Char(length(255)),
+            "tinytext" => ColumnType::Text(255),
+            "text" => ColumnType::Text(65_535),
+            "mediumtext" => ColumnType::Text(16_777_215),
+            "longtext" => ColumnType::Text(4_294_967_295),
+            "binary" | "varbinary" => ColumnType::Binary(length(255)),
+            "tinyblob" => ColumnType::Binary(255),
+            "blob" => ColumnType::Binary(65_535),
+            "mediumblob" => ColumnType::Binary(16_777_215),
+            "longblob" => ColumnType::Binary(4_294_967_295),
+            "enum" => ColumnType::Enum(parse_members(&column_type[lower.find('(').unwrap_or(0)..])),
+            "set" => ColumnType::Set(parse_members(&column_type[lower.find('(').unwrap_or(0)..])),
+            "json" => ColumnType::Json,
+            "geometry" | "point" | "linestring" | "polygon" | "multipoint"
+            | "multilinestring" | "multipolygon" | "geometrycollection" | "geomcollection" => {
+                ColumnType::Spatial
+            }
+            _ => ColumnType::Other,
+        }
+    }
+
+    /// Check a non-null value and convert it to a bind value
+    fn convert(&self, field: &Field) -> std::result::Result<SqlParam, String> {
+        match self {
+            ColumnType::Integer { min, max } => {
+                let n = integer(field)?;
+                if n < *min || n > *max {
+                    return Err(format!("{} is out of range {}..={}", n, min, max));
+                }
+                Ok(int_param(n))
+            }
+            ColumnType::Bit(bits) => {
+                let n = integer(field)?;
+                if n < 0 || n >= 1i128 << bits {
+                    return Err(format!("{} does not fit in BIT({})", n, bits));
+                }
+                Ok(int_param(n))
+            }
+            ColumnType::Decimal {
+                precision,
+                scale,
+                unsigned,
+            } => {
+                let text = match field {
+                    Field::Json(Value::Number(n)) => n.to_string(),
+                    other => scalar_text(other)?,
+                };
+                check_decimal(text.trim(), *precision, *scale, *unsigned)?;
+                Ok(SqlParam::Decimal(text.trim().to_string()))
+            }
+            ColumnType::Float => match field {
+                Field::Json(Value::Number(n)) => n
+                    .as_f64()
+                    .map(SqlParam::Float)
+                    .ok_or_else(|| format!("{} is not a number", n)),
+                other => {
+                    let text = scalar_text(other)?;
+                    text.trim()
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|n| n.is_finite())
+                        .map(SqlParam::Float)
+                        .ok_or_else(|| format!("'{}' is not a number", text))
+                }
+            },
+            ColumnType::Date => {
+                let text = scalar_text(field)?;
+                NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d")
+                    .map(SqlParam::Date)
+                    .map_err(|_| format!("'{}' is not a date (YYYY-MM-DD)", text))
+            }
+            ColumnType::DateTime => {
+                let text = scalar_text(field)?;
+                params::parse_datetime(text.trim()).ok_or_else(|| {
+                    format!(
+                        "'{}' is not a datetime (YYYY-MM-DD HH:MM:SS[.ffffff] or RFC 3339)",
+                        text
+                    )
+                })
+            }
+            ColumnType::Time => {
+                let text = scalar_text(field)?;
+                if time_pattern().is_match(text.trim()) {
+                    Ok(SqlParam::String(text.trim().to_string()))
+                } else {
+                    Err(format!("'{}' is not a time ([-]HH:MM:SS[.ffffff])", text))
+                }
+            }
+            ColumnType::Year => {
+                let n = integer(field)?;
+                if n == 0 || (1901..=2155).contains(&n) {
+                    Ok(int_param(n))
+                } else {
+                    Err(format!("{} is not a year between 1901 and 2155", n))
+                }
+            }
+            ColumnType::Char(max) => {
+                let text = scalar_text(field)?;
+                let length = text.chars().count() as u64;
+                if length > *max {
+                    return Err(format!("{} characters exceed the length of {}", length, max));
+                }
+                Ok(SqlParam::String(text))
+            }
+            ColumnType::Text(max) => {
+                let text = scalar_text(field)?;
+                if text.len() as u64 > *max {
+                    return Err(format!("{} bytes exceed the length of {}", text.len(), max));
+                }
+                Ok(SqlParam::String(text))
+            }
+            ColumnType::Binary(max) => {
+                let bytes = binary(field)?;
+                if bytes.len() as u64 > *max {
+                    return Err(format!("{} bytes exceed the length of {}", bytes.len(), max));
+                }
+                Ok(SqlParam::Binary(bytes))
+            }
+            ColumnType::Enum(members) => {
+                let text = scalar_text(field)?;
+                member(members, &text).map(|m| SqlParam::String(m.to_string()))
+            }
+            ColumnType::Set(members) => {
+                let items: Vec<String> = match field {
+                    Field::Json(Value::Array(items)) => items
+                        .iter()
+                        .map(|item| {
+                            item.as_str()
+                                .map(str::to_string)
+                                .ok_or_else(|| "SET members must be strings".to_string())
+                        })
+                        .collect::<std::result::Result<_, _>>()?,
+                    Field::Text(text) if text.trim_start().starts_with('[') => {
+                        serde_json::from_str(text)
+                            .map_err(|_| format!("'{}' is not a JSON array of strings", text))?
+                    }
+                    other => {
+                        let text = scalar_text(other)?;
+                        if text.is_empty() {
+                            Vec::new()
+                        } else {
+                            text.split(',').map(str::to_string).collect()
+                        }
+                    }
+                };
+                let items = items
+                    .iter()
+                    .map(|item| member(members, item))
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                Ok(SqlParam::String(items.join(",")))
+            }
+            ColumnType::Json => match field {
+                Field::Json(value) => Ok(SqlParam::String(value.to_string())),
+                Field::Text(text) => serde_json::from_str::<Value>(text)
+                    .map(|_| SqlParam::String(text.clone()))
+                    .map_err(|e| format!("invalid JSON: {}", e)),
+                Field::Null => Ok(SqlParam::Null),
+            },
+            ColumnType::Spatial => tagged_binary(field)
+                .map(SqlParam::Binary)
+                .ok_or_else(|| {
+                    "spatial values must be {\"type\": \"binary\", \"value\": \"<base64>\"}"
+                        .to_string()
+                }),
+            ColumnType::Other => scalar_text(field).map(SqlParam::String),
+        }
+    }
+}
+
+/// Members of an `enum('a','b')` or `set(...)` type
+fn parse_members(args: &str) -> Vec<String> {
+    let mut members = Vec::new();
+    let mut chars = args.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\'' {
+            continue;
+        }
+        let mut member = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                '\'' if chars.peek() == Some(&'\'') => {
+                    chars.next();
+                    member.push('\'');
+                }
+                '\'' => break,
+                '\\' => {
+                    if let Some(escaped) = chars.next() {
+                        member.push(escaped);
+                    }
+                }
+                c => member.push(c),
+            }
+        }
+        members.push(member);
+    }
+    members
+}
+
+fn member<'a>(members: &'a [String], value: &str) -> std::result::Result<&'a str, String> {
+    members
+        .iter()
+        .find(|member| member.eq_ignore_ascii_case(value))
+        .map(String::as_str)
+        .ok_or_else(|| format!("'{}' is not one of {}", value, members.join(", ")))
+}
+
+fn int_param(n: i128) -> SqlParam {
+    match i64::try_from(n) {
+        Ok(n) => SqlParam::Int(n),
+        Err(_) => SqlParam::UInt(n as u64),
+    }
+}
+
+fn integer(field: &Field) -> std::result::Result<i128, String> {
+    match field {
+        Field::Json(Value::Bool(b)) => Ok(i128::from(*b)),
+        Field::Json(Value::Number(n)) => n
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| n.as_u64().map(i128::from))
+            .ok_or_else(|| format!("{} is not an integer", n)),
+        other => {
+            let text = scalar_text(other)?;
+            match text.trim() {
+                "true" | "TRUE" => Ok(1),
+                "false" | "FALSE" => Ok(0),
+                trimmed => trimmed
+                    .parse()
+                    .map_err(|_| format!("'{}' is not an integer", text)),
+            }
+        }
+    }
+}
+
+/// Check that a decimal fits `DECIMAL(precision, scale)` without rounding
+fn check_decimal(
+    text: &str,
+    precision: u32,
+    scale: u32,
+    unsigned: bool,
+) -> std::result::Result<(), String> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| Regex::new(r"^([+-]?)(\d*)(?:\.(\d*))?$").unwrap());
+    let captures = pattern
+        .captures(text)
+        .filter(|c| !(c[2].is_empty() && c.get(3).is_none_or(|f| f.as_str().is_empty())))
+        .ok_or_else(|| format!("'{}' is not a decimal number", text))?;
+
+    let integer_digits = captures[2].trim_start_matches('0').len() as u32;
+    let fraction_digits = captures
+        .get(3)
+        .map_or(0, |f| f.as_str().trim_end_matches('0').len()) as u32;
+    if unsigned && &captures[1] == "-" && (integer_digits > 0 || fraction_digits > 0) {
+        return Err(format!("{} is negative but the column is unsigned", text));
+    }
+    if integer_digits > precision.saturating_sub(scale) || fraction_digits > scale {
+        return Err(format!(
+            "{} does not fit DECIMAL({},{}) without rounding",
+            text, precision, scale
+        ));
+    }
+    Ok(())
+}
+
+fn time_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"^-?\d{1,3}:[0-5]\d:[0-5]\d(\.\d{1,6})?$").unwrap())
+}
+
+/// Text of a string, number or boolean value
+fn scalar_text(field: &Field) -> std::result::Result<String, String> {
+    match field {
+        Field::Text(text) => Ok(text.clone()),
+        Field::Json(Value::String(s)) => Ok(s.clone()),
+        Field::Json(value @ (Value::Number(_) | Value::Bool(_))) => Ok(value.to_string()),
+        Field::Json(_) => Err("arrays and objects are only accepted for JSON and SET columns, or as tagged binary values".to_string()),
+        Field::Null => Err("unexpected NULL".to_string()),
+    }
+}
+
+/// Bytes of a binary value: tagged base64, or the bytes of a plain string
+fn binary(field: &Field) -> std::result::Result<Vec<u8>, String> {
+    if let Some(bytes) = tagged_binary(field) {
+        return Ok(bytes);
+    }
+    scalar_text(field).map(String::into_bytes)
+}
+
+/// Bytes of `{"type": "binary", "value": "<base64>"}`, also in the text of a CSV field
+fn tagged_binary(field: &Field) -> Option<Vec<u8>> {
+    let parsed;
+    let value = match field {
+        Field::Json(value) => value,
+        Field::Text(text) if text.starts_with('{') => {
+            parsed = serde_json::from_str::<Value>(text).ok()?;
+            &parsed
+        }
+        _ => return None,
+    };
+    if value.get("type")? != "binary" {
+        return None;
+    }
+    base64::engine::general_purpose::STANDARD
+        .decode(value.get("value")?.as_str()?)
+        .ok()
+}
+
+fn io_error(error: io::Error) -> McpError {
+    McpError::QueryExecutionError(format!("Import failed: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    fn reader(text: &str, format: ImportFormat) -> Result<RecordReader> {
+        RecordReader::new(Box::new(Cursor::new(text.as_bytes().to_vec())), format)
+    }
+
+    fn records(reader: &mut RecordReader) -> Vec<Record> {
+        std::iter::from_fn(|| reader.next_record().unwrap()).collect()
+    }
+
+    fn column(name: &str, data_type: &str, nullable: bool) -> ColumnSchema {
+        ColumnSchema {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            nullable,
+            default_value: None,
+            comment: None,
+            extra: None,
+        }
+    }
+
+    fn text(s: &str) -> Field {
+        Field::Text(s.to_string())
+    }
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(detect_format("a.csv"), (Some(ImportFormat::Csv), Compression::None));
+        assert_eq!(detect_format("dir/a.NDJSON.gz"), (Some(ImportFormat::Ndjson), Compression::Gzip));
+        assert_eq!(detect_format("a.jsonl.zst"), (Some(ImportFormat::Ndjson), Compression::Zstd));
+        assert_eq!(detect_format("a.txt"), (None, Compression::None));
+    }
+
+    #[test]
+    fn test_resolve_path_requires_existing_file_in_root() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("in.csv"), "id\n1\n").unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+
+        let root = dir.path().canonicalize().unwrap();
+        assert_eq!(resolve_path(dir.path(), "in.csv").unwrap(), root.join("in.csv"));
+        for name in ["missing.csv", "sub", "../in.csv", "/etc/passwd", ""] {
+            assert!(
+                matches!(resolve_path(dir.path(), name), Err(McpError::InvalidStatement(_))),
+                "{:?} was accepted",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_csv_records() {
+        let mut reader = reader(
+            "\u{feff}id,name,note\r\n1,\"a,b\",\n2,\"\",\"two\nlines \"\"quoted\"\"\"\n\n3,c\n",
+            ImportFormat::Csv,
+        )
+        .unwrap();
+        assert_eq!(reader.columns(), ["id", "name", "note"]);
+
+        let records = records(&mut reader);
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].line, 2);
+        assert_eq!(records[0].fields, Ok(vec![text("1"), text("a,b"), Field::Null]));
+        assert_eq!(
+            records[1].fields,
+            Ok(vec![text("2"), text(""), text("two\nlines \"quoted\"")])
+        );
+        assert_eq!(records[2].line, 6);
+        assert!(records[2].fields.is_err());
+    }
+
+    #[test]
+    fn test_csv_unclosed_quote_is_an_error() {
+        let mut reader = reader("id\n\"open\n", ImportFormat::Csv).unwrap();
+        assert!(matches!(reader.next_record(), Err(McpError::InvalidStatement(_))));
+    }
+
+    #[test]
+    fn test_ndjson_records() {
+        let mut reader = reader(
+            "{\"id\": 1, \"name\": \"a\"}\n\n{\"id\": 2}\n{\"id\": 3, \"other\": 1}\n[1]\n",
+            ImportFormat::Ndjson,
+        )
+        .unwrap();
+        assert_eq!(reader.columns(), ["id", "name"]);
+
+        let records = records(&mut reader);
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0].fields, Ok(vec![Field::Json(json!(1)), Field::Json(json!("a"))]));
+        assert_eq!(records[1].line, 3);
+        assert_eq!(records[1].fields, Ok(vec![Field::Json(json!(2)), Field::Null]));
+        assert!(records[2].fields.is_err());
+        assert!(records[3].fields.is_err());
+    }
+
+    #[test]
+    fn test_plan_matches_columns() {
+        let mut id = column("id", "int", false);
+        id.extra = Some("auto_increment".to_string());
+        let mut total = column("total", "int", true);
+        total.extra = Some("VIRTUAL GENERATED".to_string());
+        let table = vec![id, column("Name", "varchar(10)", false), column("note", "text", true), total];
+
+        let plan = ImportPlan::new(&["name".to_string()], &table).unwrap();
+        assert_eq!(plan.column_names(), ["Name"]);
+
+        let plan = |names: &[&str]| {
+            ImportPlan::new(&names.iter().map(|n| n.to_string()).collect::<Vec<_>>(), &table)
+        };
+        for names in [&["name", "NAME"][..], &["name", "unknown"], &["note"], &["name", "total"], &["name", ""]] {
+            assert!(
+                matches!(plan(names), Err(McpError::InvalidStatement(_))),
+                "{:?} was accepted",
+                names
+            );
+        }
+    }
+
+    #[test]
+    fn test_convert_checks_types() {
+        let cases: Vec<(&str, Field, std::result::Result<SqlParam, ()>)> = vec![
+            ("tinyint unsigned", text("255"), Ok(SqlParam::Int(255))),
+            ("tinyint unsigned", text("256"), Err(())),
+            ("tinyint(1)", Field::Json(json!(true)), Ok(SqlParam::Int(1))),
+            ("bigint unsigned", Field::Json(json!(u64::MAX)), Ok(SqlParam::UInt(u64::MAX))),
+            ("int", text("1.5"), Err(())),
+            ("bit(2)", text("3"), Ok(SqlParam::Int(3))),
+            ("bit(2)", text("4"), Err(())),
+            ("decimal(5,2)", text("123.40"), Ok(SqlParam::Decimal("123.40".to_string()))),
+            ("decimal(5,2)", text("1234.5"), Err(())),
+            ("decimal(5,2)", text("1.234"), Err(())),
+            ("decimal(5,2) unsigned", text("-1"), Err(())),
+            ("double", Field::Json(json!(1.5)), Ok(SqlParam::Float(1.5))),
+            ("double", text("abc"), Err(())),
+            ("date", text("2024-02-29"), Ok(SqlParam::Date(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()))),
+            ("date", text("2023-02-29"), Err(())),
+            ("timestamp(3)", text("2024-05-01T13:45:00Z"), Ok(params::parse_datetime("2024-05-01 13:45:00").unwrap())),
+            ("time", text("-838:59:59"), Ok(SqlParam::String("-838:59:59".to_string()))),
+            ("time", text("12:60:00"), Err(())),
+            ("year", text("2024"), Ok(SqlParam::Int(2024))),
+            ("year", text("1800"), Err(())),
+            ("varchar(3)", text("äöü"), Ok(SqlParam::String("äöü".to_string()))),
+            ("varchar(3)", text("abcd"), Err(())),
+            ("varchar(3)", Field::Json(json!(12)), Ok(SqlParam::String("12".to_string()))),
+            ("varchar(3)", Field::Json(json!([1])), Err(())),
+            ("varbinary(2)", Field::Json(json!({"type": "binary", "value": "3q0="})), Ok(SqlParam::Binary(vec![0xde, 0xad]))),
+            ("varbinary(2)", text("abc"), Err(())),
+            ("enum('a','It''s')", text("it's"), Ok(SqlParam::String("It's".to_string()))),
+            ("enum('a','b')", text("c"), Err(())),
+            ("set('x','y')", Field::Json(json!(["y", "x"])), Ok(SqlParam::String("y,x".to_string()))),
+            ("set('x','y')", text("[\"x\"]"), Ok(SqlParam::String("x".to_string()))),
+            ("set('x','y')", text("x,z"), Err(())),
+            ("set('x','y')", text(""), Ok(SqlParam::String(String::new()))),
+            ("json", Field::Json(json!({"a": [1]})), Ok(SqlParam::String("{\"a\":[1]}".to_string()))),
+            ("json", text("{bad"), Err(())),
+            ("point", text("POINT(1 2)"), Err(())),
+        ];
+
+        for (data_type, field, expected) in cases {
+            let result = ColumnType::parse(data_type).convert(&field).map_err(|_| ());
+            assert_eq!(result, expected, "{} {:?}", data_type, field);
+        }
+    }
+
+    #[test]
+    fn test_convert_rejects_null_in_not_null_column() {
+        let plan = ImportPlan::new(
+            &["a".to_string(), "b".to_string()],
+            &[column("a", "int", true), column("b", "int", false)],
+        )
+        .unwrap();
+
+        let record = Record {
+            line: 7,
+            fields: Ok(vec![Field::Null, text("1")]),
+        };
+        assert_eq!(plan.convert(&record).unwrap(), vec![SqlParam::Null, SqlParam::Int(1)]);
+
+        let record = Record {
+            line: 8,
+            fields: Ok(vec![text("1"), Field::Null]),
+        };
+        let issue = plan.convert(&record).unwrap_err();
+        assert_eq!(issue.line, 8);
+        assert_eq!(issue.column.as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn test_insert_sql() {
+        let plan = ImportPlan::new(
+            &["id".to_string(), "name".to_string()],
+            &[column("id", "int", false), column("name", "text", true)],
+        )
+        .unwrap();
+        let key = vec!["id".to_string()];
+
+        assert_eq!(
+            plan.insert_sql("t", 2, OnDuplicate::Error, &key),
+            "INSERT INTO `t` (`id`, `name`) VALUES (?, ?), (?, ?)"
+        );
+        assert_eq!(
+            plan.insert_sql("t", 1, OnDuplicate::Ignore, &key),
+            "INSERT INTO `t` (`id`, `name`) VALUES (?, ?) ON DUPLICATE KEY UPDATE `id` = `id`"
+        );
+        assert_eq!(
+            plan.insert_sql("t", 1, OnDuplicate::Update, &key),
+            "INSERT INTO `t` (`id`, `name`) VALUES (?, ?) ON DUPLICATE KEY UPDATE `name` = VALUES(`name`)"
+        );
+        assert_eq!(plan.max_batch_rows(100_000), MAX_PLACEHOLDERS / 2);
+    }
+
+    #[test]
+    fn test_read_batches() {
+        let plan = ImportPlan::new(&["id".to_string()], &[column("id", "int", false)]).unwrap();
+
+        // A dry run checks every row
+        let summary = read_batches(
+            reader("id\n1\nx\n3\ny\n", ImportFormat::Csv).unwrap(),
+            &plan,
+            2,
+            None,
+        )
+        .unwrap();
+        assert_eq!(summary.rows, 4);
+        assert_eq!(summary.issue_count, 2);
+        assert_eq!(summary.issues[0].line, 3);
+
+        // An import sends batches and stops at the first invalid row
+        let (sender, mut receiver) = mpsc::channel(10);
+        let summary = read_batches(
+            reader("id\n1\n2\n3\nx\n5\n", ImportFormat::Csv).unwrap(),
+            &plan,
+            2,
+            Some(sender),
+        )
+        .unwrap();
+        assert_eq!(summary.issue_count, 1);
+        let batch = receiver.try_recv().unwrap();
+        assert_eq!(batch.lines, (2, 3));
+        assert_eq!(batch.rows, vec![vec![SqlParam::Int(1)], vec![SqlParam::Int(2)]]);
+        // Row 3 is read but never sent, as the import will be rolled back
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_open_compressed_file() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("in.csv.gz");
+        let mut encoder =
+            flate2::write::GzEncoder::new(File::create(&path).unwrap(), flate2::Compression::default());
+        encoder.write_all(b"id\n1\n").unwrap();
+        encoder.finish().unwrap();
+
+        let mut reader = RecordReader::open(&path, ImportFormat::Csv, Compression::Gzip).unwrap();
+        assert_eq!(reader.columns(), ["id"]);
+        assert_eq!(records(&mut reader).len(), 1);
+    }
+}
@@ -1,11 +1,23 @@
+pub mod advisor;
+pub mod breaker;
 pub mod cache;
 pub mod config;
+pub mod digest;
 pub mod error;
+pub mod explain;
+pub mod export;
+pub mod format;
+pub mod import;
 pub mod logging;
 pub mod manager;
 pub mod mcp_server;
 pub mod memory;
 pub mod monitoring;
+pub mod params;
 pub mod pool;
 pub mod resources;
+pub mod sql;
 pub mod tools;
+pub mod transaction;
+pub mod types;
+pub mod variables;
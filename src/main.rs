@@ -1,12 +1,25 @@
+mod advisor;
+mod breaker;
+mod cache;
 mod config;
+mod digest;
 mod error;
+mod explain;
+mod export;
+mod format;
+mod import;
 mod logging;
 mod manager;
 mod mcp_server;
 mod monitoring;
+mod params;
 mod pool;
 mod resources;
+mod sql;
 mod tools;
+mod transaction;
+mod types;
+mod variables;
 
 use config::ServerConfig;
 use logging::init_tracing;
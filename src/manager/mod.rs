@@ -1,7 +1,14 @@
-use crate::config::DataSourceConfig;
-use crate::error::{McpError, Result};
+use crate::breaker::{CircuitBreaker, CircuitInfo, CircuitState};
+use crate::config::{DataSourceConfig, DatabaseAllowlist, HealthConfig, ResultLimits};
+use crate::error::{retry_with_backoff, McpError, Result, RetryConfig};
+use crate::pool;
+use crate::sql;
+use crate::types::TypeMapping;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
 /// Connection status for a data source
@@ -11,21 +18,117 @@ pub enum ConnectionStatus {
     Unavailable,
 }
 
+/// Results of the background health checks of a data source
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SourceHealth {
+    /// Failed checks since the last successful one
+    pub consecutive_failures: u32,
+    /// Failed checks since the server started
+    pub total_failures: u64,
+    /// Error of the last failed check, sanitized
+    pub last_error: Option<String>,
+    /// Time of the last successful check (RFC 3339)
+    pub last_success: Option<String>,
+    /// Time of the last failed check (RFC 3339)
+    pub last_failure: Option<String>,
+}
+
 /// Information about a data source (without credentials)
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct DataSourceInfo {
     pub key: String,
     pub name: String,
     pub status: ConnectionStatus,
+    pub health: SourceHealth,
+    pub circuit: CircuitInfo,
+}
+
+/// Status of a data source together with its health check results and the
+/// circuit breaker guarding tool calls
+#[derive(Debug, Clone)]
+struct SourceState {
+    status: ConnectionStatus,
+    health: SourceHealth,
+    breaker: CircuitBreaker,
 }
 
+impl SourceState {
+    fn record_success(&mut self, key: &str) {
+        self.health.consecutive_failures = 0;
+        self.health.last_success = Some(now());
+
+        if self.status == ConnectionStatus::Unavailable {
+            self.status = ConnectionStatus::Available;
+            tracing::info!(key = %key, "Data source reconnected, marked as available");
+        }
+    }
+
+    fn record_failure(&mut self, key: &str, error: &McpError, failure_threshold: u32) {
+        // Other errors (timeouts, SQL errors) say nothing about the server being reachable
+        if !error.is_connection_error() {
+            tracing::debug!(key = %key, error = %error.sanitize(), "Ignoring non-connection error");
+            return;
+        }
+
+        self.health.consecutive_failures = self.health.consecutive_failures.saturating_add(1);
+        self.health.total_failures += 1;
+        self.health.last_error = Some(error.sanitize());
+        self.health.last_failure = Some(now());
+
+        if self.status == ConnectionStatus::Available
+            && self.health.consecutive_failures >= failure_threshold
+        {
+            self.status = ConnectionStatus::Unavailable;
+            tracing::warn!(
+                key = %key,
+                consecutive_failures = self.health.consecutive_failures,
+                error = %error.sanitize(),
+                "Data source marked as unavailable"
+            );
+        } else {
+            tracing::warn!(
+                key = %key,
+                consecutive_failures = self.health.consecutive_failures,
+                error = %error.sanitize(),
+                "Health check failed"
+            );
+        }
+    }
+}
+
+type SourceStates = RwLock<HashMap<String, SourceState>>;
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+const DEFAULT_QUERY_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_MAX_QUERY_TIMEOUT: Duration = Duration::from_secs(300);
+const DEFAULT_RESULT_LIMITS: ResultLimits = ResultLimits {
+    max_rows: 10_000,
+    max_result_bytes: 5 * 1024 * 1024,
+};
+
 /// Manages data sources and their connection pools
 pub struct DataSourceManager {
     sources: HashMap<String, Arc<DataSourceConfig>>,
-    // Track connection status for each data source (wrapped in RwLock for concurrent access)
-    status: Arc<RwLock<HashMap<String, ConnectionStatus>>>,
+    // Compiled database allowlists, one per data source
+    allowlists: HashMap<String, DatabaseAllowlist>,
+    // Track connection status and health for each data source (wrapped in RwLock for concurrent access)
+    states: Arc<SourceStates>,
+    // Background health check schedule and thresholds
+    health_config: HealthConfig,
+    // Retries of connection attempts and operations that are safe to repeat
+    retry_config: RetryConfig,
     // Handle for the background reconnection task
-    reconnect_task_handle: Option<tokio::task::JoinHandle<()>>,
+    reconnect_task_handle: Mutex<Option<tokio::task::JoinHandle<()>>>,
+    // Server-wide query timeout and the cap for all overrides
+    default_query_timeout: Duration,
+    max_query_timeout: Duration,
+    // Server-wide result limits, which also cap all overrides
+    result_limits: ResultLimits,
+    // How result values without a single JSON form are rendered
+    type_mapping: TypeMapping,
 }
 
 impl std::fmt::Debug for DataSourceManager {
@@ -35,7 +138,11 @@ impl std::fmt::Debug for DataSourceManager {
             .field("status", &"<RwLock>")
             .field(
                 "reconnect_task_active",
-                &self.reconnect_task_handle.is_some(),
+                &self
+                    .reconnect_task_handle
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .is_some(),
             )
             .finish()
     }
@@ -45,6 +152,7 @@ impl DataSourceManager {
     /// Create a new data source manager
     pub async fn new(configs: Vec<DataSourceConfig>) -> Result<Self> {
         let mut sources = HashMap::new();
+        let mut allowlists = HashMap::new();
         let mut status_map = HashMap::new();
 
         // Validate that all keys are unique
@@ -66,82 +174,123 @@ impl DataSourceManager {
                 "Registering data source"
             );
             
+            let allowlist = config
+                .database_allowlist()
+                .map_err(|e| McpError::ConfigurationError(e.to_string()))?;
+
             let key = config.key.clone();
-            sources.insert(key.clone(), Arc::new(config));
             // Initially mark all data sources as available
-            status_map.insert(key, ConnectionStatus::Available);
+            status_map.insert(
+                key.clone(),
+                SourceState {
+                    status: ConnectionStatus::Available,
+                    health: SourceHealth::default(),
+                    breaker: CircuitBreaker::new(config.circuit_breaker.clone()),
+                },
+            );
+            allowlists.insert(key.clone(), allowlist);
+            sources.insert(key, Arc::new(config));
         }
 
-        let status = Arc::new(RwLock::new(status_map));
+        let states = Arc::new(RwLock::new(status_map));
 
         Ok(Self {
             sources,
-            status,
-            reconnect_task_handle: None,
+            allowlists,
+            states,
+            health_config: HealthConfig::default(),
+            retry_config: RetryConfig::default(),
+            reconnect_task_handle: Mutex::new(None),
+            default_query_timeout: DEFAULT_QUERY_TIMEOUT,
+            max_query_timeout: DEFAULT_MAX_QUERY_TIMEOUT,
+            result_limits: DEFAULT_RESULT_LIMITS,
+            type_mapping: TypeMapping::default(),
         })
     }
 
-    /// Start the background reconnection task
-    /// This task will periodically check unavailable data sources and attempt to reconnect
-    pub fn start_reconnection_task(mut self) -> Self {
-        let status = self.status.clone();
+    /// Set the server-wide query timeout and the maximum any override may request
+    pub fn with_query_timeouts(mut self, default: Duration, max: Duration) -> Self {
+        self.max_query_timeout = max;
+        self.default_query_timeout = default.min(max);
+        self
+    }
+
+    /// Set the server-wide result limits, which also cap all overrides
+    pub fn with_result_limits(mut self, limits: ResultLimits) -> Self {
+        self.result_limits = limits;
+        self
+    }
+
+    /// Set how result values without a single JSON form are rendered
+    pub fn with_type_mapping(mut self, mapping: TypeMapping) -> Self {
+        self.type_mapping = mapping;
+        self
+    }
+
+    /// Get how result values without a single JSON form are rendered
+    pub fn type_mapping(&self) -> TypeMapping {
+        self.type_mapping
+    }
+
+    /// Set the background health check schedule and thresholds
+    pub fn with_health_config(mut self, config: HealthConfig) -> Self {
+        self.health_config = config;
+        self
+    }
+
+    /// Set how connection attempts and operations that are safe to repeat are retried
+    pub fn with_retry_config(mut self, config: RetryConfig) -> Self {
+        self.retry_config = config;
+        self
+    }
+
+    /// Get how connection attempts and operations that are safe to repeat are retried
+    pub fn retry_config(&self) -> &RetryConfig {
+        &self.retry_config
+    }
+
+    /// Start the background health check and reconnection task
+    /// Every `interval_secs` each available data source is probed and marked unavailable
+    /// after `failure_threshold` consecutive connection failures; unavailable data
+    /// sources are probed with retries and marked available again once one succeeds
+    pub fn start_reconnection_task(self) -> Self {
+        let states = self.states.clone();
         let sources = self.sources.clone();
+        let health_config = self.health_config.clone();
+        let retry_config = self.retry_config.clone();
 
         let handle = tokio::spawn(async move {
-            let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+            let period = health_config.interval();
+            let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
             interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
             loop {
                 interval.tick().await;
 
-                let unavailable_keys: Vec<String> = {
-                    let status_guard = status.read().await;
-                    status_guard
-                        .iter()
-                        .filter(|(_, &s)| s == ConnectionStatus::Unavailable)
-                        .map(|(k, _)| k.clone())
-                        .collect()
-                };
-
-                if !unavailable_keys.is_empty() {
-                    tracing::info!(
-                        count = unavailable_keys.len(),
-                        "Attempting to reconnect to unavailable data sources"
-                    );
-
-                    for key in unavailable_keys {
-                        if let Some(config) = sources.get(&key) {
-                            tracing::debug!(
-                                key = %key,
-                                host = %config.host,
-                                "Attempting reconnection"
-                            );
-
-                            // Here we would attempt to create a test connection
-                            // For now, we'll just log the attempt
-                            // In a real implementation, this would try to establish a connection
-                            // and update the status accordingly
-                            
-                            // Placeholder for actual connection test
-                            // let connection_result = test_connection(config).await;
-                            // if connection_result.is_ok() {
-                            //     let mut status_guard = status.write().await;
-                            //     status_guard.insert(key.clone(), ConnectionStatus::Available);
-                            //     tracing::info!(key = %key, "Data source reconnected successfully");
-                            // }
-                        }
-                    }
-                }
+                futures::future::join_all(
+                    sources
+                        .values()
+                        .map(|config| check_source(&states, config, &health_config, &retry_config)),
+                )
+                .await;
             }
         });
 
-        self.reconnect_task_handle = Some(handle);
+        *self
+            .reconnect_task_handle
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(handle);
         self
     }
 
     /// Stop the background reconnection task
-    pub fn stop_reconnection_task(&mut self) {
-        if let Some(handle) = self.reconnect_task_handle.take() {
+    pub fn stop_reconnection_task(&self) {
+        let handle = self
+            .reconnect_task_handle
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        if let Some(handle) = handle {
             handle.abort();
             tracing::info!("Background reconnection task stopped");
         }
@@ -165,18 +314,25 @@ impl DataSourceManager {
 
     /// List all data sources (without exposing credentials)
     pub async fn list_sources(&self) -> Vec<DataSourceInfo> {
-        let status_guard = self.status.read().await;
+        let now = Instant::now();
+        let states_guard = self.states.read().await;
         self.sources
             .iter()
             .map(|(key, config)| {
-                let status = status_guard
+                let (status, health, circuit) = states_guard
                     .get(key)
-                    .cloned()
-                    .unwrap_or(ConnectionStatus::Unavailable);
+                    .map(|state| (state.status, state.health.clone(), state.breaker.info(now)))
+                    .unwrap_or((
+                        ConnectionStatus::Unavailable,
+                        SourceHealth::default(),
+                        CircuitInfo::default(),
+                    ));
                 DataSourceInfo {
                     key: key.clone(),
                     name: config.name.clone(),
                     status,
+                    health,
+                    circuit,
                 }
             })
             .collect()
@@ -184,15 +340,27 @@ impl DataSourceManager {
 
     /// Get the status of a specific data source
     pub async fn get_status(&self, key: &str) -> Option<ConnectionStatus> {
-        let status_guard = self.status.read().await;
-        status_guard.get(key).cloned()
+        let states_guard = self.states.read().await;
+        states_guard.get(key).map(|state| state.status)
+    }
+
+    /// Get the health check results of a specific data source
+    pub async fn get_health(&self, key: &str) -> Option<SourceHealth> {
+        let states_guard = self.states.read().await;
+        states_guard.get(key).map(|state| state.health.clone())
+    }
+
+    /// Get the circuit breaker state of a specific data source
+    pub async fn get_circuit(&self, key: &str) -> Option<CircuitInfo> {
+        let states_guard = self.states.read().await;
+        states_guard.get(key).map(|state| state.breaker.info(Instant::now()))
     }
 
     /// Update the status of a data source
     pub async fn set_status(&self, key: &str, status: ConnectionStatus) {
-        if self.sources.contains_key(key) {
-            let mut status_guard = self.status.write().await;
-            status_guard.insert(key.to_string(), status);
+        let mut states_guard = self.states.write().await;
+        if let Some(state) = states_guard.get_mut(key) {
+            state.status = status;
 
             match status {
                 ConnectionStatus::Available => {
@@ -205,13 +373,43 @@ impl DataSourceManager {
         }
     }
 
+    /// Record a successful check of a data source, marking it available again
+    pub async fn record_success(&self, key: &str) {
+        let mut states_guard = self.states.write().await;
+        if let Some(state) = states_guard.get_mut(key) {
+            state.record_success(key);
+        }
+    }
+
+    /// Record a failed check of a data source; connection errors count towards
+    /// marking it unavailable, other errors are ignored
+    pub async fn record_failure(&self, key: &str, error: &McpError) {
+        let mut states_guard = self.states.write().await;
+        if let Some(state) = states_guard.get_mut(key) {
+            state.record_failure(key, error, self.health_config.failure_threshold);
+        }
+    }
+
     /// Check if a data source is available
+    /// A data source whose circuit is open is not; once its cool-down has passed,
+    /// the first caller is let through as the half-open probe
     pub async fn is_available(&self, key: &str) -> bool {
-        let status_guard = self.status.read().await;
-        status_guard
-            .get(key)
-            .map(|s| *s == ConnectionStatus::Available)
-            .unwrap_or(false)
+        let mut states_guard = self.states.write().await;
+        let Some(state) = states_guard.get_mut(key) else {
+            return false;
+        };
+        if state.status != ConnectionStatus::Available {
+            return false;
+        }
+
+        let was_open = state.breaker.state() == CircuitState::Open;
+        let allowed = state.breaker.try_acquire(Instant::now());
+        if !allowed {
+            tracing::debug!(key = %key, "Circuit open, failing fast");
+        } else if was_open {
+            tracing::info!(key = %key, "Circuit half-open, letting a probe call through");
+        }
+        allowed
     }
 
     /// Get all data source keys
@@ -269,28 +467,304 @@ impl DataSourceManager {
         }
     }
 
+    /// Check if a data source allows administrative tools such as `mysql_kill`
+    pub fn check_admin_permission(&self, key: &str) -> Result<()> {
+        let config = self.get_source(key)
+            .ok_or_else(|| McpError::InvalidDataSourceKey(key.to_string()))?;
+
+        if config.admin {
+            Ok(())
+        } else {
+            Err(McpError::PermissionDenied(format!(
+                "Data source '{}' does not allow administrative tools (admin is not set)",
+                key
+            )))
+        }
+    }
+
+    /// Check if a database is allowed by the data source's `databases` allowlist
+    pub fn is_database_allowed(&self, key: &str, database: &str) -> bool {
+        self.allowlists
+            .get(key)
+            .map(|allowlist| allowlist.allows(database))
+            .unwrap_or(false)
+    }
+
+    /// Check if a data source's allowlist allows every database
+    pub fn allows_all_databases(&self, key: &str) -> bool {
+        self.allowlists
+            .get(key)
+            .is_some_and(|allowlist| allowlist.is_unrestricted())
+    }
+
+    /// Check that a database is allowed for a data source
+    pub fn check_database_access(&self, key: &str, database: &str) -> Result<()> {
+        self.validate_key(key)?;
+
+        if self.is_database_allowed(key, database) {
+            Ok(())
+        } else {
+            tracing::warn!(
+                datasource_key = %key,
+                database = %database,
+                "Rejected access to database outside the allowlist"
+            );
+            Err(McpError::DatabaseNotAllowed(database.to_string()))
+        }
+    }
+
+    /// Check that every database a statement names explicitly is allowed,
+    /// so qualified names like `otherdb.users` cannot reach a denied schema
+    pub fn check_statement_database_access(&self, key: &str, statement: &str) -> Result<()> {
+        self.validate_key(key)?;
+
+        let restricted = self
+            .allowlists
+            .get(key)
+            .is_some_and(|allowlist| !allowlist.is_unrestricted());
+        if !restricted {
+            return Ok(());
+        }
+
+        if sql::lists_databases(statement) {
+            return Err(McpError::InvalidStatement(
+                "SHOW DATABASES is not available for data sources with a database allowlist; use mysql_list_databases instead".to_string(),
+            ));
+        }
+
+        for database in sql::referenced_schemas(statement) {
+            self.check_database_access(key, &database)?;
+        }
+
+        Ok(())
+    }
+
+    /// Resolve the timeout for a call against a data source
+    ///
+    /// A requested per-call timeout wins over the data source override, which wins
+    /// over the server default; the result is capped at the configured maximum.
+    pub fn query_timeout(&self, key: &str, requested: Option<Duration>) -> Duration {
+        let configured = self
+            .sources
+            .get(key)
+            .and_then(|config| config.query_timeout())
+            .unwrap_or(self.default_query_timeout);
+        let timeout = requested.unwrap_or(configured);
+
+        if timeout > self.max_query_timeout {
+            tracing::debug!(
+                datasource_key = %key,
+                requested_ms = timeout.as_millis() as u64,
+                max_ms = self.max_query_timeout.as_millis() as u64,
+                "Capping query timeout at the configured maximum"
+            );
+            self.max_query_timeout
+        } else {
+            timeout
+        }
+    }
+
+    /// Resolve the result limits for a query against a data source
+    ///
+    /// Requested per-call limits win over the data source overrides, which win
+    /// over the server limits; the result is capped at the server limits.
+    pub fn result_limits(
+        &self,
+        key: &str,
+        max_rows: Option<usize>,
+        max_result_bytes: Option<usize>,
+    ) -> ResultLimits {
+        let config = self.sources.get(key);
+        let max_rows = max_rows
+            .or_else(|| config.and_then(|config| config.max_rows))
+            .unwrap_or(self.result_limits.max_rows);
+        let max_result_bytes = max_result_bytes
+            .or_else(|| config.and_then(|config| config.max_result_bytes))
+            .unwrap_or(self.result_limits.max_result_bytes);
+
+        ResultLimits {
+            max_rows: max_rows.min(self.result_limits.max_rows),
+            max_result_bytes: max_result_bytes.min(self.result_limits.max_result_bytes),
+        }
+    }
+
+    /// Run a database operation that is safe to repeat under the data source's
+    /// query timeout, retrying transient failures with backoff within it
+    ///
+    /// Every attempt is fed to the circuit breaker.
+    pub async fn run_with_retry<T, F, Fut>(
+        &self,
+        key: &str,
+        requested: Option<Duration>,
+        operation: F,
+    ) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let operation = &operation;
+        let attempts = self.retry(key, move || async move {
+            let result = operation().await;
+            self.record_call(key, &result).await;
+            result
+        });
+        // Timeouts are not fed to the breaker, so there is nothing left to record
+        self.time_limited(key, requested, attempts).await
+    }
+
+    /// Retry an operation that is safe to repeat after transient failures, with backoff
+    ///
+    /// Retrying stops as soon as the data source's circuit opens, rather than
+    /// adding load to a server that keeps failing.
+    pub async fn retry<T, F, Fut>(&self, key: &str, operation: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        // The caller checked availability before the first attempt
+        let first = AtomicBool::new(true);
+        let (first, operation) = (&first, &operation);
+        retry_with_backoff(
+            move || async move {
+                if !first.swap(false, Ordering::Relaxed) && !self.is_available(key).await {
+                    return Err(McpError::DataSourceUnavailable(format!(
+                        "Data source '{}' is currently unavailable",
+                        key
+                    )));
+                }
+                operation().await
+            },
+            &self.retry_config,
+        )
+        .await
+    }
+
+    /// Run a database operation for a data source under its query timeout
+    pub async fn run_with_timeout<T>(
+        &self,
+        key: &str,
+        requested: Option<Duration>,
+        operation: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let result = self.time_limited(key, requested, operation).await;
+        self.record_call(key, &result).await;
+        result
+    }
+
+    /// Run a database operation under the data source's query timeout
+    async fn time_limited<T>(
+        &self,
+        key: &str,
+        requested: Option<Duration>,
+        operation: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let timeout = self.query_timeout(key, requested);
+        match tokio::time::timeout(timeout, operation).await {
+            Ok(result) => result,
+            Err(_) => {
+                tracing::error!(
+                    datasource_key = %key,
+                    timeout_ms = timeout.as_millis(),
+                    "Database operation timed out"
+                );
+                Err(McpError::QueryTimeout { thread_id: None })
+            }
+        }
+    }
+
+    /// Feed the outcome of a tool call to the data source's circuit breaker
+    pub async fn record_call<T>(&self, key: &str, result: &Result<T>) {
+        let mut states_guard = self.states.write().await;
+        let Some(state) = states_guard.get_mut(key) else {
+            return;
+        };
+
+        match result {
+            Err(e) if e.is_connection_error() => {
+                if state.breaker.record_failure(Instant::now()) {
+                    tracing::warn!(
+                        key = %key,
+                        error = %e.sanitize(),
+                        "Circuit opened, failing calls fast"
+                    );
+                }
+            }
+            // A timed out call says nothing about whether the server is reachable
+            Err(McpError::QueryTimeout { .. }) => {}
+            // Any other outcome means the server answered
+            _ => {
+                if state.breaker.state() != CircuitState::Closed {
+                    tracing::info!(key = %key, "Circuit closed");
+                }
+                state.breaker.record_success();
+            }
+        }
+    }
+
     /// Get the permission level for a data source
     pub fn get_permission(&self, key: &str) -> Option<crate::config::Permission> {
         self.get_source(key).map(|config| config.permission)
     }
 }
 
+impl Drop for DataSourceManager {
+    fn drop(&mut self) {
+        self.stop_reconnection_task();
+    }
+}
+
+/// Probe a data source, with retries if it is unavailable, and record the result
+async fn check_source(
+    states: &SourceStates,
+    config: &DataSourceConfig,
+    health_config: &HealthConfig,
+    retry_config: &RetryConfig,
+) {
+    let available = states
+        .read()
+        .await
+        .get(&config.key)
+        .is_some_and(|state| state.status == ConnectionStatus::Available);
+
+    let probe_timeout = health_config.probe_timeout();
+    let result = if available {
+        probe_with_timeout(config, probe_timeout).await
+    } else {
+        tracing::debug!(key = %config.key, host = %config.host, "Attempting reconnection");
+        retry_with_backoff(|| probe_with_timeout(config, probe_timeout), retry_config).await
+    };
+
+    let mut states_guard = states.write().await;
+    if let Some(state) = states_guard.get_mut(&config.key) {
+        match result {
+            Ok(()) => state.record_success(&config.key),
+            Err(e) => state.record_failure(&config.key, &e, health_config.failure_threshold),
+        }
+    }
+}
+
+async fn probe_with_timeout(config: &DataSourceConfig, timeout: Duration) -> Result<()> {
+    tokio::time::timeout(timeout, pool::probe(config))
+        .await
+        .unwrap_or_else(|_| {
+            Err(McpError::ConnectionFailed(format!(
+                "Health check timed out after {}s",
+                timeout.as_secs()
+            )))
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::PoolConfig;
 
     fn create_test_config(key: &str, name: &str) -> DataSourceConfig {
         DataSourceConfig {
             key: key.to_string(),
             name: name.to_string(),
-            host: "localhost".to_string(),
-            port: 3306,
             username: "test".to_string(),
             password: "test".to_string(),
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: crate::config::Permission::default(),
+            ..Default::default()
         }
     }
 
@@ -391,6 +865,254 @@ mod tests {
         assert!(manager.is_available("db1").await);
     }
 
+    #[tokio::test]
+    async fn test_connection_failures_mark_source_unavailable() {
+        let configs = vec![create_test_config("db1", "Database 1")];
+        let manager = DataSourceManager::new(configs)
+            .await
+            .unwrap()
+            .with_health_config(HealthConfig {
+                failure_threshold: 2,
+                ..Default::default()
+            });
+        let error = McpError::ConnectionFailed("Connection refused".to_string());
+
+        manager.record_failure("db1", &error).await;
+        assert!(manager.is_available("db1").await);
+
+        manager.record_failure("db1", &error).await;
+        assert!(!manager.is_available("db1").await);
+        let health = manager.get_health("db1").await.unwrap();
+        assert_eq!(health.consecutive_failures, 2);
+        assert_eq!(health.total_failures, 2);
+        assert!(health.last_error.is_some());
+        assert!(health.last_failure.is_some());
+
+        // A success resets the run of failures but not the total
+        manager.record_success("db1").await;
+        assert!(manager.is_available("db1").await);
+        let health = manager.get_health("db1").await.unwrap();
+        assert_eq!(health.consecutive_failures, 0);
+        assert_eq!(health.total_failures, 2);
+        assert!(health.last_success.is_some());
+
+        let sources = manager.list_sources().await;
+        assert_eq!(sources[0].health, health);
+    }
+
+    #[tokio::test]
+    async fn test_non_connection_errors_are_ignored() {
+        let configs = vec![create_test_config("db1", "Database 1")];
+        let manager = DataSourceManager::new(configs)
+            .await
+            .unwrap()
+            .with_health_config(HealthConfig {
+                failure_threshold: 1,
+                ..Default::default()
+            });
+
+        manager
+            .record_failure("db1", &McpError::QueryTimeout { thread_id: None })
+            .await;
+        assert!(manager.is_available("db1").await);
+        assert_eq!(manager.get_health("db1").await, Some(SourceHealth::default()));
+    }
+
+    #[tokio::test]
+    async fn test_check_source_records_probe_failure() {
+        let mut config = create_test_config("db1", "Database 1");
+        config.host = "127.0.0.1".to_string();
+        config.port = 1;
+        let manager = DataSourceManager::new(vec![config.clone()]).await.unwrap();
+        let health_config = HealthConfig {
+            failure_threshold: 1,
+            ..Default::default()
+        };
+
+        check_source(&manager.states, &config, &health_config, &RetryConfig::default()).await;
+
+        assert!(!manager.is_available("db1").await);
+        let health = manager.get_health("db1").await.unwrap();
+        assert_eq!(health.consecutive_failures, 1);
+        assert!(health.last_error.unwrap().contains("Health check"));
+    }
+
+    #[tokio::test]
+    async fn test_connection_failures_open_circuit() {
+        let mut config = create_test_config("db1", "Database 1");
+        config.circuit_breaker.failure_threshold = 2;
+        let manager = DataSourceManager::new(vec![config]).await.unwrap();
+        let refused = || async {
+            Err::<(), _>(McpError::ConnectionFailed("Connection refused".to_string()))
+        };
+
+        // Errors other than connection failures do not count
+        let _ = manager
+            .run_with_timeout("db1", None, async {
+                Err::<(), _>(McpError::QueryExecutionError("Unknown column".to_string()))
+            })
+            .await;
+        let _ = manager.run_with_timeout("db1", None, refused()).await;
+        assert!(manager.is_available("db1").await);
+
+        let _ = manager.run_with_timeout("db1", None, refused()).await;
+        assert!(!manager.is_available("db1").await);
+        let circuit = manager.get_circuit("db1").await.unwrap();
+        assert_eq!(circuit.state, CircuitState::Open);
+        assert_eq!(circuit.recent_failures, 2);
+
+        let sources = manager.list_sources().await;
+        assert_eq!(sources[0].circuit, circuit);
+
+        // A call that reaches the server closes the circuit again
+        manager.run_with_timeout("db1", None, async { Ok(()) }).await.unwrap();
+        assert!(manager.is_available("db1").await);
+        assert_eq!(manager.get_circuit("db1").await.unwrap().state, CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_run_with_retry_retries_transient_errors_only() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let manager = DataSourceManager::new(vec![create_test_config("db1", "Database 1")])
+            .await
+            .unwrap()
+            .with_retry_config(RetryConfig {
+                max_attempts: 3,
+                initial_backoff_ms: 1,
+                max_backoff_ms: 1,
+                backoff_multiplier: 1.0,
+            });
+
+        // A deadlock is retried until the operation succeeds
+        let attempts = AtomicU32::new(0);
+        let result = manager
+            .run_with_retry("db1", None, || async {
+                if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                    Err(McpError::QueryExecutionError(
+                        "error returned from database: 1213 (40001): Deadlock found when trying to get lock"
+                            .to_string(),
+                    ))
+                } else {
+                    Ok(42)
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+        // A syntax error is returned straight away
+        let attempts = AtomicU32::new(0);
+        let result = manager
+            .run_with_retry("db1", None, || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>(McpError::QueryExecutionError(
+                    "error returned from database: 1064 (42000): You have an error in your SQL syntax"
+                        .to_string(),
+                ))
+            })
+            .await;
+        assert!(matches!(result, Err(McpError::QueryExecutionError(_))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_with_retry_stops_when_circuit_opens() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let mut config = create_test_config("db1", "Database 1");
+        config.circuit_breaker.failure_threshold = 2;
+        let manager = DataSourceManager::new(vec![config])
+            .await
+            .unwrap()
+            .with_retry_config(RetryConfig {
+                max_attempts: 5,
+                initial_backoff_ms: 1,
+                max_backoff_ms: 1,
+                backoff_multiplier: 1.0,
+            });
+
+        let attempts = AtomicU32::new(0);
+        let result = manager
+            .run_with_retry("db1", None, || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>(McpError::ConnectionFailed("Connection refused".to_string()))
+            })
+            .await;
+
+        // The second failure opened the circuit, so the third attempt never ran
+        assert!(matches!(result, Err(McpError::DataSourceUnavailable(_))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        let circuit = manager.get_circuit("db1").await.unwrap();
+        assert_eq!(circuit.state, CircuitState::Open);
+        assert_eq!(circuit.recent_failures, 2);
+    }
+
+    #[tokio::test]
+    async fn test_new_manager_with_invalid_database_pattern() {
+        let mut config = create_test_config("db1", "Database 1");
+        config.databases = vec!["/[/".to_string()];
+
+        let result = DataSourceManager::new(vec![config]).await;
+        assert!(matches!(result.unwrap_err(), McpError::ConfigurationError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_check_database_access() {
+        let mut restricted = create_test_config("db1", "Database 1");
+        restricted.databases = vec!["app_*".to_string(), "reporting".to_string()];
+        let configs = vec![restricted, create_test_config("db2", "Database 2")];
+        let manager = DataSourceManager::new(configs).await.unwrap();
+
+        assert!(manager.check_database_access("db1", "app_orders").is_ok());
+        assert!(manager.check_database_access("db1", "reporting").is_ok());
+        assert!(matches!(
+            manager.check_database_access("db1", "billing"),
+            Err(McpError::DatabaseNotAllowed(_))
+        ));
+
+        // An empty allowlist allows every database
+        assert!(manager.check_database_access("db2", "billing").is_ok());
+
+        assert!(matches!(
+            manager.check_database_access("nonexistent", "app_orders"),
+            Err(McpError::InvalidDataSourceKey(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_check_statement_database_access() {
+        let mut restricted = create_test_config("db1", "Database 1");
+        restricted.databases = vec!["app_*".to_string()];
+        let configs = vec![restricted, create_test_config("db2", "Database 2")];
+        let manager = DataSourceManager::new(configs).await.unwrap();
+
+        assert!(manager
+            .check_statement_database_access("db1", "SELECT * FROM users")
+            .is_ok());
+        assert!(manager
+            .check_statement_database_access("db1", "SELECT * FROM app_orders.orders")
+            .is_ok());
+        assert!(matches!(
+            manager.check_statement_database_access(
+                "db1",
+                "SELECT * FROM users u JOIN billing.invoices i ON u.id = i.user_id"
+            ),
+            Err(McpError::DatabaseNotAllowed(database)) if database == "billing"
+        ));
+        assert!(matches!(
+            manager.check_statement_database_access("db1", "SHOW DATABASES"),
+            Err(McpError::InvalidStatement(_))
+        ));
+
+        assert!(manager
+            .check_statement_database_access("db2", "SELECT * FROM billing.invoices")
+            .is_ok());
+        assert!(manager
+            .check_statement_database_access("db2", "SHOW DATABASES")
+            .is_ok());
+    }
+
     #[tokio::test]
     async fn test_keys() {
         let configs = vec![
@@ -404,4 +1126,51 @@ mod tests {
         assert!(keys.contains(&"db1".to_string()));
         assert!(keys.contains(&"db2".to_string()));
     }
+
+    #[tokio::test]
+    async fn test_query_timeout_resolution() {
+        let mut slow = create_test_config("slow", "Reporting");
+        slow.query_timeout_secs = Some(120);
+        let configs = vec![create_test_config("db1", "Database 1"), slow];
+        let manager = DataSourceManager::new(configs)
+            .await
+            .unwrap()
+            .with_query_timeouts(Duration::from_secs(10), Duration::from_secs(60));
+
+        assert_eq!(manager.query_timeout("db1", None), Duration::from_secs(10));
+        // Overrides are capped at the maximum
+        assert_eq!(manager.query_timeout("slow", None), Duration::from_secs(60));
+        assert_eq!(
+            manager.query_timeout("slow", Some(Duration::from_millis(500))),
+            Duration::from_millis(500)
+        );
+        assert_eq!(
+            manager.query_timeout("db1", Some(Duration::from_secs(3600))),
+            Duration::from_secs(60)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_result_limits_resolution() {
+        let mut small = create_test_config("small", "Small");
+        small.max_rows = Some(100);
+        let configs = vec![create_test_config("db1", "Database 1"), small];
+        let manager = DataSourceManager::new(configs)
+            .await
+            .unwrap()
+            .with_result_limits(ResultLimits {
+                max_rows: 1000,
+                max_result_bytes: 4096,
+            });
+
+        let limits = manager.result_limits("db1", None, None);
+        assert_eq!(limits.max_rows, 1000);
+        assert_eq!(limits.max_result_bytes, 4096);
+        assert_eq!(manager.result_limits("small", None, None).max_rows, 100);
+        assert_eq!(manager.result_limits("small", Some(10), None).max_rows, 10);
+        // Requests are capped at the server limits
+        let limits = manager.result_limits("small", Some(5000), Some(1 << 20));
+        assert_eq!(limits.max_rows, 1000);
+        assert_eq!(limits.max_result_bytes, 4096);
+    }
 }
@@ -1,17 +1,25 @@
-use crate::config::ServerConfig;
-use crate::error::{McpError as AppError, Result};
+use crate::config::{ExportConfig, ImportConfig, ServerConfig};
+use crate::digest::{DigestSource, TopQueriesOrder};
+use crate::error::{ErrorKind, McpError as AppError, Result, ToolError};
+use crate::export::{Compression, ExportFormat};
+use crate::format::ResultFormat;
+use crate::import::OnDuplicate;
 use crate::manager::DataSourceManager;
 use crate::monitoring::MonitoringService;
+use crate::params::StatementParams;
 use crate::pool::ConnectionPoolManager;
 use crate::resources::ResourceProvider;
 use crate::tools::*;
+use crate::transaction::TransactionManager;
 use rmcp::Error as McpError;
 use rmcp::handler::server::ServerHandler;
 use rmcp::model::*;
-use rmcp::service::{RequestContext, RoleServer};
+use rmcp::service::{Peer, RequestContext, RoleServer};
 use std::collections::HashMap;
+use std::str::FromStr;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock};
 
 /// MySQL MCP Server Handler implementation
 #[derive(Clone)]
@@ -19,11 +27,19 @@ pub struct MySqlMcpServerHandler {
     manager: Arc<DataSourceManager>,
     pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>>,
     server_info: ServerInfo,
+    transactions: Arc<TransactionManager>,
+    idempotent_results: Arc<IdempotentResults>,
+    streams: Arc<StreamManager>,
+    stream_chunk_size: usize,
+    export: ExportConfig,
+    import: ImportConfig,
     _monitoring_service: Arc<tokio::sync::Mutex<Option<MonitoringService>>>,
+    transaction_reaper: Arc<tokio::sync::Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    stream_reaper: Arc<tokio::sync::Mutex<Option<tokio::task::JoinHandle<()>>>>,
 }
 
 impl MySqlMcpServerHandler {
-    /// Cleanup all resources (connection pools, monitoring service)
+    /// Cleanup all resources (open streams and transactions, connection pools, monitoring service)
     pub async fn cleanup(&self) {
         tracing::info!("Cleaning up MCP server handler resources...");
 
@@ -36,6 +52,25 @@ impl MySqlMcpServerHandler {
             }
         }
 
+        // Stop health checks
+        self.manager.stop_reconnection_task();
+
+        // Close open streams, killing their queries
+        {
+            if let Some(reaper) = self.stream_reaper.lock().await.take() {
+                reaper.abort();
+            }
+            let _ = self.streams.cancel_all().await;
+        }
+
+        // Roll back open transactions before their connections' pools are closed
+        {
+            if let Some(reaper) = self.transaction_reaper.lock().await.take() {
+                reaper.abort();
+            }
+            self.transactions.rollback_all().await;
+        }
+
         // Close all connection pools
         {
             let pool_managers_guard = self.pool_managers.read().await;
@@ -50,9 +85,32 @@ impl MySqlMcpServerHandler {
 
     /// Create a new MySQL MCP Server Handler
     pub async fn new(config: ServerConfig) -> Result<Self> {
+        let query_timeout = config.query_timeout();
+        let max_query_timeout = config.max_query_timeout();
+        let result_limits = config.result_limits();
+        let type_mapping = config
+            .types
+            .type_mapping()
+            .map_err(|e| AppError::ConfigurationError(e.to_string()))?;
+        let transactions = Arc::new(TransactionManager::new(config.transactions.clone()));
+        let streams = Arc::new(StreamManager::with_config(config.streams.clone()));
+        let stream_chunk_size = config.stream_chunk_size;
+        let export = config.export.clone();
+        let import = config.import.clone();
+        let health = config.health.clone();
+        let retry = config.retry.clone();
+
         // Create data source manager from config
-        let manager = DataSourceManager::new(config.data_sources).await?;
+        let manager = DataSourceManager::new(config.data_sources)
+            .await?
+            .with_query_timeouts(query_timeout, max_query_timeout)
+            .with_result_limits(result_limits)
+            .with_type_mapping(type_mapping)
+            .with_health_config(health)
+            .with_retry_config(retry)
+            .start_reconnection_task();
         let manager = Arc::new(manager);
+        let idempotent_results = Arc::new(IdempotentResults::new(&manager));
 
         // Create shared pool managers
         let pool_managers = Arc::new(RwLock::new(HashMap::new()));
@@ -92,11 +150,25 @@ impl MySqlMcpServerHandler {
             60, // Log statistics every 60 seconds
         ).start();
 
+        // Roll back interactive transactions left idle by clients
+        let transaction_reaper = transactions.spawn_idle_reaper();
+
+        // Close streams left idle by clients
+        let stream_reaper = streams.spawn_idle_reaper();
+
         Ok(Self {
             manager,
             pool_managers,
             server_info,
+            transactions,
+            idempotent_results,
+            streams,
+            stream_chunk_size,
+            export,
+            import,
             _monitoring_service: Arc::new(tokio::sync::Mutex::new(Some(monitoring_service))),
+            transaction_reaper: Arc::new(tokio::sync::Mutex::new(Some(transaction_reaper))),
+            stream_reaper: Arc::new(tokio::sync::Mutex::new(Some(stream_reaper))),
         })
     }
 }
@@ -130,7 +202,39 @@ impl ServerHandler for MySqlMcpServerHandler {
                         },
                         "query": {
                             "type": "string",
-                            "description": "The SQL query to execute"
+                            "description": "The SQL query to execute. Use ? or :name placeholders for values instead of interpolating them"
+                        },
+                        "params": {
+                            "type": "array",
+                            "description": "Optional values for ? placeholders, in order. Values may be null, booleans, numbers, strings or tagged objects {\"type\": \"decimal\" | \"datetime\" | \"binary\", \"value\": \"...\"} (binary values are base64)"
+                        },
+                        "named_params": {
+                            "type": "object",
+                            "description": "Optional values for :name placeholders, keyed by name. Accepts the same value types as params; cannot be combined with params"
+                        },
+                        "transaction_id": {
+                            "type": "string",
+                            "description": "Optional handle from mysql_begin to run inside that transaction"
+                        },
+                        "timeout_ms": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional timeout in milliseconds for this call, capped at the server's max_query_timeout_secs. Defaults to the data source's query timeout"
+                        },
+                        "max_rows": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional maximum number of rows to return, capped at the server's max_rows. Larger results are truncated and flagged with truncated: true"
+                        },
+                        "max_result_bytes": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional maximum size in bytes of the returned rows as JSON, capped at the server's max_result_bytes. Larger results are truncated and flagged with truncated: true"
+                        },
+                        "format": {
+                            "type": "string",
+                            "enum": ["json", "json_compact", "json_columnar", "csv", "tsv", "markdown", "ndjson"],
+                            "description": "Optional encoding of the result. json (the default) returns the full result; the others return just the rows, as an array of objects, arrays keyed by column, CSV, TSV, a Markdown table or one JSON object per line, followed by a JSON summary with rows_returned and truncated"
                         }
                     },
                     "required": ["datasource_key", "database", "query"]
@@ -152,37 +256,137 @@ impl ServerHandler for MySqlMcpServerHandler {
                         },
                         "statement": {
                             "type": "string",
-                            "description": "The DML statement to execute"
+                            "description": "The DML statement to execute. Use ? or :name placeholders for values instead of interpolating them"
+                        },
+                        "params": {
+                            "type": "array",
+                            "description": "Optional values for ? placeholders, in order. Values may be null, booleans, numbers, strings or tagged objects {\"type\": \"decimal\" | \"datetime\" | \"binary\", \"value\": \"...\"} (binary values are base64)"
+                        },
+                        "named_params": {
+                            "type": "object",
+                            "description": "Optional values for :name placeholders, keyed by name. Accepts the same value types as params; cannot be combined with params"
+                        },
+                        "transaction_id": {
+                            "type": "string",
+                            "description": "Optional handle from mysql_begin to run inside that transaction"
+                        },
+                        "idempotency_key": {
+                            "type": "string",
+                            "description": "Optional key identifying this statement. A repeated call with the same key within 10 minutes returns the first call's result instead of running the statement again, and the key marks the statement as safe to run twice, allowing it to be retried after a transient failure (deadlock, lock wait timeout, lost connection). Ignored inside transactions"
+                        },
+                        "timeout_ms": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional timeout in milliseconds for this call, capped at the server's max_query_timeout_secs. Defaults to the data source's query timeout"
                         }
                     },
                     "required": ["datasource_key", "database", "statement"]
                 })).unwrap()),
             ),
             Tool::new(
-                "mysql_list_datasources",
-                "List all configured data sources",
+                "mysql_transaction",
+                "Execute several DML statements atomically in a single transaction, rolling back on the first failure",
                 Arc::new(serde_json::from_value(serde_json::json!({
                     "type": "object",
-                    "properties": {}
+                    "properties": {
+                        "datasource_key": {
+                            "type": "string",
+                            "description": "The data source key to connect to"
+                        },
+                        "database": {
+                            "type": "string",
+                            "description": "The database name to execute on"
+                        },
+                        "statements": {
+                            "type": "array",
+                            "description": "The statements to execute, in order",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "statement": {
+                                        "type": "string",
+                                        "description": "The DML statement to execute"
+                                    },
+                                    "params": {
+                                        "type": "array",
+                                        "description": "Optional values for ? placeholders, in order"
+                                    },
+                                    "named_params": {
+                                        "type": "object",
+                                        "description": "Optional values for :name placeholders, keyed by name"
+                                    }
+                                },
+                                "required": ["statement"]
+                            }
+                        },
+                        "isolation_level": {
+                            "type": "string",
+                            "enum": ["READ UNCOMMITTED", "READ COMMITTED", "REPEATABLE READ", "SERIALIZABLE"],
+                            "description": "Optional isolation level for this transaction. Defaults to the server's setting"
+                        },
+                        "timeout_ms": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional timeout in milliseconds for the whole transaction, capped at the server's max_query_timeout_secs. Defaults to the data source's query timeout"
+                        }
+                    },
+                    "required": ["datasource_key", "database", "statements"]
                 })).unwrap()),
             ),
             Tool::new(
-                "mysql_list_databases",
-                "List all databases for a specified data source",
+                "mysql_begin",
+                "Start an interactive transaction and return its handle. Pass the handle as transaction_id to mysql_query and mysql_execute, then finish with mysql_commit or mysql_rollback",
                 Arc::new(serde_json::from_value(serde_json::json!({
                     "type": "object",
                     "properties": {
                         "datasource_key": {
                             "type": "string",
-                            "description": "The data source key to list databases for"
+                            "description": "The data source key to connect to"
+                        },
+                        "database": {
+                            "type": "string",
+                            "description": "The database name for the transaction"
+                        },
+                        "isolation_level": {
+                            "type": "string",
+                            "enum": ["READ UNCOMMITTED", "READ COMMITTED", "REPEATABLE READ", "SERIALIZABLE"],
+                            "description": "Optional isolation level for this transaction. Defaults to the server's setting"
                         }
                     },
-                    "required": ["datasource_key"]
+                    "required": ["datasource_key", "database"]
                 })).unwrap()),
             ),
             Tool::new(
-                "mysql_list_tables",
-                "List all tables in a specified database",
+                "mysql_commit",
+                "Commit an interactive transaction started with mysql_begin",
+                Arc::new(serde_json::from_value(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "transaction_id": {
+                            "type": "string",
+                            "description": "The handle returned by mysql_begin"
+                        }
+                    },
+                    "required": ["transaction_id"]
+                })).unwrap()),
+            ),
+            Tool::new(
+                "mysql_rollback",
+                "Roll back an interactive transaction started with mysql_begin",
+                Arc::new(serde_json::from_value(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "transaction_id": {
+                            "type": "string",
+                            "description": "The handle returned by mysql_begin"
+                        }
+                    },
+                    "required": ["transaction_id"]
+                })).unwrap()),
+            ),
+            Tool::new(
+                "mysql_query_stream",
+                "Run a read-only SQL query and stream its rows in chunks. Returns a stream_id with the first chunk; fetch the rest with mysql_fetch_chunk until is_last is true, or stop early with mysql_close_stream",
                 Arc::new(serde_json::from_value(serde_json::json!({
                     "type": "object",
                     "properties": {
@@ -192,15 +396,70 @@ impl ServerHandler for MySqlMcpServerHandler {
                         },
                         "database": {
                             "type": "string",
-                            "description": "The database name to list tables from"
+                            "description": "The database name to query"
+                        },
+                        "query": {
+                            "type": "string",
+                            "description": "The SQL query to execute. Use ? or :name placeholders for values instead of interpolating them"
+                        },
+                        "params": {
+                            "type": "array",
+                            "description": "Optional values for ? placeholders, in order. Values may be null, booleans, numbers, strings or tagged objects {\"type\": \"decimal\" | \"datetime\" | \"binary\", \"value\": \"...\"} (binary values are base64)"
+                        },
+                        "named_params": {
+                            "type": "object",
+                            "description": "Optional values for :name placeholders, keyed by name. Accepts the same value types as params; cannot be combined with params"
+                        },
+                        "chunk_size": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional number of rows per chunk, capped at the server's stream_chunk_size (the default)"
+                        },
+                        "timeout_ms": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional timeout in milliseconds for reading each chunk, capped at the server's max_query_timeout_secs. Defaults to the data source's query timeout"
+                        },
+                        "format": {
+                            "type": "string",
+                            "enum": ["json", "json_compact", "json_columnar", "csv", "tsv", "markdown", "ndjson"],
+                            "description": "Optional encoding of every chunk of the stream, as for mysql_query. Each chunk's rows are followed by a JSON summary with stream_id, chunk_number and is_last; CSV and TSV chunks after the first have no header row, so they can be concatenated"
                         }
                     },
-                    "required": ["datasource_key", "database"]
+                    "required": ["datasource_key", "database", "query"]
                 })).unwrap()),
             ),
             Tool::new(
-                "mysql_describe_table",
-                "Get the schema of a specified table",
+                "mysql_fetch_chunk",
+                "Fetch the next chunk of a stream started with mysql_query_stream. The stream ends after the chunk with is_last set",
+                Arc::new(serde_json::from_value(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "stream_id": {
+                            "type": "string",
+                            "description": "The stream_id returned by mysql_query_stream"
+                        }
+                    },
+                    "required": ["stream_id"]
+                })).unwrap()),
+            ),
+            Tool::new(
+                "mysql_close_stream",
+                "Close a stream started with mysql_query_stream before reading all of it, stopping its query",
+                Arc::new(serde_json::from_value(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "stream_id": {
+                            "type": "string",
+                            "description": "The stream_id returned by mysql_query_stream"
+                        }
+                    },
+                    "required": ["stream_id"]
+                })).unwrap()),
+            ),
+            Tool::new(
+                "mysql_export",
+                "Run a read-only SQL query and write all of its rows to a file in the server's export directory, for results too large to return. Returns the file's path, row count, size and SHA-256",
                 Arc::new(serde_json::from_value(serde_json::json!({
                     "type": "object",
                     "properties": {
@@ -210,171 +469,980 @@ impl ServerHandler for MySqlMcpServerHandler {
                         },
                         "database": {
                             "type": "string",
-                            "description": "The database name"
+                            "description": "The database name to query"
+                        },
+                        "query": {
+                            "type": "string",
+                            "description": "The SQL query to execute. Use ? or :name placeholders for values instead of interpolating them"
+                        },
+                        "params": {
+                            "type": "array",
+                            "description": "Optional values for ? placeholders, in order. Values may be null, booleans, numbers, strings or tagged objects {\"type\": \"decimal\" | \"datetime\" | \"binary\", \"value\": \"...\"} (binary values are base64)"
+                        },
+                        "named_params": {
+                            "type": "object",
+                            "description": "Optional values for :name placeholders, keyed by name. Accepts the same value types as params; cannot be combined with params"
+                        },
+                        "file": {
+                            "type": "string",
+                            "description": "File to write, relative to the export directory, e.g. reports/orders.csv.gz. Missing subdirectories are created"
+                        },
+                        "format": {
+                            "type": "string",
+                            "enum": ["csv", "ndjson", "parquet"],
+                            "description": "File format. Defaults to csv"
+                        },
+                        "compression": {
+                            "type": "string",
+                            "enum": ["none", "gzip", "zstd"],
+                            "description": "Optional compression. CSV and NDJSON files are compressed as a whole; Parquet files compress their column data. Defaults to none"
+                        },
+                        "overwrite": {
+                            "type": "boolean",
+                            "description": "Replace the file if it exists. Defaults to false"
+                        },
+                        "timeout_ms": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional timeout in milliseconds for the whole export, capped at the server's export.timeout_secs (the default)"
+                        }
+                    },
+                    "required": ["datasource_key", "database", "query", "file"]
+                })).unwrap()),
+            ),
+            Tool::new(
+                "mysql_import",
+                "Load a CSV or NDJSON file from the server's import directory into a table, in a single transaction. File columns are matched to table columns by name and every value is checked against its column type first; any invalid row or failed insert rolls the whole import back. Requires update permission",
+                Arc::new(serde_json::from_value(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "datasource_key": {
+                            "type": "string",
+                            "description": "The data source key to connect to"
+                        },
+                        "database": {
+                            "type": "string",
+                            "description": "The database containing the table"
                         },
                         "table": {
                             "type": "string",
-                            "description": "The table name to describe"
+                            "description": "The table to insert rows into"
+                        },
+                        "file": {
+                            "type": "string",
+                            "description": "File to read, relative to the import directory, e.g. incoming/orders.csv.gz. CSV files start with a header row of column names; NDJSON files hold one object per line"
+                        },
+                        "format": {
+                            "type": "string",
+                            "enum": ["csv", "ndjson"],
+                            "description": "File format. Defaults to the one named by the file extension (.csv, .ndjson, .jsonl)"
+                        },
+                        "compression": {
+                            "type": "string",
+                            "enum": ["none", "gzip", "zstd"],
+                            "description": "Compression of the file. Defaults to the one named by the file extension (.gz, .zst)"
+                        },
+                        "on_duplicate": {
+                            "type": "string",
+                            "enum": ["error", "ignore", "update"],
+                            "description": "What to do with rows whose primary or unique key already exists: fail the import, keep the existing row, or overwrite it. Defaults to error"
+                        },
+                        "dry_run": {
+                            "type": "boolean",
+                            "description": "Only read and check the file, reporting every invalid row, without inserting anything. Defaults to false"
+                        },
+                        "progress_token": {
+                            "type": ["string", "integer"],
+                            "description": "Optional token; when given, notifications/progress messages carrying it report the rows written after each batch"
+                        },
+                        "timeout_ms": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional timeout in milliseconds for the whole import, capped at the server's import.timeout_secs (the default)"
                         }
                     },
-                    "required": ["datasource_key", "database", "table"]
+                    "required": ["datasource_key", "database", "table", "file"]
                 })).unwrap()),
             ),
             Tool::new(
-                "mysql_get_connection_stats",
-                "Get connection pool statistics for data sources",
+                "mysql_explain",
+                "Show how MySQL executes a statement without running it: EXPLAIN FORMAT=JSON parsed into a plan tree with access types, chosen and possible indexes, row estimates, filtered percentages, costs and temporary table / filesort flags, plus red flags such as full table scans, large sorts and unused indexes. With analyze, a read-only query is also run under EXPLAIN ANALYZE (MySQL 8.0.18+) to time each step",
                 Arc::new(serde_json::from_value(serde_json::json!({
                     "type": "object",
                     "properties": {
                         "datasource_key": {
                             "type": "string",
-                            "description": "Optional data source key to get stats for. If not provided, returns stats for all data sources"
+                            "description": "The data source key to connect to"
+                        },
+                        "database": {
+                            "type": "string",
+                            "description": "The database name to use"
+                        },
+                        "query": {
+                            "type": "string",
+                            "description": "The SELECT, TABLE, WITH, INSERT, UPDATE, DELETE or REPLACE statement to explain. DML statements need update permission but are not run"
+                        },
+                        "params": {
+                            "type": "array",
+                            "description": "Optional values for ? placeholders, in order. Values may be null, booleans, numbers, strings or tagged objects {\"type\": \"decimal\" | \"datetime\" | \"binary\", \"value\": \"...\"} (binary values are base64)"
+                        },
+                        "named_params": {
+                            "type": "object",
+                            "description": "Optional values for :name placeholders, keyed by name. Accepts the same value types as params; cannot be combined with params"
+                        },
+                        "analyze": {
+                            "type": "boolean",
+                            "description": "Also run the query under EXPLAIN ANALYZE to report actual rows and timings. Read-only queries only. Defaults to false"
+                        },
+                        "timeout_ms": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional timeout in milliseconds, capped at the server's max_query_timeout_secs"
                         }
-                    }
+                    },
+                    "required": ["datasource_key", "database", "query"]
                 })).unwrap()),
             ),
-        ];
-
-        Ok(ListToolsResult { tools, next_cursor: None })
-    }
-
-    async fn call_tool(
-        &self,
-        request: CallToolRequestParam,
-        _context: RequestContext<RoleServer>,
-    ) -> std::result::Result<CallToolResult, McpError> {
-        // Generate a trace ID for this tool call
-        let trace_id = uuid::Uuid::new_v4();
-        
-        async move {
-            tracing::info!(
-                trace_id = %trace_id,
-                tool_name = %request.name,
-                "Tool call started"
-            );
-
-            let result = match request.name.as_ref() {
-                "mysql_query" => self.handle_query_tool(request.arguments.map(|a| serde_json::Value::Object(a))).await,
-                "mysql_execute" => self.handle_execute_tool(request.arguments.map(|a| serde_json::Value::Object(a))).await,
-                "mysql_list_datasources" => self.handle_list_datasources_tool().await,
-                "mysql_list_databases" => self.handle_list_databases_tool(request.arguments.map(|a| serde_json::Value::Object(a))).await,
-                "mysql_list_tables" => self.handle_list_tables_tool(request.arguments.map(|a| serde_json::Value::Object(a))).await,
-                "mysql_describe_table" => self.handle_describe_table_tool(request.arguments.map(|a| serde_json::Value::Object(a))).await,
-                "mysql_get_connection_stats" => self.handle_connection_stats_tool(request.arguments.map(|a| serde_json::Value::Object(a))).await,
-                _ => {
-                    tracing::error!(trace_id = %trace_id, "Unknown tool requested");
-                    return Err(McpError::invalid_params(
-                        format!("Unknown tool: {}", request.name),
-                        None
-                    ))
-                }
-            };
-
-            match &result {
-                Ok(_) => tracing::info!(trace_id = %trace_id, "Tool call completed successfully"),
-                Err(e) => tracing::error!(trace_id = %trace_id, error = %e.sanitize(), "Tool call failed"),
-            }
-
-            result.map_err(|e| McpError::internal_error(e.sanitize(), None))
-        }.await
-    }
-
-    async fn list_resources(
-        &self,
-        _request: PaginatedRequestParam,
-        _context: RequestContext<RoleServer>,
-    ) -> std::result::Result<ListResourcesResult, McpError> {
-        tracing::debug!("Listing resources");
-
-        // For now, return empty list as resources are accessed via templates
-        Ok(ListResourcesResult {
-            resources: vec![],
-            next_cursor: None,
-        })
-    }
-
-    async fn list_resource_templates(
-        &self,
-        _request: PaginatedRequestParam,
-        _context: RequestContext<RoleServer>,
-    ) -> std::result::Result<ListResourceTemplatesResult, McpError> {
-        tracing::debug!("Listing resource templates");
-
-        let resource_provider = ResourceProvider::new(self.manager.clone(), self.pool_managers.clone());
-        let templates = resource_provider.list_resource_templates();
-
-        let resource_templates: Vec<ResourceTemplate> = templates
-            .into_iter()
-            .map(|t| {
-                Annotated::new(RawResourceTemplate {
-                    uri_template: t.uri_template,
-                    name: t.name,
-                    description: Some(t.description),
-                    mime_type: Some(t.mime_type),
-                }, None)
-            })
-            .collect();
-
-        Ok(ListResourceTemplatesResult {
-            resource_templates,
-            next_cursor: None,
-        })
-    }
-
-    async fn read_resource(
-        &self,
-        request: ReadResourceRequestParam,
-        _context: RequestContext<RoleServer>,
-    ) -> std::result::Result<ReadResourceResult, McpError> {
-        // Generate a trace ID for this resource read
-        let trace_id = uuid::Uuid::new_v4();
-        
-        async move {
+            Tool::new(
+                "mysql_suggest_indexes",
+                "Suggest indexes for a query, or for the statements that took the most time according to performance_schema's digest table. Compares the WHERE, JOIN, ORDER BY and GROUP BY columns with the existing indexes and proposes composite indexes as CREATE INDEX statements, flags duplicate and left-prefix redundant indexes, and lists indexes unused since the server started (sys.schema_unused_indexes). Advisory only: nothing is executed",
+                Arc::new(serde_json::from_value(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "datasource_key": {
+                            "type": "string",
+                            "description": "The data source key to connect to"
+                        },
+                        "database": {
+                            "type": "string",
+                            "description": "The database whose tables are analysed"
+                        },
+                        "query": {
+                            "type": "string",
+                            "description": "The statement to analyse; it is explained to show how tables are read today, but not run. Give either query or from_digests"
+                        },
+                        "params": {
+                            "type": "array",
+                            "description": "Optional values for ? placeholders in query, in order. Accepts the same value types as mysql_query"
+                        },
+                        "named_params": {
+                            "type": "object",
+                            "description": "Optional values for :name placeholders in query, keyed by name; cannot be combined with params"
+                        },
+                        "from_digests": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Analyse this many of the SELECT, UPDATE and DELETE statements with the most total time in the database, from performance_schema.events_statements_summary_by_digest"
+                        },
+                        "timeout_ms": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional timeout in milliseconds for each metadata query, capped at the server's max_query_timeout_secs"
+                        }
+                    },
+                    "required": ["datasource_key", "database"]
+                })).unwrap()),
+            ),
+            Tool::new(
+                "mysql_processlist",
+                "List the server's threads, longest running first, from performance_schema.threads (or information_schema.PROCESSLIST): id, user, host, database, command, time, state, running statement, and whether the connection belongs to this server. Threads in databases outside the data source's allowlist are left out",
+                Arc::new(serde_json::from_value(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "datasource_key": {
+                            "type": "string",
+                            "description": "The data source key to connect to"
+                        },
+                        "include_sleeping": {
+                            "type": "boolean",
+                            "description": "Include idle connections. Defaults to false"
+                        },
+                        "min_time_secs": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "description": "Only threads whose current command has run at least this many seconds"
+                        },
+                        "user": {
+                            "type": "string",
+                            "description": "Only threads of this MySQL user"
+                        },
+                        "database": {
+                            "type": "string",
+                            "description": "Only threads using this database"
+                        },
+                        "timeout_ms": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional timeout in milliseconds, capped at the server's max_query_timeout_secs"
+                        }
+                    },
+                    "required": ["datasource_key"]
+                })).unwrap()),
+            ),
+            Tool::new(
+                "mysql_lock_waits",
+                "Show InnoDB lock waits from sys.innodb_lock_waits, each with the waiting and the blocking transaction, thread and statement, together with long-running transactions from information_schema.INNODB_TRX",
+                Arc::new(serde_json::from_value(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "datasource_key": {
+                            "type": "string",
+                            "description": "The data source key to connect to"
+                        },
+                        "min_transaction_secs": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "description": "Only list transactions open at least this many seconds. Defaults to 10"
+                        },
+                        "timeout_ms": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional timeout in milliseconds, capped at the server's max_query_timeout_secs"
+                        }
+                    },
+                    "required": ["datasource_key"]
+                })).unwrap()),
+            ),
+            Tool::new(
+                "mysql_kill",
+                "Kill a MySQL thread's running statement (KILL QUERY) or its whole connection (KILL CONNECTION). Only allowed on data sources with admin set. Connections of this server's own pools are refused unless allow_own_connection is set",
+                Arc::new(serde_json::from_value(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "datasource_key": {
+                            "type": "string",
+                            "description": "The data source key to connect to"
+                        },
+                        "thread_id": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "The thread id, as listed by mysql_processlist"
+                        },
+                        "query_only": {
+                            "type": "boolean",
+                            "description": "Kill only the running statement and keep the connection. Defaults to false, which closes the connection and rolls back its transaction"
+                        },
+                        "allow_own_connection": {
+                            "type": "boolean",
+                            "description": "Allow killing a connection of this server's own pools. Defaults to false"
+                        },
+                        "timeout_ms": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional timeout in milliseconds, capped at the server's max_query_timeout_secs"
+                        }
+                    },
+                    "required": ["datasource_key", "thread_id"]
+                })).unwrap()),
+            ),
+            Tool::new(
+                "mysql_top_queries",
+                "Find the statements that cost a server the most: the top digests from performance_schema.events_statements_summary_by_digest by total latency, rows examined or errors, with normalised SQL, run counts, average and p95 latency, rows examined and sent per run, and first / last seen. Falls back to parsing the data source's slow query log where performance_schema is off",
+                Arc::new(serde_json::from_value(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "datasource_key": {
+                            "type": "string",
+                            "description": "The data source key to connect to"
+                        },
+                        "database": {
+                            "type": "string",
+                            "description": "Only statements run in this database. Defaults to every allowed database"
+                        },
+                        "order_by": {
+                            "type": "string",
+                            "enum": ["total_latency", "rows_examined", "errors"],
+                            "description": "What to rank statements by. Defaults to total_latency"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": 100,
+                            "description": "Number of statements to return. Defaults to 10"
+                        },
+                        "source": {
+                            "type": "string",
+                            "enum": ["auto", "performance_schema", "slow_log"],
+                            "description": "Where to read statistics from. auto (the default) reads performance_schema and falls back to the data source's slow_query_log if it is unavailable or empty"
+                        },
+                        "timeout_ms": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional timeout in milliseconds, capped at the server's max_query_timeout_secs"
+                        }
+                    },
+                    "required": ["datasource_key"]
+                })).unwrap()),
+            ),
+            Tool::new(
+                "mysql_variables",
+                "Show a data source's global server variables (SHOW GLOBAL VARIABLES), optionally filtered by name",
+                Arc::new(serde_json::from_value(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "datasource_key": {
+                            "type": "string",
+                            "description": "The data source key to connect to"
+                        },
+                        "filter": {
+                            "type": "string",
+                            "description": "Only variables whose name contains this text, case-insensitive. A pattern containing % is matched like LIKE instead, e.g. innodb_buffer_pool%"
+                        },
+                        "timeout_ms": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional timeout in milliseconds, capped at the server's max_query_timeout_secs"
+                        }
+                    },
+                    "required": ["datasource_key"]
+                })).unwrap()),
+            ),
+            Tool::new(
+                "mysql_status",
+                "Show a data source's global status counters (SHOW GLOBAL STATUS), optionally filtered by name, with derived rates: queries and transactions per second, buffer pool hit ratio, threads running and connected, slow queries, on-disk temporary tables and aborted connects per second. With interval_secs, two snapshots are taken that many seconds apart and rates cover that interval; otherwise they are averaged over the server's uptime",
+                Arc::new(serde_json::from_value(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "datasource_key": {
+                            "type": "string",
+                            "description": "The data source key to connect to"
+                        },
+                        "filter": {
+                            "type": "string",
+                            "description": "Only counters whose name contains this text, case-insensitive. A pattern containing % is matched like LIKE instead, e.g. Innodb_buffer_pool%"
+                        },
+                        "interval_secs": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": 60,
+                            "description": "Seconds between two snapshots; rates then cover this interval rather than the server's uptime"
+                        },
+                        "timeout_ms": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional timeout in milliseconds for each snapshot, capped at the server's max_query_timeout_secs"
+                        }
+                    },
+                    "required": ["datasource_key"]
+                })).unwrap()),
+            ),
+            Tool::new(
+                "mysql_compare_variables",
+                "Diff the global server variables of two data sources, listing values that differ and variables set on only one side. Variables that identify a server (hostname, server_id, server_uuid, ...) are left out",
+                Arc::new(serde_json::from_value(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "left_datasource_key": {
+                            "type": "string",
+                            "description": "The first data source key to compare"
+                        },
+                        "right_datasource_key": {
+                            "type": "string",
+                            "description": "The second data source key to compare"
+                        },
+                        "filter": {
+                            "type": "string",
+                            "description": "Only compare variables whose name contains this text, case-insensitive. A pattern containing % is matched like LIKE instead"
+                        },
+                        "timeout_ms": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional timeout in milliseconds, capped at the server's max_query_timeout_secs"
+                        }
+                    },
+                    "required": ["left_datasource_key", "right_datasource_key"]
+                })).unwrap()),
+            ),
+            Tool::new(
+                "mysql_list_datasources",
+                "List all configured data sources with their status, health check results and circuit breaker state",
+                Arc::new(serde_json::from_value(serde_json::json!({
+                    "type": "object",
+                    "properties": {}
+                })).unwrap()),
+            ),
+            Tool::new(
+                "mysql_list_databases",
+                "List all databases for a specified data source",
+                Arc::new(serde_json::from_value(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "datasource_key": {
+                            "type": "string",
+                            "description": "The data source key to list databases for"
+                        },
+                        "timeout_ms": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional timeout in milliseconds for this call, capped at the server's max_query_timeout_secs. Defaults to the data source's query timeout"
+                        }
+                    },
+                    "required": ["datasource_key"]
+                })).unwrap()),
+            ),
+            Tool::new(
+                "mysql_list_tables",
+                "List all tables in a specified database",
+                Arc::new(serde_json::from_value(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "datasource_key": {
+                            "type": "string",
+                            "description": "The data source key to connect to"
+                        },
+                        "database": {
+                            "type": "string",
+                            "description": "The database name to list tables from"
+                        },
+                        "timeout_ms": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional timeout in milliseconds for this call, capped at the server's max_query_timeout_secs. Defaults to the data source's query timeout"
+                        }
+                    },
+                    "required": ["datasource_key", "database"]
+                })).unwrap()),
+            ),
+            Tool::new(
+                "mysql_describe_table",
+                "Get the schema of a specified table",
+                Arc::new(serde_json::from_value(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "datasource_key": {
+                            "type": "string",
+                            "description": "The data source key to connect to"
+                        },
+                        "database": {
+                            "type": "string",
+                            "description": "The database name"
+                        },
+                        "table": {
+                            "type": "string",
+                            "description": "The table name to describe"
+                        },
+                        "timeout_ms": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Optional timeout in milliseconds for this call, capped at the server's max_query_timeout_secs. Defaults to the data source's query timeout"
+                        }
+                    },
+                    "required": ["datasource_key", "database", "table"]
+                })).unwrap()),
+            ),
+            Tool::new(
+                "mysql_get_connection_stats",
+                "Get connection pool statistics and circuit breaker state for data sources",
+                Arc::new(serde_json::from_value(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "datasource_key": {
+                            "type": "string",
+                            "description": "Optional data source key to get stats for. If not provided, returns stats for all data sources"
+                        }
+                    }
+                })).unwrap()),
+            ),
+        ];
+
+        Ok(ListToolsResult { tools, next_cursor: None })
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        // Generate a trace ID for this tool call
+        let trace_id = uuid::Uuid::new_v4();
+        let peer = context.peer.clone();
+        
+        let call = async move {
+            tracing::info!(
+                trace_id = %trace_id,
+                tool_name = %request.name,
+                "Tool call started"
+            );
+
+            let result = match request.name.as_ref() {
+                "mysql_query" => self.handle_query_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_execute" => self.handle_execute_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_transaction" => self.handle_transaction_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_begin" => self.handle_begin_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_commit" => self.handle_commit_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_rollback" => self.handle_rollback_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_query_stream" => self.handle_query_stream_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_fetch_chunk" => self.handle_fetch_chunk_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_close_stream" => self.handle_close_stream_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_export" => self.handle_export_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_import" => self.handle_import_tool(request.arguments.map(serde_json::Value::Object), peer).await,
+                "mysql_explain" => self.handle_explain_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_suggest_indexes" => self.handle_suggest_indexes_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_processlist" => self.handle_processlist_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_lock_waits" => self.handle_lock_waits_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_kill" => self.handle_kill_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_top_queries" => self.handle_top_queries_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_variables" => self.handle_variables_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_status" => self.handle_status_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_compare_variables" => self.handle_compare_variables_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_list_datasources" => self.handle_list_datasources_tool().await,
+                "mysql_list_databases" => self.handle_list_databases_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_list_tables" => self.handle_list_tables_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_describe_table" => self.handle_describe_table_tool(request.arguments.map(serde_json::Value::Object)).await,
+                "mysql_get_connection_stats" => self.handle_connection_stats_tool(request.arguments.map(serde_json::Value::Object)).await,
+                _ => {
+                    tracing::error!(trace_id = %trace_id, "Unknown tool requested");
+                    return Err(McpError::invalid_params(
+                        format!("Unknown tool: {}", request.name),
+                        None
+                    ))
+                }
+            };
+
+            match &result {
+                Ok(_) => tracing::info!(trace_id = %trace_id, "Tool call completed successfully"),
+                Err(e) => tracing::error!(trace_id = %trace_id, error = %e.sanitize(), "Tool call failed"),
+            }
+
+            match result {
+                Ok(result) => Ok(result),
+                // Malformed arguments are protocol errors, like an unknown tool
+                Err(AppError::InvalidArgument(message)) => {
+                    Err(McpError::invalid_params(message, None))
+                }
+                // Everything else failed while running the tool, and is reported
+                // as a tool error the client can read and act on
+                Err(e) => tool_error(&e),
+            }
+        };
+
+        // On `notifications/cancelled` the call is dropped, which kills any
+        // statement it is still running on the server
+        tokio::select! {
+            result = call => result,
+            _ = context.ct.cancelled() => {
+                tracing::warn!(trace_id = %trace_id, "Tool call cancelled");
+                tool_error(&AppError::Cancelled)
+            }
+        }
+    }
+
+    async fn list_resources(
+        &self,
+        _request: PaginatedRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> std::result::Result<ListResourcesResult, McpError> {
+        tracing::debug!("Listing resources");
+
+        // For now, return empty list as resources are accessed via templates
+        Ok(ListResourcesResult {
+            resources: vec![],
+            next_cursor: None,
+        })
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: PaginatedRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> std::result::Result<ListResourceTemplatesResult, McpError> {
+        tracing::debug!("Listing resource templates");
+
+        let resource_provider = ResourceProvider::new(self.manager.clone(), self.pool_managers.clone());
+        let templates = resource_provider.list_resource_templates();
+
+        let resource_templates: Vec<ResourceTemplate> = templates
+            .into_iter()
+            .map(|t| {
+                Annotated::new(RawResourceTemplate {
+                    uri_template: t.uri_template,
+                    name: t.name,
+                    description: Some(t.description),
+                    mime_type: Some(t.mime_type),
+                }, None)
+            })
+            .collect();
+
+        Ok(ListResourceTemplatesResult {
+            resource_templates,
+            next_cursor: None,
+        })
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> std::result::Result<ReadResourceResult, McpError> {
+        // Generate a trace ID for this resource read
+        let trace_id = uuid::Uuid::new_v4();
+        
+        async move {
             tracing::info!(
                 trace_id = %trace_id,
                 uri = %request.uri,
                 "Resource read started"
             );
 
-            let resource_provider = ResourceProvider::new(self.manager.clone(), self.pool_managers.clone());
-            
-            let content = resource_provider
-                .get_resource(&request.uri)
-                .await
-                .map_err(|e| {
-                    tracing::error!(trace_id = %trace_id, error = %e.sanitize(), "Resource read failed");
-                    McpError::internal_error(e.sanitize(), None)
-                })?;
+            let resource_provider = ResourceProvider::new(self.manager.clone(), self.pool_managers.clone());
+            
+            let content = resource_provider
+                .get_resource(&request.uri)
+                .await
+                .map_err(|e| {
+                    tracing::error!(trace_id = %trace_id, error = %e.sanitize(), "Resource read failed");
+                    resource_error(&e)
+                })?;
+
+            tracing::info!(trace_id = %trace_id, "Resource read completed successfully");
+
+            Ok(ReadResourceResult {
+                contents: vec![ResourceContents::TextResourceContents {
+                    uri: content.uri,
+                    mime_type: Some(content.mime_type),
+                    text: content.content,
+                }],
+            })
+        }.await
+    }
+}
+
+impl MySqlMcpServerHandler {
+    async fn handle_query_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
+        
+        let datasource_key = args["datasource_key"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("datasource_key is required".to_string()))?;
+        let database = args["database"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("database is required".to_string()))?;
+        let query = args["query"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("query is required".to_string()))?;
+        let params = StatementParams::from_json(args.get("params"), args.get("named_params"))
+            .map_err(invalid_argument)?;
+
+        let transaction_id = args.get("transaction_id").and_then(|v| v.as_str());
+
+        let format = format_arg(&args)?;
+
+        let tool = QueryTool::new(self.manager.clone(), self.pool_managers.clone())
+            .with_timeout(timeout_arg(&args)?)
+            .with_result_limits(
+                limit_arg(&args, "max_rows")?,
+                limit_arg(&args, "max_result_bytes")?,
+            );
+        let result = match transaction_id {
+            Some(transaction_id) => {
+                tool.execute_in_transaction(
+                    &self.transactions,
+                    transaction_id,
+                    datasource_key,
+                    database,
+                    query,
+                    &params,
+                )
+                .await?
+            }
+            None => {
+                tool.execute_with_params(datasource_key, database, query, &params)
+                    .await?
+            }
+        };
+
+        Ok(CallToolResult::success(query_result_content(&result, format)?))
+    }
+
+    async fn handle_execute_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
+        
+        let datasource_key = args["datasource_key"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("datasource_key is required".to_string()))?;
+        let database = args["database"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("database is required".to_string()))?;
+        let statement = args["statement"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("statement is required".to_string()))?;
+        let params = StatementParams::from_json(args.get("params"), args.get("named_params"))
+            .map_err(invalid_argument)?;
+
+        let transaction_id = args.get("transaction_id").and_then(|v| v.as_str());
+
+        let idempotency_key = args
+            .get("idempotency_key")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+
+        let tool = ExecuteTool::new(self.manager.clone(), self.pool_managers.clone())
+            .with_timeout(timeout_arg(&args)?)
+            .with_idempotency_key(idempotency_key, self.idempotent_results.clone());
+        let result = match transaction_id {
+            Some(transaction_id) => {
+                tool.execute_in_transaction(
+                    &self.transactions,
+                    transaction_id,
+                    datasource_key,
+                    database,
+                    statement,
+                    &params,
+                )
+                .await?
+            }
+            None => {
+                tool.execute_with_params(datasource_key, database, statement, &params)
+                    .await?
+            }
+        };
+
+        let text = serde_json::to_string_pretty(&result)
+            .map_err(|e| AppError::QueryExecutionError(e.to_string()))?;
+
+        Ok(CallToolResult::success(vec![
+            Annotated::new(RawContent::text(text), None)
+        ]))
+    }
+
+    async fn handle_transaction_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
+
+        let datasource_key = args["datasource_key"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("datasource_key is required".to_string()))?;
+        let database = args["database"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("database is required".to_string()))?;
+        let statements = args["statements"]
+            .as_array()
+            .ok_or_else(|| AppError::InvalidArgument("statements must be an array".to_string()))?
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let statement = entry["statement"].as_str().ok_or_else(|| {
+                    AppError::InvalidArgument(format!("statements[{}].statement is required", index))
+                })?;
+                let params = StatementParams::from_json(entry.get("params"), entry.get("named_params"))
+                    .map_err(|e| AppError::InvalidArgument(format!("statements[{}]: {}", index, e)))?;
+                Ok(TransactionStatement {
+                    statement: statement.to_string(),
+                    params,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let isolation_level = args
+            .get("isolation_level")
+            .and_then(|v| v.as_str())
+            .map(IsolationLevel::from_str)
+            .transpose()
+            .map_err(invalid_argument)?;
+
+        let tool = TransactionTool::new(self.manager.clone(), self.pool_managers.clone())
+            .with_timeout(timeout_arg(&args)?);
+        let result = tool
+            .execute(datasource_key, database, &statements, isolation_level)
+            .await?;
+
+        let text = serde_json::to_string_pretty(&result)
+            .map_err(|e| AppError::QueryExecutionError(e.to_string()))?;
+
+        Ok(CallToolResult::success(vec![
+            Annotated::new(RawContent::text(text), None)
+        ]))
+    }
+
+    async fn handle_begin_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
+
+        let datasource_key = args["datasource_key"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("datasource_key is required".to_string()))?;
+        let database = args["database"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("database is required".to_string()))?;
+        let isolation_level = args
+            .get("isolation_level")
+            .and_then(|v| v.as_str())
+            .map(IsolationLevel::from_str)
+            .transpose()
+            .map_err(invalid_argument)?;
+
+        let tool = TransactionTool::new(self.manager.clone(), self.pool_managers.clone());
+        let transaction_id = tool
+            .begin(&self.transactions, datasource_key, database, isolation_level)
+            .await?;
+
+        let text = serde_json::to_string_pretty(&serde_json::json!({
+            "transaction_id": transaction_id,
+            "datasource_key": datasource_key,
+            "database": database,
+            "idle_timeout_secs": self.transactions.idle_timeout().as_secs(),
+        }))
+        .map_err(|e| AppError::QueryExecutionError(e.to_string()))?;
+
+        Ok(CallToolResult::success(vec![
+            Annotated::new(RawContent::text(text), None)
+        ]))
+    }
+
+    async fn handle_commit_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
+
+        let transaction_id = args["transaction_id"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("transaction_id is required".to_string()))?;
 
-            tracing::info!(trace_id = %trace_id, "Resource read completed successfully");
+        let tool = TransactionTool::new(self.manager.clone(), self.pool_managers.clone());
+        tool.commit(&self.transactions, transaction_id).await?;
 
-            Ok(ReadResourceResult {
-                contents: vec![ResourceContents::TextResourceContents {
-                    uri: content.uri,
-                    mime_type: Some(content.mime_type),
-                    text: content.content,
-                }],
-            })
-        }.await
+        let text = serde_json::to_string_pretty(&serde_json::json!({
+            "transaction_id": transaction_id,
+            "committed": true,
+        }))
+        .map_err(|e| AppError::QueryExecutionError(e.to_string()))?;
+
+        Ok(CallToolResult::success(vec![
+            Annotated::new(RawContent::text(text), None)
+        ]))
     }
-}
 
-impl MySqlMcpServerHandler {
-    async fn handle_query_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
-        let args = args.ok_or_else(|| AppError::InvalidStatement("Missing arguments".to_string()))?;
-        
+    async fn handle_rollback_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
+
+        let transaction_id = args["transaction_id"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("transaction_id is required".to_string()))?;
+
+        let tool = TransactionTool::new(self.manager.clone(), self.pool_managers.clone());
+        tool.rollback(&self.transactions, transaction_id).await?;
+
+        let text = serde_json::to_string_pretty(&serde_json::json!({
+            "transaction_id": transaction_id,
+            "committed": false,
+        }))
+        .map_err(|e| AppError::QueryExecutionError(e.to_string()))?;
+
+        Ok(CallToolResult::success(vec![
+            Annotated::new(RawContent::text(text), None)
+        ]))
+    }
+
+    async fn handle_query_stream_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
+
+        let datasource_key = args["datasource_key"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("datasource_key is required".to_string()))?;
+        let database = args["database"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("database is required".to_string()))?;
+        let query = args["query"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("query is required".to_string()))?;
+        let params = StatementParams::from_json(args.get("params"), args.get("named_params"))
+            .map_err(invalid_argument)?;
+        let chunk_size = match args.get("chunk_size") {
+            None | Some(serde_json::Value::Null) => self.stream_chunk_size,
+            Some(value) => match value.as_u64() {
+                Some(size) if size > 0 => (size as usize).min(self.stream_chunk_size),
+                _ => {
+                    return Err(AppError::InvalidArgument(
+                        "chunk_size must be a positive integer".to_string(),
+                    ))
+                }
+            },
+        };
+
+        let tool = QueryTool::new(self.manager.clone(), self.pool_managers.clone())
+            .with_timeout(timeout_arg(&args)?)
+            .with_format(format_arg(&args)?);
+        let result = tool
+            .open_stream(
+                &self.streams,
+                datasource_key,
+                database,
+                query,
+                &params,
+                chunk_size,
+            )
+            .await?;
+
+        Ok(CallToolResult::success(stream_chunk_content(&result)?))
+    }
+
+    async fn handle_fetch_chunk_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
+
+        let stream_id = args["stream_id"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("stream_id is required".to_string()))?;
+
+        let result = self.streams.fetch_chunk(stream_id).await?;
+
+        Ok(CallToolResult::success(stream_chunk_content(&result)?))
+    }
+
+    async fn handle_close_stream_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
+
+        let stream_id = args["stream_id"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("stream_id is required".to_string()))?;
+
+        // Closing a stream that already ended is not an error
+        self.streams.remove_stream(stream_id).await?;
+
+        let text = serde_json::to_string_pretty(&serde_json::json!({
+            "stream_id": stream_id,
+            "closed": true,
+        }))
+        .map_err(|e| AppError::QueryExecutionError(e.to_string()))?;
+
+        Ok(CallToolResult::success(vec![
+            Annotated::new(RawContent::text(text), None)
+        ]))
+    }
+
+    async fn handle_export_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
+
         let datasource_key = args["datasource_key"]
             .as_str()
-            .ok_or_else(|| AppError::InvalidStatement("datasource_key is required".to_string()))?;
+            .ok_or_else(|| AppError::InvalidArgument("datasource_key is required".to_string()))?;
         let database = args["database"]
             .as_str()
-            .ok_or_else(|| AppError::InvalidStatement("database is required".to_string()))?;
+            .ok_or_else(|| AppError::InvalidArgument("database is required".to_string()))?;
         let query = args["query"]
             .as_str()
-            .ok_or_else(|| AppError::InvalidStatement("query is required".to_string()))?;
+            .ok_or_else(|| AppError::InvalidArgument("query is required".to_string()))?;
+        let params = StatementParams::from_json(args.get("params"), args.get("named_params"))
+            .map_err(invalid_argument)?;
+        let file = args["file"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("file is required".to_string()))?;
+        let request = ExportRequest {
+            file: file.to_string(),
+            format: match args["format"].as_str() {
+                Some(format) => format.parse().map_err(invalid_argument)?,
+                None => ExportFormat::Csv,
+            },
+            compression: match args["compression"].as_str() {
+                Some(compression) => compression.parse().map_err(invalid_argument)?,
+                None => Compression::None,
+            },
+            overwrite: args["overwrite"].as_bool().unwrap_or(false),
+        };
 
-        let tool = QueryTool::new(self.manager.clone(), self.pool_managers.clone());
-        let result = tool.execute(datasource_key, database, query).await?;
+        let tool = ExportTool::new(
+            self.manager.clone(),
+            self.pool_managers.clone(),
+            self.export.clone(),
+        )
+        .with_timeout(timeout_arg(&args)?);
+        let result = tool
+            .export(datasource_key, database, query, &params, &request)
+            .await?;
 
         let text = serde_json::to_string_pretty(&result)
             .map_err(|e| AppError::QueryExecutionError(e.to_string()))?;
@@ -384,21 +1452,314 @@ impl MySqlMcpServerHandler {
         ]))
     }
 
-    async fn handle_execute_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
-        let args = args.ok_or_else(|| AppError::InvalidStatement("Missing arguments".to_string()))?;
-        
+    async fn handle_import_tool(
+        &self,
+        args: Option<serde_json::Value>,
+        peer: Peer<RoleServer>,
+    ) -> Result<CallToolResult> {
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
+
         let datasource_key = args["datasource_key"]
             .as_str()
-            .ok_or_else(|| AppError::InvalidStatement("datasource_key is required".to_string()))?;
+            .ok_or_else(|| AppError::InvalidArgument("datasource_key is required".to_string()))?;
         let database = args["database"]
             .as_str()
-            .ok_or_else(|| AppError::InvalidStatement("database is required".to_string()))?;
-        let statement = args["statement"]
+            .ok_or_else(|| AppError::InvalidArgument("database is required".to_string()))?;
+        let table = args["table"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("table is required".to_string()))?;
+        let file = args["file"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("file is required".to_string()))?;
+        let request = ImportRequest {
+            file: file.to_string(),
+            table: table.to_string(),
+            format: args["format"].as_str().map(str::parse).transpose().map_err(invalid_argument)?,
+            compression: args["compression"].as_str().map(str::parse).transpose().map_err(invalid_argument)?,
+            on_duplicate: match args["on_duplicate"].as_str() {
+                Some(mode) => mode.parse().map_err(invalid_argument)?,
+                None => OnDuplicate::default(),
+            },
+            dry_run: args["dry_run"].as_bool().unwrap_or(false),
+        };
+
+        let mut tool = ImportTool::new(
+            self.manager.clone(),
+            self.pool_managers.clone(),
+            self.import.clone(),
+        )
+        .with_timeout(timeout_arg(&args)?);
+
+        // Progress is forwarded by one task so that notifications stay in order
+        let forwarder = match progress_token_arg(&args)? {
+            Some(progress_token) => {
+                let (sender, mut receiver) = mpsc::unbounded_channel::<ImportProgress>();
+                tool = tool.with_progress(sender);
+                Some(tokio::spawn(async move {
+                    while let Some(progress) = receiver.recv().await {
+                        let notification = ProgressNotificationParam {
+                            progress_token: progress_token.clone(),
+                            progress: u32::try_from(progress.rows).unwrap_or(u32::MAX),
+                            total: None,
+                        };
+                        if let Err(e) = peer.notify_progress(notification).await {
+                            tracing::debug!(error = %e, "Failed to send progress notification");
+                        }
+                    }
+                }))
+            }
+            None => None,
+        };
+
+        let result = tool.import(datasource_key, database, &request).await;
+
+        // Dropping the tool closes the channel; the last notifications go out
+        // before the result
+        drop(tool);
+        if let Some(forwarder) = forwarder {
+            let _ = forwarder.await;
+        }
+
+        let text = serde_json::to_string_pretty(&result?)
+            .map_err(|e| AppError::QueryExecutionError(e.to_string()))?;
+
+        Ok(CallToolResult::success(vec![
+            Annotated::new(RawContent::text(text), None)
+        ]))
+    }
+
+    async fn handle_explain_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
+
+        let datasource_key = args["datasource_key"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("datasource_key is required".to_string()))?;
+        let database = args["database"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("database is required".to_string()))?;
+        let query = args["query"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("query is required".to_string()))?;
+        let params = StatementParams::from_json(args.get("params"), args.get("named_params"))
+            .map_err(invalid_argument)?;
+        let analyze = args["analyze"].as_bool().unwrap_or(false);
+
+        let tool = ExplainTool::new(self.manager.clone(), self.pool_managers.clone())
+            .with_timeout(timeout_arg(&args)?);
+        let result = tool
+            .explain(datasource_key, database, query, &params, analyze)
+            .await?;
+
+        let text = serde_json::to_string_pretty(&result)
+            .map_err(|e| AppError::QueryExecutionError(e.to_string()))?;
+
+        Ok(CallToolResult::success(vec![
+            Annotated::new(RawContent::text(text), None)
+        ]))
+    }
+
+    async fn handle_suggest_indexes_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
+
+        let datasource_key = args["datasource_key"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("datasource_key is required".to_string()))?;
+        let database = args["database"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("database is required".to_string()))?;
+
+        let tool = IndexAdvisorTool::new(self.manager.clone(), self.pool_managers.clone())
+            .with_timeout(timeout_arg(&args)?);
+        let result = match (args["query"].as_str(), args.get("from_digests")) {
+            (Some(query), None) => {
+                let params = StatementParams::from_json(args.get("params"), args.get("named_params"))
+                    .map_err(invalid_argument)?;
+                tool.suggest_for_query(datasource_key, database, query, &params)
+                    .await?
+            }
+            (None, Some(limit)) => {
+                let limit = limit.as_u64().ok_or_else(|| {
+                    AppError::InvalidArgument("from_digests must be a positive integer".to_string())
+                })?;
+                tool.suggest_from_digests(datasource_key, database, limit as usize)
+                    .await?
+            }
+            _ => {
+                return Err(AppError::InvalidArgument(
+                    "Give either query or from_digests".to_string(),
+                ))
+            }
+        };
+
+        let text = serde_json::to_string_pretty(&result)
+            .map_err(|e| AppError::QueryExecutionError(e.to_string()))?;
+
+        Ok(CallToolResult::success(vec![
+            Annotated::new(RawContent::text(text), None)
+        ]))
+    }
+
+    async fn handle_processlist_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
+
+        let datasource_key = args["datasource_key"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("datasource_key is required".to_string()))?;
+        let filter = ProcessFilter {
+            include_sleeping: args["include_sleeping"].as_bool().unwrap_or(false),
+            min_time_secs: args["min_time_secs"].as_u64().unwrap_or(0),
+            user: args["user"].as_str().map(String::from),
+            database: args["database"].as_str().map(String::from),
+        };
+
+        let tool = ProcessTool::new(self.manager.clone(), self.pool_managers.clone())
+            .with_timeout(timeout_arg(&args)?);
+        let result = tool.processlist(datasource_key, &filter).await?;
+
+        let text = serde_json::to_string_pretty(&result)
+            .map_err(|e| AppError::QueryExecutionError(e.to_string()))?;
+
+        Ok(CallToolResult::success(vec![
+            Annotated::new(RawContent::text(text), None)
+        ]))
+    }
+
+    async fn handle_lock_waits_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
+
+        let datasource_key = args["datasource_key"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("datasource_key is required".to_string()))?;
+        let min_transaction_secs = args["min_transaction_secs"].as_u64().unwrap_or(10);
+
+        let tool = ProcessTool::new(self.manager.clone(), self.pool_managers.clone())
+            .with_timeout(timeout_arg(&args)?);
+        let result = tool.lock_waits(datasource_key, min_transaction_secs).await?;
+
+        let text = serde_json::to_string_pretty(&result)
+            .map_err(|e| AppError::QueryExecutionError(e.to_string()))?;
+
+        Ok(CallToolResult::success(vec![
+            Annotated::new(RawContent::text(text), None)
+        ]))
+    }
+
+    async fn handle_kill_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
+
+        let datasource_key = args["datasource_key"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("datasource_key is required".to_string()))?;
+        let thread_id = args["thread_id"]
+            .as_u64()
+            .ok_or_else(|| AppError::InvalidArgument("thread_id is required".to_string()))?;
+        let target = if args["query_only"].as_bool().unwrap_or(false) {
+            KillTarget::Query
+        } else {
+            KillTarget::Connection
+        };
+        let allow_own_connection = args["allow_own_connection"].as_bool().unwrap_or(false);
+
+        let tool = ProcessTool::new(self.manager.clone(), self.pool_managers.clone())
+            .with_timeout(timeout_arg(&args)?);
+        let result = tool
+            .kill(datasource_key, thread_id, target, allow_own_connection)
+            .await?;
+
+        let text = serde_json::to_string_pretty(&result)
+            .map_err(|e| AppError::QueryExecutionError(e.to_string()))?;
+
+        Ok(CallToolResult::success(vec![
+            Annotated::new(RawContent::text(text), None)
+        ]))
+    }
+
+    async fn handle_top_queries_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
+
+        let datasource_key = args["datasource_key"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("datasource_key is required".to_string()))?;
+        let database = args["database"].as_str();
+        let order_by = match args["order_by"].as_str() {
+            Some(order_by) => TopQueriesOrder::from_str(order_by).map_err(invalid_argument)?,
+            None => TopQueriesOrder::default(),
+        };
+        let source = match args["source"].as_str() {
+            Some(source) => DigestSource::from_str(source).map_err(invalid_argument)?,
+            None => DigestSource::default(),
+        };
+        let limit = args["limit"].as_u64().unwrap_or(10).min(100) as usize;
+
+        let tool = TopQueriesTool::new(self.manager.clone(), self.pool_managers.clone())
+            .with_timeout(timeout_arg(&args)?);
+        let result = tool
+            .top_queries(datasource_key, database, order_by, limit, source)
+            .await?;
+
+        let text = serde_json::to_string_pretty(&result)
+            .map_err(|e| AppError::QueryExecutionError(e.to_string()))?;
+
+        Ok(CallToolResult::success(vec![
+            Annotated::new(RawContent::text(text), None)
+        ]))
+    }
+
+    async fn handle_variables_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
+
+        let datasource_key = args["datasource_key"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("datasource_key is required".to_string()))?;
+        let filter = args["filter"].as_str();
+
+        let tool = VariablesTool::new(self.manager.clone(), self.pool_managers.clone())
+            .with_timeout(timeout_arg(&args)?);
+        let result = tool.variables(datasource_key, filter).await?;
+
+        let text = serde_json::to_string_pretty(&result)
+            .map_err(|e| AppError::QueryExecutionError(e.to_string()))?;
+
+        Ok(CallToolResult::success(vec![
+            Annotated::new(RawContent::text(text), None)
+        ]))
+    }
+
+    async fn handle_status_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
+
+        let datasource_key = args["datasource_key"]
             .as_str()
-            .ok_or_else(|| AppError::InvalidStatement("statement is required".to_string()))?;
+            .ok_or_else(|| AppError::InvalidArgument("datasource_key is required".to_string()))?;
+        let filter = args["filter"].as_str();
+        let interval = args["interval_secs"].as_u64().map(Duration::from_secs);
 
-        let tool = ExecuteTool::new(self.manager.clone(), self.pool_managers.clone());
-        let result = tool.execute(datasource_key, database, statement).await?;
+        let tool = VariablesTool::new(self.manager.clone(), self.pool_managers.clone())
+            .with_timeout(timeout_arg(&args)?);
+        let result = tool.status(datasource_key, filter, interval).await?;
+
+        let text = serde_json::to_string_pretty(&result)
+            .map_err(|e| AppError::QueryExecutionError(e.to_string()))?;
+
+        Ok(CallToolResult::success(vec![
+            Annotated::new(RawContent::text(text), None)
+        ]))
+    }
+
+    async fn handle_compare_variables_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
+
+        let left_key = args["left_datasource_key"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("left_datasource_key is required".to_string()))?;
+        let right_key = args["right_datasource_key"]
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("right_datasource_key is required".to_string()))?;
+        let filter = args["filter"].as_str();
+
+        let tool = VariablesTool::new(self.manager.clone(), self.pool_managers.clone())
+            .with_timeout(timeout_arg(&args)?);
+        let result = tool.compare_variables(left_key, right_key, filter).await?;
 
         let text = serde_json::to_string_pretty(&result)
             .map_err(|e| AppError::QueryExecutionError(e.to_string()))?;
@@ -421,13 +1782,14 @@ impl MySqlMcpServerHandler {
     }
 
     async fn handle_list_databases_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
-        let args = args.ok_or_else(|| AppError::InvalidStatement("Missing arguments".to_string()))?;
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
         
         let datasource_key = args["datasource_key"]
             .as_str()
-            .ok_or_else(|| AppError::InvalidStatement("datasource_key is required".to_string()))?;
+            .ok_or_else(|| AppError::InvalidArgument("datasource_key is required".to_string()))?;
 
-        let tool = ListTool::new(self.manager.clone(), self.pool_managers.clone());
+        let tool = ListTool::new(self.manager.clone(), self.pool_managers.clone())
+            .with_timeout(timeout_arg(&args)?);
         let result = tool.list_databases(datasource_key).await?;
 
         let text = serde_json::to_string_pretty(&result)
@@ -439,16 +1801,17 @@ impl MySqlMcpServerHandler {
     }
 
     async fn handle_list_tables_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
-        let args = args.ok_or_else(|| AppError::InvalidStatement("Missing arguments".to_string()))?;
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
         
         let datasource_key = args["datasource_key"]
             .as_str()
-            .ok_or_else(|| AppError::InvalidStatement("datasource_key is required".to_string()))?;
+            .ok_or_else(|| AppError::InvalidArgument("datasource_key is required".to_string()))?;
         let database = args["database"]
             .as_str()
-            .ok_or_else(|| AppError::InvalidStatement("database is required".to_string()))?;
+            .ok_or_else(|| AppError::InvalidArgument("database is required".to_string()))?;
 
-        let tool = SchemaTool::new(self.manager.clone(), self.pool_managers.clone());
+        let tool = SchemaTool::new(self.manager.clone(), self.pool_managers.clone())
+            .with_timeout(timeout_arg(&args)?);
         let result = tool.list_tables(datasource_key, database).await?;
 
         let text = serde_json::to_string_pretty(&result)
@@ -460,19 +1823,20 @@ impl MySqlMcpServerHandler {
     }
 
     async fn handle_describe_table_tool(&self, args: Option<serde_json::Value>) -> Result<CallToolResult> {
-        let args = args.ok_or_else(|| AppError::InvalidStatement("Missing arguments".to_string()))?;
+        let args = args.ok_or_else(|| AppError::InvalidArgument("Missing arguments".to_string()))?;
         
         let datasource_key = args["datasource_key"]
             .as_str()
-            .ok_or_else(|| AppError::InvalidStatement("datasource_key is required".to_string()))?;
+            .ok_or_else(|| AppError::InvalidArgument("datasource_key is required".to_string()))?;
         let database = args["database"]
             .as_str()
-            .ok_or_else(|| AppError::InvalidStatement("database is required".to_string()))?;
+            .ok_or_else(|| AppError::InvalidArgument("database is required".to_string()))?;
         let table = args["table"]
             .as_str()
-            .ok_or_else(|| AppError::InvalidStatement("table is required".to_string()))?;
+            .ok_or_else(|| AppError::InvalidArgument("table is required".to_string()))?;
 
-        let tool = SchemaTool::new(self.manager.clone(), self.pool_managers.clone());
+        let tool = SchemaTool::new(self.manager.clone(), self.pool_managers.clone())
+            .with_timeout(timeout_arg(&args)?);
         let result = tool.describe_table(datasource_key, database, table).await?;
 
         let text = serde_json::to_string_pretty(&result)
@@ -498,3 +1862,151 @@ impl MySqlMcpServerHandler {
         ]))
     }
 }
+
+/// Report a failure to read an argument as a malformed argument
+fn invalid_argument(error: AppError) -> AppError {
+    match error {
+        AppError::InvalidStatement(message) => AppError::InvalidArgument(message),
+        error => error,
+    }
+}
+
+/// Result of a failed tool call: `isError` set, with the sanitized error, its
+/// kind, a hint and whether it is worth retrying as JSON content
+fn tool_error(error: &AppError) -> std::result::Result<CallToolResult, McpError> {
+    let text = serde_json::to_string_pretty(&ToolError::from(error))
+        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+    Ok(CallToolResult::error(vec![
+        Annotated::new(RawContent::text(text), None)
+    ]))
+}
+
+/// Protocol error for a failed resource read, with the error code matching
+/// its kind and the `ToolError` fields as data
+fn resource_error(error: &AppError) -> McpError {
+    let message = error.sanitize();
+    let data = serde_json::to_value(ToolError::from(error)).ok();
+    match error.kind() {
+        ErrorKind::NotFound => McpError::resource_not_found(message, data),
+        ErrorKind::InvalidRequest => McpError::invalid_params(message, data),
+        ErrorKind::PermissionDenied => McpError::invalid_request(message, data),
+        _ => McpError::internal_error(message, data),
+    }
+}
+
+/// Read the optional per-call `timeout_ms` argument
+fn timeout_arg(args: &serde_json::Value) -> Result<Option<Duration>> {
+    match args.get("timeout_ms") {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(value) => match value.as_u64() {
+            Some(ms) if ms > 0 => Ok(Some(Duration::from_millis(ms))),
+            _ => Err(AppError::InvalidArgument(
+                "timeout_ms must be a positive integer (milliseconds)".to_string(),
+            )),
+        },
+    }
+}
+
+/// Read the optional `progress_token` argument
+fn progress_token_arg(args: &serde_json::Value) -> Result<Option<ProgressToken>> {
+    match args.get("progress_token") {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(serde_json::Value::String(token)) => {
+            Ok(Some(NumberOrString::String(token.as_str().into())))
+        }
+        Some(value) => value
+            .as_u64()
+            .and_then(|token| u32::try_from(token).ok())
+            .map(|token| Some(NumberOrString::Number(token)))
+            .ok_or_else(|| {
+                AppError::InvalidArgument(
+                    "progress_token must be a string or a non-negative integer".to_string(),
+                )
+            }),
+    }
+}
+
+/// Read the optional `format` argument
+fn format_arg(args: &serde_json::Value) -> Result<ResultFormat> {
+    match args.get("format") {
+        None | Some(serde_json::Value::Null) => Ok(ResultFormat::default()),
+        Some(value) => value
+            .as_str()
+            .ok_or_else(|| AppError::InvalidArgument("format must be a string".to_string()))?
+            .parse()
+            .map_err(invalid_argument),
+    }
+}
+
+/// Content of a `mysql_query` result in the requested format
+///
+/// Formats other than `json` return the encoded rows, followed by the rest of
+/// the result as compact JSON.
+fn query_result_content(result: &QueryResult, format: ResultFormat) -> Result<Vec<Content>> {
+    let Some(formatter) = format.formatter() else {
+        let text = serde_json::to_string_pretty(result)
+            .map_err(|e| AppError::QueryExecutionError(e.to_string()))?;
+        return Ok(vec![Annotated::new(RawContent::text(text), None)]);
+    };
+
+    let mut summary = serde_json::json!({
+        "format": format.name(),
+        "rows_returned": result.rows_returned,
+        "truncated": result.truncated,
+    });
+    if let Some(hint) = &result.hint {
+        summary["hint"] = hint.as_str().into();
+    }
+
+    Ok(vec![
+        Annotated::new(
+            RawContent::text(formatter.format(&result.columns, &result.rows, true)),
+            None,
+        ),
+        Annotated::new(RawContent::text(summary.to_string()), None),
+    ])
+}
+
+/// Content of a stream chunk in the format its stream was opened with
+///
+/// Formats other than `json` return the encoded rows, followed by the stream's
+/// state as compact JSON. Only the first chunk has a header row.
+fn stream_chunk_content(chunk: &StreamChunk) -> Result<Vec<Content>> {
+    let Some(formatter) = chunk.format.formatter() else {
+        let text = serde_json::to_string_pretty(chunk)
+            .map_err(|e| AppError::QueryExecutionError(e.to_string()))?;
+        return Ok(vec![Annotated::new(RawContent::text(text), None)]);
+    };
+
+    let rows = &chunk.chunk;
+    let summary = serde_json::json!({
+        "stream_id": chunk.stream_id,
+        "format": chunk.format.name(),
+        "chunk_number": rows.chunk_number,
+        "is_last": rows.is_last,
+        "total_rows": rows.total_rows,
+    });
+
+    Ok(vec![
+        Annotated::new(
+            RawContent::text(formatter.format(&rows.columns, &rows.rows, rows.chunk_number == 0)),
+            None,
+        ),
+        Annotated::new(RawContent::text(summary.to_string()), None),
+    ])
+}
+
+/// Read an optional per-call result limit argument
+fn limit_arg(args: &serde_json::Value, name: &str) -> Result<Option<usize>> {
+    match args.get(name) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(value) => match value.as_u64() {
+            Some(limit) if limit > 0 => Ok(Some(limit as usize)),
+            _ => Err(AppError::InvalidArgument(format!(
+                "{} must be a positive integer",
+                name
+            ))),
+        },
+    }
+}
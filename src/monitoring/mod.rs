@@ -132,19 +132,15 @@ async fn log_pool_statistics(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{DataSourceConfig, PoolConfig};
+    use crate::config::DataSourceConfig;
 
     fn create_test_config(key: &str, name: &str) -> DataSourceConfig {
         DataSourceConfig {
             key: key.to_string(),
             name: name.to_string(),
-            host: "localhost".to_string(),
-            port: 3306,
             username: "test".to_string(),
             password: "test".to_string(),
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: crate::config::Permission::default(),
+            ..Default::default()
         }
     }
 
@@ -0,0 +1,437 @@
+//! Typed bind parameters for parameterized statements
+//!
+//! Tool calls supply values as JSON, either positionally (`params`, bound to
+//! `?` placeholders) or by name (`named_params`, bound to `:name`
+//! placeholders). Plain JSON scalars map to MySQL values directly; values
+//! without a JSON equivalent use tagged objects:
+//!
+//! ```json
+//! {"type": "decimal", "value": "1234.5600"}
+//! {"type": "datetime", "value": "2024-05-01 13:45:00"}
+//! {"type": "binary", "value": "3q2+7w=="}
+//! ```
+
+use crate::error::{McpError, Result};
+use crate::sql::{self, TokenKind};
+use base64::Engine;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use regex::Regex;
+use serde_json::Value;
+use sqlx::mysql::{MySql, MySqlArguments};
+use sqlx::query::Query;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+/// A single value bound to a statement placeholder
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlParam {
+    Null,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    String(String),
+    /// Exact decimal, bound in its textual form so no precision is lost
+    Decimal(String),
+    DateTime(NaiveDateTime),
+    Date(NaiveDate),
+    Binary(Vec<u8>),
+}
+
+impl SqlParam {
+    /// Convert a JSON tool argument into a bind value
+    pub fn from_json(value: &Value) -> std::result::Result<Self, String> {
+        match value {
+            Value::Null => Ok(SqlParam::Null),
+            Value::Bool(b) => Ok(SqlParam::Bool(*b)),
+            Value::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    Ok(SqlParam::Int(i))
+                } else if let Some(u) = n.as_u64() {
+                    Ok(SqlParam::UInt(u))
+                } else {
+                    n.as_f64()
+                        .map(SqlParam::Float)
+                        .ok_or_else(|| format!("unsupported number {}", n))
+                }
+            }
+            Value::String(s) => Ok(SqlParam::String(s.clone())),
+            Value::Object(object) => {
+                let tag = object.get("type").and_then(Value::as_str).ok_or(
+                    "objects must be tagged with a \"type\" of decimal, datetime or binary",
+                )?;
+                let value = object
+                    .get("value")
+                    .ok_or_else(|| format!("tagged {} value is missing \"value\"", tag))?;
+                parse_tagged(tag, value)
+            }
+            Value::Array(_) => Err("arrays are not supported as bind values".to_string()),
+        }
+    }
+}
+
+fn parse_tagged(tag: &str, value: &Value) -> std::result::Result<SqlParam, String> {
+    match tag {
+        "decimal" => {
+            let text = match value {
+                Value::String(s) => s.trim().to_string(),
+                Value::Number(n) => n.to_string(),
+                _ => return Err("decimal value must be a string or number".to_string()),
+            };
+            if decimal_pattern().is_match(&text) {
+                Ok(SqlParam::Decimal(text))
+            } else {
+                Err(format!("invalid decimal '{}'", text))
+            }
+        }
+        "datetime" => {
+            let text = value
+                .as_str()
+                .ok_or("datetime value must be a string")?
+                .trim();
+            parse_datetime(text).ok_or_else(|| {
+                format!(
+                    "invalid datetime '{}', expected 'YYYY-MM-DD', 'YYYY-MM-DD HH:MM:SS[.ffffff]' or RFC 3339",
+                    text
+                )
+            })
+        }
+        "binary" => {
+            let text = value
+                .as_str()
+                .ok_or("binary value must be a base64 string")?;
+            base64::engine::general_purpose::STANDARD
+                .decode(text)
+                .map(SqlParam::Binary)
+                .map_err(|e| format!("invalid base64 binary value: {}", e))
+        }
+        other => Err(format!(
+            "unknown value type '{}', expected decimal, datetime or binary",
+            other
+        )),
+    }
+}
+
+fn decimal_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"^[+-]?(\d+\.?\d*|\.\d+)([eE][+-]?\d+)?$").unwrap())
+}
+
+/// Parse a date or datetime. Values with a UTC offset are converted to UTC.
+pub(crate) fn parse_datetime(text: &str) -> Option<SqlParam> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(text) {
+        return Some(SqlParam::DateTime(datetime.naive_utc()));
+    }
+
+    for format in ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"] {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(text, format) {
+            return Some(SqlParam::DateTime(datetime));
+        }
+    }
+
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .ok()
+        .map(SqlParam::Date)
+}
+
+/// Bind arguments supplied with a statement
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatementParams {
+    /// Values for `?` placeholders, in order
+    pub positional: Vec<SqlParam>,
+    /// Values for `:name` placeholders, keyed by name without the colon
+    pub named: BTreeMap<String, SqlParam>,
+}
+
+impl StatementParams {
+    /// Parse the `params` and `named_params` tool arguments
+    pub fn from_json(params: Option<&Value>, named_params: Option<&Value>) -> Result<Self> {
+        let positional = match params {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(values)) => values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    SqlParam::from_json(value)
+                        .map_err(|e| McpError::InvalidStatement(format!("params[{}]: {}", i, e)))
+                })
+                .collect::<Result<Vec<_>>>()?,
+            Some(_) => {
+                return Err(McpError::InvalidStatement(
+                    "params must be an array".to_string(),
+                ))
+            }
+        };
+
+        let named = match named_params {
+            None | Some(Value::Null) => BTreeMap::new(),
+            Some(Value::Object(values)) => values
+                .iter()
+                .map(|(name, value)| {
+                    let name = name.strip_prefix(':').unwrap_or(name);
+                    SqlParam::from_json(value)
+                        .map(|param| (name.to_string(), param))
+                        .map_err(|e| {
+                            McpError::InvalidStatement(format!("named_params.{}: {}", name, e))
+                        })
+                })
+                .collect::<Result<BTreeMap<_, _>>>()?,
+            Some(_) => {
+                return Err(McpError::InvalidStatement(
+                    "named_params must be an object".to_string(),
+                ))
+            }
+        };
+
+        Ok(Self { positional, named })
+    }
+
+    /// Check if no bind values were supplied
+    pub fn is_empty(&self) -> bool {
+        self.positional.is_empty() && self.named.is_empty()
+    }
+
+    /// Match the supplied values against the statement's placeholders
+    ///
+    /// Named placeholders are rewritten to `?` so the statement can be
+    /// prepared by MySQL, with values ordered by occurrence (a name may be
+    /// used more than once).
+    pub fn bind(&self, statement: &str) -> Result<BoundStatement> {
+        if !self.positional.is_empty() && !self.named.is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Use either params or named_params, not both".to_string(),
+            ));
+        }
+
+        let tokens = sql::tokenize(statement);
+        let positional_count = tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::Placeholder)
+            .count();
+        let named_tokens: Vec<_> = tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::NamedPlaceholder)
+            .collect();
+
+        if named_tokens.is_empty() {
+            if !self.named.is_empty() {
+                return Err(McpError::InvalidStatement(
+                    "named_params were supplied but the statement has no :name placeholders"
+                        .to_string(),
+                ));
+            }
+            if positional_count != self.positional.len() {
+                return Err(McpError::InvalidStatement(format!(
+                    "Statement has {} ? placeholder(s) but {} param(s) were supplied",
+                    positional_count,
+                    self.positional.len()
+                )));
+            }
+
+            return Ok(BoundStatement {
+                sql: statement.to_string(),
+                args: self.positional.clone(),
+            });
+        }
+
+        if positional_count > 0 {
+            return Err(McpError::InvalidStatement(
+                "Statement mixes ? and :name placeholders".to_string(),
+            ));
+        }
+        if !self.positional.is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Statement uses :name placeholders; supply named_params instead of params"
+                    .to_string(),
+            ));
+        }
+
+        let mut sql = String::with_capacity(statement.len());
+        let mut args = Vec::with_capacity(named_tokens.len());
+        let mut last = 0;
+        for token in &named_tokens {
+            let name = &token.text[1..];
+            let value = self.named.get(name).ok_or_else(|| {
+                McpError::InvalidStatement(format!("Missing named param :{}", name))
+            })?;
+            sql.push_str(&statement[last..token.start]);
+            sql.push('?');
+            args.push(value.clone());
+            last = token.end;
+        }
+        sql.push_str(&statement[last..]);
+
+        let unused: Vec<&str> = self
+            .named
+            .keys()
+            .filter(|name| !named_tokens.iter().any(|t| &t.text[1..] == name.as_str()))
+            .map(String::as_str)
+            .collect();
+        if !unused.is_empty() {
+            return Err(McpError::InvalidStatement(format!(
+                "named_params not used by the statement: {}",
+                unused.join(", ")
+            )));
+        }
+
+        Ok(BoundStatement { sql, args })
+    }
+}
+
+/// A statement with `?` placeholders and the values to bind to them
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundStatement {
+    pub sql: String,
+    pub args: Vec<SqlParam>,
+}
+
+impl BoundStatement {
+    /// Build a sqlx query with all values bound
+    pub fn query(&self) -> Query<'_, MySql, MySqlArguments> {
+        self.args
+            .iter()
+            .fold(sqlx::query(&self.sql), |query, arg| match arg {
+                SqlParam::Null => query.bind(None::<String>),
+                SqlParam::Bool(v) => query.bind(*v),
+                SqlParam::Int(v) => query.bind(*v),
+                SqlParam::UInt(v) => query.bind(*v),
+                SqlParam::Float(v) => query.bind(*v),
+                SqlParam::String(v) | SqlParam::Decimal(v) => query.bind(v.as_str()),
+                SqlParam::DateTime(v) => query.bind(*v),
+                SqlParam::Date(v) => query.bind(*v),
+                SqlParam::Binary(v) => query.bind(v.as_slice()),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_scalar_values() {
+        assert_eq!(SqlParam::from_json(&json!(null)), Ok(SqlParam::Null));
+        assert_eq!(SqlParam::from_json(&json!(true)), Ok(SqlParam::Bool(true)));
+        assert_eq!(SqlParam::from_json(&json!(-5)), Ok(SqlParam::Int(-5)));
+        assert_eq!(
+            SqlParam::from_json(&json!(u64::MAX)),
+            Ok(SqlParam::UInt(u64::MAX))
+        );
+        assert_eq!(SqlParam::from_json(&json!(1.5)), Ok(SqlParam::Float(1.5)));
+        assert_eq!(
+            SqlParam::from_json(&json!("O'Reilly")),
+            Ok(SqlParam::String("O'Reilly".to_string()))
+        );
+        assert!(SqlParam::from_json(&json!([1, 2])).is_err());
+    }
+
+    #[test]
+    fn test_tagged_values() {
+        assert_eq!(
+            SqlParam::from_json(&json!({"type": "decimal", "value": "12345678901234567890.0001"})),
+            Ok(SqlParam::Decimal("12345678901234567890.0001".to_string()))
+        );
+        assert!(SqlParam::from_json(&json!({"type": "decimal", "value": "1; DROP"})).is_err());
+
+        let expected = NaiveDate::from_ymd_opt(2024, 5, 1)
+            .unwrap()
+            .and_hms_micro_opt(13, 45, 0, 250_000)
+            .unwrap();
+        assert_eq!(
+            SqlParam::from_json(&json!({"type": "datetime", "value": "2024-05-01 13:45:00.25"})),
+            Ok(SqlParam::DateTime(expected))
+        );
+        assert_eq!(
+            SqlParam::from_json(
+                &json!({"type": "datetime", "value": "2024-05-01T15:45:00.25+02:00"})
+            ),
+            Ok(SqlParam::DateTime(expected))
+        );
+        assert_eq!(
+            SqlParam::from_json(&json!({"type": "datetime", "value": "2024-05-01"})),
+            Ok(SqlParam::Date(NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()))
+        );
+        assert!(SqlParam::from_json(&json!({"type": "datetime", "value": "yesterday"})).is_err());
+
+        assert_eq!(
+            SqlParam::from_json(&json!({"type": "binary", "value": "3q2+7w=="})),
+            Ok(SqlParam::Binary(vec![0xde, 0xad, 0xbe, 0xef]))
+        );
+        assert!(SqlParam::from_json(&json!({"type": "binary", "value": "not base64!"})).is_err());
+
+        assert!(SqlParam::from_json(&json!({"type": "uuid", "value": "x"})).is_err());
+        assert!(SqlParam::from_json(&json!({"value": "x"})).is_err());
+    }
+
+    #[test]
+    fn test_from_json_reports_argument_position() {
+        let result =
+            StatementParams::from_json(Some(&json!([1, {"type": "decimal", "value": "x"}])), None);
+        match result {
+            Err(McpError::InvalidStatement(message)) => assert!(message.starts_with("params[1]")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert!(StatementParams::from_json(Some(&json!({"a": 1})), None).is_err());
+        assert!(StatementParams::from_json(None, Some(&json!([1]))).is_err());
+    }
+
+    #[test]
+    fn test_bind_positional() {
+        let params = StatementParams::from_json(Some(&json!([1, "a"])), None).unwrap();
+
+        let bound = params
+            .bind("SELECT * FROM t WHERE id = ? AND name = ? AND note = '?'")
+            .unwrap();
+        assert_eq!(
+            bound.args,
+            vec![SqlParam::Int(1), SqlParam::String("a".to_string())]
+        );
+
+        assert!(matches!(
+            params.bind("SELECT * FROM t WHERE id = ?"),
+            Err(McpError::InvalidStatement(_))
+        ));
+        assert!(matches!(
+            StatementParams::default().bind("SELECT ?"),
+            Err(McpError::InvalidStatement(_))
+        ));
+        assert!(StatementParams::default().bind("SELECT 1").is_ok());
+    }
+
+    #[test]
+    fn test_bind_named() {
+        let params =
+            StatementParams::from_json(None, Some(&json!({"id": 7, ":name": "x"}))).unwrap();
+
+        let bound = params
+            .bind("SELECT * FROM t WHERE id = :id OR parent = :id OR name = :name -- :ignored")
+            .unwrap();
+        assert_eq!(
+            bound.sql,
+            "SELECT * FROM t WHERE id = ? OR parent = ? OR name = ? -- :ignored"
+        );
+        assert_eq!(
+            bound.args,
+            vec![
+                SqlParam::Int(7),
+                SqlParam::Int(7),
+                SqlParam::String("x".to_string())
+            ]
+        );
+
+        // Missing and unused names
+        assert!(params.bind("SELECT :id, :other").is_err());
+        assert!(params.bind("SELECT :id").is_err());
+        // Mixed placeholder styles
+        assert!(params.bind("SELECT :id, :name, ?").is_err());
+    }
+
+    #[test]
+    fn test_bind_rejects_both_argument_styles() {
+        let params =
+            StatementParams::from_json(Some(&json!([1])), Some(&json!({"id": 1}))).unwrap();
+        assert!(params.bind("SELECT ?").is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use crate::error::{McpError, Result};
+use crate::format::{self, ResultFormat};
 use crate::manager::DataSourceManager;
-use crate::pool::ConnectionPoolManager;
+use crate::pool::{get_or_create_pool_manager, ConnectionPoolManager};
 use crate::tools::{DatabaseInfo, TableInfo, TableSchema};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -33,34 +34,71 @@ impl ResourceProvider {
     /// - mysql://{key}/{db}/tables - List tables in a database
     /// - mysql://{key}/{db}/tables/{table} - Get table schema
     /// - mysql://{key}/{db}/schema - Get complete database schema
+    ///
+    /// All but the database schema accept a `?format=` query, e.g.
+    /// `mysql://{key}/{db}/tables?format=csv`, which encodes the listed items
+    /// (the columns, for a table schema) instead of the JSON document.
     pub async fn get_resource(&self, uri: &str) -> Result<ResourceContent> {
         tracing::info!(uri = %uri, "Getting resource");
 
         // Parse the URI
+        let (uri, format) = split_format(uri)?;
         let parsed = self.parse_uri(uri)?;
 
+        // Every resource except the data source list reads metadata from MySQL
+        // and runs under the data source's query timeout
         match parsed {
-            ParsedUri::Datasources => self.get_datasources_resource().await,
+            ParsedUri::Datasources => self.get_datasources_resource(format).await,
             ParsedUri::Databases { datasource_key } => {
-                self.get_databases_resource(&datasource_key).await
+                self.manager
+                    .run_with_timeout(
+                        &datasource_key,
+                        None,
+                        self.get_databases_resource(&datasource_key, format),
+                    )
+                    .await
             }
             ParsedUri::Tables {
                 datasource_key,
                 database,
-            } => self.get_tables_resource(&datasource_key, &database).await,
+            } => {
+                self.manager
+                    .run_with_timeout(
+                        &datasource_key,
+                        None,
+                        self.get_tables_resource(&datasource_key, &database, format),
+                    )
+                    .await
+            }
             ParsedUri::TableSchema {
                 datasource_key,
                 database,
                 table,
             } => {
-                self.get_table_schema_resource(&datasource_key, &database, &table)
+                self.manager
+                    .run_with_timeout(
+                        &datasource_key,
+                        None,
+                        self.get_table_schema_resource(&datasource_key, &database, &table, format),
+                    )
                     .await
             }
             ParsedUri::DatabaseSchema {
                 datasource_key,
                 database,
             } => {
-                self.get_database_schema_resource(&datasource_key, &database)
+                if format != ResultFormat::Json {
+                    return Err(McpError::InvalidResourceUri(format!(
+                        "The database schema is only available as json, not {}",
+                        format
+                    )));
+                }
+                self.manager
+                    .run_with_timeout(
+                        &datasource_key,
+                        None,
+                        self.get_database_schema_resource(&datasource_key, &database),
+                    )
                     .await
             }
         }
@@ -121,23 +159,26 @@ impl ResourceProvider {
     }
 
     /// Get datasources resource
-    async fn get_datasources_resource(&self) -> Result<ResourceContent> {
+    async fn get_datasources_resource(&self, format: ResultFormat) -> Result<ResourceContent> {
         let datasources = self.manager.list_sources().await;
 
-        let content = serde_json::to_string_pretty(&serde_json::json!({
-            "datasources": datasources
-        }))
-        .map_err(|e| McpError::QueryExecutionError(format!("Failed to serialize: {}", e)))?;
-
-        Ok(ResourceContent {
-            uri: "mysql://datasources".to_string(),
-            mime_type: "application/json".to_string(),
-            content,
-        })
+        list_content(
+            "mysql://datasources".to_string(),
+            format,
+            serde_json::json!({
+                "datasources": datasources
+            }),
+            &["key", "name", "status"],
+            &datasources,
+        )
     }
 
     /// Get databases resource for a data source
-    async fn get_databases_resource(&self, datasource_key: &str) -> Result<ResourceContent> {
+    async fn get_databases_resource(
+        &self,
+        datasource_key: &str,
+        format: ResultFormat,
+    ) -> Result<ResourceContent> {
         // Validate data source key
         self.manager.validate_key(datasource_key)?;
 
@@ -150,18 +191,9 @@ impl ResourceProvider {
         }
 
         // Get or create pool manager
-        let mut pool_managers = self.pool_managers.write().await;
-        if !pool_managers.contains_key(datasource_key) {
-            let config = self
-                .manager
-                .get_source(datasource_key)
-                .ok_or_else(|| McpError::InvalidDataSourceKey(datasource_key.to_string()))?;
-            let pool_manager = ConnectionPoolManager::new((*config).clone()).await?;
-            pool_managers.insert(datasource_key.to_string(), pool_manager);
-        }
-
-        let pool_manager = pool_managers.get_mut(datasource_key).unwrap();
-        let pool = pool_manager.get_pool("information_schema").await?;
+        let pool_manager =
+            get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key).await?;
+        let pool = &pool_manager.get_metadata_pool().await?;
 
         // Query databases
         let query = "SELECT 
@@ -175,13 +207,19 @@ impl ResourceProvider {
         let rows: Vec<sqlx::mysql::MySqlRow> = sqlx::query(query)
             .fetch_all(pool)
             .await
-            .map_err(|e| McpError::QueryExecutionError(e.to_string()))?;
+            .map_err(McpError::from)?;
 
         let mut databases: Vec<DatabaseInfo> = Vec::new();
 
         for row in rows {
             use sqlx::Row;
             let name: String = row.try_get("name").unwrap_or_default();
+
+            // Hide databases outside the allowlist
+            if !self.manager.is_database_allowed(datasource_key, &name) {
+                continue;
+            }
+
             let charset: String = row.try_get("charset").unwrap_or_default();
             let collation: String = row.try_get("collation").unwrap_or_default();
 
@@ -209,17 +247,16 @@ impl ResourceProvider {
             });
         }
 
-        let content = serde_json::to_string_pretty(&serde_json::json!({
-            "datasource_key": datasource_key,
-            "databases": databases
-        }))
-        .map_err(|e| McpError::QueryExecutionError(format!("Failed to serialize: {}", e)))?;
-
-        Ok(ResourceContent {
-            uri: format!("mysql://{}/databases", datasource_key),
-            mime_type: "application/json".to_string(),
-            content,
-        })
+        list_content(
+            format!("mysql://{}/databases", datasource_key),
+            format,
+            serde_json::json!({
+                "datasource_key": datasource_key,
+                "databases": databases
+            }),
+            &["name", "size_bytes", "charset", "collation"],
+            &databases,
+        )
     }
 
     /// Get tables resource for a database
@@ -227,10 +264,14 @@ impl ResourceProvider {
         &self,
         datasource_key: &str,
         database: &str,
+        format: ResultFormat,
     ) -> Result<ResourceContent> {
         // Validate data source key
         self.manager.validate_key(datasource_key)?;
 
+        // Check the database allowlist
+        self.manager.check_database_access(datasource_key, database)?;
+
         // Check if data source is available
         if !self.manager.is_available(datasource_key).await {
             return Err(McpError::DataSourceUnavailable(format!(
@@ -240,18 +281,9 @@ impl ResourceProvider {
         }
 
         // Get or create pool manager
-        let mut pool_managers = self.pool_managers.write().await;
-        if !pool_managers.contains_key(datasource_key) {
-            let config = self
-                .manager
-                .get_source(datasource_key)
-                .ok_or_else(|| McpError::InvalidDataSourceKey(datasource_key.to_string()))?;
-            let pool_manager = ConnectionPoolManager::new((*config).clone()).await?;
-            pool_managers.insert(datasource_key.to_string(), pool_manager);
-        }
-
-        let pool_manager = pool_managers.get_mut(datasource_key).unwrap();
-        let pool = pool_manager.get_pool(database).await?;
+        let pool_manager =
+            get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key).await?;
+        let pool = &pool_manager.get_pool(database).await?;
 
         // Query tables
         let query = format!(
@@ -269,14 +301,7 @@ impl ResourceProvider {
         let rows: Vec<sqlx::mysql::MySqlRow> = sqlx::query(&query)
             .fetch_all(pool)
             .await
-            .map_err(|e| {
-                let error_msg = e.to_string();
-                if error_msg.contains("Unknown database") {
-                    McpError::DatabaseNotFound(database.to_string())
-                } else {
-                    McpError::QueryExecutionError(error_msg)
-                }
-            })?;
+            .map_err(McpError::from)?;
 
         let tables: Vec<TableInfo> = rows
             .iter()
@@ -291,18 +316,17 @@ impl ResourceProvider {
             })
             .collect();
 
-        let content = serde_json::to_string_pretty(&serde_json::json!({
-            "datasource_key": datasource_key,
-            "database": database,
-            "tables": tables
-        }))
-        .map_err(|e| McpError::QueryExecutionError(format!("Failed to serialize: {}", e)))?;
-
-        Ok(ResourceContent {
-            uri: format!("mysql://{}/{}/tables", datasource_key, database),
-            mime_type: "application/json".to_string(),
-            content,
-        })
+        list_content(
+            format!("mysql://{}/{}/tables", datasource_key, database),
+            format,
+            serde_json::json!({
+                "datasource_key": datasource_key,
+                "database": database,
+                "tables": tables
+            }),
+            &["name", "row_count", "size_bytes", "engine"],
+            &tables,
+        )
     }
 
     /// Get table schema resource
@@ -311,10 +335,14 @@ impl ResourceProvider {
         datasource_key: &str,
         database: &str,
         table: &str,
+        format: ResultFormat,
     ) -> Result<ResourceContent> {
         // Validate data source key
         self.manager.validate_key(datasource_key)?;
 
+        // Check the database allowlist
+        self.manager.check_database_access(datasource_key, database)?;
+
         // Check if data source is available
         if !self.manager.is_available(datasource_key).await {
             return Err(McpError::DataSourceUnavailable(format!(
@@ -324,18 +352,9 @@ impl ResourceProvider {
         }
 
         // Get or create pool manager
-        let mut pool_managers = self.pool_managers.write().await;
-        if !pool_managers.contains_key(datasource_key) {
-            let config = self
-                .manager
-                .get_source(datasource_key)
-                .ok_or_else(|| McpError::InvalidDataSourceKey(datasource_key.to_string()))?;
-            let pool_manager = ConnectionPoolManager::new((*config).clone()).await?;
-            pool_managers.insert(datasource_key.to_string(), pool_manager);
-        }
-
-        let pool_manager = pool_managers.get_mut(datasource_key).unwrap();
-        let pool = pool_manager.get_pool(database).await?;
+        let pool_manager =
+            get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key).await?;
+        let pool = &pool_manager.get_pool(database).await?;
 
         // Check if table exists
         let table_exists_query = format!(
@@ -348,7 +367,7 @@ impl ResourceProvider {
         let exists_row: (i64,) = sqlx::query_as(&table_exists_query)
             .fetch_one(pool)
             .await
-            .map_err(|e| McpError::QueryExecutionError(e.to_string()))?;
+            .map_err(McpError::from)?;
 
         if exists_row.0 == 0 {
             return Err(McpError::TableNotFound(format!(
@@ -371,14 +390,16 @@ impl ResourceProvider {
             indexes,
         };
 
-        let content = serde_json::to_string_pretty(&schema)
+        let json = serde_json::to_value(&schema)
             .map_err(|e| McpError::QueryExecutionError(format!("Failed to serialize: {}", e)))?;
 
-        Ok(ResourceContent {
-            uri: format!("mysql://{}/{}/tables/{}", datasource_key, database, table),
-            mime_type: "application/json".to_string(),
-            content,
-        })
+        list_content(
+            format!("mysql://{}/{}/tables/{}", datasource_key, database, table),
+            format,
+            json,
+            &["name", "data_type", "nullable", "default_value", "comment", "extra"],
+            &schema.columns,
+        )
     }
 
     /// Get complete database schema resource
@@ -390,6 +411,9 @@ impl ResourceProvider {
         // Validate data source key
         self.manager.validate_key(datasource_key)?;
 
+        // Check the database allowlist
+        self.manager.check_database_access(datasource_key, database)?;
+
         // Check if data source is available
         if !self.manager.is_available(datasource_key).await {
             return Err(McpError::DataSourceUnavailable(format!(
@@ -399,18 +423,9 @@ impl ResourceProvider {
         }
 
         // Get or create pool manager
-        let mut pool_managers = self.pool_managers.write().await;
-        if !pool_managers.contains_key(datasource_key) {
-            let config = self
-                .manager
-                .get_source(datasource_key)
-                .ok_or_else(|| McpError::InvalidDataSourceKey(datasource_key.to_string()))?;
-            let pool_manager = ConnectionPoolManager::new((*config).clone()).await?;
-            pool_managers.insert(datasource_key.to_string(), pool_manager);
-        }
-
-        let pool_manager = pool_managers.get_mut(datasource_key).unwrap();
-        let pool = pool_manager.get_pool(database).await?;
+        let pool_manager =
+            get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key).await?;
+        let pool = &pool_manager.get_pool(database).await?;
 
         // Get all tables
         let query = format!(
@@ -424,14 +439,7 @@ impl ResourceProvider {
         let rows: Vec<sqlx::mysql::MySqlRow> = sqlx::query(&query)
             .fetch_all(pool)
             .await
-            .map_err(|e| {
-                let error_msg = e.to_string();
-                if error_msg.contains("Unknown database") {
-                    McpError::DatabaseNotFound(database.to_string())
-                } else {
-                    McpError::QueryExecutionError(error_msg)
-                }
-            })?;
+            .map_err(McpError::from)?;
 
         // Get schema for each table
         let mut schemas = Vec::new();
@@ -543,6 +551,63 @@ pub struct ResourceTemplate {
     pub mime_type: String,
 }
 
+/// Split a `?format=` query off a resource URI
+fn split_format(uri: &str) -> Result<(&str, ResultFormat)> {
+    let Some((path, query)) = uri.split_once('?') else {
+        return Ok((uri, ResultFormat::default()));
+    };
+
+    let mut format = ResultFormat::default();
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        match pair.split_once('=') {
+            Some(("format", name)) => {
+                format = name.parse().map_err(|_| {
+                    McpError::InvalidResourceUri(format!(
+                        "Unknown format '{}' in URI: {}",
+                        name, uri
+                    ))
+                })?
+            }
+            _ => {
+                return Err(McpError::InvalidResourceUri(format!(
+                    "Unsupported query '{}' in URI; only format is accepted: {}",
+                    pair, uri
+                )))
+            }
+        }
+    }
+    Ok((path, format))
+}
+
+/// Content of a resource that lists `records`
+///
+/// As `json` it is the whole `document`; other formats encode just the records,
+/// with `fields` as the columns.
+fn list_content<T: Serialize>(
+    uri: String,
+    format: ResultFormat,
+    document: serde_json::Value,
+    fields: &[&str],
+    records: &[T],
+) -> Result<ResourceContent> {
+    let Some(formatter) = format.formatter() else {
+        let content = serde_json::to_string_pretty(&document)
+            .map_err(|e| McpError::QueryExecutionError(format!("Failed to serialize: {}", e)))?;
+        return Ok(ResourceContent {
+            uri,
+            mime_type: "application/json".to_string(),
+            content,
+        });
+    };
+
+    let (columns, rows) = format::tabulate(fields, records);
+    Ok(ResourceContent {
+        uri: format!("{}?format={}", uri, format),
+        mime_type: formatter.mime_type().to_string(),
+        content: formatter.format(&columns, &rows, true),
+    })
+}
+
 // Helper functions for getting schema information
 
 /// Get column information for a table
@@ -557,7 +622,8 @@ async fn get_columns(
             COLUMN_TYPE as data_type,
             IS_NULLABLE as nullable,
             COLUMN_DEFAULT as default_value,
-            COLUMN_COMMENT as comment
+            COLUMN_COMMENT as comment,
+            EXTRA as extra
          FROM information_schema.COLUMNS
          WHERE TABLE_SCHEMA = '{}' AND TABLE_NAME = '{}'
          ORDER BY ORDINAL_POSITION",
@@ -568,7 +634,7 @@ async fn get_columns(
     let rows: Vec<sqlx::mysql::MySqlRow> = sqlx::query(&query)
         .fetch_all(pool)
         .await
-        .map_err(|e| McpError::QueryExecutionError(e.to_string()))?;
+        .map_err(McpError::from)?;
 
     let columns: Vec<crate::tools::ColumnSchema> = rows
         .iter()
@@ -583,6 +649,10 @@ async fn get_columns(
                     .unwrap_or(false),
                 default_value: row.try_get("default_value").ok(),
                 comment: row.try_get("comment").ok(),
+                extra: row
+                    .try_get::<String, _>("extra")
+                    .ok()
+                    .filter(|extra| !extra.is_empty()),
             }
         })
         .collect();
@@ -610,7 +680,7 @@ async fn get_primary_key(
     let rows: Vec<sqlx::mysql::MySqlRow> = sqlx::query(&query)
         .fetch_all(pool)
         .await
-        .map_err(|e| McpError::QueryExecutionError(e.to_string()))?;
+        .map_err(McpError::from)?;
 
     if rows.is_empty() {
         Ok(None)
@@ -648,7 +718,7 @@ async fn get_foreign_keys(
     let rows: Vec<sqlx::mysql::MySqlRow> = sqlx::query(&query)
         .fetch_all(pool)
         .await
-        .map_err(|e| McpError::QueryExecutionError(e.to_string()))?;
+        .map_err(McpError::from)?;
 
     // Group by constraint name
     let mut fk_map: HashMap<String, crate::tools::ForeignKey> = HashMap::new();
@@ -705,7 +775,7 @@ async fn get_indexes(
     let rows: Vec<sqlx::mysql::MySqlRow> = sqlx::query(&query)
         .fetch_all(pool)
         .await
-        .map_err(|e| McpError::QueryExecutionError(e.to_string()))?;
+        .map_err(McpError::from)?;
 
     // Group by index name
     let mut index_map: HashMap<String, crate::tools::Index> = HashMap::new();
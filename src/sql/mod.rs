@@ -0,0 +1,1768 @@
+//! Lightweight MySQL tokenizer and statement classifier
+//!
+//! The tokenizer understands string literals, quoted identifiers, user and
+//! system variables, `?` / `:name` placeholders and all three comment styles
+//! (`#`, `-- ` and `/* */`).
+//! MySQL executable comments (`/*! ... */`) are tokenized as regular SQL,
+//! because the server executes their contents.
+
+use std::fmt;
+
+/// Kind of a lexical token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Keyword or unquoted identifier
+    Word,
+    /// Backtick-quoted identifier
+    QuotedIdent,
+    /// Single- or double-quoted string literal
+    String,
+    /// Numeric literal
+    Number,
+    /// Positional parameter placeholder (`?`)
+    Placeholder,
+    /// Named parameter placeholder (`:name`)
+    NamedPlaceholder,
+    /// User (`@var`) or system (`@@var`) variable
+    Variable,
+    /// Assignment operator (`:=`)
+    Assign,
+    /// Any other single punctuation or operator character
+    Punct(char),
+}
+
+/// A token with its position in the source text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Token<'_> {
+    /// Check if this token is the given keyword (case-insensitive)
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Word && self.text.eq_ignore_ascii_case(keyword)
+    }
+
+    /// Check if this token is the given punctuation character
+    pub fn is_punct(&self, ch: char) -> bool {
+        self.kind == TokenKind::Punct(ch)
+    }
+
+    /// Get the identifier name for words and quoted identifiers
+    pub fn identifier(&self) -> Option<String> {
+        match self.kind {
+            TokenKind::Word => Some(self.text.to_string()),
+            TokenKind::QuotedIdent => {
+                let inner = &self.text[1..self.text.len().saturating_sub(1).max(1)];
+                Some(inner.replace("``", "`"))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Split SQL text into tokens, skipping whitespace and comments
+pub fn tokenize(sql: &str) -> Vec<Token<'_>> {
+    let bytes = sql.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    let mut in_executable_comment = false;
+
+    while i < bytes.len() {
+        let b = bytes[i];
+
+        if b.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        // End of an executable comment: resume normal tokenizing
+        if in_executable_comment && b == b'*' && bytes.get(i + 1) == Some(&b'/') {
+            in_executable_comment = false;
+            i += 2;
+            continue;
+        }
+
+        // Comments
+        if b == b'#' || (b == b'-' && bytes.get(i + 1) == Some(&b'-') && is_comment_dash(bytes, i + 2)) {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+            continue;
+        }
+
+        if b == b'/' && bytes.get(i + 1) == Some(&b'*') {
+            if bytes.get(i + 2) == Some(&b'!') {
+                // Executable comment, optionally versioned: /*!80000 ... */
+                in_executable_comment = true;
+                i += 3;
+                while i < bytes.len() && bytes[i].is_ascii_digit() {
+                    i += 1;
+                }
+                continue;
+            }
+
+            // Regular comment or optimizer hint
+            i += 2;
+            while i < bytes.len() && !(bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/')) {
+                i += 1;
+            }
+            i = (i + 2).min(bytes.len());
+            continue;
+        }
+
+        let start = i;
+        let kind = match b {
+            b'\'' | b'"' => {
+                i = skip_quoted(bytes, i, b, true);
+                TokenKind::String
+            }
+            b'`' => {
+                i = skip_quoted(bytes, i, b'`', false);
+                TokenKind::QuotedIdent
+            }
+            b'@' => {
+                i += 1;
+                if bytes.get(i) == Some(&b'@') {
+                    i += 1;
+                }
+                match bytes.get(i) {
+                    Some(&q @ (b'\'' | b'"' | b'`')) => i = skip_quoted(bytes, i, q, q != b'`'),
+                    _ => {
+                        while i < bytes.len() && (is_word_byte(bytes[i]) || bytes[i] == b'.') {
+                            i += 1;
+                        }
+                    }
+                }
+                TokenKind::Variable
+            }
+            b'?' => {
+                i += 1;
+                TokenKind::Placeholder
+            }
+            b':' if bytes.get(i + 1) == Some(&b'=') => {
+                i += 2;
+                TokenKind::Assign
+            }
+            b':' if bytes.get(i + 1).is_some_and(|b| b.is_ascii_alphabetic() || *b == b'_') => {
+                i += 1;
+                while i < bytes.len() && is_word_byte(bytes[i]) {
+                    i += 1;
+                }
+                TokenKind::NamedPlaceholder
+            }
+            _ if is_word_byte(b) => match b.is_ascii_digit().then(|| number_end(bytes, i)).flatten() {
+                Some(end) => {
+                    i = end;
+                    TokenKind::Number
+                }
+                // Identifiers may start with a digit (`2024_archive`)
+                None => {
+                    while i < bytes.len() && is_word_byte(bytes[i]) {
+                        i += 1;
+                    }
+                    TokenKind::Word
+                }
+            },
+            _ => {
+                // Advance by a whole UTF-8 character
+                let ch = sql[i..].chars().next().unwrap_or('\0');
+                i += ch.len_utf8().max(1);
+                TokenKind::Punct(ch)
+            }
+        };
+
+        tokens.push(Token {
+            kind,
+            text: &sql[start..i],
+            start,
+            end: i,
+        });
+    }
+
+    tokens
+}
+
+/// `--` only starts a comment when followed by whitespace or end of input
+fn is_comment_dash(bytes: &[u8], index: usize) -> bool {
+    bytes.get(index).map(|b| b.is_ascii_whitespace()).unwrap_or(true)
+}
+
+/// End of the numeric literal starting at `start`: decimal, hexadecimal
+/// (`0x1F`) or binary (`0b101`), with an optional fraction and exponent
+///
+/// `None` if the word there continues past the literal, which makes it an
+/// identifier such as `2024_archive`.
+fn number_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let skip = |mut i: usize, accept: fn(&u8) -> bool| {
+        while bytes.get(i).is_some_and(accept) {
+            i += 1;
+        }
+        i
+    };
+
+    let end = match (bytes[start], bytes.get(start + 1)) {
+        (b'0', Some(b'x' | b'X')) => skip(start + 2, u8::is_ascii_hexdigit),
+        (b'0', Some(b'b' | b'B')) => skip(start + 2, |b| matches!(b, b'0' | b'1')),
+        _ => {
+            let mut i = skip(start, u8::is_ascii_digit);
+            if bytes.get(i) == Some(&b'.') && bytes.get(i + 1).is_some_and(u8::is_ascii_digit) {
+                i = skip(i + 1, u8::is_ascii_digit);
+            }
+            if matches!(bytes.get(i), Some(b'e' | b'E')) {
+                let sign = usize::from(matches!(bytes.get(i + 1), Some(b'+' | b'-')));
+                let exponent_end = skip(i + 1 + sign, u8::is_ascii_digit);
+                if exponent_end > i + 1 + sign {
+                    i = exponent_end;
+                }
+            }
+            i
+        }
+    };
+
+    (!bytes.get(end).is_some_and(|b| is_word_byte(*b))).then_some(end)
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$' || b >= 0x80
+}
+
+/// Skip a quoted literal starting at `start`, returning the index after the closing quote
+fn skip_quoted(bytes: &[u8], start: usize, quote: u8, backslash_escapes: bool) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if backslash_escapes && b == b'\\' {
+            i += 2;
+            continue;
+        }
+        if b == quote {
+            // A doubled quote is an escaped quote
+            if bytes.get(i + 1) == Some(&quote) {
+                i += 2;
+                continue;
+            }
+            return i + 1;
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+/// Split SQL text into individual statements on top-level semicolons
+/// Empty statements are skipped
+pub fn split_statements(sql: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let mut start = 0;
+
+    for token in tokenize(sql) {
+        if token.is_punct(';') {
+            let statement = sql[start..token.start].trim();
+            if !statement.is_empty() {
+                statements.push(statement);
+            }
+            start = token.end;
+        }
+    }
+
+    let statement = sql[start..].trim();
+    if !statement.is_empty() {
+        statements.push(statement);
+    }
+
+    statements
+}
+
+/// Extract the first SQL statement from a query string
+/// Returns the trimmed input if it contains no statement separator
+pub fn first_statement(sql: &str) -> &str {
+    split_statements(sql).into_iter().next().unwrap_or("")
+}
+
+/// Classification of a single SQL statement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementKind {
+    /// Provably read-only: SELECT, SHOW, DESCRIBE, EXPLAIN, TABLE, VALUES
+    ReadOnly,
+    /// Data modification: INSERT, UPDATE, DELETE, REPLACE, LOAD DATA, CALL, DO
+    Dml,
+    /// Schema change: CREATE, ALTER, DROP, TRUNCATE, RENAME
+    Ddl,
+    /// Connection-scoped state: SET, LOCK TABLES, HANDLER, transaction control,
+    /// locking reads and variable assignments
+    Session,
+    /// Server administration: GRANT, KILL, FLUSH, SET GLOBAL, SELECT ... INTO OUTFILE
+    Admin,
+    /// Anything the classifier does not recognize
+    Unknown,
+}
+
+impl StatementKind {
+    /// Check if statements of this kind are guaranteed not to modify state
+    pub fn is_read_only(&self) -> bool {
+        matches!(self, StatementKind::ReadOnly)
+    }
+
+    /// Check if statements of this kind need DDL permission
+    pub fn requires_ddl(&self) -> bool {
+        matches!(
+            self,
+            StatementKind::Ddl | StatementKind::Admin | StatementKind::Unknown
+        )
+    }
+}
+
+impl fmt::Display for StatementKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            StatementKind::ReadOnly => "read-only statement",
+            StatementKind::Dml => "data modification statement",
+            StatementKind::Ddl => "schema change (DDL) statement",
+            StatementKind::Session => "session or locking statement",
+            StatementKind::Admin => "administrative statement",
+            StatementKind::Unknown => "unrecognized statement",
+        };
+        f.write_str(description)
+    }
+}
+
+/// Built-in functions with side effects that rule out a read-only classification
+const SESSION_FUNCTIONS: &[&str] = &["GET_LOCK", "RELEASE_LOCK", "RELEASE_ALL_LOCKS"];
+const ADMIN_FUNCTIONS: &[&str] = &["LOAD_FILE"];
+
+/// Built-in functions without side effects
+///
+/// Any other function called by a query may be a stored function, which can
+/// modify data, so the query is not classified as read-only. Spatial functions
+/// are recognized by their `ST_` and `MBR` prefixes.
+const BUILTIN_FUNCTIONS: &[&str] = &[
+    // Aggregate and window functions
+    "ANY_VALUE", "AVG", "BIT_AND", "BIT_OR", "BIT_XOR", "COUNT", "CUME_DIST", "DENSE_RANK",
+    "FIRST_VALUE", "GROUP_CONCAT", "GROUPING", "JSON_ARRAYAGG", "JSON_OBJECTAGG", "LAG",
+    "LAST_VALUE", "LEAD", "MAX", "MIN", "NTH_VALUE", "NTILE", "PERCENT_RANK", "RANK",
+    "ROW_NUMBER", "STD", "STDDEV", "STDDEV_POP", "STDDEV_SAMP", "SUM", "VAR_POP", "VAR_SAMP",
+    "VARIANCE",
+    // Numeric
+    "ABS", "ACOS", "ASIN", "ATAN", "ATAN2", "BIT_COUNT", "CEIL", "CEILING", "CONV", "COS", "COT",
+    "CRC32", "DEGREES", "EXP", "FLOOR", "LN", "LOG", "LOG10", "LOG2", "MOD", "PI", "POW",
+    "POWER", "RADIANS", "RAND", "ROUND", "SIGN", "SIN", "SQRT", "TAN", "TRUNCATE",
+    // String
+    "ASCII", "BIN", "BIT_LENGTH", "CHAR", "CHAR_LENGTH", "CHARACTER_LENGTH", "CONCAT",
+    "CONCAT_WS", "ELT", "EXPORT_SET", "FIELD", "FIND_IN_SET", "FORMAT", "FROM_BASE64", "HEX",
+    "INSERT", "INSTR", "LCASE", "LEFT", "LENGTH", "LOCATE", "LOWER", "LPAD", "LTRIM",
+    "MAKE_SET", "MID", "OCT", "OCTET_LENGTH", "ORD", "POSITION", "QUOTE", "REGEXP_INSTR",
+    "REGEXP_LIKE", "REGEXP_REPLACE", "REGEXP_SUBSTR", "REPEAT", "REPLACE", "REVERSE", "RIGHT",
+    "RPAD", "RTRIM", "SOUNDEX", "SPACE", "STRCMP", "SUBSTR", "SUBSTRING", "SUBSTRING_INDEX",
+    "TO_BASE64", "TRIM", "UCASE", "UNHEX", "UPPER", "WEIGHT_STRING",
+    // Date and time
+    "ADDDATE", "ADDTIME", "CONVERT_TZ", "CURDATE", "CURRENT_DATE", "CURRENT_TIME",
+    "CURRENT_TIMESTAMP", "CURTIME", "DATE", "DATE_ADD", "DATE_FORMAT", "DATE_SUB", "DATEDIFF",
+    "DAY", "DAYNAME", "DAYOFMONTH", "DAYOFWEEK", "DAYOFYEAR", "EXTRACT", "FROM_DAYS",
+    "FROM_UNIXTIME", "GET_FORMAT", "HOUR", "LAST_DAY", "LOCALTIME", "LOCALTIMESTAMP",
+    "MAKEDATE", "MAKETIME", "MICROSECOND", "MINUTE", "MONTH", "MONTHNAME", "NOW", "PERIOD_ADD",
+    "PERIOD_DIFF", "QUARTER", "SEC_TO_TIME", "SECOND", "STR_TO_DATE", "SUBDATE", "SUBTIME",
+    "SYSDATE", "TIME", "TIME_FORMAT", "TIME_TO_SEC", "TIMEDIFF", "TIMESTAMP", "TIMESTAMPADD",
+    "TIMESTAMPDIFF", "TO_DAYS", "TO_SECONDS", "UNIX_TIMESTAMP", "UTC_DATE", "UTC_TIME",
+    "UTC_TIMESTAMP", "WEEK", "WEEKDAY", "WEEKOFYEAR", "YEAR", "YEARWEEK",
+    // Flow control and casts
+    "CAST", "COALESCE", "CONVERT", "GREATEST", "IF", "IFNULL", "INTERVAL", "ISNULL", "LEAST",
+    "NULLIF",
+    // Information
+    "BENCHMARK", "CHARSET", "COERCIBILITY", "COLLATION", "CONNECTION_ID", "CURRENT_ROLE",
+    "CURRENT_USER", "DATABASE", "FOUND_ROWS", "ICU_VERSION", "LAST_INSERT_ID", "ROLES_GRAPHML",
+    "ROW_COUNT", "SCHEMA", "SESSION_USER", "SYSTEM_USER", "USER", "VERSION",
+    // Encryption and compression
+    "AES_DECRYPT", "AES_ENCRYPT", "COMPRESS", "MD5", "RANDOM_BYTES", "SHA", "SHA1", "SHA2",
+    "STATEMENT_DIGEST", "STATEMENT_DIGEST_TEXT", "UNCOMPRESS", "UNCOMPRESSED_LENGTH",
+    "VALIDATE_PASSWORD_STRENGTH",
+    // JSON
+    "JSON_ARRAY", "JSON_ARRAY_APPEND", "JSON_ARRAY_INSERT", "JSON_CONTAINS",
+    "JSON_CONTAINS_PATH", "JSON_DEPTH", "JSON_EXTRACT", "JSON_INSERT", "JSON_KEYS",
+    "JSON_LENGTH", "JSON_MERGE", "JSON_MERGE_PATCH", "JSON_MERGE_PRESERVE", "JSON_OBJECT",
+    "JSON_OVERLAPS", "JSON_PRETTY", "JSON_QUOTE", "JSON_REMOVE", "JSON_REPLACE",
+    "JSON_SCHEMA_VALID", "JSON_SCHEMA_VALIDATION_REPORT", "JSON_SEARCH", "JSON_SET",
+    "JSON_STORAGE_FREE", "JSON_STORAGE_SIZE", "JSON_TABLE", "JSON_TYPE", "JSON_UNQUOTE",
+    "JSON_VALID", "JSON_VALUE",
+    // Spatial constructors
+    "GEOMETRYCOLLECTION", "LINESTRING", "MULTILINESTRING", "MULTIPOINT", "MULTIPOLYGON",
+    "POINT", "POLYGON",
+    // Miscellaneous
+    "BIN_TO_UUID", "DEFAULT", "FORMAT_BYTES", "FORMAT_PICO_TIME", "GTID_SUBSET",
+    "GTID_SUBTRACT", "INET_ATON", "INET_NTOA", "INET6_ATON", "INET6_NTOA", "IS_FREE_LOCK",
+    "IS_IPV4", "IS_IPV4_COMPAT", "IS_IPV4_MAPPED", "IS_IPV6", "IS_USED_LOCK", "IS_UUID",
+    "NAME_CONST", "PS_CURRENT_THREAD_ID", "PS_THREAD_ID", "SLEEP", "UUID", "UUID_SHORT",
+    "UUID_TO_BIN", "VALUES",
+];
+
+/// Keywords and type names that can be followed by a parenthesis in a query
+/// without being a function call (`IN (...)`, `CAST(x AS DECIMAL(10, 2))`)
+const PARENTHESIZED_KEYWORDS: &[&str] = &[
+    "AGAINST", "ALL", "AND", "ANY", "ARRAY", "AS", "BETWEEN", "BINARY", "BY", "CASE", "COLUMNS",
+    "DATETIME", "DEC", "DECIMAL", "DISTINCT", "DIV", "DOUBLE", "ELSE", "EXCEPT", "EXISTS",
+    "FLOAT", "FOR", "FORCE", "FROM", "HAVING", "IGNORE", "IN", "INDEX", "INT", "INTEGER",
+    "INTERSECT", "IS", "JOIN", "KEY", "LATERAL", "LIKE", "MATCH", "MEMBER", "NCHAR", "NESTED",
+    "NOT", "NUMERIC", "OF", "ON", "OR", "OVER", "PARTITION", "REAL", "REGEXP", "RLIKE", "ROW",
+    "SELECT", "SIGNED", "SOME", "THEN", "UNION", "UNSIGNED", "USE", "USING", "VARCHAR", "WHEN",
+    "WHERE", "WITH", "XOR",
+];
+
+/// Classify the first statement in a SQL string
+pub fn classify(sql: &str) -> StatementKind {
+    let tokens: Vec<Token> = tokenize(sql)
+        .into_iter()
+        .take_while(|t| !t.is_punct(';'))
+        .collect();
+
+    classify_tokens(&tokens)
+}
+
+fn classify_tokens(tokens: &[Token]) -> StatementKind {
+    // Parenthesized queries: (SELECT ...) UNION (SELECT ...)
+    let body = match tokens.iter().position(|t| !t.is_punct('(')) {
+        Some(index) => &tokens[index..],
+        None => return StatementKind::Unknown,
+    };
+
+    let first = &body[0];
+    if first.kind != TokenKind::Word {
+        return StatementKind::Unknown;
+    }
+
+    let keyword = first.text.to_ascii_uppercase();
+    let next_is = |keyword: &str| body.get(1).is_some_and(|t| t.is_keyword(keyword));
+
+    match keyword.as_str() {
+        "SELECT" | "TABLE" | "VALUES" => classify_query(tokens),
+        "WITH" => classify_with(tokens, body),
+        "SHOW" | "HELP" => StatementKind::ReadOnly,
+        "EXPLAIN" | "DESCRIBE" | "DESC" => classify_explain(&body[1..]),
+
+        "INSERT" | "UPDATE" | "DELETE" | "REPLACE" | "CALL" | "DO" => StatementKind::Dml,
+        "LOAD" if next_is("INDEX") => StatementKind::Admin,
+        "LOAD" => StatementKind::Dml,
+
+        "CREATE" | "ALTER" | "DROP" | "TRUNCATE" | "RENAME" | "IMPORT" => StatementKind::Ddl,
+
+        "SET" => classify_set(&body[1..]),
+        "START" if next_is("TRANSACTION") => StatementKind::Session,
+        "LOCK" | "UNLOCK" | "HANDLER" | "USE" | "BEGIN" | "COMMIT" | "ROLLBACK"
+        | "SAVEPOINT" | "RELEASE" | "XA" | "PREPARE" | "EXECUTE" | "DEALLOCATE" => {
+            StatementKind::Session
+        }
+
+        "GRANT" | "REVOKE" | "FLUSH" | "RESET" | "KILL" | "SHUTDOWN" | "RESTART"
+        | "INSTALL" | "UNINSTALL" | "PURGE" | "CHANGE" | "START" | "STOP" | "ANALYZE"
+        | "OPTIMIZE" | "REPAIR" | "CHECK" | "CHECKSUM" | "CACHE" | "CLONE" | "BINLOG" => {
+            StatementKind::Admin
+        }
+
+        _ => StatementKind::Unknown,
+    }
+}
+
+/// Classify a SELECT / TABLE / VALUES statement, looking for clauses that
+/// write files, assign variables or take locks
+fn classify_query(tokens: &[Token]) -> StatementKind {
+    let mut kind = StatementKind::ReadOnly;
+
+    for (i, token) in tokens.iter().enumerate() {
+        let next = tokens.get(i + 1);
+        let next_is = |keyword: &str| next.is_some_and(|t| t.is_keyword(keyword));
+
+        let found = if token.kind == TokenKind::Assign {
+            StatementKind::Session
+        } else if token.is_keyword("INTO") {
+            if next_is("OUTFILE") || next_is("DUMPFILE") {
+                StatementKind::Admin
+            } else {
+                StatementKind::Session
+            }
+        } else if (token.is_keyword("FOR") && (next_is("UPDATE") || next_is("SHARE")))
+            || (token.is_keyword("LOCK") && next_is("IN"))
+        {
+            StatementKind::Session
+        } else if token.identifier().is_some() && next.is_some_and(|t| t.is_punct('(')) {
+            let name = token.text.to_ascii_uppercase();
+            if token.kind == TokenKind::Word && ADMIN_FUNCTIONS.contains(&name.as_str()) {
+                StatementKind::Admin
+            } else if token.kind == TokenKind::Word && SESSION_FUNCTIONS.contains(&name.as_str()) {
+                StatementKind::Session
+            } else if calls_stored_function(tokens, i) {
+                StatementKind::Unknown
+            } else {
+                continue;
+            }
+        } else {
+            continue;
+        };
+
+        // Administrative findings and stored function calls take precedence
+        // over session-level ones
+        if matches!(found, StatementKind::Admin | StatementKind::Unknown) {
+            return found;
+        }
+        kind = found;
+    }
+
+    kind
+}
+
+/// Check if the first statement is a query whose only side effect is taking row
+/// locks (`FOR UPDATE`, `FOR SHARE`, `LOCK IN SHARE MODE`)
+///
+/// Such a query classifies as [`StatementKind::Session`] since its locks outlive
+/// it; without the locking clauses it would be read-only.
+pub fn is_locking_read(sql: &str) -> bool {
+    let tokens: Vec<Token> = tokenize(sql)
+        .into_iter()
+        .take_while(|t| !t.is_punct(';'))
+        .collect();
+
+    let mut unlocked = Vec::with_capacity(tokens.len());
+    let mut locking = false;
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+        let next_is = |keyword: &str| tokens.get(i + 1).is_some_and(|t| t.is_keyword(keyword));
+        if (token.is_keyword("FOR") && (next_is("UPDATE") || next_is("SHARE")))
+            || (token.is_keyword("LOCK") && next_is("IN"))
+        {
+            locking = true;
+            i += 2;
+            continue;
+        }
+        unlocked.push(token);
+        i += 1;
+    }
+
+    locking && classify_tokens(&unlocked) == StatementKind::ReadOnly
+}
+
+/// Check if the name at `index`, which is followed by a parenthesis, calls a
+/// function that may be a stored function
+fn calls_stored_function(tokens: &[Token], index: usize) -> bool {
+    let previous = index.checked_sub(1).map(|i| &tokens[i]);
+    // Built-in functions cannot be qualified with a database
+    if previous.is_some_and(|t| t.is_punct('.')) {
+        return true;
+    }
+    // Column list of a derived table: `AS d (a, b)`
+    if previous.is_some_and(|t| t.is_keyword("AS")) {
+        return false;
+    }
+    // Column list of a common table expression: `WITH t (a, b) AS (...)`
+    let mut depth = 0usize;
+    for (offset, token) in tokens[index + 1..].iter().enumerate() {
+        if token.is_punct('(') {
+            depth += 1;
+        } else if token.is_punct(')') {
+            depth -= 1;
+            if depth == 0 {
+                let after = index + offset + 2;
+                if tokens.get(after).is_some_and(|t| t.is_keyword("AS"))
+                    && tokens.get(after + 1).is_some_and(|t| t.is_punct('('))
+                {
+                    return false;
+                }
+                break;
+            }
+        }
+    }
+
+    let token = &tokens[index];
+    if token.kind == TokenKind::QuotedIdent {
+        return true;
+    }
+    let name = token.text.to_ascii_uppercase();
+    !(BUILTIN_FUNCTIONS.contains(&name.as_str())
+        || PARENTHESIZED_KEYWORDS.contains(&name.as_str())
+        || name.starts_with("ST_")
+        || name.starts_with("MBR"))
+}
+
+/// Classify a statement with common table expressions by its main clause
+fn classify_with(tokens: &[Token], body: &[Token]) -> StatementKind {
+    let mut depth = 0usize;
+
+    for token in &body[1..] {
+        match token.kind {
+            TokenKind::Punct('(') => depth += 1,
+            TokenKind::Punct(')') => depth = depth.saturating_sub(1),
+            TokenKind::Word if depth == 0 => {
+                let keyword = token.text.to_ascii_uppercase();
+                match keyword.as_str() {
+                    "SELECT" | "TABLE" | "VALUES" => return classify_query(tokens),
+                    "INSERT" | "UPDATE" | "DELETE" | "REPLACE" => return StatementKind::Dml,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    StatementKind::Unknown
+}
+
+/// Classify EXPLAIN / DESCRIBE. Plain EXPLAIN never executes the statement,
+/// but EXPLAIN ANALYZE does, so it inherits the explained statement's kind.
+fn classify_explain(rest: &[Token]) -> StatementKind {
+    match rest.first() {
+        Some(token) if token.is_keyword("ANALYZE") => {
+            // Skip an optional FORMAT = name clause
+            let mut index = 1;
+            if rest.get(index).is_some_and(|t| t.is_keyword("FORMAT")) {
+                index += 3;
+            }
+            match rest.get(index..) {
+                Some(inner) if !inner.is_empty() => classify_tokens(inner),
+                _ => StatementKind::Unknown,
+            }
+        }
+        _ => StatementKind::ReadOnly,
+    }
+}
+
+/// Classify SET. Session variables only affect the connection, global and
+/// persisted variables and passwords affect the whole server.
+fn classify_set(rest: &[Token]) -> StatementKind {
+    let is_global = rest.iter().any(|t| {
+        let upper = t.text.to_ascii_uppercase();
+        (t.kind == TokenKind::Word
+            && matches!(upper.as_str(), "GLOBAL" | "PERSIST" | "PERSIST_ONLY" | "PASSWORD"))
+            || (t.kind == TokenKind::Variable
+                && (upper.starts_with("@@GLOBAL.") || upper.starts_with("@@PERSIST")))
+    });
+
+    if is_global {
+        StatementKind::Admin
+    } else {
+        StatementKind::Session
+    }
+}
+
+/// Keywords after which the next name is a table reference, or another
+/// object that lives in a database
+const TABLE_POSITION_KEYWORDS: &[&str] = &[
+    "FROM", "JOIN", "STRAIGHT_JOIN", "INTO", "UPDATE", "TABLE", "TABLES", "TRUNCATE", "DESCRIBE",
+    "DESC", "EXPLAIN", "VIEW", "PROCEDURE", "FUNCTION", "TRIGGER", "EVENT", "REFERENCES", "CALL",
+    "HANDLER",
+];
+
+/// Statements whose `ON` and `LIKE` name an object (`CREATE INDEX i ON t`,
+/// `CREATE TABLE t LIKE s`, `GRANT ... ON t`) until a query starts
+const OBJECT_STATEMENTS: &[&str] = &["CREATE", "DROP", "ALTER", "GRANT", "REVOKE"];
+
+/// Keywords that end a comma-separated table list
+///
+/// Join conditions (`ON` / `USING`) do not: another table may follow them
+/// after a comma.
+const TABLE_LIST_END_KEYWORDS: &[&str] = &[
+    "WHERE", "GROUP", "ORDER", "HAVING", "LIMIT", "SET", "DUPLICATE", "WINDOW", "UNION",
+    "SELECT", "VALUES", "VALUE", "PARTITION", "FOR", "LOCK", "INTO",
+];
+
+/// Parsing state of one parenthesis level in `referenced_schemas`
+struct Level {
+    /// Subqueries and parenthesized table references, as opposed to function
+    /// arguments and column lists (`EXTRACT(YEAR FROM t.created)`)
+    query: bool,
+    /// Inside a comma-separated table list, where a name after a comma is a
+    /// table reference
+    in_table_list: bool,
+}
+
+/// SHOW statements whose first FROM / IN names a table rather than a database
+const SHOW_TABLE_OBJECTS: &[&str] = &["COLUMNS", "FIELDS", "INDEX", "INDEXES", "KEYS"];
+
+/// Collect the databases the first statement names explicitly
+///
+/// Covers schema-qualified table references (`FROM otherdb.users`),
+/// three-part column references (`otherdb.users.id`), schema-qualified
+/// function calls, `CREATE / DROP / ALTER DATABASE name`, `USE name` and
+/// `SHOW ... FROM name`. Unqualified names resolve against the connection's
+/// default database and are not reported.
+///
+/// The check fails closed: a two-part name outside the known table positions
+/// is only taken as `table.column` when its first part is a table or alias
+/// the statement introduces; otherwise the first part is reported as a
+/// database.
+pub fn referenced_schemas(sql: &str) -> Vec<String> {
+    let tokens: Vec<Token> = tokenize(sql)
+        .into_iter()
+        .take_while(|t| !t.is_punct(';'))
+        .collect();
+
+    let is_statement = |keyword: &str| tokens.first().is_some_and(|t| t.is_keyword(keyword));
+    let is_show = is_statement("SHOW");
+    let is_rename = is_statement("RENAME");
+    let is_delete = is_statement("DELETE");
+    let names_objects = tokens.first().is_some_and(|t| {
+        t.kind == TokenKind::Word
+            && OBJECT_STATEMENTS.contains(&t.text.to_ascii_uppercase().as_str())
+    });
+    let show_table_first = is_show
+        && tokens.iter().skip(1).take(2).any(|t| {
+            t.kind == TokenKind::Word
+                && SHOW_TABLE_OBJECTS.contains(&t.text.to_ascii_uppercase().as_str())
+        });
+
+    let mut schemas: Vec<String> = Vec::new();
+    let mut push = |name: String| {
+        if !schemas.contains(&name) {
+            schemas.push(name);
+        }
+    };
+
+    // First parts of two-part names outside table positions, and the tables
+    // and aliases the statement introduces that they may refer to
+    let mut qualifiers: Vec<String> = Vec::new();
+    let mut local_names: Vec<String> = Vec::new();
+    if names_objects && tokens.iter().any(|t| t.is_keyword("TRIGGER")) {
+        local_names.extend(["NEW".to_string(), "OLD".to_string()]);
+    }
+
+    let mut table_position = false;
+    let mut database_position = false;
+    let mut seen_select = false;
+    let mut show_from_count = 0;
+    // One entry per open parenthesis, so that a table list continues after a
+    // parenthesized subquery, index hint or join column list
+    let mut levels = vec![Level {
+        query: true,
+        in_table_list: false,
+    }];
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = &tokens[i];
+        // Aliases: `AS name`, and a derived table's name after its subquery
+        if token.is_keyword("AS") || token.is_punct(')') {
+            local_names.extend(tokens.get(i + 1).and_then(Token::identifier));
+        }
+
+        let is_name_part = |index: usize| {
+            tokens
+                .get(index)
+                .is_some_and(|t| t.identifier().is_some() || t.is_punct('*'))
+        };
+        let qualified = tokens.get(i + 1).is_some_and(|t| t.is_punct('.')) && is_name_part(i + 2);
+
+        if let (true, Some(name)) = (qualified, token.identifier()) {
+            let three_part =
+                tokens.get(i + 3).is_some_and(|t| t.is_punct('.')) && is_name_part(i + 4);
+            let function_call = !three_part && tokens.get(i + 3).is_some_and(|t| t.is_punct('('));
+
+            if table_position || three_part || function_call {
+                push(name);
+            } else {
+                qualifiers.push(name);
+            }
+
+            if table_position && !three_part {
+                // A table reference, which columns may be qualified with by
+                // its name or alias
+                local_names.extend(tokens[i + 2].identifier());
+                local_names.extend(tokens.get(i + 3).and_then(Token::identifier));
+            }
+
+            table_position = false;
+            database_position = false;
+            i += if three_part { 5 } else { 3 };
+            continue;
+        }
+
+        if token.kind == TokenKind::Word {
+            let keyword = token.text.to_ascii_uppercase();
+
+            if (database_position || table_position)
+                && matches!(keyword.as_str(), "IF" | "NOT" | "EXISTS")
+            {
+                i += 1;
+                continue;
+            }
+
+            if is_show && matches!(keyword.as_str(), "FROM" | "IN") {
+                show_from_count += 1;
+                if show_table_first && show_from_count == 1 {
+                    table_position = true;
+                } else {
+                    table_position = false;
+                    database_position = true;
+                }
+                i += 1;
+                continue;
+            }
+
+            seen_select |= keyword == "SELECT";
+            let previous_is = |keyword: &str| i > 0 && tokens[i - 1].is_keyword(keyword);
+            let level = levels.last_mut().expect("the statement level is never popped");
+            let is_table_position = match keyword.as_str() {
+                "FROM" => level.query,
+                // ON DUPLICATE KEY UPDATE col = ... and FOR UPDATE are not table references
+                "UPDATE" => !previous_is("KEY") && !previous_is("FOR"),
+                "TO" => is_rename,
+                // DELETE ... USING tables; a join's USING is followed by a column list
+                "USING" => is_delete,
+                "ON" | "LIKE" => names_objects && !seen_select,
+                _ => TABLE_POSITION_KEYWORDS.contains(&keyword.as_str()),
+            };
+
+            if is_table_position {
+                table_position = true;
+                database_position = false;
+                match keyword.as_str() {
+                    "FROM" | "UPDATE" | "TABLE" | "TABLES" | "USING" => level.in_table_list = true,
+                    // Joined tables continue the list of the FROM clause
+                    "JOIN" | "STRAIGHT_JOIN" => {}
+                    _ => level.in_table_list = false,
+                }
+                i += 1;
+                continue;
+            }
+
+            let names_database = matches!(keyword.as_str(), "DATABASE" | "SCHEMA")
+                && !tokens.get(i + 1).is_some_and(|t| t.is_punct('('));
+            if names_database || (i == 0 && keyword == "USE") {
+                database_position = true;
+                i += 1;
+                continue;
+            }
+
+            if TABLE_LIST_END_KEYWORDS.contains(&keyword.as_str()) {
+                level.in_table_list = false;
+            }
+        }
+
+        if database_position {
+            if let Some(name) = token.identifier() {
+                push(name);
+            }
+        }
+
+        if table_position && token.identifier().is_some() {
+            // An unqualified table reference and its alias
+            local_names.extend(token.identifier());
+            local_names.extend(tokens.get(i + 1).and_then(Token::identifier));
+        }
+
+        if token.is_punct('(') {
+            let subquery = tokens.get(i + 1).is_some_and(|t| {
+                t.is_keyword("SELECT") || t.is_keyword("WITH") || t.is_punct('(')
+            });
+            // `FROM (db.t1, db.t2)`: the parentheses group table references
+            let table_group = table_position && !subquery;
+            levels.push(Level {
+                query: subquery || table_group,
+                in_table_list: table_group,
+            });
+            database_position = false;
+            i += 1;
+            continue;
+        } else if token.is_punct(')') && levels.len() > 1 {
+            levels.pop();
+        }
+
+        let in_table_list = levels.last().is_some_and(|level| level.in_table_list);
+        table_position = token.is_punct(',') && in_table_list;
+        database_position = false;
+        i += 1;
+    }
+
+    for qualifier in qualifiers {
+        if !local_names.iter().any(|name| name.eq_ignore_ascii_case(&qualifier)) {
+            push(qualifier);
+        }
+    }
+
+    schemas
+}
+
+/// Check if the first statement lists every database on the server
+/// (`SHOW DATABASES` / `SHOW SCHEMAS`)
+pub fn lists_databases(sql: &str) -> bool {
+    let mut tokens = tokenize(sql).into_iter();
+    tokens.next().is_some_and(|t| t.is_keyword("SHOW"))
+        && tokens
+            .next()
+            .is_some_and(|t| t.is_keyword("DATABASES") || t.is_keyword("SCHEMAS"))
+}
+
+/// Check if the first statement calls a stored procedure (`CALL`)
+///
+/// Procedures are classified as data modification, but their body can run any
+/// statement, including ones that commit a transaction.
+pub fn is_procedure_call(sql: &str) -> bool {
+    tokenize(sql)
+        .into_iter()
+        .next()
+        .is_some_and(|t| t.is_keyword("CALL"))
+}
+
+/// An item of a SELECT's select list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectItem {
+    /// A column, optionally qualified with a table name or alias
+    Column {
+        qualifier: Option<String>,
+        column: String,
+        /// Whether the item renames the column with an alias
+        aliased: bool,
+    },
+    /// `*` or `t.*`
+    AllColumns { qualifier: Option<String> },
+    /// Anything else
+    Expression,
+}
+
+/// A table reference in a SELECT's FROM clause
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectTable {
+    pub schema: Option<String>,
+    /// `None` for derived tables and table functions
+    pub table: Option<String>,
+    pub alias: Option<String>,
+}
+
+/// The select list and FROM clause of a single SELECT
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectShape {
+    pub items: Vec<SelectItem>,
+    pub tables: Vec<SelectTable>,
+    /// Outer joins and `WITH ROLLUP` can make columns of NOT NULL table
+    /// columns NULL
+    pub adds_nulls: bool,
+}
+
+/// SELECT modifiers that may come before the select list
+const SELECT_MODIFIERS: &[&str] = &[
+    "ALL", "DISTINCT", "DISTINCTROW", "HIGH_PRIORITY", "STRAIGHT_JOIN", "SQL_SMALL_RESULT",
+    "SQL_BIG_RESULT", "SQL_BUFFER_RESULT", "SQL_NO_CACHE", "SQL_CALC_FOUND_ROWS",
+];
+
+/// Keywords that end a SELECT's FROM clause
+const FROM_END_KEYWORDS: &[&str] = &[
+    "WHERE", "GROUP", "HAVING", "WINDOW", "ORDER", "LIMIT", "FOR", "LOCK", "INTO", "PROCEDURE",
+];
+
+/// Keywords that can follow a table reference, and so are not its alias
+const NOT_ALIAS_KEYWORDS: &[&str] = &[
+    "ON", "USING", "JOIN", "INNER", "CROSS", "LEFT", "RIGHT", "OUTER", "NATURAL",
+    "STRAIGHT_JOIN", "USE", "IGNORE", "FORCE", "PARTITION", "WHERE", "GROUP", "HAVING",
+    "WINDOW", "ORDER", "LIMIT", "FOR", "LOCK", "INTO", "PROCEDURE",
+];
+
+/// Words that read like a column name in a select list but are values
+const VALUE_KEYWORDS: &[&str] = &[
+    "NULL", "TRUE", "FALSE", "CURRENT_DATE", "CURRENT_TIME", "CURRENT_TIMESTAMP",
+    "CURRENT_USER", "LOCALTIME", "LOCALTIMESTAMP", "UTC_DATE", "UTC_TIME", "UTC_TIMESTAMP",
+];
+
+/// Read the select list and FROM clause of the first statement, if it is a
+/// single SELECT
+///
+/// `None` for other statements, including `WITH` queries and set operations
+/// (`UNION`, `INTERSECT`, `EXCEPT`), whose result columns belong to no one
+/// select list.
+pub fn select_shape(sql: &str) -> Option<SelectShape> {
+    let tokens: Vec<Token> = tokenize(sql)
+        .into_iter()
+        .take_while(|t| !t.is_punct(';'))
+        .collect();
+    if !tokens.first()?.is_keyword("SELECT") {
+        return None;
+    }
+
+    // Top-level positions of the clauses
+    let mut depth = 0usize;
+    let mut from = None;
+    let mut from_end = tokens.len();
+    let mut adds_nulls = false;
+    for (i, token) in tokens.iter().enumerate() {
+        if token.is_punct('(') {
+            depth += 1;
+        } else if token.is_punct(')') {
+            depth = depth.saturating_sub(1);
+        } else if depth == 0 && token.kind == TokenKind::Word {
+            let keyword = token.text.to_ascii_uppercase();
+            match keyword.as_str() {
+                "UNION" | "INTERSECT" | "EXCEPT" => return None,
+                "FROM" if from.is_none() => from = Some(i),
+                "LEFT" | "RIGHT" | "ROLLUP" => adds_nulls = true,
+                _ if from.is_some()
+                    && from_end == tokens.len()
+                    && FROM_END_KEYWORDS.contains(&keyword.as_str()) =>
+                {
+                    from_end = i
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut start = 1;
+    while tokens.get(start).is_some_and(|t| {
+        t.kind == TokenKind::Word && SELECT_MODIFIERS.contains(&t.text.to_ascii_uppercase().as_str())
+    }) {
+        start += 1;
+    }
+    let list_end = from.unwrap_or_else(|| {
+        // `SELECT ... INTO @var` without FROM
+        tokens
+            .iter()
+            .position(|t| t.is_keyword("INTO"))
+            .unwrap_or(tokens.len())
+    });
+    let items = split_top_level(&tokens[start.min(list_end)..list_end])
+        .into_iter()
+        .map(select_item)
+        .collect();
+
+    let tables = match from {
+        Some(from) => from_tables(&tokens[from + 1..from_end]),
+        None => Vec::new(),
+    };
+
+    Some(SelectShape {
+        items,
+        tables,
+        adds_nulls,
+    })
+}
+
+/// Split tokens at commas outside parentheses
+fn split_top_level<'t, 'a>(tokens: &'t [Token<'a>]) -> Vec<&'t [Token<'a>]> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        if token.is_punct('(') {
+            depth += 1;
+        } else if token.is_punct(')') {
+            depth = depth.saturating_sub(1);
+        } else if depth == 0 && token.is_punct(',') {
+            parts.push(&tokens[start..i]);
+            start = i + 1;
+        }
+    }
+    if start < tokens.len() {
+        parts.push(&tokens[start..]);
+    }
+    parts
+}
+
+/// Classify one item of a select list
+fn select_item(item: &[Token]) -> SelectItem {
+    let n = item.len();
+    // Drop the alias: `expr AS name` or `expr name`
+    let (expr, aliased) = if n >= 3 && item[n - 2].is_keyword("AS") {
+        (&item[..n - 2], true)
+    } else if n >= 2
+        && (item[n - 1].identifier().is_some() || item[n - 1].kind == TokenKind::String)
+        && !item[n - 2].is_punct('.')
+    {
+        (&item[..n - 1], true)
+    } else {
+        (item, false)
+    };
+
+    let name = |token: &Token| match token.kind {
+        TokenKind::Word
+            if VALUE_KEYWORDS.contains(&token.text.to_ascii_uppercase().as_str()) =>
+        {
+            None
+        }
+        _ => token.identifier(),
+    };
+    let dot = |index: usize| expr[index].is_punct('.');
+
+    match expr.len() {
+        1 if expr[0].is_punct('*') => SelectItem::AllColumns { qualifier: None },
+        1 => match name(&expr[0]) {
+            Some(column) => SelectItem::Column {
+                qualifier: None,
+                column,
+                aliased,
+            },
+            None => SelectItem::Expression,
+        },
+        // `t.c` and `db.t.c`
+        3 | 5 if (1..expr.len()).step_by(2).all(dot) => {
+            let qualifier = expr[expr.len() - 3].identifier();
+            let last = &expr[expr.len() - 1];
+            match (qualifier, last.identifier()) {
+                (Some(qualifier), _) if last.is_punct('*') => SelectItem::AllColumns {
+                    qualifier: Some(qualifier),
+                },
+                (Some(qualifier), Some(column)) => SelectItem::Column {
+                    qualifier: Some(qualifier),
+                    column,
+                    aliased,
+                },
+                _ => SelectItem::Expression,
+            }
+        }
+        _ => SelectItem::Expression,
+    }
+}
+
+/// Read the table references of a FROM clause
+fn from_tables(tokens: &[Token]) -> Vec<SelectTable> {
+    let mut tables = Vec::new();
+    let mut expect_table = true;
+    let mut i = 0;
+
+    // `AS name` or a bare name that is not the next clause
+    let alias_at = |i: &mut usize| -> Option<String> {
+        let token = tokens.get(*i)?;
+        if token.is_keyword("AS") {
+            *i += 2;
+            return tokens.get(*i - 1).and_then(Token::identifier);
+        }
+        let is_keyword = token.kind == TokenKind::Word
+            && NOT_ALIAS_KEYWORDS.contains(&token.text.to_ascii_uppercase().as_str());
+        if is_keyword {
+            return None;
+        }
+        let alias = token.identifier()?;
+        *i += 1;
+        Some(alias)
+    };
+
+    while i < tokens.len() {
+        let token = &tokens[i];
+
+        if expect_table {
+            expect_table = false;
+            // Derived tables, `LATERAL (...)` and table functions such as JSON_TABLE
+            let open = if token.is_punct('(') {
+                Some(i)
+            } else if tokens.get(i + 1).is_some_and(|t| t.is_punct('(')) {
+                Some(i + 1)
+            } else {
+                None
+            };
+            if let Some(open) = open {
+                i = closing_paren(tokens, open) + 1;
+                let alias = alias_at(&mut i);
+                tables.push(SelectTable {
+                    schema: None,
+                    table: None,
+                    alias,
+                });
+                continue;
+            }
+
+            if let Some(name) = token.identifier() {
+                let qualified = tokens.get(i + 1).is_some_and(|t| t.is_punct('.'));
+                let (schema, table) = match qualified {
+                    true => (Some(name), tokens.get(i + 2).and_then(Token::identifier)),
+                    false => (None, Some(name)),
+                };
+                i += if qualified { 3 } else { 1 };
+                if tokens.get(i).is_some_and(|t| t.is_keyword("PARTITION")) {
+                    i = closing_paren(tokens, i + 1) + 1;
+                }
+                let alias = alias_at(&mut i);
+                if !table.as_deref().is_some_and(|t| t.eq_ignore_ascii_case("DUAL")) {
+                    tables.push(SelectTable {
+                        schema,
+                        table,
+                        alias,
+                    });
+                }
+                continue;
+            }
+        }
+
+        if token.is_punct('(') {
+            i = closing_paren(tokens, i);
+        } else if token.is_punct(',') || token.is_keyword("JOIN") || token.is_keyword("STRAIGHT_JOIN") {
+            expect_table = true;
+        }
+        i += 1;
+    }
+
+    tables
+}
+
+/// Index of the parenthesis closing the one at `open`, or the last token if
+/// it is never closed
+fn closing_paren(tokens: &[Token], open: usize) -> usize {
+    let mut depth = 0usize;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        if token.is_punct('(') {
+            depth += 1;
+        } else if token.is_punct(')') {
+            depth = depth.saturating_sub(1);
+            if depth == 0 {
+                return i;
+            }
+        }
+    }
+    tokens.len().saturating_sub(1)
+}
+
+/// Add a `MAX_EXECUTION_TIME` optimizer hint to a SELECT so the server aborts it
+/// after `timeout_ms` milliseconds
+///
+/// The hint only takes effect right after the leading `SELECT` keyword, so other
+/// statements and SELECTs that already carry an optimizer hint are returned unchanged.
+pub fn with_max_execution_time(sql: &str, timeout_ms: u64) -> String {
+    let Some(first) = tokenize(sql).into_iter().next() else {
+        return sql.to_string();
+    };
+    if !first.is_keyword("SELECT") || sql[first.end..].trim_start().starts_with("/*+") {
+        return sql.to_string();
+    }
+
+    format!(
+        "{} /*+ MAX_EXECUTION_TIME({}) */{}",
+        &sql[..first.end],
+        timeout_ms,
+        &sql[first.end..]
+    )
+}
+
+/// Quote a table or column name as a MySQL identifier
+pub fn quote_identifier(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_skips_comments() {
+        let tokens = tokenize("SELECT /* a; b */ 1 -- trailing; comment\n# another; one\n, 2");
+        let texts: Vec<&str> = tokens.iter().map(|t| t.text).collect();
+        assert_eq!(texts, vec!["SELECT", "1", ",", "2"]);
+    }
+
+    #[test]
+    fn test_tokenize_executable_comment_is_code() {
+        let tokens = tokenize("SELECT 1 /*!50000 INTO OUTFILE '/tmp/x' */");
+        assert!(tokens.iter().any(|t| t.is_keyword("OUTFILE")));
+    }
+
+    #[test]
+    fn test_tokenize_double_dash_without_space_is_not_comment() {
+        let tokens = tokenize("SELECT 1--1");
+        assert_eq!(tokens.len(), 5);
+    }
+
+    #[test]
+    fn test_tokenize_strings_and_identifiers() {
+        let tokens = tokenize(r"SELECT 'it''s', 'a\'b', `we``ird`, @x, @@global.y, ?");
+        let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Word,
+                TokenKind::String,
+                TokenKind::Punct(','),
+                TokenKind::String,
+                TokenKind::Punct(','),
+                TokenKind::QuotedIdent,
+                TokenKind::Punct(','),
+                TokenKind::Variable,
+                TokenKind::Punct(','),
+                TokenKind::Variable,
+                TokenKind::Punct(','),
+                TokenKind::Placeholder,
+            ]
+        );
+        assert_eq!(tokens[5].identifier().unwrap(), "we`ird");
+    }
+
+    #[test]
+    fn test_tokenize_numbers_and_digit_identifiers() {
+        let tokens = tokenize("SELECT 1, 1.5, 1e5, 2.5E-3, 0x1F, 0b101, 2024_archive, 1e5x");
+        let kinds: Vec<(&str, TokenKind)> = tokens
+            .iter()
+            .filter(|t| !t.is_punct(','))
+            .map(|t| (t.text, t.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("SELECT", TokenKind::Word),
+                ("1", TokenKind::Number),
+                ("1.5", TokenKind::Number),
+                ("1e5", TokenKind::Number),
+                ("2.5E-3", TokenKind::Number),
+                ("0x1F", TokenKind::Number),
+                ("0b101", TokenKind::Number),
+                ("2024_archive", TokenKind::Word),
+                ("1e5x", TokenKind::Word),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_named_placeholders() {
+        let tokens = tokenize("SELECT :id, @a := 1, ':not_a_param', :_x2");
+        let named: Vec<&str> = tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::NamedPlaceholder)
+            .map(|t| t.text)
+            .collect();
+        assert_eq!(named, vec![":id", ":_x2"]);
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::Assign));
+    }
+
+    #[test]
+    fn test_first_statement_single() {
+        assert_eq!(first_statement("SELECT * FROM users"), "SELECT * FROM users");
+    }
+
+    #[test]
+    fn test_first_statement_multiple() {
+        assert_eq!(
+            first_statement("SELECT * FROM users; DELETE FROM users;"),
+            "SELECT * FROM users"
+        );
+    }
+
+    #[test]
+    fn test_first_statement_with_semicolon_in_string() {
+        assert_eq!(
+            first_statement("SELECT * FROM users WHERE name = 'test;data'; DELETE FROM users;"),
+            "SELECT * FROM users WHERE name = 'test;data'"
+        );
+    }
+
+    #[test]
+    fn test_first_statement_with_double_quotes() {
+        assert_eq!(
+            first_statement(r#"SELECT * FROM users WHERE name = "test;data"; DELETE FROM users;"#),
+            r#"SELECT * FROM users WHERE name = "test;data""#
+        );
+    }
+
+    #[test]
+    fn test_first_statement_with_escaped_quotes() {
+        assert_eq!(
+            first_statement(r"SELECT * FROM users WHERE name = 'test\'s;data'; DELETE FROM users;"),
+            r"SELECT * FROM users WHERE name = 'test\'s;data'"
+        );
+    }
+
+    #[test]
+    fn test_first_statement_with_semicolon_in_comment() {
+        assert_eq!(
+            first_statement("SELECT 1 # it's; fine\n; DELETE FROM users"),
+            "SELECT 1 # it's; fine"
+        );
+    }
+
+    #[test]
+    fn test_first_statement_no_semicolon() {
+        assert_eq!(
+            first_statement("SELECT * FROM users WHERE id = 1"),
+            "SELECT * FROM users WHERE id = 1"
+        );
+    }
+
+    #[test]
+    fn test_first_statement_whitespace() {
+        assert_eq!(
+            first_statement("  SELECT * FROM users  ; DELETE FROM users;  "),
+            "SELECT * FROM users"
+        );
+    }
+
+    #[test]
+    fn test_split_statements() {
+        assert_eq!(
+            split_statements("SELECT 1; ; SELECT ';'; "),
+            vec!["SELECT 1", "SELECT ';'"]
+        );
+    }
+
+    #[test]
+    fn test_classify_read_only() {
+        for sql in [
+            "SELECT * FROM users",
+            "select 1",
+            "  /* leading */ SELECT 1",
+            "(SELECT 1) UNION (SELECT 2)",
+            "SHOW TABLES",
+            "DESCRIBE users",
+            "DESC users",
+            "EXPLAIN DELETE FROM users",
+            "EXPLAIN ANALYZE SELECT * FROM users",
+            "TABLE users",
+            "VALUES ROW(1, 2)",
+            "WITH t AS (SELECT 1) SELECT * FROM t",
+            "SELECT 'INTO OUTFILE', `for` FROM users",
+        ] {
+            assert_eq!(classify(sql), StatementKind::ReadOnly, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_classify_dml() {
+        for sql in [
+            "INSERT INTO users VALUES (1, 'test')",
+            "UPDATE users SET name = 'test'",
+            "DELETE FROM users WHERE id = 1",
+            "REPLACE INTO users VALUES (1)",
+            "LOAD DATA INFILE 'x' INTO TABLE users",
+            "CALL cleanup()",
+            "WITH t AS (SELECT id FROM users) DELETE FROM users WHERE id IN (SELECT id FROM t)",
+            "EXPLAIN ANALYZE UPDATE users SET a = 1",
+            "-- comment\nDELETE FROM users",
+        ] {
+            assert_eq!(classify(sql), StatementKind::Dml, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_classify_ddl() {
+        for sql in [
+            "CREATE TABLE users (id INT)",
+            "create table users (id INT)",
+            "  CREATE TABLE users (id INT)",
+            "ALTER TABLE users ADD COLUMN name VARCHAR(255)",
+            "DROP TABLE users",
+            "TRUNCATE TABLE users",
+            "RENAME TABLE users TO customers",
+            "/*!40101 DROP TABLE users */",
+        ] {
+            assert_eq!(classify(sql), StatementKind::Ddl, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_classify_session() {
+        for sql in [
+            "SET @a = 1",
+            "SET SESSION sql_mode = ''",
+            "LOCK TABLES users WRITE",
+            "UNLOCK TABLES",
+            "HANDLER users OPEN",
+            "START TRANSACTION",
+            "BEGIN",
+            "SELECT * FROM users FOR UPDATE",
+            "SELECT * FROM users FOR SHARE",
+            "SELECT * FROM users LOCK IN SHARE MODE",
+            "SELECT id INTO @id FROM users LIMIT 1",
+            "SELECT @a := 1",
+            "SELECT GET_LOCK('x', 10)",
+        ] {
+            assert_eq!(classify(sql), StatementKind::Session, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_is_locking_read() {
+        for sql in [
+            "SELECT * FROM users WHERE id = 1 FOR UPDATE",
+            "SELECT * FROM users FOR SHARE OF users NOWAIT",
+            "SELECT * FROM users FOR UPDATE SKIP LOCKED",
+            "SELECT * FROM users LOCK IN SHARE MODE",
+            "(SELECT id FROM users FOR UPDATE)",
+        ] {
+            assert!(is_locking_read(sql), "{}", sql);
+        }
+
+        for sql in [
+            "SELECT * FROM users",
+            "SELECT id INTO @id FROM users FOR UPDATE",
+            "SELECT GET_LOCK('x', 10) FOR UPDATE",
+            "SELECT purge_all() FROM users FOR UPDATE",
+            "LOCK TABLES users WRITE",
+            "SET @a = 1",
+        ] {
+            assert!(!is_locking_read(sql), "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_classify_admin() {
+        for sql in [
+            "GRANT ALL ON *.* TO 'x'@'%'",
+            "KILL 42",
+            "FLUSH PRIVILEGES",
+            "SET GLOBAL max_connections = 10",
+            "SET @@global.max_connections = 10",
+            "SELECT * FROM users INTO OUTFILE '/tmp/users'",
+            "SELECT * INTO DUMPFILE '/tmp/x' FROM users",
+            "SELECT 1 /*!INTO OUTFILE '/tmp/x' */",
+            "SELECT LOAD_FILE('/etc/passwd')",
+        ] {
+            assert_eq!(classify(sql), StatementKind::Admin, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_classify_unknown() {
+        assert_eq!(classify(""), StatementKind::Unknown);
+        assert_eq!(classify("THIS_IS_INVALID_SQL"), StatementKind::Unknown);
+        assert_eq!(classify("'SELECT 1'"), StatementKind::Unknown);
+    }
+
+    #[test]
+    fn test_classify_stored_function_calls() {
+        // Stored functions may modify data
+        for sql in [
+            "SELECT purge_all()",
+            "SELECT app.do_writes(1)",
+            "SELECT `purge_all`()",
+            "SELECT id FROM users WHERE id = app.next_id()",
+            "WITH t AS (SELECT purge_all()) SELECT * FROM t",
+            "SELECT LOWER(name), purge_all() FROM users FOR UPDATE",
+        ] {
+            assert_eq!(classify(sql), StatementKind::Unknown, "{}", sql);
+        }
+
+        // Built-in functions and keywords followed by a parenthesis do not count
+        for sql in [
+            "SELECT COUNT(*), MAX(id), DATE_FORMAT(NOW(), '%Y') FROM users",
+            "SELECT CAST(price AS DECIMAL(10, 2)), CONVERT(name USING utf8mb4) FROM t",
+            "SELECT * FROM t WHERE id IN (1, 2) AND NOT EXISTS (SELECT 1)",
+            "SELECT ROW_NUMBER() OVER (PARTITION BY a ORDER BY b) FROM t",
+            "SELECT * FROM t1 JOIN t2 USING (id) FORCE INDEX (PRIMARY)",
+            "WITH t (a, b) AS (SELECT 1, 2) SELECT * FROM t",
+            "SELECT * FROM (SELECT 1, 2) AS d (a, b)",
+            "SELECT ST_Distance(p, POINT(1, 2)), JSON_EXTRACT(doc, '$.a') FROM t",
+            "SELECT * FROM t WHERE MATCH (title) AGAINST ('x')",
+        ] {
+            assert_eq!(classify(sql), StatementKind::ReadOnly, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_classify_only_considers_first_statement() {
+        assert_eq!(classify("SELECT 1; DROP TABLE users"), StatementKind::ReadOnly);
+        assert_eq!(classify("DROP TABLE users; SELECT 1"), StatementKind::Ddl);
+    }
+
+    #[test]
+    fn test_requires_ddl() {
+        assert!(StatementKind::Ddl.requires_ddl());
+        assert!(StatementKind::Admin.requires_ddl());
+        assert!(StatementKind::Unknown.requires_ddl());
+        assert!(!StatementKind::Dml.requires_ddl());
+        assert!(!StatementKind::ReadOnly.requires_ddl());
+    }
+
+    #[test]
+    fn test_referenced_schemas_table_positions() {
+        let cases: &[(&str, &[&str])] = &[
+            ("SELECT * FROM users", &[]),
+            ("SELECT * FROM other.users", &["other"]),
+            ("SELECT * FROM `other db`.`users`", &["other db"]),
+            ("SELECT * FROM a.t1 x, b.t2 y WHERE x.id = y.id", &["a", "b"]),
+            ("SELECT * FROM t1 JOIN other.t2 ON t1.id = t2.id", &["other"]),
+            ("SELECT * FROM t WHERE id IN (SELECT id FROM other.t)", &["other"]),
+            ("INSERT INTO other.t (a, b) SELECT a, b FROM app.t", &["other", "app"]),
+            ("UPDATE a.t1, b.t2 SET t1.x = t2.x", &["a", "b"]),
+            ("DELETE t1 FROM other.t1", &["other"]),
+            ("DESCRIBE other.users", &["other"]),
+            ("TRUNCATE TABLE other.users", &["other"]),
+            ("RENAME TABLE t TO other.t", &["other"]),
+            ("SELECT * FROM (SELECT 1) AS d, billing.invoices", &["billing"]),
+            ("SELECT * FROM t1 USE INDEX (PRIMARY), billing.invoices", &["billing"]),
+            ("SELECT * FROM (billing.invoices)", &["billing"]),
+            ("SELECT * FROM (t1, billing.invoices)", &["billing"]),
+            ("SELECT * FROM a JOIN b USING (id), billing.invoices", &["billing"]),
+            ("SELECT * FROM a JOIN b ON a.id = b.id, billing.invoices", &["billing"]),
+            ("SELECT * FROM a JOIN b, billing.invoices", &["billing"]),
+            ("DELETE FROM t1 USING t1, billing.invoices", &["billing"]),
+        ];
+
+        for (sql, expected) in cases {
+            assert_eq!(referenced_schemas(sql), *expected, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_referenced_schemas_ignores_table_column_references() {
+        for sql in [
+            "SELECT u.name, u.* FROM users u",
+            "SELECT * FROM t1 JOIN t2 ON t1.id = t2.id ORDER BY t1.id DESC, t2.id",
+            "SELECT EXTRACT(YEAR FROM t.created) FROM t",
+            "INSERT INTO t (a) VALUES (1) ON DUPLICATE KEY UPDATE t.a = 2",
+            "INSERT INTO t SELECT * FROM s ON DUPLICATE KEY UPDATE t.a = 2, t.b = 3",
+            "SELECT * FROM t1 JOIN t2 USING (id) WHERE t1.a IN (1, 2) AND t2.b = 3",
+            "SELECT 'other.users' FROM t -- other.users",
+            "SELECT DATABASE(), 1.5",
+        ] {
+            assert!(referenced_schemas(sql).is_empty(), "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_referenced_schemas_qualified_names_anywhere() {
+        assert_eq!(referenced_schemas("SELECT other.users.id FROM users"), vec!["other"]);
+        assert_eq!(referenced_schemas("SELECT other.f(1)"), vec!["other"]);
+        assert_eq!(
+            referenced_schemas("SELECT /*!50000 other.users.id */ 1"),
+            vec!["other"]
+        );
+    }
+
+    #[test]
+    fn test_referenced_schemas_object_positions() {
+        let cases: &[(&str, &[&str])] = &[
+            ("DELETE FROM a1 USING other.users AS a1", &["other"]),
+            ("DELETE FROM other USING other.users", &["other"]),
+            ("DROP VIEW other.v", &["other"]),
+            ("DROP VIEW IF EXISTS other.v", &["other"]),
+            ("CREATE INDEX i ON other.t (a)", &["other"]),
+            ("DROP INDEX i ON other.t", &["other"]),
+            ("DROP PROCEDURE other.p", &["other"]),
+            ("CALL other.p", &["other"]),
+            ("CREATE TABLE t LIKE other.t", &["other"]),
+            ("CREATE TABLE t (LIKE other.t)", &["other"]),
+            ("CREATE TRIGGER trg BEFORE INSERT ON other.t FOR EACH ROW SET NEW.a = 1", &["other"]),
+            ("LOCK TABLES t READ, other.t WRITE", &["other"]),
+            ("SELECT * FROM 2024_archive.orders", &["2024_archive"]),
+            ("SELECT 2024_archive.orders.id FROM t", &["2024_archive"]),
+        ];
+
+        for (sql, expected) in cases {
+            assert_eq!(referenced_schemas(sql), *expected, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_referenced_schemas_fails_closed_on_unknown_qualifiers() {
+        assert_eq!(referenced_schemas("SELECT other.users FROM t"), vec!["other"]);
+        assert_eq!(referenced_schemas("SOMETHING NEW other.t"), vec!["other"]);
+
+        for sql in [
+            "SELECT d.id FROM (SELECT 1 AS id) d",
+            "SELECT x.a FROM t AS x WHERE x.b LIKE t.c",
+            "INSERT INTO t VALUES (1) AS new ON DUPLICATE KEY UPDATE a = new.a",
+            "CREATE TRIGGER trg BEFORE UPDATE ON t FOR EACH ROW SET NEW.a = OLD.a",
+            "CREATE VIEW v AS SELECT t.a FROM t JOIN s ON t.id = s.id WHERE t.a LIKE s.b",
+            "DELETE a1 FROM t AS a1 JOIN s USING (id) WHERE a1.x = s.x",
+        ] {
+            assert!(referenced_schemas(sql).is_empty(), "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_referenced_schemas_database_statements() {
+        assert_eq!(referenced_schemas("DROP DATABASE IF EXISTS other"), vec!["other"]);
+        assert_eq!(referenced_schemas("CREATE SCHEMA other"), vec!["other"]);
+        assert_eq!(referenced_schemas("USE `other`"), vec!["other"]);
+        assert_eq!(referenced_schemas("SHOW TABLES FROM other"), vec!["other"]);
+        assert_eq!(referenced_schemas("SHOW TABLE STATUS IN other"), vec!["other"]);
+        assert_eq!(referenced_schemas("SHOW CREATE DATABASE other"), vec!["other"]);
+        assert_eq!(referenced_schemas("SHOW COLUMNS FROM users FROM other"), vec!["other"]);
+        assert_eq!(referenced_schemas("SHOW INDEX FROM other.users"), vec!["other"]);
+        assert!(referenced_schemas("SHOW FULL COLUMNS FROM users").is_empty());
+    }
+
+    #[test]
+    fn test_lists_databases() {
+        assert!(lists_databases("SHOW DATABASES"));
+        assert!(lists_databases("show schemas like 'app%'"));
+        assert!(!lists_databases("SHOW TABLES"));
+        assert!(!lists_databases("SELECT 'SHOW DATABASES'"));
+    }
+
+    #[test]
+    fn test_is_procedure_call() {
+        assert!(is_procedure_call("CALL cleanup()"));
+        assert!(is_procedure_call("/* nightly */ call app.cleanup(1)"));
+        assert!(is_procedure_call("/*!50000 CALL cleanup */"));
+        assert!(!is_procedure_call("SELECT 'CALL cleanup()'"));
+        assert!(!is_procedure_call("INSERT INTO calls VALUES (1)"));
+    }
+
+    fn column(qualifier: Option<&str>, column: &str, aliased: bool) -> SelectItem {
+        SelectItem::Column {
+            qualifier: qualifier.map(str::to_string),
+            column: column.to_string(),
+            aliased,
+        }
+    }
+
+    #[test]
+    fn test_select_shape() {
+        let shape = select_shape(
+            "SELECT DISTINCT u.id, name AS who, `o`.`total` + 1 AS t, o.*, COUNT(*) \
+             FROM app.users AS u JOIN orders o ON o.user_id = u.id \
+             WHERE u.id > 1 GROUP BY u.id",
+        )
+        .unwrap();
+        assert_eq!(
+            shape.items,
+            vec![
+                column(Some("u"), "id", false),
+                column(None, "name", true),
+                SelectItem::Expression,
+                SelectItem::AllColumns {
+                    qualifier: Some("o".to_string())
+                },
+                SelectItem::Expression,
+            ]
+        );
+        assert_eq!(
+            shape.tables,
+            vec![
+                SelectTable {
+                    schema: Some("app".to_string()),
+                    table: Some("users".to_string()),
+                    alias: Some("u".to_string()),
+                },
+                SelectTable {
+                    schema: None,
+                    table: Some("orders".to_string()),
+                    alias: Some("o".to_string()),
+                },
+            ]
+        );
+        assert!(!shape.adds_nulls);
+
+        let shape = select_shape("SELECT * FROM a LEFT JOIN (SELECT 1 AS x) d ON true").unwrap();
+        assert_eq!(shape.items, vec![SelectItem::AllColumns { qualifier: None }]);
+        assert_eq!(shape.tables[1].table, None);
+        assert_eq!(shape.tables[1].alias.as_deref(), Some("d"));
+        assert!(shape.adds_nulls);
+
+        assert!(select_shape("SELECT 1").unwrap().tables.is_empty());
+        assert!(select_shape("SELECT 1 FROM DUAL").unwrap().tables.is_empty());
+        // The result columns of these do not come from one select list
+        assert!(select_shape("SELECT a FROM t UNION SELECT b FROM u").is_none());
+        assert!(select_shape("WITH c AS (SELECT 1) SELECT * FROM c").is_none());
+        assert!(select_shape("SHOW TABLES").is_none());
+    }
+
+    #[test]
+    fn test_with_max_execution_time() {
+        assert_eq!(
+            with_max_execution_time("SELECT * FROM users", 1500),
+            "SELECT /*+ MAX_EXECUTION_TIME(1500) */ * FROM users"
+        );
+        assert_eq!(
+            with_max_execution_time("/* report */ select id FROM t UNION SELECT 1", 10),
+            "/* report */ select /*+ MAX_EXECUTION_TIME(10) */ id FROM t UNION SELECT 1"
+        );
+        // Only a leading SELECT can carry the hint
+        for sql in [
+            "SHOW TABLES",
+            "WITH c AS (SELECT 1) SELECT * FROM c",
+            "(SELECT 1) UNION (SELECT 2)",
+            "UPDATE t SET a = (SELECT 1)",
+            "SELECT /*+ MAX_EXECUTION_TIME(5) */ 1",
+            "",
+        ] {
+            assert_eq!(with_max_execution_time(sql, 1000), sql);
+        }
+    }
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(quote_identifier("users"), "`users`");
+        assert_eq!(quote_identifier("odd`name"), "`odd``name`");
+    }
+}
@@ -1,17 +1,46 @@
+use crate::advisor::{self, IndexAdvice, QueryShape, UnusedIndex};
+use crate::breaker::CircuitInfo;
+use crate::cache::TtlCache;
+use crate::config::{ExportConfig, ImportConfig, ResultLimits, StreamConfig};
+use crate::digest::{
+    DigestSource, DigestStats, SlowLogAggregator, SlowLogReader, TopQueries, TopQueriesOrder,
+};
 use crate::error::{McpError, Result};
+use crate::explain::{self, ExplainResult};
+use crate::export::{self, Compression, ExportFormat, ExportTarget, ExportedFile};
+use crate::format::ResultFormat;
+use crate::import::{
+    self, ImportBatch, ImportFormat, ImportIssue, ImportPlan, OnDuplicate, ReadSummary,
+    RecordReader,
+};
 use crate::manager::{DataSourceManager, DataSourceInfo};
-use crate::pool::ConnectionPoolManager;
+use crate::params::{BoundStatement, StatementParams};
+use crate::pool::{
+    get_or_create_pool_manager, ConnectionPoolManager, StatementConnection, TrackedConnection,
+};
+use crate::sql::{self, SelectItem, SelectShape, StatementKind};
+use crate::transaction::{OpenTransaction, TransactionManager};
+pub use crate::transaction::IsolationLevel;
+use crate::types::TypeMapping;
+use crate::variables::{self, ServerVariable, Snapshot, StatusReport, VariableDiff};
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
-use sqlx::{Column, Row, TypeInfo, ValueRef};
-use std::collections::HashMap;
-use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::{RwLock, Mutex};
+use sqlx::mysql::{MySqlConnection, MySqlQueryResult, MySqlRow};
+use sqlx::{Column, Connection, Executor, MySql, Row, Statement, TypeInfo};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot, Mutex, OnceCell, RwLock};
 
 /// Query tool for executing SQL queries
 pub struct QueryTool {
     manager: Arc<DataSourceManager>,
     pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>>,
+    timeout: Option<Duration>,
+    max_rows: Option<usize>,
+    max_result_bytes: Option<usize>,
+    format: ResultFormat,
 }
 
 impl QueryTool {
@@ -23,9 +52,39 @@ impl QueryTool {
         Self {
             manager,
             pool_managers,
+            timeout: None,
+            max_rows: None,
+            max_result_bytes: None,
+            format: ResultFormat::default(),
         }
     }
 
+    /// Request a timeout for this call instead of the configured one
+    /// (capped at `max_query_timeout_secs`); `None` keeps the configured one
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Request result limits for this call instead of the configured ones
+    /// (capped at the server's `max_rows` and `max_result_bytes`); `None` keeps
+    /// the configured one
+    pub fn with_result_limits(
+        mut self,
+        max_rows: Option<usize>,
+        max_result_bytes: Option<usize>,
+    ) -> Self {
+        self.max_rows = max_rows;
+        self.max_result_bytes = max_result_bytes;
+        self
+    }
+
+    /// Encode the chunks of streams opened by this tool in `format`
+    pub fn with_format(mut self, format: ResultFormat) -> Self {
+        self.format = format;
+        self
+    }
+
     /// Execute a SQL query
     pub async fn execute(
         &self,
@@ -33,6 +92,163 @@ impl QueryTool {
         database: &str,
         query: &str,
     ) -> Result<QueryResult> {
+        self.execute_with_params(datasource_key, database, query, &StatementParams::default())
+            .await
+    }
+
+    /// Execute a SQL query with bind values for its `?` or `:name` placeholders
+    pub async fn execute_with_params(
+        &self,
+        datasource_key: &str,
+        database: &str,
+        query: &str,
+        params: &StatementParams,
+    ) -> Result<QueryResult> {
+        let bound = self
+            .prepare(datasource_key, database, query, params, false)
+            .await?;
+
+        // Get or create pool manager for this data source
+        let pool_manager =
+            get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key).await?;
+
+        // Read-only queries are safe to repeat after a transient failure
+        self.manager
+            .retry(datasource_key, || {
+                self.run(
+                    datasource_key,
+                    database,
+                    pool_manager.get_tracked_connection(database),
+                    &bound,
+                )
+            })
+            .await
+    }
+
+    /// Execute a SQL query inside a transaction opened with `mysql_begin`
+    pub async fn execute_in_transaction(
+        &self,
+        transactions: &TransactionManager,
+        transaction_id: &str,
+        datasource_key: &str,
+        database: &str,
+        query: &str,
+        params: &StatementParams,
+    ) -> Result<QueryResult> {
+        let bound = self
+            .prepare(datasource_key, database, query, params, true)
+            .await?;
+        let transaction = transactions.get(transaction_id)?;
+        check_transaction_target(&transaction, datasource_key, database)?;
+
+        let result = self
+            .run(datasource_key, database, transaction.connection(), &bound)
+            .await;
+
+        if matches!(result, Err(McpError::QueryTimeout { thread_id: Some(_) })) {
+            // Killing the query took the pinned connection with it
+            transactions.abort(transaction_id).await;
+        }
+        result
+    }
+
+    /// Run a query and stream its rows, returning the first chunk
+    ///
+    /// Rows are read from the server as chunks are fetched rather than loaded into
+    /// memory at once. Unless the first chunk is the last, the stream is registered
+    /// with `streams` and keeps its connection until it is exhausted, closed or
+    /// expires. The timeout applies to reading each chunk.
+    pub async fn open_stream(
+        &self,
+        streams: &StreamManager,
+        datasource_key: &str,
+        database: &str,
+        query: &str,
+        params: &StatementParams,
+        chunk_size: usize,
+    ) -> Result<StreamChunk> {
+        // Before the availability check, so a refused stream does not take the
+        // circuit's half-open probe
+        streams.check_capacity().await?;
+        let bound = self
+            .prepare(datasource_key, database, query, params, false)
+            .await?;
+
+        let timeout = self.manager.query_timeout(datasource_key, self.timeout);
+        let session_timeout = self.manager.query_timeout(datasource_key, None);
+        let chunk_size = chunk_size.max(1);
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        let opened = async {
+            // Get or create pool manager for this data source
+            let pool_manager =
+                get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key)
+                    .await?;
+
+            let conn =
+                tokio::time::timeout_at(deadline, pool_manager.get_tracked_connection(database))
+                    .await
+                    .map_err(|_| McpError::QueryTimeout { thread_id: None })??;
+            let thread_id = conn.thread_id();
+            let timed_out = |_| McpError::QueryTimeout {
+                thread_id: Some(thread_id),
+            };
+
+            // Dropping the stream kills the query
+            let stream = tokio::time::timeout_at(
+                deadline,
+                QueryResultStream::from_query(
+                    conn,
+                    bound,
+                    session_timeout,
+                    chunk_size,
+                    timeout,
+                    self.manager.type_mapping(),
+                ),
+            )
+            .await
+            .map_err(timed_out)??
+            .with_format(self.format);
+            let chunk = tokio::time::timeout_at(deadline, stream.read_chunk())
+                .await
+                .map_err(timed_out)??;
+            Ok((stream, chunk, thread_id))
+        }
+        .await;
+        self.manager.record_call(datasource_key, &opened).await;
+        let (stream, chunk, thread_id) = opened?;
+
+        tracing::info!(
+            datasource_key = %datasource_key,
+            database = %database,
+            stream_id = %stream.stream_id(),
+            thread_id = thread_id,
+            rows = chunk.rows.len(),
+            is_last = chunk.is_last,
+            "Query stream opened"
+        );
+
+        let stream_id = stream.stream_id().to_string();
+        if !chunk.is_last {
+            streams.try_register_stream(stream).await?;
+        }
+
+        Ok(StreamChunk {
+            stream_id,
+            chunk,
+            format: self.format,
+        })
+    }
+
+    /// Validate a query and bind its values
+    async fn prepare(
+        &self,
+        datasource_key: &str,
+        database: &str,
+        query: &str,
+        params: &StatementParams,
+        in_transaction: bool,
+    ) -> Result<BoundStatement> {
         // Validate parameters
         if datasource_key.is_empty() {
             return Err(McpError::InvalidStatement(
@@ -56,6 +272,38 @@ impl QueryTool {
         // Check query permission
         self.manager.check_query_permission(datasource_key)?;
 
+        // Check the database allowlist
+        self.manager
+            .check_database_access(datasource_key, database)?;
+
+        // Extract only the first statement if multiple statements are present
+        let first_statement = sql::first_statement(query);
+
+        // Only statements that are provably read-only may run on the query path
+        let kind = sql::classify(first_statement);
+        if in_transaction && sql::is_locking_read(first_statement) {
+            // Its locks are released with the transaction; taking them needs update permission
+            self.manager.check_update_permission(datasource_key)?;
+        } else if !kind.is_read_only() {
+            tracing::warn!(
+                datasource_key = %datasource_key,
+                database = %database,
+                statement_kind = %kind,
+                "Rejected non read-only statement on query path"
+            );
+            return Err(McpError::InvalidStatement(format!(
+                "mysql_query only accepts read-only statements, got a {}; use mysql_execute instead",
+                kind
+            )));
+        }
+
+        // Qualified names must not reach databases outside the allowlist
+        self.manager
+            .check_statement_database_access(datasource_key, first_statement)?;
+
+        // Match bind values against the statement's placeholders
+        let bound = params.bind(first_statement)?;
+
         // Check if data source is available
         if !self.manager.is_available(datasource_key).await {
             return Err(McpError::DataSourceUnavailable(format!(
@@ -64,228 +312,556 @@ impl QueryTool {
             )));
         }
 
-        // Extract only the first statement if multiple statements are present
-        let first_statement = extract_first_statement(query);
-
         tracing::info!(
             datasource_key = %datasource_key,
             database = %database,
             query_length = first_statement.len(),
+            param_count = bound.args.len(),
             "Executing query"
         );
 
-        // Get or create pool manager for this data source
-        let mut pool_managers = self.pool_managers.write().await;
-        if !pool_managers.contains_key(datasource_key) {
-            let config = self
-                .manager
-                .get_source(datasource_key)
-                .ok_or_else(|| McpError::InvalidDataSourceKey(datasource_key.to_string()))?;
-            let pool_manager = ConnectionPoolManager::new((*config).clone()).await?;
-            pool_managers.insert(datasource_key.to_string(), pool_manager);
-        }
-
-        let pool_manager = pool_managers.get_mut(datasource_key).unwrap();
+        Ok(bound)
+    }
 
+    /// Run a query with timeout and result limits and log the outcome
+    ///
+    /// Waiting for the connection counts towards the timeout. A query still
+    /// running when it expires is killed on the server along with its connection,
+    /// and so is one whose result was truncated.
+    async fn run<C: StatementConnection>(
+        &self,
+        datasource_key: &str,
+        database: &str,
+        connection: impl Future<Output = Result<C>>,
+        query: &BoundStatement,
+    ) -> Result<QueryResult> {
         // Execute query with timeout
+        let timeout = self.manager.query_timeout(datasource_key, self.timeout);
+        let session_timeout = self.manager.query_timeout(datasource_key, None);
+        let limits =
+            self.manager
+                .result_limits(datasource_key, self.max_rows, self.max_result_bytes);
+        let mapping = self.manager.type_mapping();
         let start = std::time::Instant::now();
-        let result = tokio::time::timeout(
-            Duration::from_secs(30),
-            execute_query(pool_manager, database, first_statement),
-        )
-        .await;
+        let deadline = tokio::time::Instant::from_std(start) + timeout;
+        let result = match tokio::time::timeout_at(deadline, connection).await {
+            Ok(Ok(mut conn)) => {
+                let query = async {
+                    let limited = limit_execution_time(&mut conn, timeout).await;
+                    (execute_query(&mut conn, query, limits, &mapping).await, limited)
+                };
+                match tokio::time::timeout_at(deadline, query).await {
+                    Ok((Ok(result), _)) if result.truncated => {
+                        conn.abandon();
+                        Ok(result)
+                    }
+                    Ok((result, limited)) => {
+                        if !limited
+                            || timeout == session_timeout
+                            || restore_execution_time(&mut conn, session_timeout).await
+                        {
+                            conn.finish();
+                        }
+                        result
+                    }
+                    // Dropping the unfinished connection kills the query
+                    Err(_) => Err(McpError::QueryTimeout {
+                        thread_id: Some(conn.thread_id()),
+                    }),
+                }
+            }
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(McpError::QueryTimeout { thread_id: None }),
+        };
+        self.manager.record_call(datasource_key, &result).await;
 
         let execution_time = start.elapsed();
 
         match result {
-            Ok(Ok(query_result)) => {
+            Ok(query_result) => {
                 tracing::info!(
                     datasource_key = %datasource_key,
                     database = %database,
                     rows = query_result.rows.len(),
+                    truncated = query_result.truncated,
                     execution_time_ms = execution_time.as_millis(),
                     "Query executed successfully"
                 );
                 Ok(query_result)
             }
-            Ok(Err(e)) => {
+            Err(McpError::QueryTimeout { thread_id }) => {
                 tracing::error!(
                     datasource_key = %datasource_key,
                     database = %database,
-                    error = %e.sanitize(),
-                    execution_time_ms = execution_time.as_millis(),
-                    "Query execution failed"
+                    timeout_ms = timeout.as_millis(),
+                    killed_thread_id = ?thread_id,
+                    "Query execution timed out"
                 );
-                Err(e)
+                Err(McpError::QueryTimeout { thread_id })
             }
-            Err(_) => {
+            Err(e) => {
                 tracing::error!(
                     datasource_key = %datasource_key,
                     database = %database,
-                    "Query execution timed out after 30 seconds"
+                    error = %e.sanitize(),
+                    execution_time_ms = execution_time.as_millis(),
+                    "Query execution failed"
                 );
-                Err(McpError::QueryTimeout)
+                Err(e)
             }
         }
     }
 }
 
-/// Extract the first SQL statement from a query string
-/// Handles multiple statements separated by semicolons
-fn extract_first_statement(query: &str) -> &str {
-    let trimmed = query.trim();
+/// Check that a transaction belongs to the data source and database of a call
+fn check_transaction_target(
+    transaction: &OpenTransaction,
+    datasource_key: &str,
+    database: &str,
+) -> Result<()> {
+    if transaction.datasource_key() != datasource_key || transaction.database() != database {
+        return Err(McpError::InvalidStatement(format!(
+            "Transaction '{}' belongs to data source '{}', database '{}'",
+            transaction.id(),
+            transaction.datasource_key(),
+            transaction.database()
+        )));
+    }
+    Ok(())
+}
 
-    // Find the first semicolon that's not inside a string literal
-    let mut in_single_quote = false;
-    let mut in_double_quote = false;
-    let mut escape_next = false;
+/// Set the session's `max_execution_time` to a call's timeout, so a client-side
+/// timeout does not leave the query running on the server
+///
+/// Unlike a `MAX_EXECUTION_TIME` hint, which only takes effect after a leading
+/// SELECT, the session limit applies to WITH and UNION queries too. Returns
+/// whether the limit was set; servers without it (e.g. MariaDB) rely on the
+/// client-side timeout alone.
+async fn limit_execution_time(conn: &mut MySqlConnection, timeout: Duration) -> bool {
+    let limit = format!("SET SESSION max_execution_time = {}", timeout.as_millis());
+    conn.execute(limit.as_str()).await.is_ok()
+}
 
-    for (i, ch) in trimmed.char_indices() {
-        if escape_next {
-            escape_next = false;
-            continue;
-        }
+/// Put the session's `max_execution_time` back to the data source's query
+/// timeout before the connection is reused, returning whether that succeeded
+async fn restore_execution_time(conn: &mut MySqlConnection, session_timeout: Duration) -> bool {
+    limit_execution_time(conn, session_timeout).await
+}
 
-        match ch {
-            '\\' => escape_next = true,
-            '\'' if !in_double_quote => in_single_quote = !in_single_quote,
-            '"' if !in_single_quote => in_double_quote = !in_double_quote,
-            ';' if !in_single_quote && !in_double_quote => {
-                return trimmed[..i].trim();
-            }
-            _ => {}
+/// Execute a query and return the results
+///
+/// Rows are read until the result set ends or a limit is hit; the rest of a
+/// truncated result set is left unread.
+async fn execute_query(
+    conn: &mut MySqlConnection,
+    query: &BoundStatement,
+    limits: ResultLimits,
+    mapping: &TypeMapping,
+) -> Result<QueryResult> {
+    let columns = prepare_columns(conn, query).await?;
+    let mut rows = query.query().fetch(conn);
+    let mut limiter = ResultLimiter::new(limits);
+    limiter.columns = columns;
+
+    while let Some(row) = rows.next().await {
+        let row = row.map_err(McpError::from)?;
+
+        if !limiter.push(to_query_row(&row, mapping)) {
+            break;
         }
     }
 
-    // No semicolon found or only one statement
-    trimmed
+    Ok(limiter.finish())
 }
 
-/// Execute a query and return the results
-async fn execute_query(
-    pool_manager: &mut ConnectionPoolManager,
-    database: &str,
-    query: &str,
-) -> Result<QueryResult> {
-    let pool = pool_manager.get_pool(database).await?;
+/// Collects query rows up to the result limits
+struct ResultLimiter {
+    limits: ResultLimits,
+    columns: Vec<ColumnMetadata>,
+    rows: Vec<QueryRow>,
+    bytes: usize,
+    truncated: bool,
+}
 
-    // Execute the query
-    let rows: Vec<sqlx::mysql::MySqlRow> = sqlx::query(query)
-        .fetch_all(pool)
-        .await
-        .map_err(|e| {
-            // Check if it's a database not found error
-            let error_msg = e.to_string();
-            if error_msg.contains("Unknown database") {
-                McpError::DatabaseNotFound(database.to_string())
+impl ResultLimiter {
+    fn new(limits: ResultLimits) -> Self {
+        Self {
+            limits,
+            columns: Vec::new(),
+            rows: Vec::new(),
+            bytes: 0,
+            truncated: false,
+        }
+    }
+
+    /// Add a row, returning false once a limit is hit and the row was left out
+    fn push(&mut self, row: QueryRow) -> bool {
+        let row_bytes = serde_json::to_vec(&row.values).map_or(0, |json| json.len());
+        if self.rows.len() >= self.limits.max_rows
+            || self.bytes + row_bytes > self.limits.max_result_bytes
+        {
+            self.truncated = true;
+            return false;
+        }
+
+        self.bytes += row_bytes;
+        self.rows.push(row);
+        true
+    }
+
+    fn finish(self) -> QueryResult {
+        let hint = self.truncated.then(|| {
+            let limit = if self.rows.len() >= self.limits.max_rows {
+                format!("max_rows ({})", self.limits.max_rows)
             } else {
-                McpError::QueryExecutionError(error_msg)
-            }
-        })?;
+                format!("max_result_bytes ({})", self.limits.max_result_bytes)
+            };
+            format!(
+                "Result truncated at {}; page through it with LIMIT/OFFSET or a keyset WHERE clause, or read all of it with mysql_query_stream",
+                limit
+            )
+        });
+
+        QueryResult {
+            columns: self.columns,
+            rows_returned: self.rows.len(),
+            rows: self.rows,
+            affected_rows: 0, // SELECT queries don't have affected rows
+            truncated: self.truncated,
+            hint,
+        }
+    }
+}
 
-    // Extract column metadata from the first row (if any)
-    let columns = if let Some(first_row) = rows.first() {
-        first_row
-            .columns()
-            .iter()
-            .map(|col| ColumnMetadata {
-                name: col.name().to_string(),
-                data_type: col.type_info().name().to_string(),
-                nullable: true, // MySQL doesn't provide this info easily from query results
-            })
-            .collect()
-    } else {
-        vec![]
+/// Prepare a query and return the metadata of its result columns
+///
+/// Every query runs as a prepared statement, and the driver caches it, so the
+/// query itself reuses this one. Unlike the rows, the statement lists the
+/// columns of an empty result too.
+///
+/// The driver does not expose the column definitions beyond their type, so
+/// columns the select list takes straight from a table are described from
+/// `information_schema.COLUMNS`; computed columns only have their type.
+async fn prepare_columns(
+    conn: &mut MySqlConnection,
+    query: &BoundStatement,
+) -> Result<Vec<ColumnMetadata>> {
+    let statement = conn
+        .prepare(&query.sql)
+        .await
+        .map_err(McpError::from)?;
+    let columns = statement.columns();
+
+    let shape = sql::select_shape(&query.sql).filter(|shape| !shape.tables.is_empty());
+    let source_columns = match &shape {
+        Some(shape) if !columns.is_empty() => source_columns(conn, shape)
+            .await
+            .unwrap_or_else(|e| {
+                // The columns are still listed, only without their table attributes
+                tracing::debug!(error = %e.sanitize(), "Failed to look up result column origins");
+                Vec::new()
+            }),
+        _ => Vec::new(),
+    };
+
+    let names: Vec<&str> = columns.iter().map(|col| col.name()).collect();
+    let origins = match &shape {
+        Some(shape) => column_origins(shape, &names, &source_columns),
+        None => vec![None; columns.len()],
     };
+    let adds_nulls = shape.as_ref().is_some_and(|shape| shape.adds_nulls);
 
-    // Convert rows to our format
-    let result_rows: Vec<QueryRow> = rows
+    Ok(columns
         .iter()
+        .zip(origins)
+        .map(|(col, origin)| {
+            describe_column(col.name(), col.type_info().name(), origin, adds_nulls)
+        })
+        .collect())
+}
+
+/// A column of a table a query selects from, as `information_schema.COLUMNS`
+/// describes it
+#[derive(Debug, Clone, Default, PartialEq)]
+struct SourceColumn {
+    /// The connection's default database, which unqualified tables are in
+    current_schema: String,
+    schema: String,
+    table: String,
+    name: String,
+    nullable: bool,
+    /// e.g. `int(10) unsigned zerofill`
+    column_type: String,
+    key: String,
+    extra: String,
+    character_set: Option<String>,
+    max_length: Option<u64>,
+    precision: Option<u64>,
+    scale: Option<u64>,
+    datetime_precision: Option<u64>,
+}
+
+/// Look up the columns of the tables in a SELECT's FROM clause
+async fn source_columns(
+    conn: &mut MySqlConnection,
+    shape: &SelectShape,
+) -> Result<Vec<SourceColumn>> {
+    let mut tables: Vec<(Option<&str>, &str)> = Vec::new();
+    for table in &shape.tables {
+        if let Some(name) = &table.table {
+            let table = (table.schema.as_deref(), name.as_str());
+            if !tables.contains(&table) {
+                tables.push(table);
+            }
+        }
+    }
+    if tables.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let condition = vec!["(TABLE_SCHEMA = COALESCE(?, DATABASE()) AND TABLE_NAME = ?)"; tables.len()];
+    let query = format!(
+        "SELECT
+            COALESCE(DATABASE(), '') AS current_schema,
+            CAST(TABLE_SCHEMA AS CHAR) AS table_schema,
+            CAST(TABLE_NAME AS CHAR) AS table_name,
+            CAST(COLUMN_NAME AS CHAR) AS column_name,
+            CAST(IS_NULLABLE AS CHAR) AS is_nullable,
+            CAST(COLUMN_TYPE AS CHAR) AS column_type,
+            CAST(COLUMN_KEY AS CHAR) AS column_key,
+            CAST(EXTRA AS CHAR) AS extra,
+            CAST(CHARACTER_SET_NAME AS CHAR) AS character_set,
+            CAST(CHARACTER_MAXIMUM_LENGTH AS UNSIGNED) AS max_length,
+            CAST(NUMERIC_PRECISION AS UNSIGNED) AS numeric_precision,
+            CAST(NUMERIC_SCALE AS UNSIGNED) AS numeric_scale,
+            CAST(DATETIME_PRECISION AS UNSIGNED) AS datetime_precision
+         FROM information_schema.COLUMNS
+         WHERE {}
+         ORDER BY TABLE_SCHEMA, TABLE_NAME, ORDINAL_POSITION",
+        condition.join(" OR ")
+    );
+
+    let mut lookup = sqlx::query(&query);
+    for (schema, table) in tables {
+        lookup = lookup.bind(schema).bind(table);
+    }
+    let rows = lookup.fetch_all(conn).await.map_err(McpError::from)?;
+
+    rows.iter()
         .map(|row| {
-            let values: Vec<serde_json::Value> = row
-                .columns()
+            Ok(SourceColumn {
+                current_schema: row.try_get("current_schema")?,
+                schema: row.try_get("table_schema")?,
+                table: row.try_get("table_name")?,
+                name: row.try_get("column_name")?,
+                nullable: row.try_get::<String, _>("is_nullable")? == "YES",
+                column_type: row.try_get("column_type")?,
+                key: row.try_get("column_key")?,
+                extra: row.try_get("extra")?,
+                character_set: row.try_get("character_set")?,
+                max_length: row.try_get("max_length")?,
+                precision: row.try_get("numeric_precision")?,
+                scale: row.try_get("numeric_scale")?,
+                datetime_precision: row.try_get("datetime_precision")?,
+            })
+        })
+        .collect::<std::result::Result<_, sqlx::Error>>()
+        .map_err(McpError::from)
+}
+
+/// The table column each result column is selected from, where the select
+/// list names one
+///
+/// `*` expands to the columns of the tables in order. If the expansion does not
+/// line up with the result columns, e.g. because `NATURAL JOIN` merged columns,
+/// no origins are reported at all rather than wrong ones.
+fn column_origins<'c>(
+    shape: &SelectShape,
+    names: &[&str],
+    source_columns: &'c [SourceColumn],
+) -> Vec<Option<&'c SourceColumn>> {
+    let unknown = || vec![None; names.len()];
+
+    // Columns of each table reference; `None` for derived tables and tables
+    // `information_schema` does not list, such as temporary ones
+    let tables: Vec<Option<Vec<&SourceColumn>>> = shape
+        .tables
+        .iter()
+        .map(|reference| {
+            let table = reference.table.as_deref()?;
+            let columns: Vec<&SourceColumn> = source_columns
                 .iter()
-                .enumerate()
-                .map(|(i, col)| {
-                    // Try to extract the value based on the column type
-                    extract_value(row, i, col.type_info().name())
+                .filter(|column| {
+                    let schema = reference.schema.as_deref().unwrap_or(&column.current_schema);
+                    column.schema.eq_ignore_ascii_case(schema)
+                        && column.table.eq_ignore_ascii_case(table)
                 })
                 .collect();
-
-            QueryRow { values }
+            (!columns.is_empty()).then_some(columns)
         })
         .collect();
+    // The table references a qualifier can name: an alias, or a table without one
+    let named = |qualifier: &Option<String>| -> Vec<usize> {
+        shape
+            .tables
+            .iter()
+            .enumerate()
+            .filter(|(_, reference)| match qualifier {
+                None => true,
+                Some(qualifier) => match (&reference.alias, &reference.table) {
+                    (Some(alias), _) => alias.eq_ignore_ascii_case(qualifier),
+                    (None, Some(table)) => table.eq_ignore_ascii_case(qualifier),
+                    (None, None) => false,
+                },
+            })
+            .map(|(i, _)| i)
+            .collect()
+    };
 
-    Ok(QueryResult {
-        columns,
-        rows: result_rows,
-        affected_rows: 0, // SELECT queries don't have affected rows
-    })
+    // Each origin with whether the result column keeps the column's name
+    let mut origins: Vec<(Option<&SourceColumn>, bool)> = Vec::new();
+    for item in &shape.items {
+        match item {
+            SelectItem::Expression => origins.push((None, false)),
+            SelectItem::Column {
+                qualifier,
+                column,
+                aliased,
+            } => {
+                let mut found = Vec::new();
+                for i in named(qualifier) {
+                    match &tables[i] {
+                        Some(columns) => found.extend(
+                            columns
+                                .iter()
+                                .filter(|source| source.name.eq_ignore_ascii_case(column))
+                                .map(|source| Some(*source)),
+                        ),
+                        // An unknown table may have it too
+                        None => found.push(None),
+                    }
+                }
+                let origin = match found.as_slice() {
+                    [Some(source)] => Some(*source),
+                    _ => None,
+                };
+                origins.push((origin, !aliased));
+            }
+            SelectItem::AllColumns { qualifier } => {
+                for i in named(qualifier) {
+                    match &tables[i] {
+                        Some(columns) => origins.extend(columns.iter().map(|c| (Some(*c), true))),
+                        None => return unknown(),
+                    }
+                }
+            }
+        }
+    }
+
+    let lined_up = origins.len() == names.len()
+        && origins.iter().zip(names).all(|((origin, named_after), name)| {
+            !named_after || origin.is_none_or(|origin| origin.name.eq_ignore_ascii_case(name))
+        });
+    if !lined_up {
+        return unknown();
+    }
+    origins.into_iter().map(|(origin, _)| origin).collect()
 }
 
-/// Extract a value from a row at the given index
-fn extract_value(row: &sqlx::mysql::MySqlRow, index: usize, type_name: &str) -> serde_json::Value {
-    use sqlx::Row;
+/// Binary string types, as the driver names them
+const BINARY_TYPES: &[&str] = &["BINARY", "VARBINARY", "TINYBLOB", "BLOB", "MEDIUMBLOB", "LONGBLOB"];
+
+/// Metadata of a result column from its type and, if it is selected straight
+/// from a table, the table column
+fn describe_column(
+    name: &str,
+    data_type: &str,
+    origin: Option<&SourceColumn>,
+    adds_nulls: bool,
+) -> ColumnMetadata {
+    let mut metadata = ColumnMetadata {
+        name: name.to_string(),
+        data_type: data_type.to_string(),
+        nullable: true,
+        unsigned: data_type.ends_with(" UNSIGNED"),
+        binary: BINARY_TYPES.contains(&data_type),
+        ..Default::default()
+    };
 
-    // Check if the value is NULL
-    if row.try_get_raw(index).map(|v| v.is_null()).unwrap_or(true) {
-        return serde_json::Value::Null;
+    if let Some(source) = origin {
+        let column_type = source.column_type.to_ascii_lowercase();
+        metadata.nullable = source.nullable || adds_nulls;
+        metadata.unsigned |= column_type.contains(" unsigned");
+        metadata.zerofill = column_type.contains(" zerofill");
+        metadata.length = source.max_length.map(|length| u32::try_from(length).unwrap_or(u32::MAX));
+        metadata.character_set = source.character_set.clone();
+        metadata.precision = source.precision;
+        metadata.scale = source.scale.or(source.datetime_precision);
+        metadata.key = Some(source.key.clone()).filter(|key| !key.is_empty());
+        metadata.auto_increment = source.extra.to_ascii_lowercase().contains("auto_increment");
+        metadata.origin = Some(ColumnOrigin {
+            schema: source.schema.clone(),
+            table: source.table.clone(),
+            column: source.name.clone(),
+        });
     }
+    metadata
+}
 
-    // Try to extract based on type
-    match type_name.to_uppercase().as_str() {
-        "TINYINT" | "SMALLINT" | "INT" | "MEDIUMINT" | "INTEGER" => {
-            row.try_get::<i32, _>(index)
-                .ok()
-                .map(|v| serde_json::json!(v))
-                .unwrap_or(serde_json::Value::Null)
-        }
-        "BIGINT" => row
-            .try_get::<i64, _>(index)
-            .ok()
-            .map(|v| serde_json::json!(v))
-            .unwrap_or(serde_json::Value::Null),
-        "FLOAT" | "DOUBLE" | "DECIMAL" | "NUMERIC" => row
-            .try_get::<f64, _>(index)
-            .ok()
-            .map(|v| serde_json::json!(v))
-            .unwrap_or(serde_json::Value::Null),
-        "BOOLEAN" | "BOOL" => row
-            .try_get::<bool, _>(index)
-            .ok()
-            .map(|v| serde_json::json!(v))
-            .unwrap_or(serde_json::Value::Null),
-        "DATE" | "DATETIME" | "TIMESTAMP" => row
-            .try_get::<chrono::NaiveDateTime, _>(index)
-            .ok()
-            .map(|v| serde_json::json!(v.to_string()))
-            .or_else(|| {
-                row.try_get::<chrono::NaiveDate, _>(index)
-                    .ok()
-                    .map(|v| serde_json::json!(v.to_string()))
-            })
-            .unwrap_or(serde_json::Value::Null),
-        "TIME" => row
-            .try_get::<chrono::NaiveTime, _>(index)
-            .ok()
-            .map(|v| serde_json::json!(v.to_string()))
-            .unwrap_or(serde_json::Value::Null),
-        _ => {
-            // Default to string for all other types
-            row.try_get::<String, _>(index)
-                .ok()
-                .map(|v| serde_json::json!(v))
-                .unwrap_or(serde_json::Value::Null)
-        }
-    }
+/// Convert a result row to our format
+fn to_query_row(row: &MySqlRow, mapping: &TypeMapping) -> QueryRow {
+    let values: Vec<serde_json::Value> = (0..row.columns().len())
+        .map(|i| mapping.column_value(row, i))
+        .collect();
+
+    QueryRow { values }
 }
 
 /// Column metadata
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The type comes from the statement's column definition; the other attributes
+/// come from the table column the value is selected from, and are unset for
+/// computed columns.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ColumnMetadata {
     pub name: String,
     pub data_type: String,
+    /// Whether the column can be NULL; computed columns always can
     pub nullable: bool,
+    #[serde(default)]
+    pub unsigned: bool,
+    #[serde(default)]
+    pub zerofill: bool,
+    /// Binary string: `BINARY`, `VARBINARY` or a `BLOB` type
+    #[serde(default)]
+    pub binary: bool,
+    /// Maximum length of string columns, in characters (bytes for binary strings)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub length: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub character_set: Option<String>,
+    /// Total digits of numeric columns
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub precision: Option<u64>,
+    /// Digits after the decimal point, or fractional second digits of temporal columns
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<u64>,
+    /// The column's key in its table: `PRI`, `UNI` or `MUL`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(default)]
+    pub auto_increment: bool,
+    /// The table column the value is selected from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<ColumnOrigin>,
+}
+
+/// A table column a result column is selected from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnOrigin {
+    pub schema: String,
+    pub table: String,
+    pub column: String,
 }
 
 /// A row in the query result
@@ -300,78 +876,29 @@ pub struct QueryResult {
     pub columns: Vec<ColumnMetadata>,
     pub rows: Vec<QueryRow>,
     pub affected_rows: u64,
+    /// Whether rows were left out because a result limit was hit
+    #[serde(default)]
+    pub truncated: bool,
+    /// Number of rows in `rows`
+    #[serde(default)]
+    pub rows_returned: usize,
+    /// How to get the rest of a truncated result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{DataSourceConfig, PoolConfig};
-
-    #[test]
-    fn test_extract_first_statement_single() {
-        let query = "SELECT * FROM users";
-        assert_eq!(extract_first_statement(query), "SELECT * FROM users");
-    }
-
-    #[test]
-    fn test_extract_first_statement_multiple() {
-        let query = "SELECT * FROM users; DELETE FROM users;";
-        assert_eq!(extract_first_statement(query), "SELECT * FROM users");
-    }
-
-    #[test]
-    fn test_extract_first_statement_with_semicolon_in_string() {
-        let query = "SELECT * FROM users WHERE name = 'test;data'; DELETE FROM users;";
-        assert_eq!(
-            extract_first_statement(query),
-            "SELECT * FROM users WHERE name = 'test;data'"
-        );
-    }
-
-    #[test]
-    fn test_extract_first_statement_with_double_quotes() {
-        let query = r#"SELECT * FROM users WHERE name = "test;data"; DELETE FROM users;"#;
-        assert_eq!(
-            extract_first_statement(query),
-            r#"SELECT * FROM users WHERE name = "test;data""#
-        );
-    }
-
-    #[test]
-    fn test_extract_first_statement_with_escaped_quotes() {
-        let query = r"SELECT * FROM users WHERE name = 'test\'s;data'; DELETE FROM users;";
-        assert_eq!(
-            extract_first_statement(query),
-            r"SELECT * FROM users WHERE name = 'test\'s;data'"
-        );
-    }
-
-    #[test]
-    fn test_extract_first_statement_no_semicolon() {
-        let query = "SELECT * FROM users WHERE id = 1";
-        assert_eq!(
-            extract_first_statement(query),
-            "SELECT * FROM users WHERE id = 1"
-        );
-    }
-
-    #[test]
-    fn test_extract_first_statement_whitespace() {
-        let query = "  SELECT * FROM users  ; DELETE FROM users;  ";
-        assert_eq!(extract_first_statement(query), "SELECT * FROM users");
-    }
+    use crate::config::DataSourceConfig;
 
     fn create_test_config(key: &str) -> DataSourceConfig {
         DataSourceConfig {
             key: key.to_string(),
             name: "Test Database".to_string(),
-            host: "localhost".to_string(),
-            port: 3306,
             username: "test".to_string(),
             password: "test".to_string(),
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: crate::config::Permission::default(),
+            ..Default::default()
         }
     }
 
@@ -426,87 +953,599 @@ mod tests {
         ));
     }
 
-    #[test]
-    fn test_is_ddl_statement_create() {
-        assert!(is_ddl_statement("CREATE TABLE users (id INT)"));
-        assert!(is_ddl_statement("create table users (id INT)"));
-        assert!(is_ddl_statement("  CREATE TABLE users (id INT)"));
-    }
-
-    #[test]
-    fn test_is_ddl_statement_alter() {
-        assert!(is_ddl_statement("ALTER TABLE users ADD COLUMN name VARCHAR(255)"));
-        assert!(is_ddl_statement("alter table users add column name varchar(255)"));
-    }
+    #[tokio::test]
+    async fn test_query_tool_rejects_non_read_only_statements() {
+        let mut config = create_test_config("test-db");
+        config.permission = crate::config::Permission::Ddl;
+        let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
+        let pool_managers = Arc::new(RwLock::new(HashMap::new()));
+        let tool = QueryTool::new(manager, pool_managers);
 
-    #[test]
-    fn test_is_ddl_statement_drop() {
-        assert!(is_ddl_statement("DROP TABLE users"));
-        assert!(is_ddl_statement("drop table users"));
+        for query in [
+            "DELETE FROM users",
+            "DROP TABLE users",
+            "WITH t AS (SELECT 1) DELETE FROM users",
+            "SELECT * FROM users INTO OUTFILE '/tmp/users'",
+            "SELECT * FROM users FOR UPDATE",
+            "LOCK TABLES users WRITE",
+            "SET @a = 1",
+            "CALL cleanup()",
+            "HANDLER users OPEN",
+        ] {
+            let result = tool.execute("test-db", "testdb", query).await;
+            assert!(
+                matches!(result, Err(McpError::InvalidStatement(_))),
+                "{} should be rejected",
+                query
+            );
+        }
     }
 
-    #[test]
-    fn test_is_ddl_statement_truncate() {
-        assert!(is_ddl_statement("TRUNCATE TABLE users"));
-        assert!(is_ddl_statement("truncate table users"));
+    #[tokio::test]
+    async fn test_rejected_calls_do_not_take_the_circuit_probe() {
+        let mut config = create_test_config("test-db");
+        config.permission = crate::config::Permission::Update;
+        config.circuit_breaker.failure_threshold = 1;
+        config.circuit_breaker.cool_down_secs = 0;
+        let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
+        let pool_managers = Arc::new(RwLock::new(HashMap::new()));
+        let _ = manager
+            .run_with_timeout("test-db", None, async {
+                Err::<(), _>(McpError::ConnectionFailed("Connection refused".to_string()))
+            })
+            .await;
+
+        let query_tool = QueryTool::new(manager.clone(), pool_managers.clone());
+        let result = query_tool.execute("test-db", "testdb", "DELETE FROM users").await;
+        assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+
+        let execute_tool = ExecuteTool::new(manager.clone(), pool_managers.clone());
+        let result = execute_tool
+            .execute("test-db", "testdb", "DROP TABLE users")
+            .await;
+        assert!(matches!(result, Err(McpError::PermissionDenied(_))));
+
+        let result = execute_tool
+            .execute_with_params(
+                "test-db",
+                "testdb",
+                "DELETE FROM users WHERE id = ?",
+                &StatementParams::default(),
+            )
+            .await;
+        assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+
+        let streams = StreamManager::with_config(StreamConfig {
+            max_open: 0,
+            ..Default::default()
+        });
+        let result = query_tool
+            .open_stream(&streams, "test-db", "testdb", "SELECT 1", &StatementParams::default(), 10)
+            .await;
+        assert!(matches!(result, Err(McpError::PoolError(_))));
+
+        // The open circuit is still waiting for a valid call to probe with
+        let circuit = manager.get_circuit("test-db").await.unwrap();
+        assert_eq!(circuit.state, crate::breaker::CircuitState::Open);
+        assert!(pool_managers.read().await.is_empty());
     }
 
-    #[test]
-    fn test_is_ddl_statement_rename() {
-        assert!(is_ddl_statement("RENAME TABLE users TO customers"));
-        assert!(is_ddl_statement("rename table users to customers"));
-    }
+    #[tokio::test]
+    async fn test_tools_reject_databases_outside_allowlist() {
+        let mut config = create_test_config("test-db");
+        config.databases = vec!["app_*".to_string()];
+        config.permission = crate::config::Permission::Ddl;
+        let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
+        let pool_managers = Arc::new(RwLock::new(HashMap::new()));
 
-    #[test]
-    fn test_is_not_ddl_statement() {
-        assert!(!is_ddl_statement("SELECT * FROM users"));
-        assert!(!is_ddl_statement("INSERT INTO users VALUES (1, 'test')"));
-        assert!(!is_ddl_statement("UPDATE users SET name = 'test'"));
-        assert!(!is_ddl_statement("DELETE FROM users WHERE id = 1"));
+        let query_tool = QueryTool::new(manager.clone(), pool_managers.clone());
+        let result = query_tool.execute("test-db", "billing", "SELECT 1").await;
+        assert!(matches!(result, Err(McpError::DatabaseNotAllowed(_))));
+
+        let result = query_tool
+            .execute("test-db", "app_orders", "SELECT * FROM billing.invoices")
+            .await;
+        assert!(matches!(result, Err(McpError::DatabaseNotAllowed(_))));
+
+        let execute_tool = ExecuteTool::new(manager.clone(), pool_managers.clone());
+        let result = execute_tool
+            .execute("test-db", "app_orders", "DELETE FROM billing.invoices")
+            .await;
+        assert!(matches!(result, Err(McpError::DatabaseNotAllowed(_))));
+
+        let schema_tool = SchemaTool::new(manager.clone(), pool_managers.clone());
+        let result = schema_tool.list_tables("test-db", "billing").await;
+        assert!(matches!(result, Err(McpError::DatabaseNotAllowed(_))));
+        let result = schema_tool
+            .describe_table("test-db", "billing", "invoices")
+            .await;
+        assert!(matches!(result, Err(McpError::DatabaseNotAllowed(_))));
+
+        // Nothing was connected on the way
+        assert!(pool_managers.read().await.is_empty());
     }
 
     #[tokio::test]
-    async fn test_execute_tool_validates_empty_datasource_key() {
-        let configs = vec![create_test_config("test-db")];
-        let manager = Arc::new(DataSourceManager::new(configs).await.unwrap());
+    async fn test_tools_reject_mismatched_bind_parameters() {
+        let mut config = create_test_config("test-db");
+        config.permission = crate::config::Permission::Update;
+        let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
         let pool_managers = Arc::new(RwLock::new(HashMap::new()));
-        let tool = ExecuteTool::new(manager, pool_managers);
 
-        let result = tool.execute("", "testdb", "INSERT INTO users VALUES (1)").await;
-        assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), McpError::InvalidStatement(_)));
+        let query_tool = QueryTool::new(manager.clone(), pool_managers.clone());
+        let params = StatementParams::from_json(Some(&serde_json::json!([1, 2])), None).unwrap();
+        let result = query_tool
+            .execute_with_params(
+                "test-db",
+                "testdb",
+                "SELECT * FROM users WHERE id = ?",
+                &params,
+            )
+            .await;
+        assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+
+        let execute_tool = ExecuteTool::new(manager.clone(), pool_managers.clone());
+        let params = StatementParams::from_json(None, Some(&serde_json::json!({"id": 1}))).unwrap();
+        let result = execute_tool
+            .execute_with_params(
+                "test-db",
+                "testdb",
+                "UPDATE users SET name = :name WHERE id = :id",
+                &params,
+            )
+            .await;
+        assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+
+        // Nothing was connected on the way
+        assert!(pool_managers.read().await.is_empty());
+    }
+
+    fn transaction_statement(statement: &str) -> TransactionStatement {
+        TransactionStatement {
+            statement: statement.to_string(),
+            params: StatementParams::default(),
+        }
     }
 
     #[tokio::test]
-    async fn test_execute_tool_validates_empty_database() {
-        let configs = vec![create_test_config("test-db")];
-        let manager = Arc::new(DataSourceManager::new(configs).await.unwrap());
+    async fn test_transaction_tool_validates_statements_before_connecting() {
+        let mut config = create_test_config("test-db");
+        config.permission = crate::config::Permission::Ddl;
+        let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
         let pool_managers = Arc::new(RwLock::new(HashMap::new()));
-        let tool = ExecuteTool::new(manager, pool_managers);
+        let tool = TransactionTool::new(manager, pool_managers.clone());
+
+        let result = tool.execute("test-db", "testdb", &[], None).await;
+        assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+
+        for rejected in [
+            "CREATE TABLE t (id INT)",
+            "COMMIT",
+            "SET autocommit = 1",
+            "GRANT SELECT ON db.* TO 'app'@'%'",
+            "FLUSH PRIVILEGES",
+            "LOCK TABLES users WRITE",
+            "OPTIMIZE TABLE users",
+            "ANALYZE TABLE users",
+            "FROBNICATE users",
+            "DELETE FROM a; DELETE FROM b",
+            "",
+        ] {
+            let statements = vec![
+                transaction_statement("INSERT INTO users (name) VALUES ('a')"),
+                transaction_statement(rejected),
+            ];
+            let result = tool.execute("test-db", "testdb", &statements, None).await;
+            match result {
+                Err(McpError::InvalidStatement(msg)) => {
+                    assert!(msg.starts_with("statements[1]:"), "{}", msg)
+                }
+                other => panic!("'{}' should be rejected, got {:?}", rejected, other),
+            }
+        }
 
-        let result = tool.execute("test-db", "", "INSERT INTO users VALUES (1)").await;
-        assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), McpError::InvalidStatement(_)));
+        assert!(pool_managers.read().await.is_empty());
     }
 
     #[tokio::test]
-    async fn test_execute_tool_validates_empty_statement() {
-        let configs = vec![create_test_config("test-db")];
-        let manager = Arc::new(DataSourceManager::new(configs).await.unwrap());
+    async fn test_transaction_tool_requires_update_permission() {
+        let manager = Arc::new(
+            DataSourceManager::new(vec![create_test_config("test-db")])
+                .await
+                .unwrap(),
+        );
         let pool_managers = Arc::new(RwLock::new(HashMap::new()));
-        let tool = ExecuteTool::new(manager, pool_managers);
+        let tool = TransactionTool::new(manager, pool_managers);
 
-        let result = tool.execute("test-db", "testdb", "").await;
-        assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), McpError::InvalidStatement(_)));
+        let statements = vec![transaction_statement("UPDATE users SET name = 'a'")];
+        let result = tool.execute("test-db", "testdb", &statements, None).await;
+        assert!(matches!(result, Err(McpError::PermissionDenied(_))));
     }
 
     #[tokio::test]
-    async fn test_execute_tool_validates_invalid_datasource_key() {
-        let configs = vec![create_test_config("test-db")];
-        let manager = Arc::new(DataSourceManager::new(configs).await.unwrap());
+    async fn test_interactive_transactions_reject_unknown_handles() {
+        let mut config = create_test_config("test-db");
+        config.permission = crate::config::Permission::Update;
+        let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
         let pool_managers = Arc::new(RwLock::new(HashMap::new()));
-        let tool = ExecuteTool::new(manager, pool_managers);
+        let transactions = crate::transaction::TransactionManager::new(
+            crate::config::TransactionConfig::default(),
+        );
+        let params = StatementParams::default();
+
+        let query_tool = QueryTool::new(manager.clone(), pool_managers.clone());
+        let result = query_tool
+            .execute_in_transaction(
+                &transactions,
+                "missing",
+                "test-db",
+                "testdb",
+                "SELECT 1",
+                &params,
+            )
+            .await;
+        assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+
+        let execute_tool = ExecuteTool::new(manager.clone(), pool_managers.clone());
+        let result = execute_tool
+            .execute_in_transaction(
+                &transactions,
+                "missing",
+                "test-db",
+                "testdb",
+                "DELETE FROM users",
+                &params,
+            )
+            .await;
+        assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+
+        // These would commit the transaction implicitly or outlive it
+        for rejected in [
+            "DROP TABLE users",
+            "GRANT SELECT ON testdb.* TO 'app'@'%'",
+            "FLUSH TABLES",
+            "LOCK TABLES users WRITE",
+            "OPTIMIZE TABLE users",
+            "COMMIT",
+            "FROBNICATE users",
+        ] {
+            let result = execute_tool
+                .execute_in_transaction(
+                    &transactions,
+                    "missing",
+                    "test-db",
+                    "testdb",
+                    rejected,
+                    &params,
+                )
+                .await;
+            assert!(
+                matches!(&result, Err(McpError::InvalidStatement(msg)) if msg.contains("inside a transaction")),
+                "'{}' should be rejected, got {:?}",
+                rejected,
+                result
+            );
+        }
+
+        // Locking reads release their locks with the transaction, so they get as far
+        // as looking up the handle
+        for locking_read in [
+            "SELECT * FROM users WHERE id = 1 FOR UPDATE",
+            "SELECT * FROM users WHERE id = 1 FOR SHARE",
+        ] {
+            let result = execute_tool
+                .execute_in_transaction(
+                    &transactions,
+                    "missing",
+                    "test-db",
+                    "testdb",
+                    locking_read,
+                    &params,
+                )
+                .await;
+            assert!(
+                matches!(&result, Err(McpError::InvalidStatement(msg)) if msg.contains("missing")),
+                "{:?}",
+                result
+            );
+
+            let result = query_tool
+                .execute_in_transaction(
+                    &transactions,
+                    "missing",
+                    "test-db",
+                    "testdb",
+                    locking_read,
+                    &params,
+                )
+                .await;
+            assert!(
+                matches!(&result, Err(McpError::InvalidStatement(msg)) if msg.contains("missing")),
+                "{:?}",
+                result
+            );
+        }
+
+        let transaction_tool = TransactionTool::new(manager.clone(), pool_managers.clone());
+        let result = transaction_tool.commit(&transactions, "missing").await;
+        assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+        let result = transaction_tool.rollback(&transactions, "").await;
+        assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+
+        assert!(pool_managers.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_begin_validates_before_connecting() {
+        let mut config = create_test_config("test-db");
+        config.databases = vec!["app_*".to_string()];
+        let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
+        let pool_managers = Arc::new(RwLock::new(HashMap::new()));
+        let transactions = crate::transaction::TransactionManager::new(
+            crate::config::TransactionConfig::default(),
+        );
+        let tool = TransactionTool::new(manager, pool_managers.clone());
+
+        let result = tool.begin(&transactions, "", "app_orders", None).await;
+        assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+        let result = tool
+            .begin(&transactions, "invalid-key", "app_orders", None)
+            .await;
+        assert!(matches!(result, Err(McpError::InvalidDataSourceKey(_))));
+        let result = tool.begin(&transactions, "test-db", "billing", None).await;
+        assert!(matches!(result, Err(McpError::DatabaseNotAllowed(_))));
+
+        assert_eq!(transactions.open_count("test-db"), 0);
+        assert!(pool_managers.read().await.is_empty());
+    }
+
+    fn row(value: &str) -> QueryRow {
+        QueryRow {
+            values: vec![serde_json::json!(value)],
+        }
+    }
+
+    #[test]
+    fn test_result_limiter_truncates_at_max_rows() {
+        let mut limiter = ResultLimiter::new(ResultLimits {
+            max_rows: 2,
+            max_result_bytes: 1024,
+        });
+        assert!(limiter.push(row("a")));
+        assert!(limiter.push(row("b")));
+        assert!(!limiter.push(row("c")));
+
+        let result = limiter.finish();
+        assert!(result.truncated);
+        assert_eq!(result.rows_returned, 2);
+        assert_eq!(result.rows.len(), 2);
+        assert!(result.hint.unwrap().contains("max_rows (2)"));
+    }
+
+    #[test]
+    fn test_result_limiter_truncates_at_max_result_bytes() {
+        // Each row is `["xxxx"]`, 8 bytes of JSON
+        let mut limiter = ResultLimiter::new(ResultLimits {
+            max_rows: 100,
+            max_result_bytes: 20,
+        });
+        assert!(limiter.push(row("xxxx")));
+        assert!(limiter.push(row("xxxx")));
+        assert!(!limiter.push(row("xxxx")));
+
+        let result = limiter.finish();
+        assert!(result.truncated);
+        assert_eq!(result.rows_returned, 2);
+        assert!(result.hint.unwrap().contains("max_result_bytes (20)"));
+    }
+
+    #[test]
+    fn test_result_limiter_keeps_results_within_limits() {
+        let mut limiter = ResultLimiter::new(ResultLimits {
+            max_rows: 2,
+            max_result_bytes: 1024,
+        });
+        assert!(limiter.push(row("a")));
+        assert!(limiter.push(row("b")));
+
+        let result = limiter.finish();
+        assert!(!result.truncated);
+        assert_eq!(result.rows_returned, 2);
+        assert!(result.hint.is_none());
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["truncated"], false);
+        assert!(json.get("hint").is_none());
+    }
+
+    fn source(table: &str, name: &str) -> SourceColumn {
+        SourceColumn {
+            current_schema: "app".to_string(),
+            schema: "app".to_string(),
+            table: table.to_string(),
+            name: name.to_string(),
+            column_type: "int".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn origin_names(sql: &str, names: &[&str], sources: &[SourceColumn]) -> Vec<Option<String>> {
+        let shape = sql::select_shape(sql).unwrap();
+        column_origins(&shape, names, sources)
+            .into_iter()
+            .map(|origin| origin.map(|c| format!("{}.{}", c.table, c.name)))
+            .collect()
+    }
+
+    #[test]
+    fn test_column_origins() {
+        let sources = vec![
+            source("users", "id"),
+            source("users", "name"),
+            source("orders", "id"),
+            source("orders", "user_id"),
+        ];
+
+        assert_eq!(
+            origin_names(
+                "SELECT u.name AS who, o.id, COUNT(*) FROM users u JOIN orders o ON o.user_id = u.id",
+                &["who", "id", "COUNT(*)"],
+                &sources
+            ),
+            vec![Some("users.name".to_string()), Some("orders.id".to_string()), None]
+        );
+        // `id` is in both tables
+        assert_eq!(
+            origin_names(
+                "SELECT id, user_id FROM users, orders",
+                &["id", "user_id"],
+                &sources
+            ),
+            vec![None, Some("orders.user_id".to_string())]
+        );
+        assert_eq!(
+            origin_names(
+                "SELECT o.*, users.name FROM orders o, users",
+                &["id", "user_id", "name"],
+                &sources
+            ),
+            vec![
+                Some("orders.id".to_string()),
+                Some("orders.user_id".to_string()),
+                Some("users.name".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_column_origins_gives_up_when_columns_do_not_line_up() {
+        let sources = vec![
+            source("users", "id"),
+            source("users", "name"),
+            source("users_copy", "id"),
+            source("users_copy", "name"),
+        ];
+
+        // NATURAL JOIN merges the shared columns
+        assert_eq!(
+            origin_names(
+                "SELECT * FROM users NATURAL JOIN users_copy",
+                &["id", "name"],
+                &sources
+            ),
+            vec![None, None]
+        );
+        // The result has a column the select list does not account for
+        assert_eq!(
+            origin_names("SELECT name FROM users", &["id"], &sources),
+            vec![None]
+        );
+        // A derived table may have the column too
+        assert_eq!(
+            origin_names(
+                "SELECT name, users.name, t.x FROM users, (SELECT 1 AS x) t",
+                &["name", "name", "x"],
+                &sources
+            ),
+            vec![None, Some("users.name".to_string()), None]
+        );
+        assert_eq!(
+            origin_names("SELECT * FROM (SELECT id FROM users) t", &["id"], &sources),
+            vec![None]
+        );
+        // Nor are temporary tables listed
+        assert_eq!(
+            origin_names("SELECT name, x FROM users, scratch", &["name", "x"], &sources),
+            vec![None, None]
+        );
+    }
+
+    #[test]
+    fn test_describe_column() {
+        let column = SourceColumn {
+            column_type: "decimal(10,2) unsigned zerofill".to_string(),
+            key: "PRI".to_string(),
+            extra: "auto_increment".to_string(),
+            precision: Some(10),
+            scale: Some(2),
+            ..source("prices", "amount")
+        };
+        let metadata = describe_column("amount", "DECIMAL", Some(&column), false);
+        assert!(!metadata.nullable && metadata.unsigned && metadata.zerofill);
+        assert!(metadata.auto_increment);
+        assert_eq!(metadata.key.as_deref(), Some("PRI"));
+        assert_eq!((metadata.precision, metadata.scale), (Some(10), Some(2)));
+        assert_eq!(
+            metadata.origin,
+            Some(ColumnOrigin {
+                schema: "app".to_string(),
+                table: "prices".to_string(),
+                column: "amount".to_string(),
+            })
+        );
+
+        // An outer join can put NULL in a NOT NULL column
+        assert!(describe_column("amount", "DECIMAL", Some(&column), true).nullable);
+
+        let column = SourceColumn {
+            column_type: "varchar(20)".to_string(),
+            character_set: Some("utf8mb4".to_string()),
+            max_length: Some(20),
+            ..source("users", "name")
+        };
+        let metadata = describe_column("name", "VARCHAR", Some(&column), false);
+        assert_eq!(metadata.character_set.as_deref(), Some("utf8mb4"));
+        assert_eq!(metadata.length, Some(20));
+        assert!(!metadata.binary && metadata.key.is_none());
+
+        let metadata = describe_column("n", "BIGINT UNSIGNED", None, false);
+        assert!(metadata.nullable && metadata.unsigned);
+        assert!(metadata.origin.is_none() && metadata.length.is_none());
+        assert!(describe_column("data", "VARBINARY", None, false).binary);
+    }
+
+    #[tokio::test]
+    async fn test_execute_tool_validates_empty_datasource_key() {
+        let configs = vec![create_test_config("test-db")];
+        let manager = Arc::new(DataSourceManager::new(configs).await.unwrap());
+        let pool_managers = Arc::new(RwLock::new(HashMap::new()));
+        let tool = ExecuteTool::new(manager, pool_managers);
+
+        let result = tool.execute("", "testdb", "INSERT INTO users VALUES (1)").await;
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), McpError::InvalidStatement(_)));
+    }
+
+    #[tokio::test]
+    async fn test_execute_tool_validates_empty_database() {
+        let configs = vec![create_test_config("test-db")];
+        let manager = Arc::new(DataSourceManager::new(configs).await.unwrap());
+        let pool_managers = Arc::new(RwLock::new(HashMap::new()));
+        let tool = ExecuteTool::new(manager, pool_managers);
+
+        let result = tool.execute("test-db", "", "INSERT INTO users VALUES (1)").await;
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), McpError::InvalidStatement(_)));
+    }
+
+    #[tokio::test]
+    async fn test_execute_tool_validates_empty_statement() {
+        let configs = vec![create_test_config("test-db")];
+        let manager = Arc::new(DataSourceManager::new(configs).await.unwrap());
+        let pool_managers = Arc::new(RwLock::new(HashMap::new()));
+        let tool = ExecuteTool::new(manager, pool_managers);
+
+        let result = tool.execute("test-db", "testdb", "").await;
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), McpError::InvalidStatement(_)));
+    }
+
+    #[tokio::test]
+    async fn test_execute_tool_validates_invalid_datasource_key() {
+        let configs = vec![create_test_config("test-db")];
+        let manager = Arc::new(DataSourceManager::new(configs).await.unwrap());
+        let pool_managers = Arc::new(RwLock::new(HashMap::new()));
+        let tool = ExecuteTool::new(manager, pool_managers);
 
         let result = tool.execute("invalid-key", "testdb", "INSERT INTO users VALUES (1)").await;
         assert!(result.is_err());
@@ -516,6 +1555,38 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn test_idempotent_results_are_recorded_per_key() {
+        let configs = vec![create_test_config("test-db"), create_test_config("other-db")];
+        let manager = DataSourceManager::new(configs).await.unwrap();
+        let results = IdempotentResults::new(&manager);
+        let insert = StatementParams::default()
+            .bind("INSERT INTO users VALUES (1)")
+            .unwrap();
+
+        let call = results.call("test-db", "k1", "app", &insert).await.unwrap();
+        call.result
+            .set(ExecuteResult {
+                affected_rows: 1,
+                last_insert_id: Some(7),
+            })
+            .unwrap();
+
+        let repeated = results.call("test-db", "k1", "app", &insert).await.unwrap();
+        assert_eq!(repeated.result.get().unwrap().last_insert_id, Some(7));
+
+        // A key names a single statement
+        let delete = StatementParams::default().bind("DELETE FROM users").unwrap();
+        let result = results.call("test-db", "k1", "app", &delete).await;
+        assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+        let result = results.call("test-db", "k1", "other", &insert).await;
+        assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+
+        // Keys are per data source
+        let call = results.call("other-db", "k1", "app", &delete).await.unwrap();
+        assert!(call.result.get().is_none());
+    }
+
     #[tokio::test]
     async fn test_execute_tool_rejects_ddl_statements() {
         let configs = vec![create_test_config("test-db")];
@@ -548,17 +1619,109 @@ mod tests {
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), McpError::PermissionDenied(_)));
     }
+
+    #[tokio::test]
+    async fn test_execute_tool_classifies_admin_and_session_statements() {
+        let mut config = create_test_config("test-db");
+        config.permission = crate::config::Permission::Update;
+        let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
+        let pool_managers = Arc::new(RwLock::new(HashMap::new()));
+        let tool = ExecuteTool::new(manager, pool_managers);
+
+        // Administrative statements need DDL permission
+        let result = tool.execute("test-db", "testdb", "GRANT ALL ON *.* TO 'x'@'%'").await;
+        assert!(matches!(result.unwrap_err(), McpError::PermissionDenied(_)));
+
+        // DDL hidden in an executable comment is still DDL
+        let result = tool.execute("test-db", "testdb", "/*!40101 DROP TABLE users */").await;
+        assert!(matches!(result.unwrap_err(), McpError::PermissionDenied(_)));
+
+        // Connection-scoped statements are never accepted
+        let result = tool.execute("test-db", "testdb", "LOCK TABLES users WRITE").await;
+        assert!(matches!(result.unwrap_err(), McpError::InvalidStatement(_)));
+    }
+
+    #[test]
+    fn test_transaction_statement_kind() {
+        assert_eq!(
+            transaction_statement_kind("UPDATE users SET a = 1").unwrap(),
+            StatementKind::Dml
+        );
+        assert_eq!(
+            transaction_statement_kind("SELECT * FROM users FOR UPDATE").unwrap(),
+            StatementKind::Dml
+        );
+        for statement in ["CALL cleanup()", "COMMIT", "CREATE TABLE t (id INT)"] {
+            assert!(
+                matches!(
+                    transaction_statement_kind(statement),
+                    Err(McpError::InvalidStatement(_))
+                ),
+                "{} should be rejected",
+                statement
+            );
+        }
+    }
 }
 
 /// Stream handler for query results
 /// Provides chunked streaming of large result sets
+///
+/// Rows are either held in memory or, for streams opened with
+/// [`QueryTool::open_stream`], read from the server as chunks are requested.
 pub struct QueryResultStream {
-    rows: Arc<Mutex<Vec<QueryRow>>>,
+    rows: Arc<Mutex<RowBuffer>>,
     columns: Vec<ColumnMetadata>,
     chunk_size: usize,
     current_position: Arc<Mutex<usize>>,
     cancelled: Arc<Mutex<bool>>,
     stream_id: String,
+    reader: Option<StreamReader>,
+    last_used: Arc<std::sync::Mutex<Instant>>,
+    format: ResultFormat,
+}
+
+/// Rows of a stream that have not been returned yet
+struct RowBuffer {
+    rows: VecDeque<QueryRow>,
+    /// Rows still to be read from the server, `None` once the result set has ended
+    server: Option<mpsc::Receiver<Result<QueryRow>>>,
+}
+
+impl RowBuffer {
+    /// Read rows from the server until `count` are buffered or the result set ends
+    async fn fill(&mut self, count: usize) -> Result<()> {
+        while self.rows.len() < count {
+            let Some(server) = self.server.as_mut() else {
+                break;
+            };
+            match server.recv().await {
+                Some(Ok(row)) => self.rows.push_back(row),
+                Some(Err(e)) => {
+                    self.server = None;
+                    return Err(e);
+                }
+                None => self.server = None,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The task reading a stream's rows from the server
+struct StreamReader {
+    /// MySQL thread running the query
+    thread_id: u64,
+    /// Time allowed for reading a chunk
+    chunk_timeout: Duration,
+    /// Dropping the sender stops the task
+    stop: std::sync::Mutex<Option<oneshot::Sender<()>>>,
+}
+
+impl StreamReader {
+    fn stop(&self) {
+        self.stop.lock().unwrap_or_else(|e| e.into_inner()).take();
+    }
 }
 
 impl QueryResultStream {
@@ -567,29 +1730,99 @@ impl QueryResultStream {
         columns: Vec<ColumnMetadata>,
         rows: Vec<QueryRow>,
         chunk_size: usize,
+    ) -> Self {
+        let buffer = RowBuffer {
+            rows: rows.into(),
+            server: None,
+        };
+        Self::with_buffer(columns, buffer, chunk_size, None)
+    }
+
+    /// Run a query on `conn` and stream its rows from the server
+    ///
+    /// Resolves once the first row has arrived or the result set turned out to
+    /// be empty, so a query that fails right away fails here.
+    async fn from_query(
+        conn: TrackedConnection,
+        query: BoundStatement,
+        session_timeout: Duration,
+        chunk_size: usize,
+        chunk_timeout: Duration,
+        mapping: TypeMapping,
+    ) -> Result<Self> {
+        let thread_id = conn.thread_id();
+        let (columns_tx, columns_rx) = oneshot::channel();
+        // Bounded so the reader stays about one chunk ahead of the client
+        let (rows_tx, rows_rx) = mpsc::channel(chunk_size);
+        let (stop_tx, stop_rx) = oneshot::channel();
+
+        let output = RowSender {
+            columns: Some(columns_tx),
+            rows: rows_tx,
+            stop: stop_rx,
+        };
+        tokio::spawn(read_rows(conn, query, session_timeout, mapping, output));
+
+        let columns = columns_rx.await.map_err(|_| {
+            McpError::QueryExecutionError("Stream reader stopped unexpectedly".to_string())
+        })??;
+
+        let buffer = RowBuffer {
+            rows: VecDeque::new(),
+            server: Some(rows_rx),
+        };
+        let reader = StreamReader {
+            thread_id,
+            chunk_timeout,
+            stop: std::sync::Mutex::new(Some(stop_tx)),
+        };
+        Ok(Self::with_buffer(columns, buffer, chunk_size, Some(reader)))
+    }
+
+    fn with_buffer(
+        columns: Vec<ColumnMetadata>,
+        buffer: RowBuffer,
+        chunk_size: usize,
+        reader: Option<StreamReader>,
     ) -> Self {
         let stream_id = uuid::Uuid::new_v4().to_string();
-        
+
         tracing::debug!(
             stream_id = %stream_id,
-            total_rows = rows.len(),
+            buffered_rows = buffer.rows.len(),
             chunk_size = chunk_size,
+            thread_id = ?reader.as_ref().map(|r| r.thread_id),
             "Creating new query result stream"
         );
 
         Self {
-            rows: Arc::new(Mutex::new(rows)),
+            rows: Arc::new(Mutex::new(buffer)),
             columns,
             chunk_size,
             current_position: Arc::new(Mutex::new(0)),
             cancelled: Arc::new(Mutex::new(false)),
             stream_id,
+            reader,
+            last_used: Arc::new(std::sync::Mutex::new(Instant::now())),
+            format: ResultFormat::default(),
         }
     }
 
+    /// Encode the chunks of this stream in `format`
+    pub fn with_format(mut self, format: ResultFormat) -> Self {
+        self.format = format;
+        self
+    }
+
     /// Get the next chunk of rows
     /// Returns None when all rows have been consumed or stream is cancelled
     pub async fn next_chunk(&self) -> Result<Option<QueryResultChunk>> {
+        let chunk = self.read_chunk().await?;
+        Ok(Some(chunk).filter(|chunk| !chunk.rows.is_empty()))
+    }
+
+    /// Get the next chunk of rows, which is empty once all rows have been consumed
+    async fn read_chunk(&self) -> Result<QueryResultChunk> {
         // Check if stream is cancelled
         let is_cancelled = *self.cancelled.lock().await;
         if is_cancelled {
@@ -599,51 +1832,65 @@ impl QueryResultStream {
             );
             return Err(McpError::StreamCancelled);
         }
+        self.touch();
 
         let mut position = self.current_position.lock().await;
-        let rows = self.rows.lock().await;
+        let mut buffer = self.rows.lock().await;
 
-        // Check if we've reached the end
-        if *position >= rows.len() {
-            tracing::debug!(
-                stream_id = %self.stream_id,
-                total_rows = rows.len(),
-                "Stream completed, all rows consumed"
-            );
-            return Ok(None);
+        // Read one row past the chunk to know whether it is the last one
+        let filled = buffer.fill(self.chunk_size + 1).await;
+        self.touch();
+        // Cancelling stops the reader, which ends the rows from the server early
+        if *self.cancelled.lock().await {
+            return Err(McpError::StreamCancelled);
         }
+        filled?;
 
         // Calculate chunk boundaries
         let start = *position;
-        let end = std::cmp::min(start + self.chunk_size, rows.len());
-        let chunk_rows = rows[start..end].to_vec();
+        let count = std::cmp::min(self.chunk_size, buffer.rows.len());
+        let end = start + count;
+        let chunk_rows: Vec<QueryRow> = buffer.rows.drain(..count).collect();
         let chunk_number = start / self.chunk_size;
-        let is_last = end >= rows.len();
+        let is_last = buffer.rows.is_empty() && buffer.server.is_none();
 
         // Update position
         *position = end;
 
-        tracing::debug!(
-            stream_id = %self.stream_id,
-            chunk_number = chunk_number,
-            chunk_size = chunk_rows.len(),
-            is_last = is_last,
-            "Returning chunk"
-        );
+        if chunk_rows.is_empty() {
+            tracing::debug!(
+                stream_id = %self.stream_id,
+                total_rows = end,
+                "Stream completed, all rows consumed"
+            );
+        } else {
+            tracing::debug!(
+                stream_id = %self.stream_id,
+                chunk_number = chunk_number,
+                chunk_size = chunk_rows.len(),
+                is_last = is_last,
+                "Returning chunk"
+            );
+        }
 
-        Ok(Some(QueryResultChunk {
+        Ok(QueryResultChunk {
             columns: self.columns.clone(),
             rows: chunk_rows,
             chunk_number,
             is_last,
-            total_rows: rows.len(),
-        }))
+            total_rows: end + buffer.rows.len(),
+        })
     }
 
     /// Cancel the stream and clean up resources
+    ///
+    /// A query still streaming from the server is killed.
     pub async fn cancel(&self) -> Result<()> {
-        let mut cancelled = self.cancelled.lock().await;
-        *cancelled = true;
+        *self.cancelled.lock().await = true;
+
+        if let Some(reader) = &self.reader {
+            reader.stop();
+        }
 
         tracing::info!(
             stream_id = %self.stream_id,
@@ -651,8 +1898,9 @@ impl QueryResultStream {
         );
 
         // Clear the rows to free memory
-        let mut rows = self.rows.lock().await;
-        rows.clear();
+        let mut buffer = self.rows.lock().await;
+        buffer.rows.clear();
+        buffer.server = None;
 
         Ok(())
     }
@@ -668,9 +1916,12 @@ impl QueryResultStream {
     }
 
     /// Get the total number of rows in the stream
+    ///
+    /// For streams read from the server this counts the rows read so far.
     pub async fn total_rows(&self) -> usize {
-        let rows = self.rows.lock().await;
-        rows.len()
+        let position = self.current_position.lock().await;
+        let buffer = self.rows.lock().await;
+        *position + buffer.rows.len()
     }
 
     /// Get the current position in the stream
@@ -682,6 +1933,112 @@ impl QueryResultStream {
     pub fn columns(&self) -> &[ColumnMetadata] {
         &self.columns
     }
+
+    /// Encoding of the stream's chunks
+    pub fn format(&self) -> ResultFormat {
+        self.format
+    }
+
+    /// MySQL thread running the query, for streams read from the server
+    pub fn thread_id(&self) -> Option<u64> {
+        self.reader.as_ref().map(|reader| reader.thread_id)
+    }
+
+    fn touch(&self) {
+        *self.last_used.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+
+    fn idle_for(&self) -> Duration {
+        self.last_used
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .elapsed()
+    }
+}
+
+/// Where a stream's reader task sends what it reads
+struct RowSender {
+    /// Receives the columns with the first row, or the error if the query failed
+    columns: Option<oneshot::Sender<Result<Vec<ColumnMetadata>>>>,
+    rows: mpsc::Sender<Result<QueryRow>>,
+    stop: oneshot::Receiver<()>,
+}
+
+impl RowSender {
+    async fn send_error(&mut self, error: McpError) {
+        match self.columns.take() {
+            Some(columns) => {
+                let _ = columns.send(Err(error));
+            }
+            None => {
+                let _ = self.rows.send(Err(error)).await;
+            }
+        }
+    }
+}
+
+/// Read the rows of a streamed query from the server
+///
+/// If the stream is stopped before the result set has ended, the unfinished
+/// connection is dropped, which kills the query and discards the connection.
+async fn read_rows(
+    mut conn: TrackedConnection,
+    query: BoundStatement,
+    session_timeout: Duration,
+    mapping: TypeMapping,
+    mut output: RowSender,
+) {
+    // The session's execution time limit would kill a stream read at the client's pace
+    let relaxed = limit_execution_time(&mut conn, Duration::ZERO).await;
+
+    let columns = match prepare_columns(&mut conn, &query).await {
+        Ok(columns) => columns,
+        Err(e) => {
+            output.send_error(e).await;
+            return;
+        }
+    };
+
+    let complete = {
+        let mut rows = query.query().fetch(&mut *conn);
+        loop {
+            let next = tokio::select! {
+                next = rows.next() => next,
+                _ = &mut output.stop => break false,
+            };
+            let row = match next {
+                Some(Ok(row)) => row,
+                Some(Err(e)) => {
+                    output.send_error(McpError::from(e)).await;
+                    break false;
+                }
+                None => break true,
+            };
+
+            if let Some(sender) = output.columns.take() {
+                let _ = sender.send(Ok(columns.clone()));
+            }
+            tokio::select! {
+                sent = output.rows.send(Ok(to_query_row(&row, &mapping))) => {
+                    if sent.is_err() {
+                        break false;
+                    }
+                }
+                _ = &mut output.stop => break false,
+            }
+        }
+    };
+
+    // An empty result set ends without a first row
+    if let Some(sender) = output.columns.take() {
+        let _ = sender.send(Ok(columns));
+    }
+    // Let the client see the end of the result set before cleaning up
+    drop(output);
+
+    if complete && (!relaxed || restore_execution_time(&mut conn, session_timeout).await) {
+        conn.finish();
+    }
 }
 
 /// A chunk of query results from a stream
@@ -691,36 +2048,80 @@ pub struct QueryResultChunk {
     pub rows: Vec<QueryRow>,
     pub chunk_number: usize,
     pub is_last: bool,
+    /// Rows returned or buffered so far; the stream's total once `is_last`
     pub total_rows: usize,
 }
 
+/// A chunk of a stream together with the stream's ID
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamChunk {
+    pub stream_id: String,
+    #[serde(flatten)]
+    pub chunk: QueryResultChunk,
+    /// Encoding requested when the stream was opened
+    #[serde(skip)]
+    pub format: ResultFormat,
+}
+
 /// Manager for concurrent query streams
 /// Ensures isolation between different streams
 pub struct StreamManager {
     streams: Arc<RwLock<HashMap<String, Arc<QueryResultStream>>>>,
+    config: StreamConfig,
 }
 
 impl StreamManager {
     /// Create a new stream manager
     pub fn new() -> Self {
+        Self::with_config(StreamConfig::default())
+    }
+
+    /// Create a stream manager with the given limits
+    pub fn with_config(config: StreamConfig) -> Self {
         Self {
             streams: Arc::new(RwLock::new(HashMap::new())),
+            config,
         }
     }
 
     /// Register a new stream
     pub async fn register_stream(&self, stream: QueryResultStream) -> String {
-        let stream_id = stream.stream_id().to_string();
         let mut streams = self.streams.write().await;
-        streams.insert(stream_id.clone(), Arc::new(stream));
+        insert_stream(&mut streams, stream)
+    }
 
-        tracing::info!(
-            stream_id = %stream_id,
-            active_streams = streams.len(),
-            "Stream registered"
-        );
+    /// Register a new stream, failing if it would exceed the open stream limit
+    ///
+    /// Only streams reading from the server count towards the limit.
+    pub async fn try_register_stream(&self, stream: QueryResultStream) -> Result<String> {
+        let mut streams = self.streams.write().await;
+        if stream.reader.is_some() {
+            self.check_capacity_locked(&streams)?;
+        }
+        Ok(insert_stream(&mut streams, stream))
+    }
+
+    /// Check that another stream may start reading from the server
+    pub async fn check_capacity(&self) -> Result<()> {
+        let streams = self.streams.read().await;
+        self.check_capacity_locked(&streams)
+    }
 
-        stream_id
+    fn check_capacity_locked(
+        &self,
+        streams: &HashMap<String, Arc<QueryResultStream>>,
+    ) -> Result<()> {
+        let limit = self.config.max_open;
+        let open = streams.values().filter(|s| s.reader.is_some()).count();
+
+        if open >= limit {
+            tracing::warn!(limit = limit, "Open stream limit reached");
+            return Err(McpError::PoolError(format!(
+                "Too many open streams (limit {}); close one first",
+                limit
+            )));
+        }
+        Ok(())
     }
 
     /// Get a stream by ID
@@ -729,14 +2130,47 @@ impl StreamManager {
         streams.get(stream_id).cloned()
     }
 
+    /// Fetch the next chunk of a stream
+    ///
+    /// The stream is removed once its last chunk has been returned, or when
+    /// reading it fails or times out.
+    pub async fn fetch_chunk(&self, stream_id: &str) -> Result<StreamChunk> {
+        let stream = self.get_stream(stream_id).await.ok_or_else(|| {
+            McpError::InvalidStatement(format!(
+                "Unknown stream '{}'; it may have ended, been closed or expired",
+                stream_id
+            ))
+        })?;
+
+        let result = match &stream.reader {
+            Some(reader) => tokio::time::timeout(reader.chunk_timeout, stream.read_chunk())
+                .await
+                .unwrap_or(Err(McpError::QueryTimeout {
+                    thread_id: Some(reader.thread_id),
+                })),
+            None => stream.read_chunk().await,
+        };
+
+        if !matches!(&result, Ok(chunk) if !chunk.is_last) {
+            // Kills the query if it is still running
+            self.remove_stream(stream_id).await?;
+        }
+
+        result.map(|chunk| StreamChunk {
+            stream_id: stream_id.to_string(),
+            chunk,
+            format: stream.format(),
+        })
+    }
+
     /// Remove a stream
     pub async fn remove_stream(&self, stream_id: &str) -> Result<()> {
         let mut streams = self.streams.write().await;
-        
+
         if let Some(stream) = streams.remove(stream_id) {
             // Cancel the stream to clean up resources
             stream.cancel().await?;
-            
+
             tracing::info!(
                 stream_id = %stream_id,
                 remaining_streams = streams.len(),
@@ -753,214 +2187,3466 @@ impl StreamManager {
         streams.len()
     }
 
-    /// Cancel all streams
-    pub async fn cancel_all(&self) -> Result<()> {
+    /// Close streams that have been idle for longer than the idle timeout
+    /// Returns the number of streams closed
+    pub async fn close_idle(&self) -> usize {
+        let idle_timeout = self.config.idle_timeout();
         let mut streams = self.streams.write().await;
-        
-        for (stream_id, stream) in streams.iter() {
-            tracing::info!(
-                stream_id = %stream_id,
-                "Cancelling stream"
-            );
-            stream.cancel().await?;
-        }
-        
+        let expired: Vec<String> = streams
+            .iter()
+            // A locked buffer is being read and is not idle
+            .filter(|(_, s)| s.rows.try_lock().is_ok() && s.idle_for() >= idle_timeout)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        for stream_id in &expired {
+            if let Some(stream) = streams.remove(stream_id) {
+                tracing::warn!(
+                    stream_id = %stream_id,
+                    idle_timeout_secs = idle_timeout.as_secs(),
+                    "Closing idle stream"
+                );
+                let _ = stream.cancel().await;
+            }
+        }
+
+        expired.len()
+    }
+
+    /// Periodically close idle streams until the manager is dropped
+    pub fn spawn_idle_reaper(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let manager: Weak<Self> = Arc::downgrade(self);
+        let period = (self.config.idle_timeout() / 2).max(Duration::from_secs(1));
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            // Skip the first tick (immediate)
+            interval.tick().await;
+
+            loop {
+                interval.tick().await;
+
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+                manager.close_idle().await;
+            }
+        })
+    }
+
+    /// Cancel all streams
+    pub async fn cancel_all(&self) -> Result<()> {
+        let mut streams = self.streams.write().await;
+
+        for (stream_id, stream) in streams.iter() {
+            tracing::info!(
+                stream_id = %stream_id,
+                "Cancelling stream"
+            );
+            stream.cancel().await?;
+        }
+
         streams.clear();
-        
+
         tracing::info!("All streams cancelled");
-        
+
         Ok(())
     }
 }
 
+fn insert_stream(
+    streams: &mut HashMap<String, Arc<QueryResultStream>>,
+    stream: QueryResultStream,
+) -> String {
+    let stream_id = stream.stream_id().to_string();
+    streams.insert(stream_id.clone(), Arc::new(stream));
+
+    tracing::info!(
+        stream_id = %stream_id,
+        active_streams = streams.len(),
+        "Stream registered"
+    );
+
+    stream_id
+}
+
 impl Default for StreamManager {
     fn default() -> Self {
         Self::new()
     }
 }
 
-/// Execute tool for executing DML statements (INSERT, UPDATE, DELETE)
-pub struct ExecuteTool {
+/// Rows read from the server per batch handed to the export file writer
+const EXPORT_BATCH_ROWS: usize = 1000;
+
+/// Batches queued for the export file writer before reading waits for it
+const EXPORT_QUEUED_BATCHES: usize = 4;
+
+/// Export tool for writing query results to files in the export directory
+pub struct ExportTool {
+    query: QueryTool,
+    config: ExportConfig,
+    timeout: Option<Duration>,
+}
+
+/// Destination and encoding of an export
+#[derive(Debug, Clone)]
+pub struct ExportRequest {
+    /// File name relative to the export directory
+    pub file: String,
+    pub format: ExportFormat,
+    pub compression: Compression,
+    /// Replace an existing file
+    pub overwrite: bool,
+}
+
+impl ExportTool {
+    /// Create a new export tool
+    pub fn new(
+        manager: Arc<DataSourceManager>,
+        pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>>,
+        config: ExportConfig,
+    ) -> Self {
+        Self {
+            query: QueryTool::new(manager, pool_managers),
+            config,
+            timeout: None,
+        }
+    }
+
+    /// Request a timeout for this call instead of the configured one
+    /// (capped at `export.timeout_secs`); `None` keeps the configured one
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run a read-only query and write all of its rows to a file
+    ///
+    /// Queries are checked like those of `mysql_query`, but their results are
+    /// not limited. The timeout covers the whole export; a query still running
+    /// when it expires is killed and the file is not created.
+    pub async fn export(
+        &self,
+        datasource_key: &str,
+        database: &str,
+        query: &str,
+        params: &StatementParams,
+        request: &ExportRequest,
+    ) -> Result<ExportedFile> {
+        let directory = self.config.directory.as_deref().ok_or_else(|| {
+            McpError::ConfigurationError(
+                "Exports are disabled; set export.directory to enable mysql_export".to_string(),
+            )
+        })?;
+
+        let bound = self
+            .query
+            .prepare(datasource_key, database, query, params, false)
+            .await?;
+        let target = ExportTarget {
+            path: export::resolve_path(directory, &request.file)?,
+            format: request.format,
+            compression: request.compression,
+            overwrite: request.overwrite,
+            max_bytes: self.config.max_file_bytes,
+        };
+
+        let timeout = self
+            .timeout
+            .map_or(self.config.timeout(), |timeout| timeout.min(self.config.timeout()));
+        let session_timeout = self.query.manager.query_timeout(datasource_key, None);
+
+        // Get or create pool manager for this data source
+        let pool_manager = get_or_create_pool_manager(
+            &self.query.pool_managers,
+            &self.query.manager,
+            datasource_key,
+        )
+        .await?;
+
+        let mapping = self.query.manager.type_mapping();
+        let start = Instant::now();
+        let deadline = tokio::time::Instant::from_std(start) + timeout;
+        let result =
+            match tokio::time::timeout_at(deadline, pool_manager.get_tracked_connection(database))
+                .await
+            {
+                Ok(Ok(mut conn)) => {
+                    let export = async {
+                        let limited = limit_execution_time(&mut conn, timeout).await;
+                        (export_query(&mut conn, &bound, target, &mapping).await, limited)
+                    };
+                    match tokio::time::timeout_at(deadline, export).await {
+                        Ok((Ok(file), limited)) => {
+                            if !limited || restore_execution_time(&mut conn, session_timeout).await {
+                                conn.finish();
+                            }
+                            Ok(file)
+                        }
+                        // The export may have stopped partway through the rows;
+                        // dropping the connection kills the query
+                        Ok((Err(e), _)) => Err(e),
+                        Err(_) => Err(McpError::QueryTimeout {
+                            thread_id: Some(conn.thread_id()),
+                        }),
+                    }
+                }
+                Ok(Err(e)) => Err(e),
+                Err(_) => Err(McpError::QueryTimeout { thread_id: None }),
+            };
+
+        match &result {
+            Ok(file) => tracing::info!(
+                datasource_key = %datasource_key,
+                database = %database,
+                path = %file.path,
+                rows = file.rows,
+                bytes = file.bytes,
+                execution_time_ms = start.elapsed().as_millis(),
+                "Query exported"
+            ),
+            Err(e) => tracing::error!(
+                datasource_key = %datasource_key,
+                database = %database,
+                error = %e.sanitize(),
+                execution_time_ms = start.elapsed().as_millis(),
+                "Query export failed"
+            ),
+        }
+        result
+    }
+}
+
+/// Run a query and write its rows to an export file as they are read
+///
+/// The file is written on a blocking task fed through a short queue, so a slow
+/// disk holds back reading rather than filling memory with rows.
+async fn export_query(
+    conn: &mut MySqlConnection,
+    query: &BoundStatement,
+    target: ExportTarget,
+    mapping: &TypeMapping,
+) -> Result<ExportedFile> {
+    let columns = prepare_columns(conn, query).await?;
+    let (sender, receiver) = mpsc::channel(EXPORT_QUEUED_BATCHES);
+    let writer =
+        tokio::task::spawn_blocking(move || export::write_file(target, columns, receiver));
+
+    let mut rows = query.query().fetch(conn);
+    let mut batch = Vec::with_capacity(EXPORT_BATCH_ROWS);
+    while let Some(row) = rows.next().await {
+        let row = row.map_err(McpError::from)?;
+        batch.push(to_query_row(&row, mapping));
+
+        if batch.len() == EXPORT_BATCH_ROWS {
+            let full = std::mem::replace(&mut batch, Vec::with_capacity(EXPORT_BATCH_ROWS));
+            if sender.send(Some(full)).await.is_err() {
+                // The writer failed; its error is returned below
+                break;
+            }
+        }
+    }
+
+    // Sending fails only if the writer has already stopped with an error
+    if sender.send(Some(batch)).await.is_ok() {
+        let _ = sender.send(None).await;
+    }
+    writer
+        .await
+        .map_err(|e| McpError::QueryExecutionError(format!("Export failed: {}", e)))?
+}
+
+#[cfg(test)]
+mod export_tests {
+    use super::*;
+    use crate::config::DataSourceConfig;
+
+    fn create_test_config(key: &str) -> DataSourceConfig {
+        DataSourceConfig {
+            key: key.to_string(),
+            name: "Test Database".to_string(),
+            username: "test".to_string(),
+            password: "test".to_string(),
+            ..Default::default()
+        }
+    }
+
+    async fn create_tool(directory: Option<&std::path::Path>) -> ExportTool {
+        let manager = Arc::new(
+            DataSourceManager::new(vec![create_test_config("test-db")])
+                .await
+                .unwrap(),
+        );
+        let config = ExportConfig {
+            directory: directory.map(|dir| dir.to_path_buf()),
+            ..ExportConfig::default()
+        };
+        ExportTool::new(manager, Arc::new(RwLock::new(HashMap::new())), config)
+    }
+
+    fn request(file: &str) -> ExportRequest {
+        ExportRequest {
+            file: file.to_string(),
+            format: ExportFormat::Csv,
+            compression: Compression::None,
+            overwrite: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_tool_requires_export_directory() {
+        let tool = create_tool(None).await;
+
+        let result = tool
+            .export("test-db", "testdb", "SELECT 1", &StatementParams::default(), &request("out.csv"))
+            .await;
+        assert!(matches!(result, Err(McpError::ConfigurationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_export_tool_rejects_non_read_only_statements() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = create_tool(Some(dir.path())).await;
+
+        for query in ["DELETE FROM users", "SELECT * FROM users INTO OUTFILE '/tmp/users'"] {
+            let result = tool
+                .export("test-db", "testdb", query, &StatementParams::default(), &request("out.csv"))
+                .await;
+            assert!(
+                matches!(result, Err(McpError::InvalidStatement(_))),
+                "{} should be rejected",
+                query
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_tool_rejects_paths_outside_export_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = create_tool(Some(dir.path())).await;
+
+        for file in ["../out.csv", "/tmp/out.csv"] {
+            let result = tool
+                .export("test-db", "testdb", "SELECT 1", &StatementParams::default(), &request(file))
+                .await;
+            assert!(
+                matches!(result, Err(McpError::InvalidStatement(_))),
+                "{} should be rejected",
+                file
+            );
+        }
+    }
+}
+
+/// Batches converted ahead of the `INSERT` statements that write them
+const IMPORT_QUEUED_BATCHES: usize = 2;
+
+/// Import tool for loading files from the import directory into a table
+pub struct ImportTool {
     manager: Arc<DataSourceManager>,
     pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>>,
+    config: ImportConfig,
+    timeout: Option<Duration>,
+    progress: Option<mpsc::UnboundedSender<ImportProgress>>,
 }
 
-impl ExecuteTool {
-    /// Create a new execute tool
+/// Source, target and mode of an import
+#[derive(Debug, Clone)]
+pub struct ImportRequest {
+    /// File name relative to the import directory
+    pub file: String,
+    pub table: String,
+    /// Taken from the file extension when not given
+    pub format: Option<ImportFormat>,
+    /// Taken from the file extension when not given
+    pub compression: Option<Compression>,
+    pub on_duplicate: OnDuplicate,
+    /// Only read and check the file
+    pub dry_run: bool,
+}
+
+/// Rows written so far, sent after each batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImportProgress {
+    pub rows: u64,
+}
+
+/// Result of an import
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportResult {
+    pub path: String,
+    pub table: String,
+    pub format: ImportFormat,
+    pub dry_run: bool,
+    /// Table columns the file's columns were matched to, in file order
+    pub columns: Vec<String>,
+    /// Rows read from the file; an import stops reading at the first invalid row
+    pub rows_read: u64,
+    /// Rows written to the table
+    pub rows_written: u64,
+    /// As reported by MySQL, which counts an updated duplicate twice
+    pub affected_rows: u64,
+    /// Whether the rows were committed; false after a dry run or a rollback
+    pub committed: bool,
+    /// The first problems found, with the line of the file they are on
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<ImportIssue>,
+    pub error_count: u64,
+}
+
+impl ImportTool {
+    /// Create a new import tool
     pub fn new(
         manager: Arc<DataSourceManager>,
         pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>>,
+        config: ImportConfig,
     ) -> Self {
         Self {
             manager,
             pool_managers,
+            config,
+            timeout: None,
+            progress: None,
         }
     }
 
-    /// Execute a DML statement (INSERT, UPDATE, DELETE)
-    pub async fn execute(
+    /// Request a timeout for this call instead of the configured one
+    /// (capped at `import.timeout_secs`); `None` keeps the configured one
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Report the number of rows written after each batch
+    pub fn with_progress(mut self, progress: mpsc::UnboundedSender<ImportProgress>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Load the rows of a file into a table
+    ///
+    /// The whole file is inserted in one transaction, so an invalid row or a
+    /// failed `INSERT` rolls everything back; the result then reports the
+    /// problem and `committed: false`. A dry run reads and checks every row
+    /// without writing any.
+    pub async fn import(
+        &self,
+        datasource_key: &str,
+        database: &str,
+        request: &ImportRequest,
+    ) -> Result<ImportResult> {
+        let directory = self.config.directory.as_deref().ok_or_else(|| {
+            McpError::ConfigurationError(
+                "Imports are disabled; set import.directory to enable mysql_import".to_string(),
+            )
+        })?;
+
+        // Validate parameters
+        if datasource_key.is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Data source key is required".to_string(),
+            ));
+        }
+
+        if database.is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Database name is required".to_string(),
+            ));
+        }
+
+        if request.table.is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Table name is required".to_string(),
+            ));
+        }
+
+        // Validate data source key
+        self.manager.validate_key(datasource_key)?;
+
+        // Imports write rows, dry run or not
+        self.manager.check_update_permission(datasource_key)?;
+
+        // Check the database allowlist
+        self.manager.check_database_access(datasource_key, database)?;
+
+        let (detected_format, detected_compression) = import::detect_format(&request.file);
+        let format = request.format.or(detected_format).ok_or_else(|| {
+            McpError::InvalidStatement(format!(
+                "Cannot tell the format of '{}' from its extension; pass format",
+                request.file
+            ))
+        })?;
+        let compression = request.compression.unwrap_or(detected_compression);
+
+        let path = import::resolve_path(directory, &request.file)?;
+        let size = std::fs::metadata(&path)
+            .map_err(|e| McpError::QueryExecutionError(format!("Import failed: {}", e)))?
+            .len();
+        if size > self.config.max_file_bytes {
+            return Err(McpError::InvalidStatement(format!(
+                "Import file '{}' is {} bytes, more than the limit of {}",
+                request.file, size, self.config.max_file_bytes
+            )));
+        }
+
+        // Describing the table is the first call to reach the server, and it checks
+        // that the data source is available
+        let schema = SchemaTool::new(self.manager.clone(), self.pool_managers.clone())
+            .with_timeout(self.timeout)
+            .describe_table(datasource_key, database, &request.table)
+            .await?;
+
+        let reader_path = path.clone();
+        let columns = schema.columns.clone();
+        let (reader, plan) = tokio::task::spawn_blocking(move || {
+            let reader = RecordReader::open(&reader_path, format, compression)?;
+            let plan = ImportPlan::new(reader.columns(), &columns)?;
+            Ok::<_, McpError>((reader, plan))
+        })
+        .await
+        .map_err(|e| McpError::QueryExecutionError(format!("Import failed: {}", e)))??;
+
+        let mut result = ImportResult {
+            path: path.display().to_string(),
+            table: schema.table_name.clone(),
+            format,
+            dry_run: request.dry_run,
+            columns: plan.column_names(),
+            rows_read: 0,
+            rows_written: 0,
+            affected_rows: 0,
+            committed: false,
+            errors: Vec::new(),
+            error_count: 0,
+        };
+
+        let batch_rows = self.config.batch_rows;
+        if request.dry_run {
+            let summary = tokio::task::spawn_blocking(move || {
+                import::read_batches(reader, &plan, batch_rows, None)
+            })
+            .await
+            .map_err(|e| McpError::QueryExecutionError(format!("Import failed: {}", e)))??;
+            result.rows_read = summary.rows;
+            result.errors = summary.issues;
+            result.error_count = summary.issue_count;
+            return Ok(result);
+        }
+
+        let target = ImportTarget {
+            table: &schema.table_name,
+            plan: &plan,
+            on_duplicate: request.on_duplicate,
+            primary_key: schema.primary_key.as_deref().unwrap_or_default(),
+            progress: self.progress.as_ref(),
+        };
+        let (sender, receiver) = mpsc::channel(IMPORT_QUEUED_BATCHES);
+        let reader_plan = plan.clone();
+        let reader = tokio::task::spawn_blocking(move || {
+            import::read_batches(reader, &reader_plan, batch_rows, Some(sender))
+        });
+
+        // Get or create pool manager for this data source
+        let pool_manager =
+            get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key).await?;
+
+        let timeout = self
+            .timeout
+            .map_or(self.config.timeout(), |timeout| timeout.min(self.config.timeout()));
+        let start = Instant::now();
+        let deadline = tokio::time::Instant::from_std(start) + timeout;
+        let outcome =
+            match tokio::time::timeout_at(deadline, pool_manager.get_tracked_connection(database))
+                .await
+            {
+                Ok(Ok(mut conn)) => {
+                    match tokio::time::timeout_at(
+                        deadline,
+                        run_import(&mut conn, &target, receiver, reader),
+                    )
+                    .await
+                    {
+                        Ok(Ok(outcome)) => {
+                            conn.finish();
+                            Ok(outcome)
+                        }
+                        // BEGIN, COMMIT or ROLLBACK failed; the connection state is
+                        // unknown, so it is dropped unfinished and never reused
+                        Ok(Err(e)) => Err(e),
+                        // Dropping the unfinished connection kills the running
+                        // statement and rolls the transaction back
+                        Err(_) => Err(McpError::QueryTimeout {
+                            thread_id: Some(conn.thread_id()),
+                        }),
+                    }
+                }
+                Ok(Err(e)) => Err(e),
+                Err(_) => Err(McpError::QueryTimeout { thread_id: None }),
+            };
+
+        match outcome {
+            Ok(outcome) => {
+                result.rows_read = outcome.summary.rows;
+                result.rows_written = outcome.rows_written;
+                result.affected_rows = outcome.affected_rows;
+                result.committed = outcome.committed;
+                result.errors = outcome.summary.issues;
+                result.error_count = outcome.summary.issue_count;
+                tracing::info!(
+                    datasource_key = %datasource_key,
+                    database = %database,
+                    table = %result.table,
+                    path = %result.path,
+                    rows = result.rows_written,
+                    committed = result.committed,
+                    execution_time_ms = start.elapsed().as_millis(),
+                    "File imported"
+                );
+                Ok(result)
+            }
+            Err(e) => {
+                tracing::error!(
+                    datasource_key = %datasource_key,
+                    database = %database,
+                    table = %result.table,
+                    path = %result.path,
+                    error = %e.sanitize(),
+                    execution_time_ms = start.elapsed().as_millis(),
+                    "File import failed"
+                );
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Where and how the rows of an import are written
+struct ImportTarget<'a> {
+    table: &'a str,
+    plan: &'a ImportPlan,
+    on_duplicate: OnDuplicate,
+    primary_key: &'a [String],
+    progress: Option<&'a mpsc::UnboundedSender<ImportProgress>>,
+}
+
+/// What writing the rows of an import did
+struct ImportOutcome {
+    summary: ReadSummary,
+    rows_written: u64,
+    affected_rows: u64,
+    committed: bool,
+}
+
+/// Insert the batches read from a file inside a transaction on the given connection
+///
+/// Commits only if the reader got to the end of the file without finding an
+/// invalid row and every `INSERT` succeeded.
+async fn run_import(
+    conn: &mut MySqlConnection,
+    target: &ImportTarget<'_>,
+    mut batches: mpsc::Receiver<ImportBatch>,
+    reader: tokio::task::JoinHandle<Result<ReadSummary>>,
+) -> Result<ImportOutcome> {
+    let mut tx = conn.begin().await.map_err(|e| {
+        McpError::QueryExecutionError(format!("Failed to begin transaction: {}", e))
+    })?;
+
+    let mut rows_written = 0;
+    let mut affected_rows = 0;
+    let mut failure = None;
+    while let Some(batch) = batches.recv().await {
+        let statement = BoundStatement {
+            sql: target.plan.insert_sql(
+                target.table,
+                batch.rows.len(),
+                target.on_duplicate,
+                target.primary_key,
+            ),
+            args: batch.rows.iter().flatten().cloned().collect(),
+        };
+        match statement.query().execute(&mut *tx).await {
+            Ok(result) => {
+                rows_written += batch.rows.len() as u64;
+                affected_rows += result.rows_affected();
+                if let Some(progress) = target.progress {
+                    let _ = progress.send(ImportProgress { rows: rows_written });
+                }
+            }
+            Err(e) => {
+                failure = Some(ImportIssue {
+                    line: batch.lines.0,
+                    column: None,
+                    message: format!(
+                        "INSERT of the rows on lines {} to {} failed: {}",
+                        batch.lines.0,
+                        batch.lines.1,
+                        McpError::from(e).sanitize()
+                    ),
+                });
+                break;
+            }
+        }
+    }
+
+    // Stops the reader if an INSERT failed
+    drop(batches);
+    let mut summary = reader
+        .await
+        .map_err(|e| McpError::QueryExecutionError(format!("Import failed: {}", e)))??;
+    if let Some(issue) = failure {
+        summary.issue_count += 1;
+        summary.issues.push(issue);
+    }
+
+    if summary.issue_count > 0 {
+        tx.rollback().await.map_err(|e| {
+            McpError::QueryExecutionError(format!("Failed to roll back transaction: {}", e))
+        })?;
+        return Ok(ImportOutcome {
+            summary,
+            rows_written: 0,
+            affected_rows: 0,
+            committed: false,
+        });
+    }
+
+    tx.commit().await.map_err(|e| {
+        McpError::QueryExecutionError(format!("Failed to commit transaction: {}", e))
+    })?;
+
+    Ok(ImportOutcome {
+        summary,
+        rows_written,
+        affected_rows,
+        committed: true,
+    })
+}
+
+#[cfg(test)]
+mod import_tests {
+    use super::*;
+    use crate::config::{DataSourceConfig, Permission};
+
+    fn create_test_config(key: &str, permission: Permission) -> DataSourceConfig {
+        DataSourceConfig {
+            key: key.to_string(),
+            name: "Test Database".to_string(),
+            username: "test".to_string(),
+            password: "test".to_string(),
+            databases: vec!["allowed".to_string()],
+            permission,
+            ..Default::default()
+        }
+    }
+
+    async fn create_tool(permission: Permission, directory: Option<&std::path::Path>) -> ImportTool {
+        let manager = Arc::new(
+            DataSourceManager::new(vec![create_test_config("test-db", permission)])
+                .await
+                .unwrap(),
+        );
+        let config = ImportConfig {
+            directory: directory.map(|dir| dir.to_path_buf()),
+            ..ImportConfig::default()
+        };
+        ImportTool::new(manager, Arc::new(RwLock::new(HashMap::new())), config)
+    }
+
+    fn request(file: &str) -> ImportRequest {
+        ImportRequest {
+            file: file.to_string(),
+            table: "users".to_string(),
+            format: None,
+            compression: None,
+            on_duplicate: OnDuplicate::Error,
+            dry_run: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_tool_requires_import_directory() {
+        let tool = create_tool(Permission::Update, None).await;
+
+        let result = tool.import("test-db", "allowed", &request("in.csv")).await;
+        assert!(matches!(result, Err(McpError::ConfigurationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_import_tool_requires_update_permission() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = create_tool(Permission::Query, Some(dir.path())).await;
+
+        let result = tool.import("test-db", "allowed", &request("in.csv")).await;
+        assert!(matches!(result, Err(McpError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_import_tool_respects_database_allowlist() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = create_tool(Permission::Update, Some(dir.path())).await;
+
+        let result = tool.import("test-db", "other", &request("in.csv")).await;
+        assert!(matches!(result, Err(McpError::DatabaseNotAllowed(_))));
+    }
+}
+
+/// Explain tool for showing how the server executes a statement
+pub struct ExplainTool {
+    manager: Arc<DataSourceManager>,
+    pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>>,
+    timeout: Option<Duration>,
+}
+
+impl ExplainTool {
+    /// Create a new explain tool
+    pub fn new(
+        manager: Arc<DataSourceManager>,
+        pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>>,
+    ) -> Self {
+        Self {
+            manager,
+            pool_managers,
+            timeout: None,
+        }
+    }
+
+    /// Request a timeout for this call instead of the configured one
+    /// (capped at `max_query_timeout_secs`); `None` keeps the configured one
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Explain a statement with `EXPLAIN FORMAT=JSON`
+    ///
+    /// Queries need query permission and DML statements update permission,
+    /// although neither is run. With `analyze` the query is also run under
+    /// `EXPLAIN ANALYZE` to time each step, so only read-only queries are
+    /// accepted then, on MySQL 8.0.18 or later.
+    pub async fn explain(
+        &self,
+        datasource_key: &str,
+        database: &str,
+        statement: &str,
+        params: &StatementParams,
+        analyze: bool,
+    ) -> Result<ExplainResult> {
+        let bound = self
+            .prepare(datasource_key, database, statement, params, analyze)
+            .await?;
+
+        // Get or create pool manager for this data source
+        let pool_manager =
+            get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key).await?;
+
+        let timeout = self.manager.query_timeout(datasource_key, self.timeout);
+        let start = Instant::now();
+        let deadline = tokio::time::Instant::from_std(start) + timeout;
+        let result =
+            match tokio::time::timeout_at(deadline, pool_manager.get_tracked_connection(database))
+                .await
+            {
+                Ok(Ok(mut conn)) => {
+                    match tokio::time::timeout_at(
+                        deadline,
+                        run_explain(&mut conn, &bound, analyze.then_some(timeout)),
+                    )
+                    .await
+                    {
+                        Ok(result) => {
+                            conn.finish();
+                            result
+                        }
+                        // Dropping the unfinished connection kills the query
+                        Err(_) => Err(McpError::QueryTimeout {
+                            thread_id: Some(conn.thread_id()),
+                        }),
+                    }
+                }
+                Ok(Err(e)) => Err(e),
+                Err(_) => Err(McpError::QueryTimeout { thread_id: None }),
+            };
+
+        match &result {
+            Ok(explained) => tracing::info!(
+                datasource_key = %datasource_key,
+                database = %database,
+                analyze = analyze,
+                red_flags = explained.red_flags.len(),
+                execution_time_ms = start.elapsed().as_millis(),
+                "Statement explained"
+            ),
+            Err(e) => tracing::error!(
+                datasource_key = %datasource_key,
+                database = %database,
+                error = %e.sanitize(),
+                execution_time_ms = start.elapsed().as_millis(),
+                "Explain failed"
+            ),
+        }
+        result
+    }
+
+    /// Validate a statement and bind its values
+    async fn prepare(
+        &self,
+        datasource_key: &str,
+        database: &str,
+        statement: &str,
+        params: &StatementParams,
+        analyze: bool,
+    ) -> Result<BoundStatement> {
+        // Validate parameters
+        if datasource_key.is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Data source key is required".to_string(),
+            ));
+        }
+
+        if database.is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Database name is required".to_string(),
+            ));
+        }
+
+        if statement.trim().is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Statement is required".to_string(),
+            ));
+        }
+
+        // Validate data source key
+        self.manager.validate_key(datasource_key)?;
+
+        // Check the database allowlist
+        self.manager.check_database_access(datasource_key, database)?;
+
+        let first_statement = sql::first_statement(statement);
+        let kind = sql::classify(first_statement);
+        let keyword = sql::tokenize(first_statement)
+            .into_iter()
+            .find(|t| !t.is_punct('('))
+            .map(|t| t.text.to_ascii_uppercase())
+            .unwrap_or_default();
+
+        match (kind, keyword.as_str()) {
+            (StatementKind::ReadOnly, "SELECT" | "TABLE" | "WITH") => {
+                self.manager.check_query_permission(datasource_key)?;
+            }
+            (StatementKind::Dml, "INSERT" | "UPDATE" | "DELETE" | "REPLACE" | "WITH") => {
+                if analyze {
+                    // EXPLAIN ANALYZE would apply the changes
+                    return Err(McpError::InvalidStatement(
+                        "analyze runs the statement, so it is only accepted for read-only queries"
+                            .to_string(),
+                    ));
+                }
+                self.manager.check_update_permission(datasource_key)?;
+            }
+            _ => {
+                return Err(McpError::InvalidStatement(format!(
+                    "mysql_explain accepts SELECT, TABLE, WITH, INSERT, UPDATE, DELETE and REPLACE statements, got a {}",
+                    kind
+                )))
+            }
+        }
+
+        // Qualified names must not reach databases outside the allowlist
+        self.manager
+            .check_statement_database_access(datasource_key, first_statement)?;
+
+        // Match bind values against the statement's placeholders
+        let bound = params.bind(first_statement)?;
+
+        // Check if data source is available
+        if !self.manager.is_available(datasource_key).await {
+            return Err(McpError::DataSourceUnavailable(format!(
+                "Data source '{}' is currently unavailable",
+                datasource_key
+            )));
+        }
+
+        Ok(bound)
+    }
+}
+
+/// Explain a statement, and with `analyze_timeout` also time it with `EXPLAIN ANALYZE`
+async fn run_explain(
+    conn: &mut MySqlConnection,
+    statement: &BoundStatement,
+    analyze_timeout: Option<Duration>,
+) -> Result<ExplainResult> {
+    let explain = BoundStatement {
+        sql: format!("EXPLAIN FORMAT=JSON {}", statement.sql),
+        args: statement.args.clone(),
+    };
+    let plan = explain_output(conn, &explain).await?;
+    let plan: serde_json::Value = serde_json::from_str(&plan).map_err(|e| {
+        McpError::QueryExecutionError(format!("Unexpected EXPLAIN output: {}", e))
+    })?;
+
+    let analyze = match analyze_timeout {
+        Some(timeout) => {
+            let version: String = sqlx::query_scalar("SELECT VERSION()")
+                .fetch_one(&mut *conn)
+                .await
+                .map_err(McpError::from)?;
+            if !explain::supports_analyze(&version) {
+                return Err(McpError::InvalidStatement(format!(
+                    "analyze needs MySQL 8.0.18 or later; the server runs {}",
+                    version
+                )));
+            }
+
+            // The query really runs, so the server should stop it at the timeout too
+            let analyze = BoundStatement {
+                sql: format!(
+                    "EXPLAIN ANALYZE {}",
+                    sql::with_max_execution_time(&statement.sql, timeout.as_millis() as u64)
+                ),
+                args: statement.args.clone(),
+            };
+            Some(explain_output(conn, &analyze).await?)
+        }
+        None => None,
+    };
+
+    Ok(ExplainResult::new(&plan, analyze.as_deref()))
+}
+
+/// The single text value returned by an EXPLAIN statement
+async fn explain_output(
+    conn: &mut MySqlConnection,
+    statement: &BoundStatement,
+) -> Result<String> {
+    let row = statement
+        .query()
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(McpError::from)?;
+    row.try_get::<String, _>(0)
+        .or_else(|_| {
+            row.try_get::<Vec<u8>, _>(0)
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        })
+        .map_err(|e| McpError::QueryExecutionError(format!("Unexpected EXPLAIN output: {}", e)))
+}
+
+#[cfg(test)]
+mod explain_tests {
+    use super::*;
+    use crate::config::{DataSourceConfig, Permission};
+
+    fn create_test_config(key: &str, permission: Permission) -> DataSourceConfig {
+        DataSourceConfig {
+            key: key.to_string(),
+            name: "Test Database".to_string(),
+            username: "test".to_string(),
+            password: "test".to_string(),
+            permission,
+            ..Default::default()
+        }
+    }
+
+    async fn create_tool(permission: Permission) -> ExplainTool {
+        let manager = Arc::new(
+            DataSourceManager::new(vec![create_test_config("test-db", permission)])
+                .await
+                .unwrap(),
+        );
+        ExplainTool::new(manager, Arc::new(RwLock::new(HashMap::new())))
+    }
+
+    #[tokio::test]
+    async fn test_explain_tool_rejects_statements_without_a_plan() {
+        let tool = create_tool(Permission::Ddl).await;
+
+        for statement in ["SHOW TABLES", "EXPLAIN SELECT 1", "DROP TABLE users", "SET @a = 1"] {
+            let result = tool
+                .prepare("test-db", "testdb", statement, &StatementParams::default(), false)
+                .await;
+            assert!(
+                matches!(result, Err(McpError::InvalidStatement(_))),
+                "{} should be rejected",
+                statement
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_explain_tool_checks_permission_for_dml() {
+        let tool = create_tool(Permission::Query).await;
+        let params = StatementParams::default();
+
+        let result = tool
+            .prepare("test-db", "testdb", "SELECT * FROM users WHERE id = 1", &params, true)
+            .await;
+        assert!(result.is_ok());
+
+        let result = tool
+            .prepare("test-db", "testdb", "DELETE FROM users WHERE id = 1", &params, false)
+            .await;
+        assert!(matches!(result, Err(McpError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_explain_tool_analyzes_read_only_queries_only() {
+        let tool = create_tool(Permission::Update).await;
+
+        let result = tool
+            .prepare(
+                "test-db",
+                "testdb",
+                "UPDATE users SET name = 'a'",
+                &StatementParams::default(),
+                true,
+            )
+            .await;
+        assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+    }
+}
+
+/// Index advisor tool for proposing indexes from the shape of queries
+pub struct IndexAdvisorTool {
+    manager: Arc<DataSourceManager>,
+    pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>>,
+    timeout: Option<Duration>,
+}
+
+impl IndexAdvisorTool {
+    /// Create a new index advisor tool
+    pub fn new(
+        manager: Arc<DataSourceManager>,
+        pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>>,
+    ) -> Self {
+        Self {
+            manager,
+            pool_managers,
+            timeout: None,
+        }
+    }
+
+    /// Request a timeout for this call instead of the configured one
+    /// (capped at `max_query_timeout_secs`); `None` keeps the configured one
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Suggest indexes for one statement
+    ///
+    /// The statement is explained, never run, to show how each table is read
+    /// today; if it cannot be explained the advice is given without the plan.
+    pub async fn suggest_for_query(
+        &self,
+        datasource_key: &str,
+        database: &str,
+        statement: &str,
+        params: &StatementParams,
+    ) -> Result<IndexAdvice> {
+        self.validate(datasource_key, database)?;
+
+        if statement.trim().is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Statement is required".to_string(),
+            ));
+        }
+
+        let first_statement = sql::first_statement(statement);
+        let kind = sql::classify(first_statement);
+        let keyword = sql::tokenize(first_statement)
+            .into_iter()
+            .find(|t| !t.is_punct('('))
+            .map(|t| t.text.to_ascii_uppercase())
+            .unwrap_or_default();
+        if !matches!(
+            (kind, keyword.as_str()),
+            (StatementKind::ReadOnly, "SELECT" | "TABLE" | "WITH")
+                | (StatementKind::Dml, "INSERT" | "UPDATE" | "DELETE" | "REPLACE" | "WITH")
+        ) {
+            return Err(McpError::InvalidStatement(format!(
+                "mysql_suggest_indexes accepts SELECT, TABLE, WITH, INSERT, UPDATE, DELETE and REPLACE statements, got a {}",
+                kind
+            )));
+        }
+
+        // Qualified names must not reach databases outside the allowlist
+        self.manager
+            .check_statement_database_access(datasource_key, first_statement)?;
+
+        let mut advice = IndexAdvice::default();
+        let plan = match ExplainTool::new(self.manager.clone(), self.pool_managers.clone())
+            .with_timeout(self.timeout)
+            .explain(datasource_key, database, statement, params, false)
+            .await
+        {
+            Ok(plan) => Some(plan),
+            // Explaining is the first call to reach the server
+            Err(e @ McpError::DataSourceUnavailable(_)) => return Err(e),
+            Err(e) => {
+                advice
+                    .notes
+                    .push(format!("The statement could not be explained: {}", e.sanitize()));
+                None
+            }
+        };
+
+        self.advise(
+            datasource_key,
+            database,
+            &[first_statement.to_string()],
+            plan.as_ref(),
+            advice,
+        )
+        .await
+    }
+
+    /// Suggest indexes for the `limit` statements that took the most time in
+    /// `database`, from `performance_schema.events_statements_summary_by_digest`
+    pub async fn suggest_from_digests(
+        &self,
+        datasource_key: &str,
+        database: &str,
+        limit: usize,
+    ) -> Result<IndexAdvice> {
+        self.validate(datasource_key, database)?;
+
+        if limit == 0 {
+            return Err(McpError::InvalidStatement(
+                "from_digests must be at least 1".to_string(),
+            ));
+        }
+
+        // Check if data source is available
+        if !self.manager.is_available(datasource_key).await {
+            return Err(McpError::DataSourceUnavailable(format!(
+                "Data source '{}' is currently unavailable",
+                datasource_key
+            )));
+        }
+
+        // Get or create pool manager for this data source
+        let pool_manager =
+            get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key).await?;
+        let digests: Vec<String> = self
+            .manager
+            .run_with_timeout(datasource_key, self.timeout, async {
+                let pool = pool_manager.get_pool(database).await?;
+                sqlx::query_scalar(
+                    "SELECT DIGEST_TEXT FROM performance_schema.events_statements_summary_by_digest
+                     WHERE SCHEMA_NAME = ? AND DIGEST_TEXT IS NOT NULL
+                       AND (DIGEST_TEXT LIKE 'SELECT%' OR DIGEST_TEXT LIKE 'UPDATE%'
+                            OR DIGEST_TEXT LIKE 'DELETE%' OR DIGEST_TEXT LIKE 'WITH%')
+                     ORDER BY SUM_TIMER_WAIT DESC
+                     LIMIT ?",
+                )
+                .bind(database)
+                .bind(limit as u64)
+                .fetch_all(&pool)
+                .await
+                .map_err(McpError::from)
+            })
+            .await?;
+
+        // Digests are only read, but they must not reveal other databases' tables
+        let found = digests.len();
+        let queries: Vec<String> = digests
+            .into_iter()
+            .filter(|digest| {
+                self.manager
+                    .check_statement_database_access(datasource_key, digest)
+                    .is_ok()
+            })
+            .collect();
+
+        let mut advice = IndexAdvice::default();
+        if queries.len() < found {
+            advice.notes.push(format!(
+                "{} statements naming databases outside the allowlist were skipped",
+                found - queries.len()
+            ));
+        }
+        self.advise(datasource_key, database, &queries, None, advice)
+            .await
+    }
+
+    /// Validate the data source and database
+    ///
+    /// Availability is checked by whichever call reaches the server first, so a
+    /// call rejected here does not take the circuit breaker's probe.
+    fn validate(&self, datasource_key: &str, database: &str) -> Result<()> {
+        // Validate parameters
+        if datasource_key.is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Data source key is required".to_string(),
+            ));
+        }
+
+        if database.is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Database name is required".to_string(),
+            ));
+        }
+
+        // Validate data source key
+        self.manager.validate_key(datasource_key)?;
+
+        // Check query permission
+        self.manager.check_query_permission(datasource_key)?;
+
+        // Check the database allowlist
+        self.manager.check_database_access(datasource_key, database)
+    }
+
+    /// Compare the queries with the indexes of the tables they read
+    async fn advise(
+        &self,
+        datasource_key: &str,
+        database: &str,
+        queries: &[String],
+        plan: Option<&ExplainResult>,
+        mut advice: IndexAdvice,
+    ) -> Result<IndexAdvice> {
+        let schema_tool = SchemaTool::new(self.manager.clone(), self.pool_managers.clone())
+            .with_timeout(self.timeout);
+        let mut schemas: Vec<TableSchema> = Vec::new();
+        let mut skipped: Vec<String> = Vec::new();
+
+        for query in queries {
+            let shape = QueryShape::parse(query);
+            for table in &shape.tables {
+                if let Some(other) = table
+                    .database
+                    .as_deref()
+                    .filter(|other| !other.eq_ignore_ascii_case(database))
+                {
+                    let name = format!("{}.{}", other, table.name);
+                    if !skipped.contains(&name) {
+                        advice.notes.push(format!(
+                            "Table '{}' is outside '{}' and was not analysed",
+                            name, database
+                        ));
+                        skipped.push(name);
+                    }
+                    continue;
+                }
+                if skipped.contains(&table.name) {
+                    continue;
+                }
+
+                let index = match schemas
+                    .iter()
+                    .position(|schema| schema.table_name.eq_ignore_ascii_case(&table.name))
+                {
+                    Some(index) => index,
+                    None => match schema_tool
+                        .describe_table(datasource_key, database, &table.name)
+                        .await
+                    {
+                        Ok(schema) => {
+                            schemas.push(schema);
+                            schemas.len() - 1
+                        }
+                        // A common table expression or a name the query misspells
+                        Err(McpError::TableNotFound(_)) => {
+                            advice.notes.push(format!(
+                                "'{}' is not a table in '{}' and was not analysed",
+                                table.name, database
+                            ));
+                            skipped.push(table.name.clone());
+                            continue;
+                        }
+                        Err(e) => return Err(e),
+                    },
+                };
+
+                if let Some(mut suggestion) =
+                    advisor::suggest_index(&shape, table, &schemas[index], database)
+                {
+                    suggestion.current_access =
+                        plan.and_then(|plan| advisor::plan_access(&plan.plan, table));
+                    advice.add_suggestion(suggestion);
+                }
+            }
+        }
+
+        for schema in &schemas {
+            advice
+                .redundant_indexes
+                .extend(advisor::redundant_indexes(schema, database));
+        }
+
+        if !schemas.is_empty() {
+            match self.unused_indexes(datasource_key, database).await {
+                Ok(unused) => {
+                    advice.unused_indexes = unused
+                        .into_iter()
+                        .filter(|unused| schemas.iter().any(|s| s.table_name == unused.table))
+                        .collect()
+                }
+                Err(e) => advice.notes.push(format!(
+                    "Unused indexes were not checked (needs the sys schema): {}",
+                    e.sanitize()
+                )),
+            }
+        }
+
+        if let Some(plan) = plan {
+            advice.plan_red_flags = plan.red_flags.clone();
+        }
+        advice.queries_analyzed = queries.len();
+
+        tracing::info!(
+            datasource_key = %datasource_key,
+            database = %database,
+            queries = advice.queries_analyzed,
+            suggestions = advice.suggestions.len(),
+            redundant_indexes = advice.redundant_indexes.len(),
+            "Index advice prepared"
+        );
+
+        Ok(advice)
+    }
+
+    /// Indexes in `database` unused since the server started, from `sys.schema_unused_indexes`
+    async fn unused_indexes(&self, datasource_key: &str, database: &str) -> Result<Vec<UnusedIndex>> {
+        // Get or create pool manager for this data source
+        let pool_manager =
+            get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key).await?;
+        self.manager
+            .run_with_timeout(datasource_key, self.timeout, async {
+                let pool = pool_manager.get_pool(database).await?;
+                let rows: Vec<(String, String)> = sqlx::query_as(
+                    "SELECT object_name, index_name FROM sys.schema_unused_indexes
+                     WHERE object_schema = ?
+                     ORDER BY object_name, index_name",
+                )
+                .bind(database)
+                .fetch_all(&pool)
+                .await
+                .map_err(McpError::from)?;
+                Ok(rows
+                    .into_iter()
+                    .map(|(table, index)| UnusedIndex { table, index })
+                    .collect())
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod advisor_tests {
+    use super::*;
+    use crate::config::{DataSourceConfig, Permission};
+
+    fn create_test_config(key: &str, permission: Permission) -> DataSourceConfig {
+        DataSourceConfig {
+            key: key.to_string(),
+            name: "Test Database".to_string(),
+            username: "test".to_string(),
+            password: "test".to_string(),
+            databases: vec!["allowed".to_string()],
+            permission,
+            ..Default::default()
+        }
+    }
+
+    async fn create_tool(permission: Permission) -> IndexAdvisorTool {
+        let manager = Arc::new(
+            DataSourceManager::new(vec![create_test_config("test-db", permission)])
+                .await
+                .unwrap(),
+        );
+        IndexAdvisorTool::new(manager, Arc::new(RwLock::new(HashMap::new())))
+    }
+
+    #[tokio::test]
+    async fn test_index_advisor_rejects_statements_without_conditions() {
+        let tool = create_tool(Permission::Query).await;
+
+        for statement in ["", "DROP TABLE users", "SHOW TABLES"] {
+            let result = tool
+                .suggest_for_query("test-db", "allowed", statement, &StatementParams::default())
+                .await;
+            assert!(
+                matches!(result, Err(McpError::InvalidStatement(_))),
+                "{:?} should be rejected",
+                statement
+            );
+        }
+
+        let result = tool.suggest_from_digests("test-db", "allowed", 0).await;
+        assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+    }
+
+    #[tokio::test]
+    async fn test_index_advisor_respects_database_allowlist() {
+        let tool = create_tool(Permission::Query).await;
+        let params = StatementParams::default();
+
+        let result = tool
+            .suggest_for_query("test-db", "other", "SELECT * FROM t WHERE a = 1", &params)
+            .await;
+        assert!(matches!(result, Err(McpError::DatabaseNotAllowed(_))));
+
+        let result = tool
+            .suggest_for_query("test-db", "allowed", "SELECT * FROM other.t WHERE a = 1", &params)
+            .await;
+        assert!(matches!(result, Err(McpError::DatabaseNotAllowed(_))));
+
+        let result = tool.suggest_from_digests("test-db", "other", 10).await;
+        assert!(matches!(result, Err(McpError::DatabaseNotAllowed(_))));
+    }
+}
+
+/// Process tool for inspecting and killing server threads
+pub struct ProcessTool {
+    manager: Arc<DataSourceManager>,
+    pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>>,
+    timeout: Option<Duration>,
+}
+
+/// Which threads `mysql_processlist` returns
+#[derive(Debug, Clone, Default)]
+pub struct ProcessFilter {
+    /// Include idle connections (`Sleep` command)
+    pub include_sleeping: bool,
+    /// Only threads whose current command has run at least this long
+    pub min_time_secs: u64,
+    pub user: Option<String>,
+    pub database: Option<String>,
+}
+
+/// A thread from the process list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessInfo {
+    pub id: u64,
+    pub user: Option<String>,
+    pub host: Option<String>,
+    pub database: Option<String>,
+    pub command: Option<String>,
+    pub time_secs: i64,
+    pub state: Option<String>,
+    /// The running statement
+    pub info: Option<String>,
+    /// Whether the connection belongs to this server's pools
+    pub own_connection: bool,
+}
+
+/// One side of an InnoDB lock wait
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockParty {
+    pub trx_id: Option<String>,
+    pub thread_id: Option<u64>,
+    pub query: Option<String>,
+    pub lock_mode: Option<String>,
+}
+
+/// A transaction waiting for a lock another transaction holds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockWait {
+    pub wait_started: Option<String>,
+    pub wait_age_secs: i64,
+    pub locked_table: Option<String>,
+    pub locked_index: Option<String>,
+    pub lock_type: Option<String>,
+    pub waiting: LockParty,
+    pub blocking: LockParty,
+}
+
+/// An open InnoDB transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionInfo {
+    pub trx_id: String,
+    pub thread_id: Option<u64>,
+    pub state: Option<String>,
+    pub started: Option<String>,
+    pub age_secs: i64,
+    pub rows_locked: u64,
+    pub rows_modified: u64,
+    pub query: Option<String>,
+    /// Whether the connection belongs to this server's pools
+    pub own_connection: bool,
+}
+
+/// Result of `mysql_lock_waits`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockReport {
+    pub lock_waits: Vec<LockWait>,
+    /// Transactions open for at least the requested time, oldest first
+    pub transactions: Vec<TransactionInfo>,
+}
+
+/// What `mysql_kill` ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KillTarget {
+    /// `KILL QUERY`: stop the running statement, keep the connection
+    Query,
+    /// `KILL CONNECTION`: close the connection, rolling back its transaction
+    Connection,
+}
+
+/// Result of `mysql_kill`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KillResult {
+    pub thread_id: u64,
+    pub killed: KillTarget,
+    pub user: Option<String>,
+    pub own_connection: bool,
+}
+
+/// Threads from performance_schema, which reads them without the process list mutex
+const THREADS_QUERY: &str = "SELECT PROCESSLIST_ID AS id, PROCESSLIST_USER AS user,
+        PROCESSLIST_HOST AS host, PROCESSLIST_DB AS db, PROCESSLIST_COMMAND AS command,
+        CAST(IFNULL(PROCESSLIST_TIME, 0) AS SIGNED) AS time, PROCESSLIST_STATE AS state,
+        PROCESSLIST_INFO AS info
+     FROM performance_schema.threads
+     WHERE PROCESSLIST_ID IS NOT NULL
+     ORDER BY PROCESSLIST_TIME DESC";
+
+/// Threads for servers without performance_schema
+const PROCESSLIST_QUERY: &str = "SELECT ID AS id, USER AS user, HOST AS host, DB AS db,
+        COMMAND AS command, CAST(TIME AS SIGNED) AS time, STATE AS state, INFO AS info
+     FROM information_schema.PROCESSLIST
+     ORDER BY TIME DESC";
+
+const LOCK_WAITS_QUERY: &str = "SELECT CAST(wait_started AS CHAR) AS wait_started,
+        CAST(wait_age_secs AS SIGNED) AS wait_age_secs, locked_table, locked_index, locked_type,
+        CAST(waiting_trx_id AS CHAR) AS waiting_trx_id, waiting_pid, waiting_query,
+        waiting_lock_mode, CAST(blocking_trx_id AS CHAR) AS blocking_trx_id, blocking_pid,
+        blocking_query, blocking_lock_mode
+     FROM sys.innodb_lock_waits
+     ORDER BY wait_age_secs DESC";
+
+const TRANSACTIONS_QUERY: &str = "SELECT CAST(trx.trx_id AS CHAR) AS trx_id,
+        trx.trx_mysql_thread_id AS thread_id, trx.trx_state AS state,
+        CAST(trx.trx_started AS CHAR) AS started,
+        CAST(TIMESTAMPDIFF(SECOND, trx.trx_started, NOW()) AS SIGNED) AS age_secs,
+        trx.trx_rows_locked AS rows_locked, trx.trx_rows_modified AS rows_modified,
+        trx.trx_query AS query, thread.PROCESSLIST_DB AS db
+     FROM information_schema.INNODB_TRX trx
+     LEFT JOIN performance_schema.threads thread
+       ON thread.PROCESSLIST_ID = trx.trx_mysql_thread_id
+     WHERE trx.trx_started <= NOW() - INTERVAL ? SECOND
+     ORDER BY trx.trx_started";
+
+impl ProcessTool {
+    /// Create a new process tool
+    pub fn new(
+        manager: Arc<DataSourceManager>,
+        pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>>,
+    ) -> Self {
+        Self {
+            manager,
+            pool_managers,
+            timeout: None,
+        }
+    }
+
+    /// Request a timeout for this call instead of the configured one
+    /// (capped at `max_query_timeout_secs`); `None` keeps the configured one
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// List the server's threads, longest running first
+    ///
+    /// Threads in databases outside the allowlist are left out. Seeing other
+    /// users' threads needs the `PROCESS` privilege.
+    pub async fn processlist(
+        &self,
+        datasource_key: &str,
+        filter: &ProcessFilter,
+    ) -> Result<Vec<ProcessInfo>> {
+        self.validate(datasource_key)?;
+        self.manager.check_query_permission(datasource_key)?;
+        self.check_available(datasource_key).await?;
+
+        let pool_manager =
+            get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key).await?;
+        let processes = self.fetch_processes(datasource_key, &pool_manager).await?;
+
+        let processes: Vec<ProcessInfo> = processes
+            .into_iter()
+            .filter(|p| filter.include_sleeping || p.command.as_deref() != Some("Sleep"))
+            .filter(|p| p.time_secs >= filter.min_time_secs as i64)
+            .filter(|p| filter.user.is_none() || p.user == filter.user)
+            .filter(|p| filter.database.is_none() || p.database == filter.database)
+            .collect();
+
+        tracing::info!(
+            datasource_key = %datasource_key,
+            threads = processes.len(),
+            "Listed processes"
+        );
+
+        Ok(processes)
+    }
+
+    /// List InnoDB lock waits from `sys.innodb_lock_waits`, and transactions
+    /// open for at least `min_transaction_secs` from `information_schema.INNODB_TRX`
+    pub async fn lock_waits(
+        &self,
+        datasource_key: &str,
+        min_transaction_secs: u64,
+    ) -> Result<LockReport> {
+        self.validate(datasource_key)?;
+        self.manager.check_query_permission(datasource_key)?;
+        self.check_available(datasource_key).await?;
+
+        let pool_manager =
+            get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key).await?;
+        let report = self
+            .manager
+            .run_with_timeout(datasource_key, self.timeout, async {
+                let pool = pool_manager.get_metadata_pool().await?;
+
+                let rows = sqlx::query(LOCK_WAITS_QUERY)
+                    .fetch_all(&pool)
+                    .await
+                    .map_err(McpError::from)?;
+                let lock_waits = rows
+                    .iter()
+                    .map(|row| LockWait {
+                        wait_started: row_text(row, "wait_started"),
+                        wait_age_secs: row_i64(row, "wait_age_secs").unwrap_or(0),
+                        locked_table: row_text(row, "locked_table"),
+                        locked_index: row_text(row, "locked_index"),
+                        lock_type: row_text(row, "locked_type"),
+                        waiting: LockParty {
+                            trx_id: row_text(row, "waiting_trx_id"),
+                            thread_id: row_u64(row, "waiting_pid"),
+                            query: row_text(row, "waiting_query"),
+                            lock_mode: row_text(row, "waiting_lock_mode"),
+                        },
+                        blocking: LockParty {
+                            trx_id: row_text(row, "blocking_trx_id"),
+                            thread_id: row_u64(row, "blocking_pid"),
+                            query: row_text(row, "blocking_query"),
+                            lock_mode: row_text(row, "blocking_lock_mode"),
+                        },
+                    })
+                    // locked_table is `schema`.`table`
+                    .filter(|wait| {
+                        let schema = wait
+                            .locked_table
+                            .as_deref()
+                            .and_then(|table| sql::tokenize(table).first().and_then(|t| t.identifier()));
+                        schema.is_none_or(|schema| {
+                            self.manager.is_database_allowed(datasource_key, &schema)
+                        })
+                    })
+                    .collect();
+
+                let rows = sqlx::query(TRANSACTIONS_QUERY)
+                    .bind(min_transaction_secs)
+                    .fetch_all(&pool)
+                    .await
+                    .map_err(McpError::from)?;
+                let transactions = rows
+                    .iter()
+                    .filter_map(|row| {
+                        let thread_id = row_u64(row, "thread_id");
+                        let query = self.visible_statement(
+                            datasource_key,
+                            row_text(row, "db").as_deref(),
+                            row_text(row, "query"),
+                        )?;
+                        Some(TransactionInfo {
+                            trx_id: row_text(row, "trx_id").unwrap_or_default(),
+                            thread_id,
+                            state: row_text(row, "state"),
+                            started: row_text(row, "started"),
+                            age_secs: row_i64(row, "age_secs").unwrap_or(0),
+                            rows_locked: row_u64(row, "rows_locked").unwrap_or(0),
+                            rows_modified: row_u64(row, "rows_modified").unwrap_or(0),
+                            query,
+                            own_connection: thread_id
+                                .is_some_and(|id| pool_manager.owns_thread(id)),
+                        })
+                    })
+                    .collect();
+
+                Ok(LockReport {
+                    lock_waits,
+                    transactions,
+                })
+            })
+            .await?;
+
+        tracing::info!(
+            datasource_key = %datasource_key,
+            lock_waits = report.lock_waits.len(),
+            transactions = report.transactions.len(),
+            "Listed lock waits"
+        );
+
+        Ok(report)
+    }
+
+    /// Kill a statement or connection with `KILL QUERY` / `KILL CONNECTION`
+    ///
+    /// Needs `admin` on the data source. Connections of this server's own
+    /// pools are refused unless `allow_own_connection` is set, and threads in
+    /// databases outside the allowlist are always refused.
+    pub async fn kill(
+        &self,
+        datasource_key: &str,
+        thread_id: u64,
+        target: KillTarget,
+        allow_own_connection: bool,
+    ) -> Result<KillResult> {
+        self.validate(datasource_key)?;
+        self.manager.check_admin_permission(datasource_key)?;
+
+        if thread_id == 0 {
+            return Err(McpError::InvalidStatement(
+                "thread_id must be a MySQL thread id".to_string(),
+            ));
+        }
+        self.check_available(datasource_key).await?;
+
+        let pool_manager =
+            get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key).await?;
+        // Data sources may share a MySQL server, so any of the server's pools
+        // can own the thread; an id from another server errs on refusing
+        let own_connection = self
+            .pool_managers
+            .read()
+            .await
+            .values()
+            .any(|pool_manager| pool_manager.owns_thread(thread_id));
+        if own_connection && !allow_own_connection {
+            return Err(McpError::PermissionDenied(format!(
+                "Thread {} is a connection of this server's own pools; set allow_own_connection to kill it",
+                thread_id
+            )));
+        }
+
+        // Look the thread up so the allowlist applies to it
+        let process = self
+            .fetch_processes(datasource_key, &pool_manager)
+            .await?
+            .into_iter()
+            .find(|p| p.id == thread_id)
+            .ok_or_else(|| {
+                McpError::InvalidStatement(format!(
+                    "Thread {} does not exist or is not visible",
+                    thread_id
+                ))
+            })?;
+
+        let statement = match target {
+            KillTarget::Query => format!("KILL QUERY {}", thread_id),
+            KillTarget::Connection => format!("KILL CONNECTION {}", thread_id),
+        };
+        self.manager
+            .run_with_timeout(datasource_key, self.timeout, async {
+                let pool = pool_manager.get_metadata_pool().await?;
+                sqlx::query(&statement)
+                    .execute(&pool)
+                    .await
+                    .map_err(McpError::from)
+            })
+            .await?;
+
+        tracing::warn!(
+            datasource_key = %datasource_key,
+            thread_id = thread_id,
+            target = ?target,
+            own_connection = own_connection,
+            "Killed MySQL thread"
+        );
+
+        Ok(KillResult {
+            thread_id,
+            killed: target,
+            user: process.user,
+            own_connection,
+        })
+    }
+
+    /// Validate the data source key
+    fn validate(&self, datasource_key: &str) -> Result<()> {
+        // Validate parameters
+        if datasource_key.is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Data source key is required".to_string(),
+            ));
+        }
+
+        // Validate data source key
+        self.manager.validate_key(datasource_key)
+    }
+
+    /// Check availability once the call is known to be valid, since it may take
+    /// the circuit breaker's probe
+    async fn check_available(&self, datasource_key: &str) -> Result<()> {
+        // Check if data source is available
+        if !self.manager.is_available(datasource_key).await {
+            return Err(McpError::DataSourceUnavailable(format!(
+                "Data source '{}' is currently unavailable",
+                datasource_key
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Read the process list, leaving out threads the allowlist hides
+    async fn fetch_processes(
+        &self,
+        datasource_key: &str,
+        pool_manager: &ConnectionPoolManager,
+    ) -> Result<Vec<ProcessInfo>> {
+        let rows = self
+            .manager
+            .run_with_timeout(datasource_key, self.timeout, async {
+                let pool = pool_manager.get_metadata_pool().await?;
+                match sqlx::query(THREADS_QUERY).fetch_all(&pool).await {
+                    Ok(rows) => Ok(rows),
+                    Err(e) => {
+                        tracing::debug!(
+                            datasource_key = %datasource_key,
+                            error = %e,
+                            "performance_schema.threads unavailable, reading information_schema.PROCESSLIST"
+                        );
+                        sqlx::query(PROCESSLIST_QUERY)
+                            .fetch_all(&pool)
+                            .await
+                            .map_err(McpError::from)
+                    }
+                }
+            })
+            .await?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                let id = row_u64(row, "id")?;
+                let database = row_text(row, "db");
+                let info =
+                    self.visible_statement(datasource_key, database.as_deref(), row_text(row, "info"))?;
+                Some(ProcessInfo {
+                    id,
+                    user: row_text(row, "user"),
+                    host: row_text(row, "host"),
+                    database,
+                    command: row_text(row, "command"),
+                    time_secs: row_i64(row, "time").unwrap_or(0),
+                    state: row_text(row, "state"),
+                    info,
+                    own_connection: pool_manager.owns_thread(id),
+                })
+            })
+            .collect())
+    }
+
+    /// Apply the allowlist to a thread: `None` hides the thread, since its
+    /// database is not allowed; otherwise the statement, hidden if it names a
+    /// database that is not allowed
+    fn visible_statement(
+        &self,
+        datasource_key: &str,
+        database: Option<&str>,
+        statement: Option<String>,
+    ) -> Option<Option<String>> {
+        if database.is_some_and(|db| !self.manager.is_database_allowed(datasource_key, db)) {
+            return None;
+        }
+        Some(statement.filter(|statement| {
+            self.manager
+                .check_statement_database_access(datasource_key, statement)
+                .is_ok()
+        }))
+    }
+}
+
+/// A text column, whether MySQL sends it as text or binary
+fn row_text(row: &MySqlRow, column: &str) -> Option<String> {
+    row.try_get::<Option<String>, _>(column)
+        .ok()
+        .flatten()
+        .or_else(|| {
+            row.try_get::<Option<Vec<u8>>, _>(column)
+                .ok()
+                .flatten()
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        })
+}
+
+/// An integer column, signed or unsigned
+fn row_u64(row: &MySqlRow, column: &str) -> Option<u64> {
+    row.try_get::<Option<u64>, _>(column)
+        .ok()
+        .flatten()
+        .or_else(|| row_i64(row, column).and_then(|n| u64::try_from(n).ok()))
+}
+
+/// A signed integer column
+fn row_i64(row: &MySqlRow, column: &str) -> Option<i64> {
+    row.try_get::<Option<i64>, _>(column).ok().flatten()
+}
+
+#[cfg(test)]
+mod process_tests {
+    use super::*;
+    use crate::config::{DataSourceConfig, Permission};
+
+    fn create_test_config(key: &str, admin: bool) -> DataSourceConfig {
+        DataSourceConfig {
+            key: key.to_string(),
+            name: "Test Database".to_string(),
+            username: "test".to_string(),
+            password: "test".to_string(),
+            permission: Permission::Ddl,
+            admin,
+            ..Default::default()
+        }
+    }
+
+    async fn create_tool(admin: bool) -> ProcessTool {
+        let manager = Arc::new(
+            DataSourceManager::new(vec![create_test_config("test-db", admin)])
+                .await
+                .unwrap(),
+        );
+        ProcessTool::new(manager, Arc::new(RwLock::new(HashMap::new())))
+    }
+
+    #[tokio::test]
+    async fn test_kill_requires_admin() {
+        let tool = create_tool(false).await;
+
+        let result = tool.kill("test-db", 42, KillTarget::Query, false).await;
+        assert!(matches!(result, Err(McpError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_kill_rejects_invalid_thread_id() {
+        let tool = create_tool(true).await;
+
+        let result = tool.kill("test-db", 0, KillTarget::Connection, false).await;
+        assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+    }
+
+    #[tokio::test]
+    async fn test_process_tool_validates_key() {
+        let tool = create_tool(true).await;
+
+        let result = tool.processlist("missing", &ProcessFilter::default()).await;
+        assert!(matches!(result, Err(McpError::InvalidDataSourceKey(_))));
+
+        let result = tool.lock_waits("", 0).await;
+        assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+    }
+}
+
+/// Top queries tool for finding the statements that cost a server the most
+pub struct TopQueriesTool {
+    manager: Arc<DataSourceManager>,
+    pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>>,
+    timeout: Option<Duration>,
+}
+
+/// Digest statistics; `{p95}` is `QUANTILE_95`, added in MySQL 8.0.3, or `NULL`
+const DIGEST_QUERY: &str = "SELECT SCHEMA_NAME AS schema_name, DIGEST AS digest,
+        DIGEST_TEXT AS digest_text, COUNT_STAR AS count, SUM_TIMER_WAIT AS sum_wait,
+        MAX_TIMER_WAIT AS max_wait, {p95} AS p95_wait, SUM_ROWS_EXAMINED AS rows_examined,
+        SUM_ROWS_SENT AS rows_sent, SUM_ERRORS AS errors, SUM_WARNINGS AS warnings,
+        SUM_NO_INDEX_USED AS no_index_used, CAST(FIRST_SEEN AS CHAR) AS first_seen,
+        CAST(LAST_SEEN AS CHAR) AS last_seen
+     FROM performance_schema.events_statements_summary_by_digest
+     WHERE DIGEST_TEXT IS NOT NULL";
+
+/// performance_schema timers count picoseconds
+const PICOSECONDS_PER_MS: f64 = 1e9;
+
+impl TopQueriesTool {
+    /// Create a new top queries tool
+    pub fn new(
+        manager: Arc<DataSourceManager>,
+        pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>>,
+    ) -> Self {
+        Self {
+            manager,
+            pool_managers,
+            timeout: None,
+        }
+    }
+
+    /// Request a timeout for this call instead of the configured one
+    /// (capped at `max_query_timeout_secs`); `None` keeps the configured one
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The `limit` statements ranked highest by `order_by`, in `database` or in
+    /// every allowed database
+    ///
+    /// Statements in databases outside the allowlist, or naming them, are left out.
+    pub async fn top_queries(
+        &self,
+        datasource_key: &str,
+        database: Option<&str>,
+        order_by: TopQueriesOrder,
+        limit: usize,
+        source: DigestSource,
+    ) -> Result<TopQueries> {
+        // Validate parameters
+        if datasource_key.is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Data source key is required".to_string(),
+            ));
+        }
+
+        if limit == 0 {
+            return Err(McpError::InvalidStatement(
+                "limit must be at least 1".to_string(),
+            ));
+        }
+
+        // Validate data source key
+        self.manager.validate_key(datasource_key)?;
+
+        // Check query permission
+        self.manager.check_query_permission(datasource_key)?;
+
+        // Check the database allowlist
+        if let Some(database) = database {
+            self.manager.check_database_access(datasource_key, database)?;
+        }
+
+        let slow_log = self
+            .manager
+            .get_source(datasource_key)
+            .and_then(|config| config.slow_query_log.clone());
+        if source == DigestSource::SlowLog && slow_log.is_none() {
+            return Err(McpError::InvalidStatement(format!(
+                "Data source '{}' has no slow_query_log configured",
+                datasource_key
+            )));
+        }
+
+        let mut notes = Vec::new();
+        if source != DigestSource::SlowLog {
+            let result = self
+                .performance_schema_digests(datasource_key, database, order_by, limit)
+                .await;
+            let can_fall_back = source == DigestSource::Auto && slow_log.is_some();
+            match result {
+                Ok(queries) if !queries.is_empty() || !can_fall_back => {
+                    return Ok(TopQueries {
+                        source: DigestSource::PerformanceSchema,
+                        order_by,
+                        queries,
+                        notes,
+                    });
+                }
+                Ok(_) => notes.push(
+                    "performance_schema has no statement digests; read the slow query log instead"
+                        .to_string(),
+                ),
+                Err(e) if can_fall_back => notes.push(format!(
+                    "performance_schema could not be read ({}); read the slow query log instead",
+                    e.sanitize()
+                )),
+                Err(e) => return Err(e),
+            }
+        }
+
+        let path = slow_log.unwrap_or_default();
+        let queries = self
+            .slow_log_digests(datasource_key, &path, database, order_by, limit)
+            .await?;
+
+        tracing::info!(
+            datasource_key = %datasource_key,
+            queries = queries.len(),
+            "Read top queries from the slow query log"
+        );
+
+        Ok(TopQueries {
+            source: DigestSource::SlowLog,
+            order_by,
+            queries,
+            notes,
+        })
+    }
+
+    /// Read `events_statements_summary_by_digest`, highest `order_by` first
+    async fn performance_schema_digests(
+        &self,
+        datasource_key: &str,
+        database: Option<&str>,
+        order_by: TopQueriesOrder,
+        limit: usize,
+    ) -> Result<Vec<DigestStats>> {
+        // Check if data source is available
+        if !self.manager.is_available(datasource_key).await {
+            return Err(McpError::DataSourceUnavailable(format!(
+                "Data source '{}' is currently unavailable",
+                datasource_key
+            )));
+        }
+
+        // Without a database, other databases' rows are filtered out here,
+        // so the limit can only be applied afterwards
+        let restricted = database.is_none() && !self.manager.allows_all_databases(datasource_key);
+        let mut sql = DIGEST_QUERY.to_string();
+        if database.is_some() {
+            sql.push_str(" AND SCHEMA_NAME = ?");
+        }
+        sql.push_str(&format!(" ORDER BY {} DESC", order_by.column()));
+        if !restricted {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        let pool_manager =
+            get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key).await?;
+        let rows = self
+            .manager
+            .run_with_timeout(datasource_key, self.timeout, async {
+                let pool = pool_manager.get_metadata_pool().await?;
+                let fetch = |p95: &str| {
+                    let query = sql.replace("{p95}", p95);
+                    let pool = pool.clone();
+                    async move {
+                        let mut query = sqlx::query(&query);
+                        if let Some(database) = database {
+                            query = query.bind(database);
+                        }
+                        query.fetch_all(&pool).await
+                    }
+                };
+                match fetch("QUANTILE_95").await {
+                    Ok(rows) => Ok(rows),
+                    // MySQL before 8.0.3 has no histograms
+                    Err(e) if e.to_string().contains("QUANTILE_95") => fetch("NULL").await,
+                    Err(e) => Err(e),
+                }
+                .map_err(McpError::from)
+            })
+            .await?;
+
+        let mut queries: Vec<DigestStats> = rows
+            .iter()
+            .map(|row| {
+                let count = row_u64(row, "count").unwrap_or(0);
+                let per_run = |total: f64| if count == 0 { 0.0 } else { total / count as f64 };
+                let total_latency_ms = row_u64(row, "sum_wait").unwrap_or(0) as f64 / PICOSECONDS_PER_MS;
+                let rows_examined = row_u64(row, "rows_examined").unwrap_or(0);
+                let rows_sent = row_u64(row, "rows_sent").unwrap_or(0);
+                DigestStats {
+                    digest: row_text(row, "digest"),
+                    schema: row_text(row, "schema_name"),
+                    query: row_text(row, "digest_text").unwrap_or_default(),
+                    count,
+                    total_latency_ms,
+                    avg_latency_ms: per_run(total_latency_ms),
+                    p95_latency_ms: row_u64(row, "p95_wait").map(|p| p as f64 / PICOSECONDS_PER_MS),
+                    max_latency_ms: row_u64(row, "max_wait").unwrap_or(0) as f64 / PICOSECONDS_PER_MS,
+                    rows_examined,
+                    avg_rows_examined: per_run(rows_examined as f64),
+                    rows_sent,
+                    avg_rows_sent: per_run(rows_sent as f64),
+                    errors: row_u64(row, "errors"),
+                    warnings: row_u64(row, "warnings"),
+                    no_index_used: row_u64(row, "no_index_used"),
+                    first_seen: row_text(row, "first_seen"),
+                    last_seen: row_text(row, "last_seen"),
+                }
+            })
+            .filter(|digest| self.is_visible(datasource_key, digest))
+            .collect();
+        queries.truncate(limit);
+
+        tracing::info!(
+            datasource_key = %datasource_key,
+            queries = queries.len(),
+            "Read top queries from performance_schema"
+        );
+
+        Ok(queries)
+    }
+
+    /// Parse the slow query log and group its statements
+    async fn slow_log_digests(
+        &self,
+        datasource_key: &str,
+        path: &std::path::Path,
+        database: Option<&str>,
+        order_by: TopQueriesOrder,
+        limit: usize,
+    ) -> Result<Vec<DigestStats>> {
+        let path = path.to_path_buf();
+        let read = tokio::task::spawn_blocking(move || {
+            let file = std::fs::File::open(&path)?;
+            let mut aggregator = SlowLogAggregator::new();
+            for entry in SlowLogReader::new(std::io::BufReader::new(file)) {
+                aggregator.add(&entry?);
+            }
+            Ok::<_, std::io::Error>(aggregator.finish())
+        });
+
+        let digests = self
+            .manager
+            .run_with_timeout(datasource_key, self.timeout, async {
+                read.await
+                    .map_err(|e| McpError::QueryExecutionError(format!("Slow log reader failed: {}", e)))?
+                    .map_err(|e| {
+                        McpError::QueryExecutionError(format!(
+                            "Failed to read the slow query log: {}",
+                            e
+                        ))
+                    })
+            })
+            .await?;
+
+        let mut queries: Vec<DigestStats> = digests
+            .into_iter()
+            .filter(|digest| database.is_none() || digest.schema.as_deref() == database)
+            .filter(|digest| self.is_visible(datasource_key, digest))
+            .collect();
+        order_by.sort(&mut queries);
+        queries.truncate(limit);
+        Ok(queries)
+    }
+
+    /// Whether the allowlist lets a digest be shown
+    fn is_visible(&self, datasource_key: &str, digest: &DigestStats) -> bool {
+        let schema_allowed = match &digest.schema {
+            Some(schema) => self.manager.is_database_allowed(datasource_key, schema),
+            // Statements run without a default database may read any of them
+            None => self.manager.allows_all_databases(datasource_key),
+        };
+        schema_allowed
+            && self
+                .manager
+                .check_statement_database_access(datasource_key, &digest.query)
+                .is_ok()
+    }
+}
+
+#[cfg(test)]
+mod top_queries_tests {
+    use super::*;
+    use crate::config::{DataSourceConfig, Permission};
+
+    const SLOW_LOG: &str = "\
+# Time: 2024-05-01T10:00:00.000000Z
+# User@Host: app[app] @ localhost []  Id:    12
+# Query_time: 1.000000  Lock_time: 0.000000 Rows_sent: 1  Rows_examined: 900
+use shop;
+SELECT * FROM orders WHERE id = 1;
+# Time: 2024-05-01T10:00:01.000000Z
+# User@Host: app[app] @ localhost []  Id:    12
+# Query_time: 0.100000  Lock_time: 0.000000 Rows_sent: 1  Rows_examined: 5000
+SELECT * FROM items WHERE sku = 'a';
+# Time: 2024-05-01T10:00:02.000000Z
+# User@Host: app[app] @ localhost []  Id:    13
+# Query_time: 9.000000  Lock_time: 0.000000 Rows_sent: 1  Rows_examined: 1
+use secret;
+SELECT * FROM keys WHERE id = 2;
+";
+
+    async fn create_tool(slow_query_log: Option<std::path::PathBuf>) -> TopQueriesTool {
+        let config = DataSourceConfig {
+            key: "test-db".to_string(),
+            name: "Test Database".to_string(),
+            username: "test".to_string(),
+            password: "test".to_string(),
+            databases: vec!["shop".to_string()],
+            permission: Permission::Query,
+            slow_query_log,
+            ..Default::default()
+        };
+        let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
+        TopQueriesTool::new(manager, Arc::new(RwLock::new(HashMap::new())))
+    }
+
+    #[tokio::test]
+    async fn test_top_queries_from_slow_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("slow.log");
+        std::fs::write(&path, SLOW_LOG).unwrap();
+        let tool = create_tool(Some(path)).await;
+
+        let result = tool
+            .top_queries("test-db", None, TopQueriesOrder::RowsExamined, 10, DigestSource::SlowLog)
+            .await
+            .unwrap();
+        assert_eq!(result.source, DigestSource::SlowLog);
+        // The statement in `secret` is outside the allowlist
+        let queries: Vec<&str> = result.queries.iter().map(|q| q.query.as_str()).collect();
+        assert_eq!(
+            queries,
+            ["SELECT * FROM items WHERE sku = ?", "SELECT * FROM orders WHERE id = ?"]
+        );
+
+        let result = tool
+            .top_queries("test-db", Some("shop"), TopQueriesOrder::TotalLatency, 1, DigestSource::SlowLog)
+            .await
+            .unwrap();
+        assert_eq!(result.queries.len(), 1);
+        assert_eq!(result.queries[0].total_latency_ms, 1000.0);
+    }
+
+    #[tokio::test]
+    async fn test_top_queries_validates_arguments() {
+        let tool = create_tool(None).await;
+
+        let result = tool
+            .top_queries("test-db", None, TopQueriesOrder::TotalLatency, 10, DigestSource::SlowLog)
+            .await;
+        assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+
+        let result = tool
+            .top_queries("test-db", None, TopQueriesOrder::TotalLatency, 0, DigestSource::Auto)
+            .await;
+        assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+
+        let result = tool
+            .top_queries("test-db", Some("secret"), TopQueriesOrder::Errors, 10, DigestSource::Auto)
+            .await;
+        assert!(matches!(result, Err(McpError::DatabaseNotAllowed(_))));
+    }
+}
+
+/// Variables tool for inspecting server configuration and status counters
+pub struct VariablesTool {
+    manager: Arc<DataSourceManager>,
+    pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>>,
+    timeout: Option<Duration>,
+}
+
+/// Longest wait between the two `mysql_status` snapshots
+const MAX_STATUS_INTERVAL: Duration = Duration::from_secs(60);
+
+impl VariablesTool {
+    /// Create a new variables tool
+    pub fn new(
+        manager: Arc<DataSourceManager>,
+        pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>>,
+    ) -> Self {
+        Self {
+            manager,
+            pool_managers,
+            timeout: None,
+        }
+    }
+
+    /// Request a timeout for this call instead of the configured one
+    /// (capped at `max_query_timeout_secs`); `None` keeps the configured one
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Global variables, optionally filtered by name
+    pub async fn variables(
+        &self,
+        datasource_key: &str,
+        filter: Option<&str>,
+    ) -> Result<Vec<ServerVariable>> {
+        self.validate(datasource_key)?;
+        self.check_available(datasource_key).await?;
+        let snapshot = self.snapshot(datasource_key, "SHOW GLOBAL VARIABLES").await?;
+        Ok(variables::filtered(&snapshot, filter))
+    }
+
+    /// Global status, with counter rates over `interval` or, without one, over
+    /// the server's uptime
+    pub async fn status(
+        &self,
+        datasource_key: &str,
+        filter: Option<&str>,
+        interval: Option<Duration>,
+    ) -> Result<StatusReport> {
+        if interval.is_some_and(|interval| interval.is_zero() || interval > MAX_STATUS_INTERVAL) {
+            return Err(McpError::InvalidStatement(format!(
+                "interval_secs must be between 1 and {}",
+                MAX_STATUS_INTERVAL.as_secs()
+            )));
+        }
+        self.validate(datasource_key)?;
+        self.check_available(datasource_key).await?;
+
+        let first = match interval {
+            Some(interval) => {
+                let first = self.snapshot(datasource_key, "SHOW GLOBAL STATUS").await?;
+                let taken = Instant::now();
+                tokio::time::sleep(interval).await;
+                Some((first, taken))
+            }
+            None => None,
+        };
+        let last = self.snapshot(datasource_key, "SHOW GLOBAL STATUS").await?;
+
+        let report = match &first {
+            Some((first, taken)) => {
+                StatusReport::new(Some(first), &last, taken.elapsed().as_secs_f64(), filter)
+            }
+            None => StatusReport::new(None, &last, 0.0, filter),
+        };
+
+        tracing::info!(
+            datasource_key = %datasource_key,
+            sampled = report.sampled,
+            values = report.status.len(),
+            "Read server status"
+        );
+
+        Ok(report)
+    }
+
+    /// Compare the global variables of two data sources
+    pub async fn compare_variables(
+        &self,
+        left_key: &str,
+        right_key: &str,
+        filter: Option<&str>,
+    ) -> Result<VariableDiff> {
+        self.validate(left_key)?;
+        self.validate(right_key)?;
+        self.check_available(left_key).await?;
+        self.check_available(right_key).await?;
+
+        let (left, right) = tokio::try_join!(
+            self.snapshot(left_key, "SHOW GLOBAL VARIABLES"),
+            self.snapshot(right_key, "SHOW GLOBAL VARIABLES"),
+        )?;
+        let diff = VariableDiff::new(left_key, &left, right_key, &right, filter);
+
+        tracing::info!(
+            left = %left_key,
+            right = %right_key,
+            different = diff.different.len(),
+            "Compared server variables"
+        );
+
+        Ok(diff)
+    }
+
+    /// Validate the data source and permission
+    fn validate(&self, datasource_key: &str) -> Result<()> {
+        // Validate parameters
+        if datasource_key.is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Data source key is required".to_string(),
+            ));
+        }
+
+        // Validate data source key
+        self.manager.validate_key(datasource_key)?;
+
+        // Check query permission
+        self.manager.check_query_permission(datasource_key)
+    }
+
+    /// Check availability once the call is known to be valid, since it may take
+    /// the circuit breaker's probe
+    async fn check_available(&self, datasource_key: &str) -> Result<()> {
+        // Check if data source is available
+        if !self.manager.is_available(datasource_key).await {
+            return Err(McpError::DataSourceUnavailable(format!(
+                "Data source '{}' is currently unavailable",
+                datasource_key
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Read `SHOW GLOBAL VARIABLES` or `SHOW GLOBAL STATUS`
+    async fn snapshot(&self, datasource_key: &str, statement: &str) -> Result<Snapshot> {
+        // Get or create pool manager for this data source
+        let pool_manager =
+            get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key).await?;
+        self.manager
+            .run_with_timeout(datasource_key, self.timeout, async {
+                let pool = pool_manager.get_metadata_pool().await?;
+                let rows = sqlx::query(statement)
+                    .fetch_all(&pool)
+                    .await
+                    .map_err(McpError::from)?;
+                Ok(rows
+                    .iter()
+                    .filter_map(|row| {
+                        Some((
+                            row_text(row, "Variable_name")?,
+                            row_text(row, "Value").unwrap_or_default(),
+                        ))
+                    })
+                    .collect())
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod variables_tests {
+    use super::*;
+    use crate::config::DataSourceConfig;
+
+    async fn create_tool() -> VariablesTool {
+        let config = DataSourceConfig {
+            key: "test-db".to_string(),
+            name: "Test Database".to_string(),
+            username: "test".to_string(),
+            password: "test".to_string(),
+            permission: Default::default(),
+            ..Default::default()
+        };
+        let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
+        VariablesTool::new(manager, Arc::new(RwLock::new(HashMap::new())))
+    }
+
+    #[tokio::test]
+    async fn test_status_interval_is_bounded() {
+        let tool = create_tool().await;
+
+        for interval in [Duration::ZERO, Duration::from_secs(61)] {
+            let result = tool.status("test-db", None, Some(interval)).await;
+            assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compare_variables_validates_both_keys() {
+        let tool = create_tool().await;
+
+        let result = tool.compare_variables("test-db", "missing", None).await;
+        assert!(matches!(result, Err(McpError::InvalidDataSourceKey(_))));
+    }
+}
+
+/// How long the result of a call with an idempotency key is kept
+pub const IDEMPOTENCY_KEY_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// How many idempotency keys are kept per data source; the oldest go first
+pub const MAX_IDEMPOTENCY_KEYS: usize = 1000;
+
+/// Results of `mysql_execute` calls made with an idempotency key, kept per
+/// data source so that repeating a call returns its result instead of running
+/// the statement again
+pub struct IdempotentResults {
+    calls: HashMap<String, TtlCache<String, Arc<IdempotentCall>>>,
+}
+
+/// A statement run under an idempotency key, and its result once it succeeded
+#[derive(Debug)]
+struct IdempotentCall {
+    database: String,
+    statement: BoundStatement,
+    result: OnceCell<ExecuteResult>,
+}
+
+impl IdempotentResults {
+    /// Create an empty store for the manager's data sources
+    pub fn new(manager: &DataSourceManager) -> Self {
+        Self {
+            calls: manager
+                .keys()
+                .into_iter()
+                .map(|key| (key, TtlCache::new(IDEMPOTENCY_KEY_TTL, MAX_IDEMPOTENCY_KEYS)))
+                .collect(),
+        }
+    }
+
+    /// The call recorded under a key, recording this one if there is none
+    ///
+    /// A key names one statement: reusing it for another is rejected rather
+    /// than answered with the other statement's result.
+    async fn call(
+        &self,
+        datasource_key: &str,
+        idempotency_key: &str,
+        database: &str,
+        statement: &BoundStatement,
+    ) -> Result<Arc<IdempotentCall>> {
+        let calls = self
+            .calls
+            .get(datasource_key)
+            .ok_or_else(|| McpError::InvalidDataSourceKey(datasource_key.to_string()))?;
+        let call = calls
+            .get_or_insert_with(idempotency_key.to_string(), || {
+                Arc::new(IdempotentCall {
+                    database: database.to_string(),
+                    statement: statement.clone(),
+                    result: OnceCell::new(),
+                })
+            })
+            .await;
+
+        if call.database != database || call.statement != *statement {
+            return Err(McpError::InvalidStatement(format!(
+                "Idempotency key '{}' was already used for a different statement",
+                idempotency_key
+            )));
+        }
+        Ok(call)
+    }
+}
+
+/// Execute tool for executing DML statements (INSERT, UPDATE, DELETE)
+pub struct ExecuteTool {
+    manager: Arc<DataSourceManager>,
+    pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>>,
+    timeout: Option<Duration>,
+    idempotency: Option<(String, Arc<IdempotentResults>)>,
+}
+
+impl ExecuteTool {
+    /// Create a new execute tool
+    pub fn new(
+        manager: Arc<DataSourceManager>,
+        pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>>,
+    ) -> Self {
+        Self {
+            manager,
+            pool_managers,
+            timeout: None,
+            idempotency: None,
+        }
+    }
+
+    /// Request a timeout for this call instead of the configured one
+    /// (capped at `max_query_timeout_secs`); `None` keeps the configured one
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run the statement at most once per key outside transactions
+    ///
+    /// The first call with a key runs the statement, retrying transient
+    /// failures, and its result is recorded in `results`; repeating the call
+    /// returns that result. Statements are never retried otherwise, since a
+    /// statement whose connection dropped may already have been applied. The
+    /// key is the caller's assurance that running the statement twice is
+    /// harmless should that happen.
+    pub fn with_idempotency_key(
+        mut self,
+        idempotency_key: Option<String>,
+        results: Arc<IdempotentResults>,
+    ) -> Self {
+        self.idempotency = idempotency_key.map(|key| (key, results));
+        self
+    }
+
+    /// Execute a DML statement (INSERT, UPDATE, DELETE)
+    pub async fn execute(
+        &self,
+        datasource_key: &str,
+        database: &str,
+        statement: &str,
+    ) -> Result<ExecuteResult> {
+        self.execute_with_params(datasource_key, database, statement, &StatementParams::default())
+            .await
+    }
+
+    /// Execute a statement with bind values for its `?` or `:name` placeholders
+    pub async fn execute_with_params(
+        &self,
+        datasource_key: &str,
+        database: &str,
+        statement: &str,
+        params: &StatementParams,
+    ) -> Result<ExecuteResult> {
+        let bound = self
+            .prepare(datasource_key, database, statement, params, false)
+            .await?;
+
+        // Get or create pool manager for this data source
+        let pool_manager =
+            get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key).await?;
+
+        let attempt = || {
+            self.run(
+                datasource_key,
+                database,
+                pool_manager.get_tracked_connection(database),
+                &bound,
+            )
+        };
+        match &self.idempotency {
+            Some((idempotency_key, results)) => {
+                let call = results
+                    .call(datasource_key, idempotency_key, database, &bound)
+                    .await?;
+                if let Some(result) = call.result.get() {
+                    tracing::info!(
+                        datasource_key = %datasource_key,
+                        idempotency_key = %idempotency_key,
+                        "Returning recorded result of idempotent statement"
+                    );
+                    return Ok(result.clone());
+                }
+
+                // Concurrent calls with the key wait for this one; if it fails,
+                // the next of them runs the statement
+                call.result
+                    .get_or_try_init(|| {
+                        tracing::debug!(
+                            datasource_key = %datasource_key,
+                            idempotency_key = %idempotency_key,
+                            "Retrying transient failures of idempotent statement"
+                        );
+                        self.manager.retry(datasource_key, attempt)
+                    })
+                    .await
+                    .cloned()
+            }
+            None => attempt().await,
+        }
+    }
+
+    /// Execute a statement inside a transaction opened with `mysql_begin`
+    pub async fn execute_in_transaction(
+        &self,
+        transactions: &TransactionManager,
+        transaction_id: &str,
+        datasource_key: &str,
+        database: &str,
+        statement: &str,
+        params: &StatementParams,
+    ) -> Result<ExecuteResult> {
+        let bound = self
+            .prepare(datasource_key, database, statement, params, true)
+            .await?;
+        let transaction = transactions.get(transaction_id)?;
+        check_transaction_target(&transaction, datasource_key, database)?;
+
+        let result = self
+            .run(datasource_key, database, transaction.connection(), &bound)
+            .await;
+
+        if matches!(result, Err(McpError::QueryTimeout { thread_id: Some(_) })) {
+            // Killing the statement took the pinned connection with it
+            transactions.abort(transaction_id).await;
+        }
+        result
+    }
+
+    /// Validate a statement and bind its values
+    async fn prepare(
+        &self,
+        datasource_key: &str,
+        database: &str,
+        statement: &str,
+        params: &StatementParams,
+        in_transaction: bool,
+    ) -> Result<BoundStatement> {
+        // Validate parameters
+        if datasource_key.is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Data source key is required".to_string(),
+            ));
+        }
+
+        if database.is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Database name is required".to_string(),
+            ));
+        }
+
+        if statement.trim().is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Statement is required".to_string(),
+            ));
+        }
+
+        // Validate data source key
+        self.manager.validate_key(datasource_key)?;
+
+        // Classify the statement to decide which permission it needs
+        let kind = if in_transaction {
+            transaction_statement_kind(statement)?
+        } else {
+            sql::classify(statement)
+        };
+        check_execute_permission(&self.manager, datasource_key, kind, "mysql_execute")?;
+
+        // Check the database allowlist, including qualified names in the statement
+        self.manager
+            .check_database_access(datasource_key, database)?;
+        self.manager
+            .check_statement_database_access(datasource_key, statement)?;
+
+        // Match bind values against the statement's placeholders
+        let bound = params.bind(statement)?;
+
+        // Check if data source is available
+        if !self.manager.is_available(datasource_key).await {
+            return Err(McpError::DataSourceUnavailable(format!(
+                "Data source '{}' is currently unavailable",
+                datasource_key
+            )));
+        }
+
+        tracing::info!(
+            datasource_key = %datasource_key,
+            database = %database,
+            statement_length = statement.len(),
+            param_count = bound.args.len(),
+            "Executing DML statement"
+        );
+
+        Ok(bound)
+    }
+
+    /// Run a statement with timeout and log the outcome
+    ///
+    /// Waiting for the connection counts towards the timeout. A statement still
+    /// running when it expires is killed on the server along with its connection.
+    async fn run<C: StatementConnection>(
+        &self,
+        datasource_key: &str,
+        database: &str,
+        connection: impl Future<Output = Result<C>>,
+        statement: &BoundStatement,
+    ) -> Result<ExecuteResult> {
+        // Execute statement with timeout
+        let timeout = self.manager.query_timeout(datasource_key, self.timeout);
+        let start = std::time::Instant::now();
+        let deadline = tokio::time::Instant::from_std(start) + timeout;
+        let result = match tokio::time::timeout_at(deadline, connection).await {
+            Ok(Ok(mut conn)) => {
+                match tokio::time::timeout_at(
+                    deadline,
+                    execute_dml_statement(&mut *conn, statement),
+                )
+                .await
+                {
+                    Ok(result) => {
+                        conn.finish();
+                        result
+                    }
+                    // Dropping the unfinished connection kills the statement
+                    Err(_) => Err(McpError::QueryTimeout {
+                        thread_id: Some(conn.thread_id()),
+                    }),
+                }
+            }
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(McpError::QueryTimeout { thread_id: None }),
+        };
+        self.manager.record_call(datasource_key, &result).await;
+
+        let execution_time = start.elapsed();
+
+        match result {
+            Ok(execute_result) => {
+                tracing::info!(
+                    datasource_key = %datasource_key,
+                    database = %database,
+                    affected_rows = execute_result.affected_rows,
+                    last_insert_id = ?execute_result.last_insert_id,
+                    execution_time_ms = execution_time.as_millis(),
+                    "DML statement executed successfully"
+                );
+                Ok(execute_result)
+            }
+            Err(McpError::QueryTimeout { thread_id }) => {
+                tracing::error!(
+                    datasource_key = %datasource_key,
+                    database = %database,
+                    timeout_ms = timeout.as_millis(),
+                    killed_thread_id = ?thread_id,
+                    "DML statement execution timed out"
+                );
+                Err(McpError::QueryTimeout { thread_id })
+            }
+            Err(e) => {
+                tracing::error!(
+                    datasource_key = %datasource_key,
+                    database = %database,
+                    error = %e.sanitize(),
+                    execution_time_ms = execution_time.as_millis(),
+                    "DML statement execution failed"
+                );
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Execute a DML statement and return the result
+async fn execute_dml_statement<'e, E>(
+    executor: E,
+    statement: &BoundStatement,
+) -> Result<ExecuteResult>
+where
+    E: Executor<'e, Database = MySql>,
+{
+    // Execute the statement
+    let result = statement.query().execute(executor).await.map_err(McpError::from)?;
+
+    Ok(ExecuteResult::from(result))
+}
+
+/// Check the permission a statement needs on the execute path
+fn check_execute_permission(
+    manager: &DataSourceManager,
+    datasource_key: &str,
+    kind: StatementKind,
+    tool_name: &str,
+) -> Result<()> {
+    if kind.requires_ddl() {
+        // Check DDL permission
+        manager.check_ddl_permission(datasource_key)
+    } else if kind == StatementKind::Session {
+        // Connection-scoped state would leak into the shared pool
+        Err(McpError::InvalidStatement(format!(
+            "{} does not accept a {} (SET, LOCK TABLES, HANDLER, transaction control, locking reads)",
+            tool_name, kind
+        )))
+    } else {
+        // Check update permission for DML statements
+        manager.check_update_permission(datasource_key)
+    }
+}
+
+/// Check that a statement may run inside an interactive transaction and return
+/// the kind whose permission it needs
+///
+/// Anything but data modification and read-only statements may commit the
+/// transaction implicitly or outlive it, and so may a stored procedure's body.
+/// Locking reads are accepted too since their locks are released with the
+/// transaction; they need the same permission as DML.
+fn transaction_statement_kind(statement: &str) -> Result<StatementKind> {
+    if sql::is_procedure_call(statement) {
+        return Err(McpError::InvalidStatement(
+            "CALL cannot run inside a transaction, since the procedure may commit it".to_string(),
+        ));
+    }
+    match sql::classify(statement) {
+        kind @ (StatementKind::Dml | StatementKind::ReadOnly) => Ok(kind),
+        StatementKind::Session if sql::is_locking_read(statement) => Ok(StatementKind::Dml),
+        kind => Err(McpError::InvalidStatement(format!(
+            "A {} cannot run inside a transaction; only data modification, read-only \
+             and locking read statements can",
+            kind
+        ))),
+    }
+}
+
+/// Result of executing a DML statement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecuteResult {
+    pub affected_rows: u64,
+    pub last_insert_id: Option<u64>,
+}
+
+impl From<MySqlQueryResult> for ExecuteResult {
+    fn from(result: MySqlQueryResult) -> Self {
+        Self {
+            affected_rows: result.rows_affected(),
+            last_insert_id: if result.last_insert_id() > 0 {
+                Some(result.last_insert_id())
+            } else {
+                None
+            },
+        }
+    }
+}
+
+/// A statement to run inside a transaction, with its bind values
+#[derive(Debug, Clone, Default)]
+pub struct TransactionStatement {
+    pub statement: String,
+    pub params: StatementParams,
+}
+
+/// Transaction tool for running several DML statements atomically
+pub struct TransactionTool {
+    manager: Arc<DataSourceManager>,
+    pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>>,
+    timeout: Option<Duration>,
+}
+
+impl TransactionTool {
+    /// Create a new transaction tool
+    pub fn new(
+        manager: Arc<DataSourceManager>,
+        pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>>,
+    ) -> Self {
+        Self {
+            manager,
+            pool_managers,
+            timeout: None,
+        }
+    }
+
+    /// Request the time allowed for the whole transaction, from BEGIN to COMMIT,
+    /// instead of the configured query timeout (capped at `max_query_timeout_secs`)
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run statements in order on a single connection inside BEGIN/COMMIT
+    ///
+    /// Every statement is validated before a connection is taken. The first
+    /// statement that fails rolls the transaction back; the result then
+    /// reports the statements that ran and which one failed.
+    pub async fn execute(
+        &self,
+        datasource_key: &str,
+        database: &str,
+        statements: &[TransactionStatement],
+        isolation_level: Option<IsolationLevel>,
+    ) -> Result<TransactionResult> {
+        // Validate parameters
+        if datasource_key.is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Data source key is required".to_string(),
+            ));
+        }
+
+        if database.is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Database name is required".to_string(),
+            ));
+        }
+
+        if statements.is_empty() {
+            return Err(McpError::InvalidStatement(
+                "At least one statement is required".to_string(),
+            ));
+        }
+
+        // Validate data source key
+        self.manager.validate_key(datasource_key)?;
+
+        // Check the database allowlist
+        self.manager
+            .check_database_access(datasource_key, database)?;
+
+        let bound = statements
+            .iter()
+            .enumerate()
+            .map(|(index, statement)| {
+                self.validate_statement(datasource_key, statement)
+                    .map_err(|e| match e {
+                        McpError::InvalidStatement(msg) => {
+                            McpError::InvalidStatement(format!("statements[{}]: {}", index, msg))
+                        }
+                        e => e,
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Check if data source is available
+        if !self.manager.is_available(datasource_key).await {
+            return Err(McpError::DataSourceUnavailable(format!(
+                "Data source '{}' is currently unavailable",
+                datasource_key
+            )));
+        }
+
+        tracing::info!(
+            datasource_key = %datasource_key,
+            database = %database,
+            statement_count = bound.len(),
+            isolation_level = ?isolation_level,
+            "Executing transaction"
+        );
+
+        // Get or create pool manager for this data source
+        let pool_manager =
+            get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key).await?;
+
+        // Execute the transaction with timeout; waiting for the connection counts towards it
+        let timeout = self.manager.query_timeout(datasource_key, self.timeout);
+        let start = std::time::Instant::now();
+        let deadline = tokio::time::Instant::from_std(start) + timeout;
+        let result = match tokio::time::timeout_at(
+            deadline,
+            pool_manager.get_tracked_connection(database),
+        )
+        .await
+        {
+            Ok(Ok(mut conn)) => {
+                match tokio::time::timeout_at(
+                    deadline,
+                    run_transaction(&mut conn, isolation_level, &bound),
+                )
+                .await
+                {
+                    Ok(Ok(transaction_result)) => {
+                        conn.finish();
+                        Ok(transaction_result)
+                    }
+                    // BEGIN, COMMIT or ROLLBACK failed; the connection state is unknown,
+                    // so it is dropped unfinished and never reused
+                    Ok(Err(e)) => Err(e),
+                    // Dropping the unfinished connection kills the running statement and
+                    // closes the connection, which rolls the transaction back
+                    Err(_) => Err(McpError::QueryTimeout {
+                        thread_id: Some(conn.thread_id()),
+                    }),
+                }
+            }
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(McpError::QueryTimeout { thread_id: None }),
+        };
+        self.manager.record_call(datasource_key, &result).await;
+
+        let execution_time = start.elapsed();
+
+        match result {
+            Ok(transaction_result) => {
+                tracing::info!(
+                    datasource_key = %datasource_key,
+                    database = %database,
+                    committed = transaction_result.committed,
+                    failed_statement = ?transaction_result.failed_statement.as_ref().map(|f| f.index),
+                    execution_time_ms = execution_time.as_millis(),
+                    "Transaction finished"
+                );
+                Ok(transaction_result)
+            }
+            Err(McpError::QueryTimeout { thread_id }) => {
+                tracing::error!(
+                    datasource_key = %datasource_key,
+                    database = %database,
+                    timeout_ms = timeout.as_millis(),
+                    killed_thread_id = ?thread_id,
+                    "Transaction timed out and was rolled back"
+                );
+                Err(McpError::QueryTimeout { thread_id })
+            }
+            Err(e) => {
+                tracing::error!(
+                    datasource_key = %datasource_key,
+                    database = %database,
+                    error = %e.sanitize(),
+                    execution_time_ms = execution_time.as_millis(),
+                    "Transaction failed"
+                );
+                Err(e)
+            }
+        }
+    }
+
+    /// Open an interactive transaction and return its handle
+    ///
+    /// The transaction keeps a connection pinned until it is committed,
+    /// rolled back or rolled back automatically after the idle timeout.
+    pub async fn begin(
+        &self,
+        transactions: &TransactionManager,
+        datasource_key: &str,
+        database: &str,
+        isolation_level: Option<IsolationLevel>,
+    ) -> Result<String> {
+        // Validate parameters
+        if datasource_key.is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Data source key is required".to_string(),
+            ));
+        }
+
+        if database.is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Database name is required".to_string(),
+            ));
+        }
+
+        // Validate data source key
+        self.manager.validate_key(datasource_key)?;
+
+        // Check query permission; statements inside the transaction are checked as they run
+        self.manager.check_query_permission(datasource_key)?;
+
+        // Check the database allowlist
+        self.manager
+            .check_database_access(datasource_key, database)?;
+
+        // Check if data source is available
+        if !self.manager.is_available(datasource_key).await {
+            return Err(McpError::DataSourceUnavailable(format!(
+                "Data source '{}' is currently unavailable",
+                datasource_key
+            )));
+        }
+
+        // Get or create pool manager for this data source
+        let pool_manager =
+            get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key).await?;
+
+        transactions
+            .begin(&pool_manager, datasource_key, database, isolation_level)
+            .await
+    }
+
+    /// Commit an interactive transaction
+    pub async fn commit(
+        &self,
+        transactions: &TransactionManager,
+        transaction_id: &str,
+    ) -> Result<()> {
+        self.finish(transactions, transaction_id, true).await
+    }
+
+    /// Roll back an interactive transaction
+    pub async fn rollback(
+        &self,
+        transactions: &TransactionManager,
+        transaction_id: &str,
+    ) -> Result<()> {
+        self.finish(transactions, transaction_id, false).await
+    }
+
+    async fn finish(
+        &self,
+        transactions: &TransactionManager,
+        transaction_id: &str,
+        commit: bool,
+    ) -> Result<()> {
+        if transaction_id.is_empty() {
+            return Err(McpError::InvalidStatement(
+                "Transaction id is required".to_string(),
+            ));
+        }
+
+        // Not bounded by the timeout: abandoning a COMMIT halfway would leave its outcome unknown
+        if commit {
+            transactions.commit(transaction_id).await
+        } else {
+            transactions.rollback(transaction_id).await
+        }
+    }
+
+    /// Check a single statement and bind its values
+    fn validate_statement(
         &self,
         datasource_key: &str,
-        database: &str,
-        statement: &str,
-    ) -> Result<ExecuteResult> {
-        // Validate parameters
-        if datasource_key.is_empty() {
+        statement: &TransactionStatement,
+    ) -> Result<BoundStatement> {
+        let text = statement.statement.as_str();
+        if text.trim().is_empty() {
             return Err(McpError::InvalidStatement(
-                "Data source key is required".to_string(),
+                "Statement is required".to_string(),
             ));
         }
 
-        if database.is_empty() {
+        // Each entry is one statement so that all of them are classified
+        if sql::split_statements(text).len() > 1 {
             return Err(McpError::InvalidStatement(
-                "Database name is required".to_string(),
+                "contains more than one statement; list each statement separately".to_string(),
             ));
         }
 
-        if statement.trim().is_empty() {
-            return Err(McpError::InvalidStatement("Statement is required".to_string()));
+        // A procedure's body can run any of those too
+        if sql::is_procedure_call(text) {
+            return Err(McpError::InvalidStatement(
+                "mysql_transaction does not accept CALL, since the procedure may commit implicitly"
+                    .to_string(),
+            ));
         }
-
-        // Validate data source key
-        self.manager.validate_key(datasource_key)?;
-
-        // Check if data source is available
-        if !self.manager.is_available(datasource_key).await {
-            return Err(McpError::DataSourceUnavailable(format!(
-                "Data source '{}' is currently unavailable",
-                datasource_key
+        let kind = sql::classify(text);
+        if !matches!(kind, StatementKind::Dml | StatementKind::ReadOnly) {
+            // DDL, GRANT, FLUSH, LOCK TABLES and the like commit implicitly and would
+            // break atomicity; an unrecognized statement might do the same
+            return Err(McpError::InvalidStatement(format!(
+                "mysql_transaction only accepts data modification and read-only statements, not a {}",
+                kind
             )));
         }
+        check_execute_permission(&self.manager, datasource_key, kind, "mysql_transaction")?;
 
-        // Check if the statement is a DDL statement
-        if is_ddl_statement(statement) {
-            // Check DDL permission
-            self.manager.check_ddl_permission(datasource_key)?;
-        } else {
-            // Check update permission for DML statements
-            self.manager.check_update_permission(datasource_key)?;
-        }
-
-        tracing::info!(
-            datasource_key = %datasource_key,
-            database = %database,
-            statement_length = statement.len(),
-            "Executing DML statement"
-        );
-
-        // Get or create pool manager for this data source
-        let mut pool_managers = self.pool_managers.write().await;
-        if !pool_managers.contains_key(datasource_key) {
-            let config = self
-                .manager
-                .get_source(datasource_key)
-                .ok_or_else(|| McpError::InvalidDataSourceKey(datasource_key.to_string()))?;
-            let pool_manager = ConnectionPoolManager::new((*config).clone()).await?;
-            pool_managers.insert(datasource_key.to_string(), pool_manager);
-        }
-
-        let pool_manager = pool_managers.get_mut(datasource_key).unwrap();
+        // Qualified names must not reach databases outside the allowlist
+        self.manager
+            .check_statement_database_access(datasource_key, text)?;
 
-        // Execute statement with timeout
-        let start = std::time::Instant::now();
-        let result = tokio::time::timeout(
-            Duration::from_secs(30),
-            execute_dml_statement(pool_manager, database, statement),
-        )
-        .await;
+        // Match bind values against the statement's placeholders
+        statement.params.bind(text)
+    }
+}
 
-        let execution_time = start.elapsed();
+/// Run bound statements inside a transaction on the given connection
+async fn run_transaction(
+    conn: &mut MySqlConnection,
+    isolation_level: Option<IsolationLevel>,
+    statements: &[BoundStatement],
+) -> Result<TransactionResult> {
+    if let Some(level) = isolation_level {
+        // Applies to the next transaction on this connection only
+        let set_isolation = format!("SET TRANSACTION ISOLATION LEVEL {}", level.as_sql());
+        conn.execute(set_isolation.as_str())
+            .await
+            .map_err(McpError::from)?;
+    }
 
-        match result {
-            Ok(Ok(execute_result)) => {
-                tracing::info!(
-                    datasource_key = %datasource_key,
-                    database = %database,
-                    affected_rows = execute_result.affected_rows,
-                    last_insert_id = ?execute_result.last_insert_id,
-                    execution_time_ms = execution_time.as_millis(),
-                    "DML statement executed successfully"
-                );
-                Ok(execute_result)
-            }
-            Ok(Err(e)) => {
-                tracing::error!(
-                    datasource_key = %datasource_key,
-                    database = %database,
-                    error = %e.sanitize(),
-                    execution_time_ms = execution_time.as_millis(),
-                    "DML statement execution failed"
-                );
-                Err(e)
-            }
-            Err(_) => {
-                tracing::error!(
-                    datasource_key = %datasource_key,
-                    database = %database,
-                    "DML statement execution timed out after 30 seconds"
-                );
-                Err(McpError::QueryTimeout)
+    let mut tx = conn.begin().await.map_err(|e| {
+        McpError::QueryExecutionError(format!("Failed to begin transaction: {}", e))
+    })?;
+
+    let mut results = Vec::with_capacity(statements.len());
+    for (index, statement) in statements.iter().enumerate() {
+        match statement.query().execute(&mut *tx).await {
+            Ok(result) => results.push(ExecuteResult::from(result)),
+            Err(e) => {
+                tx.rollback().await.map_err(|e| {
+                    McpError::QueryExecutionError(format!("Failed to roll back transaction: {}", e))
+                })?;
+
+                return Ok(TransactionResult {
+                    committed: false,
+                    statements: results,
+                    failed_statement: Some(TransactionFailure {
+                        index,
+                        error: McpError::from(e).sanitize(),
+                    }),
+                });
             }
         }
     }
-}
-
-/// Check if a statement is a DDL statement
-/// DDL statements include: CREATE, ALTER, DROP, TRUNCATE, RENAME
-fn is_ddl_statement(statement: &str) -> bool {
-    let trimmed = statement.trim().to_uppercase();
-    
-    // Check for DDL keywords at the start of the statement
-    trimmed.starts_with("CREATE ")
-        || trimmed.starts_with("ALTER ")
-        || trimmed.starts_with("DROP ")
-        || trimmed.starts_with("TRUNCATE ")
-        || trimmed.starts_with("RENAME ")
-}
 
-/// Execute a DML statement and return the result
-async fn execute_dml_statement(
-    pool_manager: &mut ConnectionPoolManager,
-    database: &str,
-    statement: &str,
-) -> Result<ExecuteResult> {
-    let pool = pool_manager.get_pool(database).await?;
-
-    // Execute the statement
-    let result = sqlx::query(statement)
-        .execute(pool)
-        .await
-        .map_err(|e| {
-            // Check if it's a database not found error
-            let error_msg = e.to_string();
-            if error_msg.contains("Unknown database") {
-                McpError::DatabaseNotFound(database.to_string())
-            } else {
-                McpError::QueryExecutionError(error_msg)
-            }
-        })?;
+    tx.commit().await.map_err(|e| {
+        McpError::QueryExecutionError(format!("Failed to commit transaction: {}", e))
+    })?;
 
-    Ok(ExecuteResult {
-        affected_rows: result.rows_affected(),
-        last_insert_id: if result.last_insert_id() > 0 {
-            Some(result.last_insert_id())
-        } else {
-            None
-        },
+    Ok(TransactionResult {
+        committed: true,
+        statements: results,
+        failed_statement: None,
     })
 }
 
-/// Result of executing a DML statement
+/// Result of running a transaction
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ExecuteResult {
-    pub affected_rows: u64,
-    pub last_insert_id: Option<u64>,
+pub struct TransactionResult {
+    /// Whether the transaction was committed; false means it was rolled back
+    pub committed: bool,
+    /// Results of the statements that ran, in order
+    pub statements: Vec<ExecuteResult>,
+    /// The statement that caused the rollback, if any
+    pub failed_statement: Option<TransactionFailure>,
+}
+
+/// The statement that caused a transaction to roll back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionFailure {
+    /// Position of the statement in the request
+    pub index: usize,
+    pub error: String,
 }
 
 /// Schema tool for retrieving database schema information
 pub struct SchemaTool {
     manager: Arc<DataSourceManager>,
     pool_managers: Arc<RwLock<HashMap<String, ConnectionPoolManager>>>,
+    timeout: Option<Duration>,
 }
 
 impl SchemaTool {
@@ -972,9 +5658,17 @@ impl SchemaTool {
         Self {
             manager,
             pool_managers,
+            timeout: None,
         }
     }
 
+    /// Request a timeout for this call instead of the configured one
+    /// (capped at `max_query_timeout_secs`); `None` keeps the configured one
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
     /// List all tables in a database
     pub async fn list_tables(
         &self,
@@ -997,6 +5691,9 @@ impl SchemaTool {
         // Validate data source key
         self.manager.validate_key(datasource_key)?;
 
+        // Check the database allowlist
+        self.manager.check_database_access(datasource_key, database)?;
+
         // Check if data source is available
         if !self.manager.is_available(datasource_key).await {
             return Err(McpError::DataSourceUnavailable(format!(
@@ -1011,19 +5708,29 @@ impl SchemaTool {
             "Listing tables"
         );
 
-        // Get or create pool manager for this data source
-        let mut pool_managers = self.pool_managers.write().await;
-        if !pool_managers.contains_key(datasource_key) {
-            let config = self
-                .manager
-                .get_source(datasource_key)
-                .ok_or_else(|| McpError::InvalidDataSourceKey(datasource_key.to_string()))?;
-            let pool_manager = ConnectionPoolManager::new((*config).clone()).await?;
-            pool_managers.insert(datasource_key.to_string(), pool_manager);
-        }
+        let tables = self
+            .manager
+            .run_with_retry(datasource_key, self.timeout, || {
+                self.fetch_tables(datasource_key, database)
+            })
+            .await?;
+
+        tracing::info!(
+            datasource_key = %datasource_key,
+            database = %database,
+            table_count = tables.len(),
+            "Listed tables successfully"
+        );
+
+        Ok(tables)
+    }
 
-        let pool_manager = pool_managers.get_mut(datasource_key).unwrap();
-        let pool = pool_manager.get_pool(database).await?;
+    /// Read table information from information_schema
+    async fn fetch_tables(&self, datasource_key: &str, database: &str) -> Result<Vec<TableInfo>> {
+        // Get or create pool manager for this data source
+        let pool_manager =
+            get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key).await?;
+        let pool = &pool_manager.get_pool(database).await?;
 
         // Query to get table information
         let query = format!(
@@ -1041,14 +5748,7 @@ impl SchemaTool {
         let rows: Vec<sqlx::mysql::MySqlRow> = sqlx::query(&query)
             .fetch_all(pool)
             .await
-            .map_err(|e| {
-                let error_msg = e.to_string();
-                if error_msg.contains("Unknown database") {
-                    McpError::DatabaseNotFound(database.to_string())
-                } else {
-                    McpError::QueryExecutionError(error_msg)
-                }
-            })?;
+            .map_err(McpError::from)?;
 
         let tables: Vec<TableInfo> = rows
             .iter()
@@ -1063,13 +5763,6 @@ impl SchemaTool {
             })
             .collect();
 
-        tracing::info!(
-            datasource_key = %datasource_key,
-            database = %database,
-            table_count = tables.len(),
-            "Listed tables successfully"
-        );
-
         Ok(tables)
     }
 
@@ -1102,6 +5795,9 @@ impl SchemaTool {
         // Validate data source key
         self.manager.validate_key(datasource_key)?;
 
+        // Check the database allowlist
+        self.manager.check_database_access(datasource_key, database)?;
+
         // Check if data source is available
         if !self.manager.is_available(datasource_key).await {
             return Err(McpError::DataSourceUnavailable(format!(
@@ -1117,19 +5813,35 @@ impl SchemaTool {
             "Describing table"
         );
 
-        // Get or create pool manager for this data source
-        let mut pool_managers = self.pool_managers.write().await;
-        if !pool_managers.contains_key(datasource_key) {
-            let config = self
-                .manager
-                .get_source(datasource_key)
-                .ok_or_else(|| McpError::InvalidDataSourceKey(datasource_key.to_string()))?;
-            let pool_manager = ConnectionPoolManager::new((*config).clone()).await?;
-            pool_managers.insert(datasource_key.to_string(), pool_manager);
-        }
+        let schema = self
+            .manager
+            .run_with_retry(datasource_key, self.timeout, || {
+                self.fetch_table_schema(datasource_key, database, table)
+            })
+            .await?;
+
+        tracing::info!(
+            datasource_key = %datasource_key,
+            database = %database,
+            table = %table,
+            columns = schema.columns.len(),
+            "Described table successfully"
+        );
+
+        Ok(schema)
+    }
 
-        let pool_manager = pool_managers.get_mut(datasource_key).unwrap();
-        let pool = pool_manager.get_pool(database).await?;
+    /// Read the schema of a table from information_schema
+    async fn fetch_table_schema(
+        &self,
+        datasource_key: &str,
+        database: &str,
+        table: &str,
+    ) -> Result<TableSchema> {
+        // Get or create pool manager for this data source
+        let pool_manager =
+            get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key).await?;
+        let pool = &pool_manager.get_pool(database).await?;
 
         // First, check if the table exists
         let table_exists_query = format!(
@@ -1142,7 +5854,7 @@ impl SchemaTool {
         let exists_row: (i64,) = sqlx::query_as(&table_exists_query)
             .fetch_one(pool)
             .await
-            .map_err(|e| McpError::QueryExecutionError(e.to_string()))?;
+            .map_err(McpError::from)?;
 
         if exists_row.0 == 0 {
             return Err(McpError::TableNotFound(format!(
@@ -1163,14 +5875,6 @@ impl SchemaTool {
         // Get index information
         let indexes = self.get_indexes(pool, database, table).await?;
 
-        tracing::info!(
-            datasource_key = %datasource_key,
-            database = %database,
-            table = %table,
-            columns = columns.len(),
-            "Described table successfully"
-        );
-
         Ok(TableSchema {
             table_name: table.to_string(),
             columns,
@@ -1193,7 +5897,8 @@ impl SchemaTool {
                 COLUMN_TYPE as data_type,
                 IS_NULLABLE as nullable,
                 COLUMN_DEFAULT as default_value,
-                COLUMN_COMMENT as comment
+                COLUMN_COMMENT as comment,
+                EXTRA as extra
              FROM information_schema.COLUMNS
              WHERE TABLE_SCHEMA = '{}' AND TABLE_NAME = '{}'
              ORDER BY ORDINAL_POSITION",
@@ -1204,7 +5909,7 @@ impl SchemaTool {
         let rows: Vec<sqlx::mysql::MySqlRow> = sqlx::query(&query)
             .fetch_all(pool)
             .await
-            .map_err(|e| McpError::QueryExecutionError(e.to_string()))?;
+            .map_err(McpError::from)?;
 
         let columns: Vec<ColumnSchema> = rows
             .iter()
@@ -1219,6 +5924,10 @@ impl SchemaTool {
                         .unwrap_or(false),
                     default_value: row.try_get("default_value").ok(),
                     comment: row.try_get("comment").ok(),
+                    extra: row
+                        .try_get::<String, _>("extra")
+                        .ok()
+                        .filter(|extra| !extra.is_empty()),
                 }
             })
             .collect();
@@ -1247,7 +5956,7 @@ impl SchemaTool {
         let rows: Vec<sqlx::mysql::MySqlRow> = sqlx::query(&query)
             .fetch_all(pool)
             .await
-            .map_err(|e| McpError::QueryExecutionError(e.to_string()))?;
+            .map_err(McpError::from)?;
 
         if rows.is_empty() {
             Ok(None)
@@ -1286,7 +5995,7 @@ impl SchemaTool {
         let rows: Vec<sqlx::mysql::MySqlRow> = sqlx::query(&query)
             .fetch_all(pool)
             .await
-            .map_err(|e| McpError::QueryExecutionError(e.to_string()))?;
+            .map_err(McpError::from)?;
 
         // Group by constraint name
         let mut fk_map: HashMap<String, ForeignKey> = HashMap::new();
@@ -1340,7 +6049,7 @@ impl SchemaTool {
         let rows: Vec<sqlx::mysql::MySqlRow> = sqlx::query(&query)
             .fetch_all(pool)
             .await
-            .map_err(|e| McpError::QueryExecutionError(e.to_string()))?;
+            .map_err(McpError::from)?;
 
         // Group by index name
         let mut index_map: HashMap<String, Index> = HashMap::new();
@@ -1395,6 +6104,9 @@ pub struct ColumnSchema {
     pub nullable: bool,
     pub default_value: Option<String>,
     pub comment: Option<String>,
+    /// `auto_increment`, `VIRTUAL GENERATED`, `on update CURRENT_TIMESTAMP`, ...
+    #[serde(default)]
+    pub extra: Option<String>,
 }
 
 /// Foreign key definition
@@ -1422,6 +6134,7 @@ pub struct ListTool {
     // Cache for database lists with timestamp
     database_cache: Arc<RwLock<HashMap<String, (Vec<DatabaseInfo>, std::time::Instant)>>>,
     cache_duration: std::time::Duration,
+    timeout: Option<Duration>,
 }
 
 impl ListTool {
@@ -1435,9 +6148,17 @@ impl ListTool {
             pool_managers,
             database_cache: Arc::new(RwLock::new(HashMap::new())),
             cache_duration: std::time::Duration::from_secs(60),
+            timeout: None,
         }
     }
 
+    /// Request a timeout for this call instead of the configured one
+    /// (capped at `max_query_timeout_secs`); `None` keeps the configured one
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
     /// List all data sources (without exposing credentials)
     pub async fn list_datasources(&self) -> Vec<DataSourceInfo> {
         tracing::info!("Listing all data sources");
@@ -1464,14 +6185,6 @@ impl ListTool {
         // Validate data source key
         self.manager.validate_key(datasource_key)?;
 
-        // Check if data source is available
-        if !self.manager.is_available(datasource_key).await {
-            return Err(McpError::DataSourceUnavailable(format!(
-                "Data source '{}' is currently unavailable",
-                datasource_key
-            )));
-        }
-
         // Check cache first
         {
             let cache = self.database_cache.read().await;
@@ -1487,26 +6200,50 @@ impl ListTool {
             }
         }
 
+        // Check if data source is available
+        if !self.manager.is_available(datasource_key).await {
+            return Err(McpError::DataSourceUnavailable(format!(
+                "Data source '{}' is currently unavailable",
+                datasource_key
+            )));
+        }
+
         tracing::info!(
             datasource_key = %datasource_key,
             "Listing databases"
         );
 
-        // Get or create pool manager for this data source
-        let mut pool_managers = self.pool_managers.write().await;
-        if !pool_managers.contains_key(datasource_key) {
-            let config = self
-                .manager
-                .get_source(datasource_key)
-                .ok_or_else(|| McpError::InvalidDataSourceKey(datasource_key.to_string()))?;
-            let pool_manager = ConnectionPoolManager::new((*config).clone()).await?;
-            pool_managers.insert(datasource_key.to_string(), pool_manager);
+        let databases = self
+            .manager
+            .run_with_retry(datasource_key, self.timeout, || self.fetch_databases(datasource_key))
+            .await?;
+
+        tracing::info!(
+            datasource_key = %datasource_key,
+            database_count = databases.len(),
+            "Listed databases successfully"
+        );
+
+        // Update cache
+        {
+            let mut cache = self.database_cache.write().await;
+            cache.insert(
+                datasource_key.to_string(),
+                (databases.clone(), std::time::Instant::now()),
+            );
         }
 
-        let pool_manager = pool_managers.get_mut(datasource_key).unwrap();
+        Ok(databases)
+    }
+
+    /// Read the allowed databases of a data source from information_schema
+    async fn fetch_databases(&self, datasource_key: &str) -> Result<Vec<DatabaseInfo>> {
+        // Get or create pool manager for this data source
+        let pool_manager =
+            get_or_create_pool_manager(&self.pool_managers, &self.manager, datasource_key).await?;
         
         // Connect to information_schema to list databases
-        let pool = pool_manager.get_pool("information_schema").await?;
+        let pool = &pool_manager.get_metadata_pool().await?;
 
         // Query to get database information
         let query = "SELECT 
@@ -1520,13 +6257,19 @@ impl ListTool {
         let rows: Vec<sqlx::mysql::MySqlRow> = sqlx::query(query)
             .fetch_all(pool)
             .await
-            .map_err(|e| McpError::QueryExecutionError(e.to_string()))?;
+            .map_err(McpError::from)?;
 
         let mut databases: Vec<DatabaseInfo> = Vec::new();
 
         for row in rows {
             use sqlx::Row;
             let name: String = row.try_get("name").unwrap_or_default();
+
+            // Hide databases outside the allowlist
+            if !self.manager.is_database_allowed(datasource_key, &name) {
+                continue;
+            }
+
             let charset: String = row.try_get("charset").unwrap_or_default();
             let collation: String = row.try_get("collation").unwrap_or_default();
 
@@ -1554,21 +6297,6 @@ impl ListTool {
             });
         }
 
-        tracing::info!(
-            datasource_key = %datasource_key,
-            database_count = databases.len(),
-            "Listed databases successfully"
-        );
-
-        // Update cache
-        {
-            let mut cache = self.database_cache.write().await;
-            cache.insert(
-                datasource_key.to_string(),
-                (databases.clone(), std::time::Instant::now()),
-            );
-        }
-
         Ok(databases)
     }
 
@@ -1616,6 +6344,7 @@ mod stream_tests {
             name: "id".to_string(),
             data_type: "INT".to_string(),
             nullable: false,
+            ..Default::default()
         }]
     }
 
@@ -1856,6 +6585,180 @@ mod stream_tests {
         let chunk2_next = s2.next_chunk().await.unwrap();
         assert!(chunk2_next.is_some());
     }
+
+    /// A stream fed by a channel the way a server reader feeds it
+    fn server_stream(
+        chunk_size: usize,
+    ) -> (
+        QueryResultStream,
+        mpsc::Sender<Result<QueryRow>>,
+        oneshot::Receiver<()>,
+    ) {
+        let (rows_tx, rows_rx) = mpsc::channel(chunk_size);
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let buffer = RowBuffer {
+            rows: VecDeque::new(),
+            server: Some(rows_rx),
+        };
+        let reader = StreamReader {
+            thread_id: 42,
+            chunk_timeout: Duration::from_secs(5),
+            stop: std::sync::Mutex::new(Some(stop_tx)),
+        };
+        let stream =
+            QueryResultStream::with_buffer(create_test_columns(), buffer, chunk_size, Some(reader));
+        (stream, rows_tx, stop_rx)
+    }
+
+    #[tokio::test]
+    async fn test_server_stream_reads_rows_per_chunk() {
+        let (stream, rows_tx, _stop) = server_stream(2);
+        assert_eq!(stream.thread_id(), Some(42));
+
+        let reader = tokio::spawn(async move {
+            for row in create_test_rows(5) {
+                rows_tx.send(Ok(row)).await.unwrap();
+            }
+        });
+
+        let mut sizes = Vec::new();
+        loop {
+            let chunk = stream.next_chunk().await.unwrap().unwrap();
+            sizes.push(chunk.rows.len());
+            if chunk.is_last {
+                assert_eq!(chunk.total_rows, 5);
+                break;
+            }
+        }
+        reader.await.unwrap();
+
+        assert_eq!(sizes, vec![2, 2, 1]);
+        assert!(stream.next_chunk().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_server_stream_reports_read_errors() {
+        let (stream, rows_tx, _stop) = server_stream(10);
+        rows_tx
+            .send(Ok(create_test_rows(1).remove(0)))
+            .await
+            .unwrap();
+        rows_tx
+            .send(Err(McpError::QueryExecutionError(
+                "lost connection".to_string(),
+            )))
+            .await
+            .unwrap();
+
+        let result = stream.next_chunk().await;
+        assert!(matches!(result, Err(McpError::QueryExecutionError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_stops_server_reader() {
+        let (stream, rows_tx, stop) = server_stream(10);
+        let reader = tokio::spawn(async move {
+            let stopped = stop.await.is_err();
+            drop(rows_tx);
+            stopped
+        });
+
+        // Blocked waiting for rows until the stream is cancelled
+        let stream = Arc::new(stream);
+        let pending = tokio::spawn({
+            let stream = stream.clone();
+            async move { stream.next_chunk().await }
+        });
+        tokio::task::yield_now().await;
+
+        stream.cancel().await.unwrap();
+        assert!(reader.await.unwrap(), "the reader should be told to stop");
+        assert!(matches!(
+            pending.await.unwrap(),
+            Err(McpError::StreamCancelled)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_open_stream_limit_counts_server_streams() {
+        let manager = StreamManager::with_config(StreamConfig {
+            max_open: 1,
+            ..Default::default()
+        });
+
+        let (first, _rows1, _stop1) = server_stream(10);
+        let (second, _rows2, _stop2) = server_stream(10);
+        manager.try_register_stream(first).await.unwrap();
+        assert!(matches!(
+            manager.check_capacity().await,
+            Err(McpError::PoolError(_))
+        ));
+        assert!(matches!(
+            manager.try_register_stream(second).await,
+            Err(McpError::PoolError(_))
+        ));
+
+        // In-memory streams hold no connection
+        let buffered = QueryResultStream::new(create_test_columns(), create_test_rows(10), 5);
+        manager.try_register_stream(buffered).await.unwrap();
+        assert_eq!(manager.active_stream_count().await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_chunk_removes_finished_streams() {
+        let manager = StreamManager::new();
+        let stream = QueryResultStream::new(create_test_columns(), create_test_rows(3), 2);
+        let stream_id = manager.register_stream(stream).await;
+
+        let chunk = manager.fetch_chunk(&stream_id).await.unwrap();
+        assert_eq!(chunk.stream_id, stream_id);
+        assert!(!chunk.chunk.is_last);
+
+        let chunk = manager.fetch_chunk(&stream_id).await.unwrap();
+        assert!(chunk.chunk.is_last);
+        assert_eq!(manager.active_stream_count().await, 0);
+
+        let result = manager.fetch_chunk(&stream_id).await;
+        assert!(matches!(result, Err(McpError::InvalidStatement(_))));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_chunk_timeout_closes_stream() {
+        let manager = StreamManager::new();
+        let (mut stream, _rows_tx, stop) = server_stream(10);
+        stream.reader.as_mut().unwrap().chunk_timeout = Duration::from_millis(10);
+        let stream_id = manager.try_register_stream(stream).await.unwrap();
+
+        let result = manager.fetch_chunk(&stream_id).await;
+        assert!(matches!(
+            result,
+            Err(McpError::QueryTimeout {
+                thread_id: Some(42)
+            })
+        ));
+        assert!(stop.await.is_err(), "the query should be killed");
+        assert_eq!(manager.active_stream_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_close_idle_streams() {
+        let manager = StreamManager::with_config(StreamConfig {
+            idle_timeout_secs: 0,
+            ..Default::default()
+        });
+        let (stream, _rows_tx, stop) = server_stream(10);
+        manager.try_register_stream(stream).await.unwrap();
+
+        assert_eq!(manager.close_idle().await, 1);
+        assert_eq!(manager.active_stream_count().await, 0);
+        assert!(stop.await.is_err());
+
+        // Streams are not idle under the default timeout
+        let manager = StreamManager::new();
+        let stream = QueryResultStream::new(create_test_columns(), create_test_rows(3), 2);
+        manager.register_stream(stream).await;
+        assert_eq!(manager.close_idle().await, 0);
+    }
 }
 
 /// Stats tool for retrieving connection pool statistics
@@ -1915,6 +6818,7 @@ impl StatsTool {
         if let Some(pool_manager) = pool_managers.get(datasource_key) {
             // Get stats from the pool manager
             let pool_stats = pool_manager.get_stats();
+            let circuit = self.manager.get_circuit(datasource_key).await.unwrap_or_default();
             
             // Convert to ConnectionStats format
             let stats: Vec<ConnectionStats> = pool_stats
@@ -1926,6 +6830,7 @@ impl StatsTool {
                     idle_connections: ps.idle_connections,
                     total_connections: ps.total_connections,
                     queued_requests: 0, // sqlx doesn't expose this directly
+                    circuit: circuit.clone(),
                 })
                 .collect();
 
@@ -1956,6 +6861,7 @@ impl StatsTool {
         // Iterate through all pool managers
         for (datasource_key, pool_manager) in pool_managers.iter() {
             let pool_stats = pool_manager.get_stats();
+            let circuit = self.manager.get_circuit(datasource_key).await.unwrap_or_default();
             
             // Convert to ConnectionStats format
             for ps in pool_stats {
@@ -1966,6 +6872,7 @@ impl StatsTool {
                     idle_connections: ps.idle_connections,
                     total_connections: ps.total_connections,
                     queued_requests: 0, // sqlx doesn't expose this directly
+                    circuit: circuit.clone(),
                 });
             }
         }
@@ -1989,24 +6896,22 @@ pub struct ConnectionStats {
     pub idle_connections: usize,
     pub total_connections: usize,
     pub queued_requests: usize,
+    /// Circuit breaker state of the data source
+    pub circuit: CircuitInfo,
 }
 
 #[cfg(test)]
 mod stats_tests {
     use super::*;
-    use crate::config::{DataSourceConfig, PoolConfig};
+    use crate::config::DataSourceConfig;
 
     fn create_test_config(key: &str) -> DataSourceConfig {
         DataSourceConfig {
             key: key.to_string(),
             name: "Test Database".to_string(),
-            host: "localhost".to_string(),
-            port: 3306,
             username: "test".to_string(),
             password: "test".to_string(),
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: crate::config::Permission::default(),
+            ..Default::default()
         }
     }
 
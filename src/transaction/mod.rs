@@ -0,0 +1,652 @@
+use crate::config::TransactionConfig;
+use crate::error::{McpError, Result};
+use crate::pool::{acquire_error, ConnectionPoolManager, QueryKiller, StatementConnection};
+use sqlx::mysql::MySqlConnection;
+use sqlx::pool::PoolConnection;
+use sqlx::{Executor, MySql};
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+/// Transaction isolation levels supported by MySQL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsolationLevel {
+    ReadUncommitted,
+    ReadCommitted,
+    RepeatableRead,
+    Serializable,
+}
+
+impl IsolationLevel {
+    /// SQL spelling of the isolation level
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            IsolationLevel::ReadUncommitted => "READ UNCOMMITTED",
+            IsolationLevel::ReadCommitted => "READ COMMITTED",
+            IsolationLevel::RepeatableRead => "REPEATABLE READ",
+            IsolationLevel::Serializable => "SERIALIZABLE",
+        }
+    }
+}
+
+impl FromStr for IsolationLevel {
+    type Err = McpError;
+
+    /// Parse an isolation level, accepting `READ COMMITTED`, `read_committed` or `read-committed`
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_uppercase().replace(['_', '-'], " ").as_str() {
+            "READ UNCOMMITTED" => Ok(IsolationLevel::ReadUncommitted),
+            "READ COMMITTED" => Ok(IsolationLevel::ReadCommitted),
+            "REPEATABLE READ" => Ok(IsolationLevel::RepeatableRead),
+            "SERIALIZABLE" => Ok(IsolationLevel::Serializable),
+            _ => Err(McpError::InvalidStatement(format!(
+                "Unknown isolation level '{}'; expected READ UNCOMMITTED, READ COMMITTED, REPEATABLE READ or SERIALIZABLE",
+                s
+            ))),
+        }
+    }
+}
+
+/// A transaction opened by `mysql_begin`, pinned to a single connection
+///
+/// The connection stays checked out of the pool until the transaction is
+/// committed, rolled back or expires.
+pub struct OpenTransaction {
+    id: String,
+    datasource_key: String,
+    database: String,
+    conn: tokio::sync::Mutex<Option<PoolConnection<MySql>>>,
+    killer: QueryKiller,
+    last_used: Mutex<Instant>,
+}
+
+impl OpenTransaction {
+    /// Transaction handle returned to the client
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Data source the transaction runs on
+    pub fn datasource_key(&self) -> &str {
+        &self.datasource_key
+    }
+
+    /// Database the transaction runs on
+    pub fn database(&self) -> &str {
+        &self.database
+    }
+
+    /// Lock the pinned connection for a statement
+    ///
+    /// Statements on the same transaction run one at a time. Fails if the
+    /// transaction ended while waiting for the lock.
+    pub async fn connection(&self) -> Result<TransactionConnection<'_>> {
+        let guard = self.conn.lock().await;
+        if guard.is_none() {
+            return Err(unknown_transaction(&self.id));
+        }
+        self.touch();
+
+        Ok(TransactionConnection {
+            guard,
+            transaction: self,
+            finished: false,
+        })
+    }
+
+    fn touch(&self) {
+        *self.last_used.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+
+    fn idle_for(&self) -> Duration {
+        self.last_used
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .elapsed()
+    }
+
+    /// End the transaction with COMMIT or ROLLBACK and release its connection
+    async fn finish(&self, statement: &str) -> Result<()> {
+        let Some(conn) = self.conn.lock().await.take() else {
+            return Err(unknown_transaction(&self.id));
+        };
+
+        // If this fails or is cancelled the server-side state is unknown; never reuse the connection
+        let mut conn = CloseUnlessReleased::new(conn);
+        conn.execute(statement).await.map_err(|e| {
+            McpError::QueryExecutionError(format!(
+                "Failed to {} transaction: {}",
+                statement.to_lowercase(),
+                e
+            ))
+        })?;
+        conn.release();
+
+        Ok(())
+    }
+
+    /// Drop the connection without returning it to the pool
+    ///
+    /// MySQL rolls back the transaction when the connection closes.
+    async fn discard(&self) {
+        if let Some(mut conn) = self.conn.lock().await.take() {
+            conn.close_on_drop();
+        }
+    }
+}
+
+/// Exclusive access to a transaction's pinned connection
+///
+/// Dropping the guard marks the transaction as used, so a long statement does
+/// not count towards the idle timeout. Dropping it unfinished kills the
+/// statement and closes the connection, which ends the transaction.
+pub struct TransactionConnection<'a> {
+    guard: tokio::sync::MutexGuard<'a, Option<PoolConnection<MySql>>>,
+    transaction: &'a OpenTransaction,
+    finished: bool,
+}
+
+impl StatementConnection for TransactionConnection<'_> {
+    fn thread_id(&self) -> u64 {
+        self.transaction.killer.thread_id()
+    }
+
+    fn finish(mut self) {
+        self.finished = true;
+    }
+
+    /// Killing the statement would end the transaction, so the driver discards
+    /// the remaining results before the next statement instead
+    fn abandon(self) {
+        self.finish();
+    }
+}
+
+impl Deref for TransactionConnection<'_> {
+    type Target = MySqlConnection;
+
+    fn deref(&self) -> &Self::Target {
+        self.guard
+            .as_ref()
+            .expect("transaction connection checked on lock")
+    }
+}
+
+impl DerefMut for TransactionConnection<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.guard
+            .as_mut()
+            .expect("transaction connection checked on lock")
+    }
+}
+
+impl Drop for TransactionConnection<'_> {
+    fn drop(&mut self) {
+        self.transaction.touch();
+
+        if !self.finished {
+            if let Some(mut conn) = self.guard.take() {
+                conn.close_on_drop();
+                self.transaction.killer.spawn_kill();
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Registry {
+    transactions: HashMap<String, Arc<OpenTransaction>>,
+    /// Open transactions per data source, including ones still being started
+    open_counts: HashMap<String, usize>,
+}
+
+/// Manages interactive transactions started with `mysql_begin`
+pub struct TransactionManager {
+    registry: Mutex<Registry>,
+    config: TransactionConfig,
+}
+
+impl TransactionManager {
+    /// Create a new transaction manager
+    pub fn new(config: TransactionConfig) -> Self {
+        Self {
+            registry: Mutex::new(Registry::default()),
+            config,
+        }
+    }
+
+    fn registry(&self) -> std::sync::MutexGuard<'_, Registry> {
+        self.registry.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Open a transaction on a dedicated connection and return its handle
+    ///
+    /// Callers are responsible for permission and allowlist checks.
+    pub async fn begin(
+        &self,
+        pool_manager: &ConnectionPoolManager,
+        datasource_key: &str,
+        database: &str,
+        isolation_level: Option<IsolationLevel>,
+    ) -> Result<String> {
+        // Released again if starting the transaction fails or is cancelled
+        let reservation = self.reserve(datasource_key)?;
+        let (conn, killer) = start_transaction(pool_manager, database, isolation_level).await?;
+
+        let transaction = Arc::new(OpenTransaction {
+            id: uuid::Uuid::new_v4().to_string(),
+            datasource_key: datasource_key.to_string(),
+            database: database.to_string(),
+            conn: tokio::sync::Mutex::new(Some(conn)),
+            killer,
+            last_used: Mutex::new(Instant::now()),
+        });
+        let id = transaction.id.clone();
+        self.registry().transactions.insert(id.clone(), transaction);
+        reservation.keep();
+
+        tracing::info!(
+            datasource_key = %datasource_key,
+            database = %database,
+            transaction_id = %id,
+            isolation_level = ?isolation_level,
+            "Transaction started"
+        );
+        Ok(id)
+    }
+
+    /// Look up an open transaction by handle
+    pub fn get(&self, transaction_id: &str) -> Result<Arc<OpenTransaction>> {
+        self.registry()
+            .transactions
+            .get(transaction_id)
+            .cloned()
+            .ok_or_else(|| unknown_transaction(transaction_id))
+    }
+
+    /// Commit a transaction and return its connection to the pool
+    pub async fn commit(&self, transaction_id: &str) -> Result<()> {
+        let transaction = self
+            .remove(transaction_id)
+            .ok_or_else(|| unknown_transaction(transaction_id))?;
+        transaction.finish("COMMIT").await?;
+
+        tracing::info!(
+            datasource_key = %transaction.datasource_key,
+            transaction_id = %transaction_id,
+            "Transaction committed"
+        );
+        Ok(())
+    }
+
+    /// Roll back a transaction and return its connection to the pool
+    pub async fn rollback(&self, transaction_id: &str) -> Result<()> {
+        let transaction = self
+            .remove(transaction_id)
+            .ok_or_else(|| unknown_transaction(transaction_id))?;
+        transaction.finish("ROLLBACK").await?;
+
+        tracing::info!(
+            datasource_key = %transaction.datasource_key,
+            transaction_id = %transaction_id,
+            "Transaction rolled back"
+        );
+        Ok(())
+    }
+
+    /// Abandon a transaction whose connection is in an unknown state
+    ///
+    /// The connection is closed instead of being returned to the pool, which
+    /// makes MySQL roll the transaction back.
+    pub async fn abort(&self, transaction_id: &str) {
+        if let Some(transaction) = self.remove(transaction_id) {
+            transaction.discard().await;
+            tracing::warn!(
+                datasource_key = %transaction.datasource_key,
+                transaction_id = %transaction_id,
+                "Transaction aborted"
+            );
+        }
+    }
+
+    /// Roll back transactions that have been idle for longer than the idle timeout
+    /// Returns the number of transactions rolled back
+    pub async fn rollback_idle(&self) -> usize {
+        let idle_timeout = self.config.idle_timeout();
+        let expired: Vec<Arc<OpenTransaction>> = {
+            let mut registry = self.registry();
+            let ids: Vec<String> = registry
+                .transactions
+                .values()
+                // A locked connection is running a statement and is not idle
+                .filter(|t| t.conn.try_lock().is_ok() && t.idle_for() >= idle_timeout)
+                .map(|t| t.id.clone())
+                .collect();
+            ids.iter()
+                .filter_map(|id| remove_locked(&mut registry, id))
+                .collect()
+        };
+
+        for transaction in &expired {
+            tracing::warn!(
+                datasource_key = %transaction.datasource_key,
+                transaction_id = %transaction.id,
+                idle_timeout_secs = idle_timeout.as_secs(),
+                "Rolling back idle transaction"
+            );
+            if transaction.finish("ROLLBACK").await.is_err() {
+                transaction.discard().await;
+            }
+        }
+
+        expired.len()
+    }
+
+    /// Roll back every open transaction
+    pub async fn rollback_all(&self) {
+        let transactions: Vec<Arc<OpenTransaction>> = {
+            let mut registry = self.registry();
+            registry.open_counts.clear();
+            registry.transactions.drain().map(|(_, t)| t).collect()
+        };
+
+        for transaction in transactions {
+            tracing::info!(
+                datasource_key = %transaction.datasource_key,
+                transaction_id = %transaction.id,
+                "Rolling back open transaction"
+            );
+            if transaction.finish("ROLLBACK").await.is_err() {
+                transaction.discard().await;
+            }
+        }
+    }
+
+    /// Idle time after which an open transaction is rolled back
+    pub fn idle_timeout(&self) -> Duration {
+        self.config.idle_timeout()
+    }
+
+    /// Number of open transactions for a data source
+    pub fn open_count(&self, datasource_key: &str) -> usize {
+        self.registry()
+            .open_counts
+            .get(datasource_key)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Spawn a background task that rolls back idle transactions
+    ///
+    /// The task stops once the manager is dropped.
+    pub fn spawn_idle_reaper(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let manager: Weak<Self> = Arc::downgrade(self);
+        let period = (self.config.idle_timeout() / 2).max(Duration::from_secs(1));
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            // Skip the first tick (immediate)
+            interval.tick().await;
+
+            loop {
+                interval.tick().await;
+
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+                manager.rollback_idle().await;
+            }
+        })
+    }
+
+    /// Count a new transaction against the data source's limit
+    fn reserve(&self, datasource_key: &str) -> Result<Reservation<'_>> {
+        let limit = self.config.max_open_per_datasource;
+        let mut registry = self.registry();
+        let count = registry
+            .open_counts
+            .entry(datasource_key.to_string())
+            .or_default();
+
+        if *count >= limit {
+            tracing::warn!(
+                datasource_key = %datasource_key,
+                limit = limit,
+                "Open transaction limit reached"
+            );
+            return Err(McpError::PoolError(format!(
+                "Too many open transactions for data source '{}' (limit {}); commit or roll back one first",
+                datasource_key, limit
+            )));
+        }
+
+        *count += 1;
+        Ok(Reservation {
+            manager: self,
+            datasource_key: datasource_key.to_string(),
+            kept: false,
+        })
+    }
+
+    fn release(&self, datasource_key: &str) {
+        release_locked(&mut self.registry(), datasource_key);
+    }
+
+    fn remove(&self, transaction_id: &str) -> Option<Arc<OpenTransaction>> {
+        remove_locked(&mut self.registry(), transaction_id)
+    }
+}
+
+/// A slot in a data source's open transaction limit, released on drop unless kept
+struct Reservation<'a> {
+    manager: &'a TransactionManager,
+    datasource_key: String,
+    kept: bool,
+}
+
+impl Reservation<'_> {
+    /// Keep the slot for a transaction that is now registered
+    fn keep(mut self) {
+        self.kept = true;
+    }
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        if !self.kept {
+            self.manager.release(&self.datasource_key);
+        }
+    }
+}
+
+fn remove_locked(registry: &mut Registry, transaction_id: &str) -> Option<Arc<OpenTransaction>> {
+    let transaction = registry.transactions.remove(transaction_id)?;
+    release_locked(registry, &transaction.datasource_key);
+    Some(transaction)
+}
+
+fn release_locked(registry: &mut Registry, datasource_key: &str) {
+    if let Some(count) = registry.open_counts.get_mut(datasource_key) {
+        *count = count.saturating_sub(1);
+        if *count == 0 {
+            registry.open_counts.remove(datasource_key);
+        }
+    }
+}
+
+/// Take a connection from the pool and open a transaction on it
+async fn start_transaction(
+    pool_manager: &ConnectionPoolManager,
+    database: &str,
+    isolation_level: Option<IsolationLevel>,
+) -> Result<(PoolConnection<MySql>, QueryKiller)> {
+    let pool = pool_manager.get_pool(database).await?;
+    let conn = pool.acquire().await.map_err(|e| acquire_error(&pool, e))?;
+
+    // A failed or cancelled BEGIN must not hand an open transaction back to the pool
+    let mut conn = CloseUnlessReleased::new(conn);
+    let killer = QueryKiller::for_connection(pool, &mut conn).await?;
+
+    if let Some(level) = isolation_level {
+        // Applies to the next transaction on this connection only
+        let set_isolation = format!("SET TRANSACTION ISOLATION LEVEL {}", level.as_sql());
+        conn.execute(set_isolation.as_str())
+            .await
+            .map_err(McpError::from)?;
+    }
+
+    conn.execute("BEGIN").await.map_err(|e| {
+        McpError::QueryExecutionError(format!("Failed to begin transaction: {}", e))
+    })?;
+
+    Ok((conn.release(), killer))
+}
+
+/// A connection that is closed on drop unless released back to the caller
+///
+/// Used while the connection may be part-way through transaction control, so
+/// an error or a cancelled future cannot return it to the pool in that state.
+struct CloseUnlessReleased(Option<PoolConnection<MySql>>);
+
+impl CloseUnlessReleased {
+    fn new(conn: PoolConnection<MySql>) -> Self {
+        Self(Some(conn))
+    }
+
+    fn release(mut self) -> PoolConnection<MySql> {
+        self.0.take().expect("connection is only released once")
+    }
+}
+
+impl Deref for CloseUnlessReleased {
+    type Target = PoolConnection<MySql>;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref().expect("connection is only released once")
+    }
+}
+
+impl DerefMut for CloseUnlessReleased {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0.as_mut().expect("connection is only released once")
+    }
+}
+
+impl Drop for CloseUnlessReleased {
+    fn drop(&mut self) {
+        if let Some(conn) = self.0.as_mut() {
+            conn.close_on_drop();
+        }
+    }
+}
+
+fn unknown_transaction(transaction_id: &str) -> McpError {
+    McpError::InvalidStatement(format!(
+        "Unknown transaction '{}'; it may have been committed, rolled back or expired",
+        transaction_id
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_manager(max_open: usize) -> TransactionManager {
+        TransactionManager::new(TransactionConfig {
+            idle_timeout_secs: 60,
+            max_open_per_datasource: max_open,
+        })
+    }
+
+    #[test]
+    fn test_reserve_enforces_per_datasource_limit() {
+        let manager = create_test_manager(2);
+
+        manager.reserve("db1").unwrap().keep();
+        let pending = manager.reserve("db1").unwrap();
+        assert!(matches!(
+            manager.reserve("db1"),
+            Err(McpError::PoolError(_))
+        ));
+
+        // Other data sources have their own limit
+        manager.reserve("db2").unwrap().keep();
+        assert_eq!(manager.open_count("db1"), 2);
+        assert_eq!(manager.open_count("db2"), 1);
+
+        // A reservation that is not kept frees its slot
+        drop(pending);
+        assert_eq!(manager.open_count("db1"), 1);
+        assert!(manager.reserve("db1").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_transaction_is_rejected() {
+        let manager = create_test_manager(2);
+
+        assert!(matches!(
+            manager.get("missing"),
+            Err(McpError::InvalidStatement(_))
+        ));
+        assert!(matches!(
+            manager.commit("missing").await,
+            Err(McpError::InvalidStatement(_))
+        ));
+        assert!(matches!(
+            manager.rollback("missing").await,
+            Err(McpError::InvalidStatement(_))
+        ));
+        assert_eq!(manager.rollback_idle().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_begin_releases_reservation() {
+        let manager = create_test_manager(1);
+        let pool_manager = ConnectionPoolManager::new(crate::config::DataSourceConfig {
+            key: "test".to_string(),
+            name: "Test Database".to_string(),
+            host: "127.0.0.1".to_string(),
+            port: 1,
+            username: "test".to_string(),
+            password: "test".to_string(),
+            pool_config: crate::config::PoolConfig {
+                connection_timeout_secs: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        for _ in 0..2 {
+            let result = manager.begin(&pool_manager, "test", "test_db", None).await;
+            assert!(matches!(result, Err(McpError::ConnectionFailed(_))));
+            assert_eq!(manager.open_count("test"), 0);
+        }
+    }
+
+    #[test]
+    fn test_isolation_level_parsing() {
+        assert_eq!(
+            "read committed".parse::<IsolationLevel>().unwrap(),
+            IsolationLevel::ReadCommitted
+        );
+        assert_eq!(
+            "REPEATABLE_READ".parse::<IsolationLevel>().unwrap(),
+            IsolationLevel::RepeatableRead
+        );
+        assert_eq!(
+            "read-uncommitted".parse::<IsolationLevel>().unwrap(),
+            IsolationLevel::ReadUncommitted
+        );
+        assert_eq!(IsolationLevel::Serializable.as_sql(), "SERIALIZABLE");
+        assert!(matches!(
+            "snapshot".parse::<IsolationLevel>(),
+            Err(McpError::InvalidStatement(_))
+        ));
+    }
+}
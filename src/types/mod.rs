@@ -0,0 +1,352 @@
+//! Lossless mapping of MySQL result values to JSON
+//!
+//! Every column type maps to a JSON form that keeps the stored value intact:
+//!
+//! - `TINYINT` … `BIGINT`, signed or `UNSIGNED`: numbers over the full `i64` / `u64` range
+//! - `BOOLEAN` (`TINYINT(1)`): `true` / `false`, or a number for values other than 0 and 1
+//! - `FLOAT`, `DOUBLE`: numbers, `FLOAT` with single precision digits
+//! - `DECIMAL`: exact strings, `"1234.5600"`
+//! - `DATE`, `DATETIME`: `"2024-05-01"`, `"2024-05-01 13:45:00.25"`
+//! - `TIMESTAMP`: RFC 3339 in the configured time zone, `"2024-05-01T13:45:00Z"`
+//! - `TIME`: `"-838:59:59"` … `"838:59:59"`, with microseconds when present
+//! - `YEAR`, `BIT`: numbers
+//! - `ENUM`: strings; `SET`: arrays of member strings
+//! - `JSON`: embedded JSON values
+//! - `CHAR`, `VARCHAR`, `TEXT`: strings
+//! - `BINARY`, `VARBINARY`, `BLOB`: strings when valid UTF-8, otherwise tagged base64
+//! - `GEOMETRY`: tagged base64 of MySQL's internal format (SRID followed by WKB)
+//!
+//! Tagged values use the form accepted for bind parameters, so they can be
+//! passed back as is:
+//!
+//! ```json
+//! {"type": "binary", "value": "3q2+7w=="}
+//! ```
+//!
+//! Binary strings that are valid UTF-8 stay strings because the driver reports
+//! text columns with a binary collation (`utf8mb4_bin`) as binary too.
+//! Zero dates (`0000-00-00`) are returned as strings.
+
+use base64::Engine;
+use chrono::{
+    DateTime, FixedOffset, NaiveDate, NaiveDateTime, Offset, SecondsFormat, TimeDelta, Utc,
+};
+use serde_json::Value;
+use sqlx::mysql::MySqlRow;
+use sqlx::{Row, TypeInfo, ValueRef};
+
+/// How a column's values are mapped, derived from its MySQL type name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    Int,
+    UnsignedInt,
+    Boolean,
+    Float,
+    Double,
+    Decimal,
+    Date,
+    DateTime,
+    Timestamp,
+    Time,
+    Year,
+    Bit,
+    Enum,
+    Set,
+    Json,
+    Text,
+    /// `BINARY`, `VARBINARY` and the `BLOB` types
+    BinaryString,
+    Geometry,
+    Null,
+}
+
+impl ColumnKind {
+    /// Classify a type name as reported for a result column, e.g. `INT UNSIGNED`
+    pub fn from_type_name(type_name: &str) -> Self {
+        let type_name = type_name.trim().to_uppercase();
+        let (base, unsigned) = match type_name.strip_suffix(" UNSIGNED") {
+            Some(base) => (base, true),
+            None => (type_name.as_str(), false),
+        };
+
+        match base {
+            "BOOLEAN" | "BOOL" => ColumnKind::Boolean,
+            "TINYINT" | "SMALLINT" | "MEDIUMINT" | "INT" | "INTEGER" | "BIGINT" => {
+                if unsigned {
+                    ColumnKind::UnsignedInt
+                } else {
+                    ColumnKind::Int
+                }
+            }
+            "FLOAT" => ColumnKind::Float,
+            "DOUBLE" | "REAL" => ColumnKind::Double,
+            "DECIMAL" | "NUMERIC" => ColumnKind::Decimal,
+            "DATE" => ColumnKind::Date,
+            "DATETIME" => ColumnKind::DateTime,
+            "TIMESTAMP" => ColumnKind::Timestamp,
+            "TIME" => ColumnKind::Time,
+            "YEAR" => ColumnKind::Year,
+            "BIT" => ColumnKind::Bit,
+            "ENUM" => ColumnKind::Enum,
+            "SET" => ColumnKind::Set,
+            "JSON" => ColumnKind::Json,
+            "BINARY" | "VARBINARY" | "TINYBLOB" | "BLOB" | "MEDIUMBLOB" | "LONGBLOB" => {
+                ColumnKind::BinaryString
+            }
+            "GEOMETRY" => ColumnKind::Geometry,
+            "NULL" => ColumnKind::Null,
+            // CHAR, VARCHAR, the TEXT types and anything the driver adds later
+            _ => ColumnKind::Text,
+        }
+    }
+}
+
+/// A value read from a result row
+#[derive(Debug, Clone, PartialEq)]
+pub enum MySqlValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f32),
+    Double(f64),
+    /// Exact decimal in MySQL's textual form
+    Decimal(String),
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+    /// `TIMESTAMP` value; connections use UTC as their session time zone
+    Timestamp(DateTime<Utc>),
+    /// `TIME` value, an interval rather than a time of day
+    Time(TimeDelta),
+    Year(u16),
+    Bit(u64),
+    Enum(String),
+    /// Comma-separated `SET` members
+    Set(String),
+    /// JSON document text
+    Json(String),
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl MySqlValue {
+    /// Read the value of a column
+    pub fn decode(row: &MySqlRow, index: usize) -> Self {
+        let kind = match row.try_get_raw(index) {
+            Ok(raw) if raw.is_null() => return MySqlValue::Null,
+            Ok(raw) => ColumnKind::from_type_name(raw.type_info().name()),
+            Err(_) => return MySqlValue::Null,
+        };
+
+        // The kind was derived from the column type, so the driver's own
+        // compatibility check is skipped; it rejects e.g. DECIMAL as a string
+        let decoded = match kind {
+            ColumnKind::Int => row.try_get_unchecked(index).map(MySqlValue::Int),
+            ColumnKind::UnsignedInt => row.try_get_unchecked(index).map(MySqlValue::UInt),
+            ColumnKind::Boolean => {
+                row.try_get_unchecked::<i64, _>(index)
+                    .map(|value| match value {
+                        0 => MySqlValue::Bool(false),
+                        1 => MySqlValue::Bool(true),
+                        other => MySqlValue::Int(other),
+                    })
+            }
+            ColumnKind::Float => row.try_get_unchecked(index).map(MySqlValue::Float),
+            ColumnKind::Double => row.try_get_unchecked(index).map(MySqlValue::Double),
+            ColumnKind::Decimal => row.try_get_unchecked(index).map(MySqlValue::Decimal),
+            ColumnKind::Date => row.try_get_unchecked(index).map(MySqlValue::Date),
+            ColumnKind::DateTime => row.try_get_unchecked(index).map(MySqlValue::DateTime),
+            ColumnKind::Timestamp => row.try_get_unchecked(index).map(MySqlValue::Timestamp),
+            ColumnKind::Time => row.try_get_unchecked(index).map(MySqlValue::Time),
+            ColumnKind::Year => row.try_get_unchecked(index).map(MySqlValue::Year),
+            ColumnKind::Bit => row.try_get_unchecked(index).map(MySqlValue::Bit),
+            ColumnKind::Enum => row.try_get_unchecked(index).map(MySqlValue::Enum),
+            ColumnKind::Set => row.try_get_unchecked(index).map(MySqlValue::Set),
+            ColumnKind::Json => row.try_get_unchecked(index).map(MySqlValue::Json),
+            ColumnKind::Text => row.try_get_unchecked(index).map(MySqlValue::Text),
+            ColumnKind::BinaryString => row
+                .try_get_unchecked(index)
+                .map(MySqlValue::from_binary_string),
+            ColumnKind::Geometry => row.try_get_unchecked(index).map(MySqlValue::Binary),
+            ColumnKind::Null => Ok(MySqlValue::Null),
+        };
+
+        decoded.unwrap_or_else(|e| {
+            match kind {
+                // Zero dates have no chrono equivalent
+                ColumnKind::Date => MySqlValue::Text("0000-00-00".to_string()),
+                ColumnKind::DateTime | ColumnKind::Timestamp => {
+                    MySqlValue::Text("0000-00-00 00:00:00".to_string())
+                }
+                _ => {
+                    tracing::warn!(
+                        column = index,
+                        kind = ?kind,
+                        error = %e,
+                        "Failed to decode column value, returning its raw bytes"
+                    );
+                    row.try_get_unchecked(index)
+                        .map(MySqlValue::from_binary_string)
+                        .unwrap_or(MySqlValue::Null)
+                }
+            }
+        })
+    }
+
+    /// Text if the bytes are valid UTF-8, binary otherwise
+    pub fn from_binary_string(bytes: Vec<u8>) -> Self {
+        match String::from_utf8(bytes) {
+            Ok(text) => MySqlValue::Text(text),
+            Err(e) => MySqlValue::Binary(e.into_bytes()),
+        }
+    }
+
+    /// Convert the value to JSON
+    pub fn to_json(&self, mapping: &TypeMapping) -> Value {
+        match self {
+            MySqlValue::Null => Value::Null,
+            MySqlValue::Bool(value) => Value::Bool(*value),
+            MySqlValue::Int(value) => Value::from(*value),
+            MySqlValue::UInt(value) => Value::from(*value),
+            // Converting through f64 would print a FLOAT's binary approximation
+            MySqlValue::Float(value) => serde_json::to_value(value).unwrap_or(Value::Null),
+            MySqlValue::Double(value) => serde_json::to_value(value).unwrap_or(Value::Null),
+            MySqlValue::Decimal(value) => Value::String(value.clone()),
+            MySqlValue::Date(value) => Value::String(value.format("%Y-%m-%d").to_string()),
+            MySqlValue::DateTime(value) => {
+                Value::String(value.format("%Y-%m-%d %H:%M:%S%.f").to_string())
+            }
+            MySqlValue::Timestamp(value) => Value::String(mapping.format_timestamp(value)),
+            MySqlValue::Time(value) => Value::String(format_time(value)),
+            MySqlValue::Year(value) => Value::from(*value),
+            MySqlValue::Bit(value) => Value::from(*value),
+            MySqlValue::Enum(value) | MySqlValue::Text(value) => Value::String(value.clone()),
+            MySqlValue::Set(value) => Value::Array(
+                value
+                    .split(',')
+                    .filter(|member| !member.is_empty())
+                    .map(|member| Value::String(member.to_string()))
+                    .collect(),
+            ),
+            MySqlValue::Json(value) => {
+                serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.clone()))
+            }
+            MySqlValue::Binary(value) => serde_json::json!({
+                "type": "binary",
+                "value": base64::engine::general_purpose::STANDARD.encode(value),
+            }),
+        }
+    }
+}
+
+/// Format a `TIME` value as MySQL does, e.g. `-12:30:00` or `100:00:00.5`
+fn format_time(value: &TimeDelta) -> String {
+    let sign = if *value < TimeDelta::zero() { "-" } else { "" };
+    let value = value.abs();
+    let seconds = value.num_seconds();
+    let microseconds = value.subsec_nanos() / 1000;
+
+    let mut text = format!(
+        "{}{:02}:{:02}:{:02}",
+        sign,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+    if microseconds != 0 {
+        let fraction = format!("{:06}", microseconds);
+        text.push('.');
+        text.push_str(fraction.trim_end_matches('0'));
+    }
+    text
+}
+
+/// Options for values whose JSON form is a matter of choice
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypeMapping {
+    timestamp_offset: FixedOffset,
+}
+
+impl Default for TypeMapping {
+    fn default() -> Self {
+        Self::new(Utc.fix())
+    }
+}
+
+impl TypeMapping {
+    /// Render `TIMESTAMP` values at the given UTC offset
+    pub fn new(timestamp_offset: FixedOffset) -> Self {
+        Self { timestamp_offset }
+    }
+
+    pub fn timestamp_offset(&self) -> FixedOffset {
+        self.timestamp_offset
+    }
+
+    /// Format a `TIMESTAMP` value as RFC 3339 at the configured offset
+    pub fn format_timestamp(&self, value: &DateTime<Utc>) -> String {
+        value
+            .with_timezone(&self.timestamp_offset)
+            .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    }
+
+    /// Read a column of a result row as JSON
+    pub fn column_value(&self, row: &MySqlRow, index: usize) -> Value {
+        MySqlValue::decode(row, index).to_json(self)
+    }
+}
+
+/// Parse a time zone given as `UTC` or a fixed offset such as `+02:00`
+pub fn parse_time_zone(time_zone: &str) -> Option<FixedOffset> {
+    let time_zone = time_zone.trim();
+    if time_zone.eq_ignore_ascii_case("UTC") || time_zone.eq_ignore_ascii_case("Z") {
+        return Some(Utc.fix());
+    }
+    time_zone.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(&TimeDelta::zero()), "00:00:00");
+        assert_eq!(
+            format_time(&-TimeDelta::try_seconds(838 * 3600 + 59 * 60 + 59).unwrap()),
+            "-838:59:59"
+        );
+        assert_eq!(
+            format_time(&TimeDelta::try_milliseconds(3_723_500).unwrap()),
+            "01:02:03.5"
+        );
+    }
+
+    #[test]
+    fn test_parse_time_zone() {
+        assert_eq!(parse_time_zone("UTC"), Some(Utc.fix()));
+        assert_eq!(
+            parse_time_zone("+05:30"),
+            FixedOffset::east_opt(5 * 3600 + 30 * 60)
+        );
+        assert_eq!(parse_time_zone("-08:00"), FixedOffset::west_opt(8 * 3600));
+        assert_eq!(parse_time_zone("Europe/Paris"), None);
+    }
+
+    #[test]
+    fn test_timestamp_uses_configured_offset() {
+        let value = DateTime::parse_from_rfc3339("2024-05-01T13:45:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            MySqlValue::Timestamp(value).to_json(&TypeMapping::default()),
+            "2024-05-01T13:45:00Z"
+        );
+
+        let mapping = TypeMapping::new(parse_time_zone("+02:00").unwrap());
+        assert_eq!(
+            MySqlValue::Timestamp(value).to_json(&mapping),
+            "2024-05-01T15:45:00+02:00"
+        );
+    }
+}
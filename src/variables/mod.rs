@@ -0,0 +1,366 @@
+//! Server variables and status counters
+//!
+//! `SHOW GLOBAL STATUS` mostly holds counters that only grow, so a single
+//! snapshot says little about what the server is doing now. Two snapshots
+//! taken some seconds apart turn them into rates; without a second snapshot,
+//! rates are averaged over the server's uptime.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Variables or status values by name
+pub type Snapshot = BTreeMap<String, String>;
+
+/// Status values that are current levels rather than counters, so have no rate
+const GAUGES: &[&str] = &[
+    "Innodb_buffer_pool_pages_data",
+    "Innodb_buffer_pool_pages_dirty",
+    "Innodb_buffer_pool_pages_free",
+    "Innodb_buffer_pool_pages_misc",
+    "Innodb_buffer_pool_pages_total",
+    "Innodb_buffer_pool_bytes_data",
+    "Innodb_buffer_pool_bytes_dirty",
+    "Innodb_row_lock_current_waits",
+    "Max_used_connections",
+    "Open_files",
+    "Open_streams",
+    "Open_table_definitions",
+    "Open_tables",
+    "Qcache_free_blocks",
+    "Qcache_free_memory",
+    "Qcache_queries_in_cache",
+    "Qcache_total_blocks",
+    "Threads_cached",
+    "Threads_connected",
+    "Threads_running",
+    "Uptime",
+    "Uptime_since_flush_status",
+];
+
+/// Variables that identify a server rather than configure it, so always
+/// differ between two servers
+const IDENTITY_VARIABLES: &[&str] = &[
+    "gtid_executed",
+    "gtid_purged",
+    "hostname",
+    "pid_file",
+    "report_host",
+    "server_id",
+    "server_uuid",
+    "timestamp",
+];
+
+/// Whether a variable name matches a filter
+///
+/// Filters are case-insensitive. A filter containing `%` is a `LIKE` pattern,
+/// where `_` also matches any one character; since nearly every name
+/// contains `_`, a filter without `%` matches names containing it literally.
+pub fn matches_filter(name: &str, filter: &str) -> bool {
+    let name = name.to_lowercase();
+    let filter = filter.to_lowercase();
+    if !filter.contains('%') {
+        return name.contains(&filter);
+    }
+    like(name.as_bytes(), filter.as_bytes())
+}
+
+fn like(name: &[u8], pattern: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'%', rest)) => (0..=name.len()).any(|i| like(&name[i..], rest)),
+        Some((b'_', rest)) => !name.is_empty() && like(&name[1..], rest),
+        Some((c, rest)) => name.first() == Some(c) && like(&name[1..], rest),
+    }
+}
+
+/// A server or session variable
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ServerVariable {
+    pub name: String,
+    pub value: String,
+}
+
+/// The entries of a snapshot matching an optional filter
+pub fn filtered(snapshot: &Snapshot, filter: Option<&str>) -> Vec<ServerVariable> {
+    snapshot
+        .iter()
+        .filter(|(name, _)| filter.is_none_or(|filter| matches_filter(name, filter)))
+        .map(|(name, value)| ServerVariable {
+            name: name.clone(),
+            value: value.clone(),
+        })
+        .collect()
+}
+
+/// A status value, with its rate when it is a counter
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatusValue {
+    pub name: String,
+    pub value: String,
+    /// Increase per second over the interval, or over the uptime
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_second: Option<f64>,
+}
+
+/// Health indicators derived from status counters
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DerivedRates {
+    /// Statements per second (`Questions`)
+    pub qps: Option<f64>,
+    /// Commits and rollbacks per second
+    pub tps: Option<f64>,
+    /// Share of InnoDB page reads served from the buffer pool, 0 to 1
+    pub buffer_pool_hit_ratio: Option<f64>,
+    pub threads_running: Option<u64>,
+    pub threads_connected: Option<u64>,
+    pub slow_queries_per_second: Option<f64>,
+    /// Temporary tables per second that spilled to disk
+    pub tmp_disk_tables_per_second: Option<f64>,
+    pub aborted_connects_per_second: Option<f64>,
+}
+
+/// Result of `mysql_status`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatusReport {
+    /// Seconds between the two snapshots, or the uptime with a single snapshot
+    pub interval_secs: f64,
+    /// Whether rates come from two snapshots rather than the uptime
+    pub sampled: bool,
+    pub rates: DerivedRates,
+    pub status: Vec<StatusValue>,
+}
+
+impl StatusReport {
+    /// Report on `last`, with rates since `first` taken `elapsed_secs` before it,
+    /// or since the server started
+    pub fn new(first: Option<&Snapshot>, last: &Snapshot, elapsed_secs: f64, filter: Option<&str>) -> Self {
+        let number = |snapshot: &Snapshot, name: &str| -> Option<f64> {
+            snapshot.get(name).and_then(|value| value.parse::<f64>().ok())
+        };
+        let (interval_secs, sampled) = match first {
+            Some(_) => (elapsed_secs, true),
+            None => (number(last, "Uptime").unwrap_or(0.0), false),
+        };
+        // Increase of a counter over the interval
+        let delta = |name: &str| -> Option<f64> {
+            let now = number(last, name)?;
+            match first {
+                Some(first) => Some((now - number(first, name)?).max(0.0)),
+                None => Some(now),
+            }
+        };
+        let per_second = |name: &str| -> Option<f64> {
+            (interval_secs > 0.0).then_some(delta(name)? / interval_secs)
+        };
+        let gauge = |name: &str| number(last, name).map(|n| n as u64);
+
+        let rates = DerivedRates {
+            qps: per_second("Questions"),
+            tps: match (per_second("Com_commit"), per_second("Com_rollback")) {
+                (Some(commit), Some(rollback)) => Some(commit + rollback),
+                (commit, rollback) => commit.or(rollback),
+            },
+            buffer_pool_hit_ratio: match (
+                delta("Innodb_buffer_pool_read_requests"),
+                delta("Innodb_buffer_pool_reads"),
+            ) {
+                (Some(requests), Some(reads)) if requests > 0.0 => {
+                    Some((1.0 - reads / requests).clamp(0.0, 1.0))
+                }
+                _ => None,
+            },
+            threads_running: gauge("Threads_running"),
+            threads_connected: gauge("Threads_connected"),
+            slow_queries_per_second: per_second("Slow_queries"),
+            tmp_disk_tables_per_second: per_second("Created_tmp_disk_tables"),
+            aborted_connects_per_second: per_second("Aborted_connects"),
+        };
+
+        let status = filtered(last, filter)
+            .into_iter()
+            .map(|variable| StatusValue {
+                per_second: if GAUGES.iter().any(|g| g.eq_ignore_ascii_case(&variable.name)) {
+                    None
+                } else {
+                    per_second(&variable.name)
+                },
+                name: variable.name,
+                value: variable.value,
+            })
+            .collect();
+
+        Self {
+            interval_secs,
+            sampled,
+            rates,
+            status,
+        }
+    }
+}
+
+/// A variable set differently on two servers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VariableDifference {
+    pub name: String,
+    pub left: String,
+    pub right: String,
+}
+
+/// Result of `mysql_compare_variables`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VariableDiff {
+    pub left: String,
+    pub right: String,
+    pub different: Vec<VariableDifference>,
+    /// Variables only the left server has, e.g. from a plugin or a newer version
+    pub only_left: Vec<ServerVariable>,
+    pub only_right: Vec<ServerVariable>,
+    /// Number of variables set the same on both
+    pub same: usize,
+    /// Variables that identify a server, which differ by nature and are not compared
+    pub ignored: Vec<String>,
+}
+
+impl VariableDiff {
+    /// Compare the variables of two servers, named `left` and `right`
+    pub fn new(left: &str, left_vars: &Snapshot, right: &str, right_vars: &Snapshot, filter: Option<&str>) -> Self {
+        let mut diff = VariableDiff {
+            left: left.to_string(),
+            right: right.to_string(),
+            different: Vec::new(),
+            only_left: Vec::new(),
+            only_right: Vec::new(),
+            same: 0,
+            ignored: Vec::new(),
+        };
+        let selected = |name: &String| filter.is_none_or(|filter| matches_filter(name, filter));
+
+        for (name, left_value) in left_vars.iter().filter(|(name, _)| selected(name)) {
+            if IDENTITY_VARIABLES.contains(&name.to_lowercase().as_str()) {
+                diff.ignored.push(name.clone());
+                continue;
+            }
+            match right_vars.get(name) {
+                Some(right_value) if right_value == left_value => diff.same += 1,
+                Some(right_value) => diff.different.push(VariableDifference {
+                    name: name.clone(),
+                    left: left_value.clone(),
+                    right: right_value.clone(),
+                }),
+                None => diff.only_left.push(ServerVariable {
+                    name: name.clone(),
+                    value: left_value.clone(),
+                }),
+            }
+        }
+        diff.only_right = right_vars
+            .iter()
+            .filter(|(name, _)| selected(name) && !left_vars.contains_key(*name))
+            .filter(|(name, _)| !IDENTITY_VARIABLES.contains(&name.to_lowercase().as_str()))
+            .map(|(name, value)| ServerVariable {
+                name: name.clone(),
+                value: value.clone(),
+            })
+            .collect();
+
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(values: &[(&str, &str)]) -> Snapshot {
+        values
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_matches_filter() {
+        assert!(matches_filter("innodb_buffer_pool_size", "buffer_pool"));
+        assert!(matches_filter("innodb_buffer_pool_size", "INNODB%SIZE"));
+        assert!(matches_filter("max_connections", "max_connection_%"));
+        assert!(!matches_filter("max_connections", "max_connection_x"));
+        assert!(!matches_filter("sort_buffer_size", "innodb%"));
+    }
+
+    #[test]
+    fn test_status_rates_between_snapshots() {
+        let first = snapshot(&[
+            ("Questions", "1000"),
+            ("Com_commit", "100"),
+            ("Com_rollback", "0"),
+            ("Innodb_buffer_pool_read_requests", "10000"),
+            ("Innodb_buffer_pool_reads", "100"),
+            ("Threads_running", "3"),
+            ("Uptime", "500"),
+        ]);
+        let last = snapshot(&[
+            ("Questions", "1500"),
+            ("Com_commit", "150"),
+            ("Com_rollback", "10"),
+            ("Innodb_buffer_pool_read_requests", "11000"),
+            ("Innodb_buffer_pool_reads", "110"),
+            ("Threads_running", "7"),
+            ("Uptime", "510"),
+        ]);
+
+        let report = StatusReport::new(Some(&first), &last, 10.0, Some("threads%"));
+        assert!(report.sampled);
+        assert_eq!(report.rates.qps, Some(50.0));
+        assert_eq!(report.rates.tps, Some(6.0));
+        assert_eq!(report.rates.buffer_pool_hit_ratio, Some(0.99));
+        assert_eq!(report.rates.threads_running, Some(7));
+        assert_eq!(
+            report.status,
+            vec![StatusValue {
+                name: "Threads_running".to_string(),
+                value: "7".to_string(),
+                per_second: None,
+            }]
+        );
+
+        // A single snapshot averages over the uptime
+        let report = StatusReport::new(None, &last, 0.0, Some("Questions"));
+        assert!(!report.sampled);
+        assert_eq!(report.interval_secs, 510.0);
+        assert_eq!(report.status[0].per_second, Some(1500.0 / 510.0));
+    }
+
+    #[test]
+    fn test_variable_diff() {
+        let left = snapshot(&[
+            ("max_connections", "151"),
+            ("sql_mode", "STRICT_TRANS_TABLES"),
+            ("server_id", "1"),
+            ("innodb_buffer_pool_size", "134217728"),
+        ]);
+        let right = snapshot(&[
+            ("max_connections", "500"),
+            ("sql_mode", "STRICT_TRANS_TABLES"),
+            ("server_id", "2"),
+            ("rpl_semi_sync_source_enabled", "ON"),
+        ]);
+
+        let diff = VariableDiff::new("staging", &left, "prod", &right, None);
+        assert_eq!(
+            diff.different,
+            vec![VariableDifference {
+                name: "max_connections".to_string(),
+                left: "151".to_string(),
+                right: "500".to_string(),
+            }]
+        );
+        assert_eq!(diff.only_left[0].name, "innodb_buffer_pool_size");
+        assert_eq!(diff.only_right[0].name, "rpl_semi_sync_source_enabled");
+        assert_eq!(diff.same, 1);
+        assert_eq!(diff.ignored, ["server_id"]);
+
+        let diff = VariableDiff::new("staging", &left, "prod", &right, Some("sql_mode"));
+        assert!(diff.different.is_empty());
+        assert_eq!(diff.same, 1);
+    }
+}
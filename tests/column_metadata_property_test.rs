@@ -0,0 +1,83 @@
+// Feature: mysql-mcp-multi-datasource, Property 41: Column metadata serialization
+// Validates: Requirements 3.2
+
+use mysql_mcp_server::tools::{ColumnMetadata, ColumnOrigin};
+use proptest::prelude::*;
+
+// Strategy to generate column metadata with any combination of attributes
+fn arbitrary_column_metadata() -> impl Strategy<Value = ColumnMetadata> {
+    (
+        "[a-zA-Z0-9_]{1,30}",
+        "[A-Z]{3,10}( UNSIGNED)?",
+        any::<[bool; 5]>(),
+        prop::option::of(any::<u32>()),
+        prop::option::of(prop_oneof![Just("PRI"), Just("UNI"), Just("MUL")]),
+        prop::option::of(prop_oneof![Just("utf8mb4"), Just("latin1"), Just("binary")]),
+        prop::option::of((any::<u64>(), any::<u64>())),
+        prop::option::of(("[a-z_]{1,20}", "[a-z_]{1,20}", "[a-z_]{1,20}")),
+    )
+        .prop_map(|(name, data_type, flags, length, key, character_set, digits, origin)| ColumnMetadata {
+            name,
+            data_type,
+            nullable: flags[0],
+            unsigned: flags[1],
+            zerofill: flags[2],
+            binary: flags[3],
+            length,
+            character_set: character_set.map(str::to_string),
+            precision: digits.map(|(precision, _)| precision),
+            scale: digits.map(|(_, scale)| scale),
+            key: key.map(str::to_string),
+            auto_increment: flags[4],
+            origin: origin.map(|(schema, table, column)| ColumnOrigin { schema, table, column }),
+        })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(100))]
+
+    /// Property 41: Column metadata serialization
+    /// For any column metadata, the JSON form holds every attribute and parses
+    /// back to the same metadata
+    #[test]
+    fn test_column_metadata_round_trips(column in arbitrary_column_metadata()) {
+        let json = serde_json::to_value(&column).unwrap();
+        prop_assert_eq!(&json["name"], column.name.as_str());
+        prop_assert_eq!(&json["nullable"], column.nullable);
+        prop_assert_eq!(&json["unsigned"], column.unsigned);
+        prop_assert_eq!(json.get("length").and_then(|v| v.as_u64()), column.length.map(u64::from));
+        prop_assert_eq!(json.get("key").and_then(|v| v.as_str()), column.key.as_deref());
+        prop_assert_eq!(json.get("character_set").and_then(|v| v.as_str()), column.character_set.as_deref());
+        prop_assert_eq!(json.get("precision").and_then(|v| v.as_u64()), column.precision);
+        prop_assert_eq!(json.get("scale").and_then(|v| v.as_u64()), column.scale);
+        prop_assert_eq!(
+            json.get("origin").and_then(|v| v["table"].as_str()),
+            column.origin.as_ref().map(|origin| origin.table.as_str())
+        );
+
+        let parsed: ColumnMetadata = serde_json::from_value(json).unwrap();
+        prop_assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            serde_json::to_value(&column).unwrap()
+        );
+    }
+
+    /// Property 41a: Metadata without attributes still parses
+    /// For any column given only a name, type and nullability, the attributes
+    /// default to unset
+    #[test]
+    fn test_column_metadata_attributes_default(
+        name in "[a-zA-Z0-9_]{1,30}",
+        nullable in any::<bool>(),
+    ) {
+        let json = serde_json::json!({"name": name, "data_type": "INT", "nullable": nullable});
+        let column: ColumnMetadata = serde_json::from_value(json).unwrap();
+        prop_assert_eq!(column.name, name);
+        prop_assert_eq!(column.nullable, nullable);
+        prop_assert!(!column.unsigned && !column.zerofill && !column.binary && !column.auto_increment);
+        prop_assert_eq!(column.length, None);
+        prop_assert_eq!(column.key, None);
+        prop_assert_eq!(column.character_set, None);
+        prop_assert_eq!(column.origin, None);
+    }
+}
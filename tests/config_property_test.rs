@@ -4,7 +4,11 @@
 // Feature: mysql-mcp-multi-datasource, Property 2: Invalid configuration handling
 // Validates: Requirements 1.3
 
-use mysql_mcp_server::config::{DataSourceConfig, PoolConfig, ServerConfig};
+use mysql_mcp_server::config::{
+    DataSourceConfig, ExportConfig, HealthConfig, ImportConfig, PoolConfig, ServerConfig,
+    StreamConfig, TransactionConfig, TypeConfig,
+};
+use mysql_mcp_server::error::RetryConfig;
 use proptest::prelude::*;
 
 // Strategy to generate arbitrary strings (including empty ones)
@@ -64,7 +68,7 @@ fn arbitrary_datasource_config() -> impl Strategy<Value = DataSourceConfig> {
                     password,
                     databases,
                     pool_config,
-                    permission: mysql_mcp_server::config::Permission::default(),
+                    ..Default::default()
                 }
             },
         )
@@ -93,7 +97,7 @@ fn valid_datasource_config() -> impl Strategy<Value = DataSourceConfig> {
                     password,
                     databases,
                     pool_config,
-                    permission: mysql_mcp_server::config::Permission::default(),
+                    ..Default::default()
                 }
             },
         )
@@ -167,9 +171,7 @@ proptest! {
             port,
             username,
             password,
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+            ..Default::default()
         };
         
         let result = ds.validate();
@@ -192,9 +194,7 @@ proptest! {
             port,
             username,
             password,
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+            ..Default::default()
         };
         
         let result = ds.validate();
@@ -217,9 +217,7 @@ proptest! {
             port: 0,
             username,
             password,
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+            ..Default::default()
         };
         
         let result = ds.validate();
@@ -242,9 +240,7 @@ proptest! {
             port,
             username: "".to_string(),
             password,
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+            ..Default::default()
         };
         
         let result = ds.validate();
@@ -267,9 +263,7 @@ proptest! {
             port,
             username,
             password: "".to_string(),
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+            ..Default::default()
         };
         
         let result = ds.validate();
@@ -314,7 +308,17 @@ proptest! {
         let mut config = ServerConfig {
             data_sources,
             query_timeout_secs: 30,
+            max_query_timeout_secs: 300,
+            max_rows: 10_000,
+            max_result_bytes: 5_242_880,
             stream_chunk_size: 1000,
+            transactions: TransactionConfig::default(),
+            streams: StreamConfig::default(),
+            types: TypeConfig::default(),
+            export: ExportConfig::default(),
+            import: ImportConfig::default(),
+            health: HealthConfig::default(),
+            retry: RetryConfig::default(),
         };
         
         // This should not panic/crash
@@ -346,7 +350,17 @@ proptest! {
             let mut config = ServerConfig {
                 data_sources: invalid_configs,
                 query_timeout_secs: 30,
+                max_query_timeout_secs: 300,
+                max_rows: 10_000,
+                max_result_bytes: 5_242_880,
                 stream_chunk_size: 1000,
+                transactions: TransactionConfig::default(),
+                streams: StreamConfig::default(),
+                types: TypeConfig::default(),
+                export: ExportConfig::default(),
+                import: ImportConfig::default(),
+                health: HealthConfig::default(),
+                retry: RetryConfig::default(),
             };
             
             // This should not panic/crash
@@ -369,7 +383,17 @@ proptest! {
         let mut config = ServerConfig {
             data_sources: valid_configs,
             query_timeout_secs: 30,
+            max_query_timeout_secs: 300,
+            max_rows: 10_000,
+            max_result_bytes: 5_242_880,
             stream_chunk_size: 1000,
+            transactions: TransactionConfig::default(),
+            streams: StreamConfig::default(),
+            types: TypeConfig::default(),
+            export: ExportConfig::default(),
+            import: ImportConfig::default(),
+            health: HealthConfig::default(),
+            retry: RetryConfig::default(),
         };
         
         // This should not panic/crash
@@ -395,13 +419,33 @@ proptest! {
         let mut config1 = ServerConfig {
             data_sources: data_sources.clone(),
             query_timeout_secs: 30,
+            max_query_timeout_secs: 300,
+            max_rows: 10_000,
+            max_result_bytes: 5_242_880,
             stream_chunk_size: 1000,
+            transactions: TransactionConfig::default(),
+            streams: StreamConfig::default(),
+            types: TypeConfig::default(),
+            export: ExportConfig::default(),
+            import: ImportConfig::default(),
+            health: HealthConfig::default(),
+            retry: RetryConfig::default(),
         };
         
         let mut config2 = ServerConfig {
             data_sources,
             query_timeout_secs: 30,
+            max_query_timeout_secs: 300,
+            max_rows: 10_000,
+            max_result_bytes: 5_242_880,
             stream_chunk_size: 1000,
+            transactions: TransactionConfig::default(),
+            streams: StreamConfig::default(),
+            types: TypeConfig::default(),
+            export: ExportConfig::default(),
+            import: ImportConfig::default(),
+            health: HealthConfig::default(),
+            retry: RetryConfig::default(),
         };
         
         // Filter once
@@ -4,7 +4,7 @@
 // Property 28: Connection stats completeness
 // For any data source, the connection stats should include active, idle, total connection counts, and queued request count
 
-use mysql_mcp_server::config::{DataSourceConfig, PoolConfig};
+use mysql_mcp_server::config::DataSourceConfig;
 use mysql_mcp_server::manager::DataSourceManager;
 use mysql_mcp_server::pool::ConnectionPoolManager;
 use mysql_mcp_server::tools::StatsTool;
@@ -30,9 +30,7 @@ fn arb_datasource_config() -> impl Strategy<Value = DataSourceConfig> {
             port,
             username,
             password,
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+            ..Default::default()
         })
 }
 
@@ -213,13 +211,9 @@ async fn test_connection_stats_empty_datasource_key() {
     let config = DataSourceConfig {
         key: "test-db".to_string(),
         name: "Test Database".to_string(),
-        host: "localhost".to_string(),
-        port: 3306,
         username: "test".to_string(),
         password: "secret".to_string(),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+        ..Default::default()
     };
     
     let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
@@ -236,13 +230,9 @@ async fn test_connection_stats_invalid_datasource_key() {
     let config = DataSourceConfig {
         key: "test-db".to_string(),
         name: "Test Database".to_string(),
-        host: "localhost".to_string(),
-        port: 3306,
         username: "test".to_string(),
         password: "secret".to_string(),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+        ..Default::default()
     };
     
     let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
@@ -259,13 +249,9 @@ async fn test_connection_stats_no_pools_created() {
     let config = DataSourceConfig {
         key: "test-db".to_string(),
         name: "Test Database".to_string(),
-        host: "localhost".to_string(),
-        port: 3306,
         username: "test".to_string(),
         password: "secret".to_string(),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+        ..Default::default()
     };
     
     let manager = Arc::new(DataSourceManager::new(vec![config.clone()]).await.unwrap());
@@ -282,13 +268,9 @@ async fn test_connection_stats_all_datasources_empty() {
     let config = DataSourceConfig {
         key: "test-db".to_string(),
         name: "Test Database".to_string(),
-        host: "localhost".to_string(),
-        port: 3306,
         username: "test".to_string(),
         password: "secret".to_string(),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+        ..Default::default()
     };
     
     let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
@@ -305,13 +287,9 @@ async fn test_connection_stats_structure() {
     let config = DataSourceConfig {
         key: "test-db".to_string(),
         name: "Test Database".to_string(),
-        host: "localhost".to_string(),
-        port: 3306,
         username: "test".to_string(),
         password: "secret".to_string(),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+        ..Default::default()
     };
     
     let manager = Arc::new(DataSourceManager::new(vec![config.clone()]).await.unwrap());
@@ -1,7 +1,7 @@
 // Feature: mysql-mcp-multi-datasource, Property 23: Credential non-disclosure
 // Validates: Requirements 10.1, 10.2, 10.3, 10.5
 
-use mysql_mcp_server::config::{DataSourceConfig, PoolConfig};
+use mysql_mcp_server::config::DataSourceConfig;
 use mysql_mcp_server::error::{sanitize_error_message, McpError};
 use mysql_mcp_server::manager::{DataSourceManager, DataSourceInfo};
 use proptest::prelude::*;
@@ -70,9 +70,7 @@ fn datasource_with_credentials() -> impl Strategy<Value = DataSourceConfig> {
             port,
             username,
             password,
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+            ..Default::default()
         })
 }
 
@@ -215,6 +213,8 @@ proptest! {
             key: ds.key.clone(),
             name: ds.name.clone(),
             status: mysql_mcp_server::manager::ConnectionStatus::Available,
+            health: Default::default(),
+            circuit: Default::default(),
         };
         
         // Convert to string representation for checking
@@ -0,0 +1,128 @@
+// Feature: mysql-mcp-multi-datasource, Property 33: Database allowlist enforcement
+// Validates: Requirements 6.2, 11.3
+
+use mysql_mcp_server::config::{DataSourceConfig, DatabaseAllowlist, Permission};
+use mysql_mcp_server::error::McpError;
+use mysql_mcp_server::manager::DataSourceManager;
+use mysql_mcp_server::tools::{ExecuteTool, QueryTool, SchemaTool};
+use proptest::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+// Strategy to generate a data source that only allows `app_*` databases
+fn restricted_datasource_config() -> impl Strategy<Value = DataSourceConfig> {
+    "[a-zA-Z0-9_-]{1,30}".prop_map(|key| DataSourceConfig {
+        key,
+        name: "Test Database".to_string(),
+        username: "test".to_string(),
+        password: "test".to_string(),
+        databases: vec!["app_*".to_string()],
+        permission: Permission::Ddl,
+        ..Default::default()
+    })
+}
+
+// Strategy to generate database names outside the allowlist
+fn denied_database() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[b-z][a-z0-9_]{0,20}",
+        Just("mysql".to_string()),
+        Just("information_schema".to_string()),
+        Just("my_app_orders".to_string()),
+    ]
+}
+
+// Strategy to generate statements that reach `{db}` through a qualified name
+fn qualified_statement() -> impl Strategy<Value = &'static str> {
+    prop_oneof![
+        Just("SELECT * FROM {db}.users"),
+        Just("SELECT * FROM `{db}`.`users`"),
+        Just("SELECT * FROM users u JOIN {db}.orders o ON u.id = o.user_id"),
+        Just("SELECT * FROM users WHERE id IN (SELECT user_id FROM {db}.orders)"),
+        Just("SELECT {db}.users.id FROM users"),
+        Just("SHOW TABLES FROM {db}"),
+        Just("DESCRIBE {db}.users"),
+    ]
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(20))]
+
+    /// Property 33: Database allowlist enforcement
+    /// For any database outside the allowlist, every tool should reject it before connecting
+    #[test]
+    fn test_tools_reject_denied_databases(
+        config in restricted_datasource_config(),
+        database in denied_database(),
+    ) {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async {
+            let datasource_key = config.key.clone();
+            let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
+            let pool_managers = Arc::new(RwLock::new(HashMap::new()));
+
+            let query_tool = QueryTool::new(manager.clone(), pool_managers.clone());
+            let result = query_tool.execute(&datasource_key, &database, "SELECT 1").await;
+            prop_assert!(matches!(result, Err(McpError::DatabaseNotAllowed(_))), "query: {:?}", result);
+
+            let execute_tool = ExecuteTool::new(manager.clone(), pool_managers.clone());
+            let result = execute_tool.execute(&datasource_key, &database, "DELETE FROM users").await;
+            prop_assert!(matches!(result, Err(McpError::DatabaseNotAllowed(_))), "execute: {:?}", result);
+
+            let schema_tool = SchemaTool::new(manager.clone(), pool_managers.clone());
+            let result = schema_tool.list_tables(&datasource_key, &database).await;
+            prop_assert!(matches!(result, Err(McpError::DatabaseNotAllowed(_))), "schema: {:?}", result);
+
+            prop_assert!(pool_managers.read().await.is_empty());
+
+            Ok(())
+        })?;
+    }
+
+    /// Property 33a: Qualified names cannot escape the allowlist
+    /// For any statement naming a denied database explicitly, the query tool should reject it
+    /// even though the connection's default database is allowed
+    #[test]
+    fn test_qualified_names_cannot_reach_denied_databases(
+        config in restricted_datasource_config(),
+        database in denied_database(),
+        template in qualified_statement(),
+    ) {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async {
+            let datasource_key = config.key.clone();
+            let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
+            let pool_managers = Arc::new(RwLock::new(HashMap::new()));
+            let tool = QueryTool::new(manager, pool_managers);
+
+            let query = template.replace("{db}", &database);
+            let result = tool.execute(&datasource_key, "app_orders", &query).await;
+
+            prop_assert!(
+                matches!(&result, Err(McpError::DatabaseNotAllowed(denied)) if *denied == database),
+                "'{}' should be rejected, got: {:?}",
+                query, result
+            );
+
+            Ok(())
+        })?;
+    }
+
+    /// Property 33b: Glob patterns match whole names
+    /// For any suffix, `app_*` allows `app_<suffix>` and never `<prefix>app_<suffix>`
+    #[test]
+    fn test_glob_patterns_match_whole_names(
+        prefix in "[a-z]{1,5}",
+        suffix in "[a-z0-9_]{0,20}",
+    ) {
+        let allowlist = DatabaseAllowlist::new(&["app_*".to_string()]).unwrap();
+
+        let allowed = format!("app_{}", suffix);
+        let prefixed = format!("{}app_{}", prefix, suffix);
+        prop_assert!(allowlist.allows(&allowed));
+        prop_assert!(!allowlist.allows(&prefixed));
+    }
+}
@@ -4,7 +4,7 @@
 // Property 15: Database listing accuracy
 // For any valid data source key, the list-databases tool should return all accessible databases with metadata
 
-use mysql_mcp_server::config::{DataSourceConfig, PoolConfig};
+use mysql_mcp_server::config::DataSourceConfig;
 use mysql_mcp_server::manager::DataSourceManager;
 use mysql_mcp_server::tools::ListTool;
 use std::collections::HashMap;
@@ -20,13 +20,9 @@ async fn test_list_databases_validates_empty_key() {
     let config = DataSourceConfig {
         key: "test-db".to_string(),
         name: "Test Database".to_string(),
-        host: "localhost".to_string(),
-        port: 3306,
         username: "test".to_string(),
         password: "test".to_string(),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+        ..Default::default()
     };
     
     let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
@@ -49,13 +45,9 @@ async fn test_list_databases_validates_invalid_key() {
     let config = DataSourceConfig {
         key: "test-db".to_string(),
         name: "Test Database".to_string(),
-        host: "localhost".to_string(),
-        port: 3306,
         username: "test".to_string(),
         password: "test".to_string(),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+        ..Default::default()
     };
     
     let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
@@ -78,13 +70,9 @@ async fn test_list_databases_checks_availability() {
     let config = DataSourceConfig {
         key: "test-db".to_string(),
         name: "Test Database".to_string(),
-        host: "localhost".to_string(),
-        port: 3306,
         username: "test".to_string(),
         password: "test".to_string(),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+        ..Default::default()
     };
     
     let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
@@ -113,13 +101,9 @@ async fn test_list_databases_caching() {
     let config = DataSourceConfig {
         key: "test-db".to_string(),
         name: "Test Database".to_string(),
-        host: "localhost".to_string(),
-        port: 3306,
         username: "test".to_string(),
         password: "test".to_string(),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+        ..Default::default()
     };
     
     let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
@@ -145,13 +129,9 @@ async fn test_list_databases_with_real_mysql() {
     let config = DataSourceConfig {
         key: "test-db".to_string(),
         name: "Test Database".to_string(),
-        host: "localhost".to_string(),
-        port: 3306,
         username: "root".to_string(),
         password: "password".to_string(),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+        ..Default::default()
     };
     
     let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
@@ -201,13 +181,9 @@ async fn test_list_databases_caching_with_real_mysql() {
     let config = DataSourceConfig {
         key: "test-db".to_string(),
         name: "Test Database".to_string(),
-        host: "localhost".to_string(),
-        port: 3306,
         username: "root".to_string(),
         password: "password".to_string(),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+        ..Default::default()
     };
     
     let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
@@ -4,7 +4,7 @@
 // Property 14: Data source listing accuracy
 // For any configured set of data sources, the list-datasources tool should return all data source keys and names
 
-use mysql_mcp_server::config::{DataSourceConfig, PoolConfig};
+use mysql_mcp_server::config::DataSourceConfig;
 use mysql_mcp_server::manager::DataSourceManager;
 use mysql_mcp_server::tools::ListTool;
 use proptest::prelude::*;
@@ -29,9 +29,7 @@ fn arb_datasource_config() -> impl Strategy<Value = DataSourceConfig> {
             port,
             username,
             password,
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+            ..Default::default()
         })
 }
 
@@ -180,13 +178,9 @@ async fn test_list_datasources_single_source() {
     let config = DataSourceConfig {
         key: "test-db".to_string(),
         name: "Test Database".to_string(),
-        host: "localhost".to_string(),
-        port: 3306,
         username: "test".to_string(),
         password: "secret".to_string(),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+        ..Default::default()
     };
     
     let manager = Arc::new(DataSourceManager::new(vec![config.clone()]).await.unwrap());
@@ -0,0 +1,68 @@
+// Feature: mysql-mcp-multi-datasource, Property 44: Digest normalisation ignores literals
+// Validates: Requirements 3.1
+
+use mysql_mcp_server::digest::{normalize, SlowLogAggregator, SlowLogEntry};
+use proptest::prelude::*;
+
+// Strategy to generate a number or string literal
+fn arbitrary_literal() -> impl Strategy<Value = String> {
+    prop_oneof![
+        any::<i64>().prop_map(|n| n.to_string()),
+        (0u32..10_000, 0u32..100).prop_map(|(a, b)| format!("{}.{}", a, b)),
+        "[a-zA-Z0-9 ,;()%_-]{0,12}".prop_map(|s| format!("'{}'", s)),
+    ]
+}
+
+// Strategy to generate a list of literals, which may include NULL
+fn arbitrary_list() -> impl Strategy<Value = Vec<String>> {
+    prop::collection::vec(
+        prop_oneof![arbitrary_literal(), Just("NULL".to_string())],
+        1..6,
+    )
+}
+
+/// A statement with the given literals filled in
+fn statement(literals: &[String], list: &[String], limit: u32) -> String {
+    format!(
+        "SELECT id, name FROM orders WHERE customer_id = {} AND total > {} AND status IN ({}) LIMIT {}",
+        literals[0],
+        literals[1],
+        list.join(", "),
+        limit
+    )
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(100))]
+
+    /// Property 44: Digest normalisation ignores literals
+    /// For any two runs of the same statement that differ only in their literal
+    /// values and in the length of literal lists, the normalised text is the same
+    /// and the slow log aggregation counts them as one statement
+    #[test]
+    fn test_normalize_ignores_literal_values(
+        first in prop::collection::vec(arbitrary_literal(), 2),
+        second in prop::collection::vec(arbitrary_literal(), 2),
+        first_list in arbitrary_list(),
+        second_list in arbitrary_list(),
+        first_limit in any::<u32>(),
+        second_limit in any::<u32>(),
+    ) {
+        let a = statement(&first, &first_list, first_limit);
+        let b = statement(&second, &second_list, second_limit);
+        prop_assert_eq!(normalize(&a), normalize(&b));
+        prop_assert!(!normalize(&a).contains('\''));
+
+        let mut aggregator = SlowLogAggregator::new();
+        for text in [&a, &b] {
+            aggregator.add(&SlowLogEntry {
+                query_time_secs: 1.0,
+                statement: text.clone(),
+                ..Default::default()
+            });
+        }
+        let digests = aggregator.finish();
+        prop_assert_eq!(digests.len(), 1);
+        prop_assert_eq!(digests[0].count, 2);
+    }
+}
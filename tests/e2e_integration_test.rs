@@ -1,12 +1,13 @@
 // End-to-end integration tests for MySQL MCP Server
 // These tests use a real MySQL database (via Docker) to test the complete system
 
-use mysql_mcp_server::config::{DataSourceConfig, PoolConfig, ServerConfig, Permission};
+use mysql_mcp_server::config::{DataSourceConfig, Permission};
 use mysql_mcp_server::manager::DataSourceManager;
 use mysql_mcp_server::pool::ConnectionPoolManager;
 use mysql_mcp_server::tools::*;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
 // Helper function to check if MySQL is available
@@ -28,15 +29,14 @@ fn create_test_datasource_config(key: &str) -> DataSourceConfig {
             .unwrap_or(3306),
         username: std::env::var("MYSQL_USER").unwrap_or_else(|_| "root".to_string()),
         password: std::env::var("MYSQL_PASSWORD").unwrap_or_else(|_| "testpass".to_string()),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
         permission: Permission::Update,
+        ..Default::default()
     }
 }
 
 // Helper function to create a test database
-async fn create_test_database(pool_manager: &mut ConnectionPoolManager, db_name: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let pool = pool_manager.get_pool("mysql").await?;
+async fn create_test_database(pool_manager: &ConnectionPoolManager, db_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let pool = &pool_manager.get_pool("mysql").await?;
     
     // Drop database if exists
     let drop_query = format!("DROP DATABASE IF EXISTS {}", db_name);
@@ -51,11 +51,11 @@ async fn create_test_database(pool_manager: &mut ConnectionPoolManager, db_name:
 
 // Helper function to create a test table
 async fn create_test_table(
-    pool_manager: &mut ConnectionPoolManager,
+    pool_manager: &ConnectionPoolManager,
     db_name: &str,
     table_name: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let pool = pool_manager.get_pool(db_name).await?;
+    let pool = &pool_manager.get_pool(db_name).await?;
     
     let create_query = format!(
         "CREATE TABLE IF NOT EXISTS {} (
@@ -75,12 +75,12 @@ async fn create_test_table(
 
 // Helper function to insert test data
 async fn insert_test_data(
-    pool_manager: &mut ConnectionPoolManager,
+    pool_manager: &ConnectionPoolManager,
     db_name: &str,
     table_name: &str,
     count: usize,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let pool = pool_manager.get_pool(db_name).await?;
+    let pool = &pool_manager.get_pool(db_name).await?;
     
     for i in 0..count {
         let insert_query = format!(
@@ -102,19 +102,19 @@ async fn test_e2e_complete_query_flow() {
     }
 
     let config = create_test_datasource_config("test-db");
-    let mut pool_manager = ConnectionPoolManager::new(config.clone()).await
+    let pool_manager = ConnectionPoolManager::new(config.clone()).await
         .expect("Failed to create pool manager");
 
     // Create test database
-    create_test_database(&mut pool_manager, "e2e_test_db").await
+    create_test_database(&pool_manager, "e2e_test_db").await
         .expect("Failed to create test database");
 
     // Create test table
-    create_test_table(&mut pool_manager, "e2e_test_db", "users").await
+    create_test_table(&pool_manager, "e2e_test_db", "users").await
         .expect("Failed to create test table");
 
     // Insert test data
-    insert_test_data(&mut pool_manager, "e2e_test_db", "users", 10).await
+    insert_test_data(&pool_manager, "e2e_test_db", "users", 10).await
         .expect("Failed to insert test data");
 
     // Create manager and tools
@@ -153,6 +153,43 @@ async fn test_e2e_complete_query_flow() {
     println!("✓ Complete query flow test passed");
 }
 
+#[tokio::test]
+async fn test_e2e_timeout_override_applies_to_with_queries() {
+    // Skip if MySQL is not available
+    if !is_mysql_available().await {
+        eprintln!("Skipping test: MySQL not available. Set MYSQL_HOST, MYSQL_USER, MYSQL_PASSWORD environment variables.");
+        return;
+    }
+
+    // Connections of the pool get a 1 second max_execution_time
+    let config = DataSourceConfig {
+        query_timeout_secs: Some(1),
+        ..create_test_datasource_config("test-db")
+    };
+    let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
+    let pool_managers = Arc::new(RwLock::new(HashMap::new()));
+
+    // A WITH query has no leading SELECT to carry a hint; SLEEP returns 1 when
+    // the server interrupts it
+    let query_tool = QueryTool::new(manager.clone(), pool_managers.clone())
+        .with_timeout(Some(Duration::from_secs(5)));
+    let result = query_tool
+        .execute("test-db", "mysql", "WITH t AS (SELECT SLEEP(2) AS s) SELECT s FROM t")
+        .await
+        .expect("WITH query with a longer timeout failed");
+    assert_eq!(result.rows[0].values[0], serde_json::json!(0));
+
+    // The connection is back at the data source's timeout for the next call
+    let query_tool = QueryTool::new(manager, pool_managers);
+    let result = query_tool
+        .execute("test-db", "mysql", "SELECT @@SESSION.max_execution_time")
+        .await
+        .expect("Query failed");
+    assert_eq!(result.rows[0].values[0], serde_json::json!(1000));
+
+    println!("✓ Timeout override test passed");
+}
+
 #[tokio::test]
 async fn test_e2e_multi_datasource_concurrent_access() {
     // Skip if MySQL is not available
@@ -165,27 +202,27 @@ async fn test_e2e_multi_datasource_concurrent_access() {
     let config1 = create_test_datasource_config("datasource-1");
     let config2 = create_test_datasource_config("datasource-2");
 
-    let mut pool_manager1 = ConnectionPoolManager::new(config1.clone()).await
+    let pool_manager1 = ConnectionPoolManager::new(config1.clone()).await
         .expect("Failed to create pool manager 1");
-    let mut pool_manager2 = ConnectionPoolManager::new(config2.clone()).await
+    let pool_manager2 = ConnectionPoolManager::new(config2.clone()).await
         .expect("Failed to create pool manager 2");
 
     // Create test databases
-    create_test_database(&mut pool_manager1, "concurrent_db1").await
+    create_test_database(&pool_manager1, "concurrent_db1").await
         .expect("Failed to create db1");
-    create_test_database(&mut pool_manager2, "concurrent_db2").await
+    create_test_database(&pool_manager2, "concurrent_db2").await
         .expect("Failed to create db2");
 
     // Create test tables
-    create_test_table(&mut pool_manager1, "concurrent_db1", "table1").await
+    create_test_table(&pool_manager1, "concurrent_db1", "table1").await
         .expect("Failed to create table1");
-    create_test_table(&mut pool_manager2, "concurrent_db2", "table2").await
+    create_test_table(&pool_manager2, "concurrent_db2", "table2").await
         .expect("Failed to create table2");
 
     // Insert different amounts of data
-    insert_test_data(&mut pool_manager1, "concurrent_db1", "table1", 5).await
+    insert_test_data(&pool_manager1, "concurrent_db1", "table1", 5).await
         .expect("Failed to insert data into table1");
-    insert_test_data(&mut pool_manager2, "concurrent_db2", "table2", 8).await
+    insert_test_data(&pool_manager2, "concurrent_db2", "table2", 8).await
         .expect("Failed to insert data into table2");
 
     // Create manager with both data sources
@@ -239,12 +276,12 @@ async fn test_e2e_error_recovery_scenarios() {
     }
 
     let config = create_test_datasource_config("test-db");
-    let mut pool_manager = ConnectionPoolManager::new(config.clone()).await
+    let pool_manager = ConnectionPoolManager::new(config.clone()).await
         .expect("Failed to create pool manager");
 
-    create_test_database(&mut pool_manager, "error_test_db").await
+    create_test_database(&pool_manager, "error_test_db").await
         .expect("Failed to create test database");
-    create_test_table(&mut pool_manager, "error_test_db", "test_table").await
+    create_test_table(&pool_manager, "error_test_db", "test_table").await
         .expect("Failed to create test table");
 
     let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
@@ -300,16 +337,16 @@ async fn test_e2e_streaming_query() {
     }
 
     let config = create_test_datasource_config("test-db");
-    let mut pool_manager = ConnectionPoolManager::new(config.clone()).await
+    let pool_manager = ConnectionPoolManager::new(config.clone()).await
         .expect("Failed to create pool manager");
 
-    create_test_database(&mut pool_manager, "stream_test_db").await
+    create_test_database(&pool_manager, "stream_test_db").await
         .expect("Failed to create test database");
-    create_test_table(&mut pool_manager, "stream_test_db", "large_table").await
+    create_test_table(&pool_manager, "stream_test_db", "large_table").await
         .expect("Failed to create test table");
 
     // Insert a large dataset (2500 rows to test multiple chunks)
-    insert_test_data(&mut pool_manager, "stream_test_db", "large_table", 2500).await
+    insert_test_data(&pool_manager, "stream_test_db", "large_table", 2500).await
         .expect("Failed to insert test data");
 
     let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
@@ -369,12 +406,12 @@ async fn test_e2e_execute_tool_dml_operations() {
     }
 
     let config = create_test_datasource_config("test-db");
-    let mut pool_manager = ConnectionPoolManager::new(config.clone()).await
+    let pool_manager = ConnectionPoolManager::new(config.clone()).await
         .expect("Failed to create pool manager");
 
-    create_test_database(&mut pool_manager, "dml_test_db").await
+    create_test_database(&pool_manager, "dml_test_db").await
         .expect("Failed to create test database");
-    create_test_table(&mut pool_manager, "dml_test_db", "dml_table").await
+    create_test_table(&pool_manager, "dml_test_db", "dml_table").await
         .expect("Failed to create test table");
 
     let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
@@ -463,12 +500,12 @@ async fn test_e2e_schema_tools() {
     }
 
     let config = create_test_datasource_config("test-db");
-    let mut pool_manager = ConnectionPoolManager::new(config.clone()).await
+    let pool_manager = ConnectionPoolManager::new(config.clone()).await
         .expect("Failed to create pool manager");
 
-    create_test_database(&mut pool_manager, "schema_test_db").await
+    create_test_database(&pool_manager, "schema_test_db").await
         .expect("Failed to create test database");
-    create_test_table(&mut pool_manager, "schema_test_db", "schema_table").await
+    create_test_table(&pool_manager, "schema_test_db", "schema_table").await
         .expect("Failed to create test table");
 
     let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
@@ -572,11 +609,11 @@ async fn test_e2e_connection_stats() {
     }
 
     let config = create_test_datasource_config("test-db");
-    let mut pool_manager = ConnectionPoolManager::new(config.clone()).await
+    let pool_manager = ConnectionPoolManager::new(config.clone()).await
         .expect("Failed to create pool manager");
 
     // Create a test database and execute some queries to populate stats
-    create_test_database(&mut pool_manager, "stats_test_db").await
+    create_test_database(&pool_manager, "stats_test_db").await
         .expect("Failed to create test database");
 
     let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
@@ -615,14 +652,14 @@ async fn test_e2e_multi_statement_handling() {
     }
 
     let config = create_test_datasource_config("test-db");
-    let mut pool_manager = ConnectionPoolManager::new(config.clone()).await
+    let pool_manager = ConnectionPoolManager::new(config.clone()).await
         .expect("Failed to create pool manager");
 
-    create_test_database(&mut pool_manager, "multi_stmt_db").await
+    create_test_database(&pool_manager, "multi_stmt_db").await
         .expect("Failed to create test database");
-    create_test_table(&mut pool_manager, "multi_stmt_db", "test_table").await
+    create_test_table(&pool_manager, "multi_stmt_db", "test_table").await
         .expect("Failed to create test table");
-    insert_test_data(&mut pool_manager, "multi_stmt_db", "test_table", 5).await
+    insert_test_data(&pool_manager, "multi_stmt_db", "test_table", 5).await
         .expect("Failed to insert test data");
 
     let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
@@ -0,0 +1,101 @@
+// Feature: mysql-mcp-multi-datasource, Property 43: Import round trip
+// Validates: Requirements 11.1, 13.1
+
+use mysql_mcp_server::export::Compression;
+use mysql_mcp_server::format::ResultFormat;
+use mysql_mcp_server::import::{ImportFormat, ImportPlan, RecordReader};
+use mysql_mcp_server::params::SqlParam;
+use mysql_mcp_server::tools::{ColumnMetadata, ColumnSchema, QueryRow};
+use proptest::prelude::*;
+use serde_json::{json, Value};
+
+// Strategy to generate values that need quoting or escaping in text formats
+fn arbitrary_value() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        Just(Value::Null),
+        any::<i64>().prop_map(|n| json!(n)),
+        "[a-z ,;\"\t\n\r]{0,12}".prop_map(Value::String),
+    ]
+}
+
+// Strategy to generate a result with 1-5 distinct columns
+fn arbitrary_result() -> impl Strategy<Value = (Vec<ColumnMetadata>, Vec<QueryRow>)> {
+    prop::collection::btree_set("[a-z]{1,8}", 1..=5).prop_flat_map(|names| {
+        let width = names.len();
+        let columns = names
+            .into_iter()
+            .map(|name| ColumnMetadata {
+                name,
+                data_type: "VARCHAR".to_string(),
+                nullable: true,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let rows = prop::collection::vec(
+            prop::collection::vec(arbitrary_value(), width).prop_map(|values| QueryRow { values }),
+            1..20,
+        );
+        (Just(columns), rows)
+    })
+}
+
+/// Table with a nullable text column for each result column
+fn table_columns(columns: &[ColumnMetadata]) -> Vec<ColumnSchema> {
+    columns
+        .iter()
+        .map(|column| ColumnSchema {
+            name: column.name.to_uppercase(),
+            data_type: "varchar(255)".to_string(),
+            nullable: true,
+            default_value: None,
+            comment: None,
+            extra: None,
+        })
+        .collect()
+}
+
+/// Bind value the import is expected to produce for a text column
+fn expected_param(value: &Value) -> SqlParam {
+    match value {
+        Value::Null => SqlParam::Null,
+        Value::String(s) => SqlParam::String(s.clone()),
+        other => SqlParam::String(other.to_string()),
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(100))]
+
+    /// Property 43: Import round trip
+    /// For any result written as CSV or NDJSON, importing the file into a table
+    /// with the same columns yields every row's values, with NULL distinct from
+    /// an empty string
+    #[test]
+    fn test_import_reads_exported_rows((columns, rows) in arbitrary_result(), ndjson in any::<bool>()) {
+        let (result_format, format, name) = if ndjson {
+            (ResultFormat::Ndjson, ImportFormat::Ndjson, "rows.ndjson")
+        } else {
+            (ResultFormat::Csv, ImportFormat::Csv, "rows.csv")
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(name);
+        std::fs::write(&path, result_format.formatter().unwrap().format(&columns, &rows, true)).unwrap();
+
+        let mut reader = RecordReader::open(&path, format, Compression::None).unwrap();
+        let plan = ImportPlan::new(reader.columns(), &table_columns(&columns)).unwrap();
+
+        // Columns are matched by name, whatever their order in the file
+        let order: Vec<usize> = plan
+            .column_names()
+            .iter()
+            .map(|name| columns.iter().position(|c| c.name.to_uppercase() == *name).unwrap())
+            .collect();
+
+        for row in &rows {
+            let record = reader.next_record().unwrap().unwrap();
+            let expected: Vec<SqlParam> = order.iter().map(|&i| expected_param(&row.values[i])).collect();
+            prop_assert_eq!(plan.convert(&record).unwrap(), expected);
+        }
+        prop_assert!(reader.next_record().unwrap().is_none());
+    }
+}
@@ -0,0 +1,119 @@
+// Feature: mysql-mcp-multi-datasource, Property 36: Transaction handle isolation
+// Validates: Requirements 11.1, 11.3
+
+use mysql_mcp_server::config::{DataSourceConfig, Permission, TransactionConfig};
+use mysql_mcp_server::error::McpError;
+use mysql_mcp_server::manager::DataSourceManager;
+use mysql_mcp_server::params::StatementParams;
+use mysql_mcp_server::tools::{ExecuteTool, QueryTool, TransactionTool};
+use mysql_mcp_server::transaction::TransactionManager;
+use proptest::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+// Strategy to generate a writable data source
+fn valid_datasource_config() -> impl Strategy<Value = DataSourceConfig> {
+    "[a-zA-Z0-9_-]{1,30}".prop_map(|key| DataSourceConfig {
+        key,
+        name: "Test Database".to_string(),
+        username: "test".to_string(),
+        password: "test".to_string(),
+        permission: Permission::Update,
+        ..Default::default()
+    })
+}
+
+// Strategy to generate transaction handles that were never issued
+fn unknown_transaction_id() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[a-zA-Z0-9_-]{1,40}",
+        "[0-9a-f]{8}-[0-9a-f]{4}-4[0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}",
+    ]
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(20))]
+
+    /// Property 36: Transaction handle isolation
+    /// For any handle that was never issued (or has ended), every transaction-aware tool
+    /// should reject it without touching a connection
+    #[test]
+    fn test_unknown_transaction_handles_are_rejected(
+        config in valid_datasource_config(),
+        transaction_id in unknown_transaction_id(),
+    ) {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async {
+            let datasource_key = config.key.clone();
+            let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
+            let pool_managers = Arc::new(RwLock::new(HashMap::new()));
+            let transactions = TransactionManager::new(TransactionConfig::default());
+            let params = StatementParams::default();
+
+            let query_tool = QueryTool::new(manager.clone(), pool_managers.clone());
+            let result = query_tool
+                .execute_in_transaction(&transactions, &transaction_id, &datasource_key, "testdb", "SELECT 1", &params)
+                .await;
+            prop_assert!(matches!(result, Err(McpError::InvalidStatement(_))), "query: {:?}", result);
+
+            let execute_tool = ExecuteTool::new(manager.clone(), pool_managers.clone());
+            let result = execute_tool
+                .execute_in_transaction(&transactions, &transaction_id, &datasource_key, "testdb", "DELETE FROM users", &params)
+                .await;
+            prop_assert!(matches!(result, Err(McpError::InvalidStatement(_))), "execute: {:?}", result);
+
+            let transaction_tool = TransactionTool::new(manager.clone(), pool_managers.clone());
+            let result = transaction_tool.commit(&transactions, &transaction_id).await;
+            prop_assert!(matches!(result, Err(McpError::InvalidStatement(_))), "commit: {:?}", result);
+            let result = transaction_tool.rollback(&transactions, &transaction_id).await;
+            prop_assert!(matches!(result, Err(McpError::InvalidStatement(_))), "rollback: {:?}", result);
+
+            prop_assert!(pool_managers.read().await.is_empty());
+            prop_assert_eq!(transactions.open_count(&datasource_key), 0);
+
+            Ok(())
+        })?;
+    }
+}
+
+proptest! {
+    // Each failed begin waits for the connection timeout
+    #![proptest_config(ProptestConfig::with_cases(5))]
+
+    /// Property 36a: Failed begins never hold a transaction slot
+    /// For any per-data-source limit, repeated failures to start a transaction should
+    /// leave the open transaction count at zero
+    #[test]
+    fn test_failed_begin_never_leaks_slots(
+        config in valid_datasource_config(),
+        max_open in 1usize..3,
+    ) {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async {
+            let mut config = config;
+            config.host = "127.0.0.1".to_string();
+            config.port = 1;
+            config.pool_config.connection_timeout_secs = 1;
+
+            let datasource_key = config.key.clone();
+            let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
+            let pool_managers = Arc::new(RwLock::new(HashMap::new()));
+            let transactions = TransactionManager::new(TransactionConfig {
+                max_open_per_datasource: max_open,
+                ..Default::default()
+            });
+            let tool = TransactionTool::new(manager, pool_managers);
+
+            for _ in 0..=max_open {
+                let result = tool.begin(&transactions, &datasource_key, "testdb", None).await;
+                prop_assert!(matches!(result, Err(McpError::ConnectionFailed(_))), "got: {:?}", result);
+                prop_assert_eq!(transactions.open_count(&datasource_key), 0);
+            }
+
+            Ok(())
+        })?;
+    }
+}
@@ -4,7 +4,7 @@
 // Property 16: Invalid data source key handling
 // For any invalid data source key, operations should return an authentication error
 
-use mysql_mcp_server::config::{DataSourceConfig, PoolConfig};
+use mysql_mcp_server::config::DataSourceConfig;
 use mysql_mcp_server::error::McpError;
 use mysql_mcp_server::manager::DataSourceManager;
 use mysql_mcp_server::tools::{ListTool, QueryTool, ExecuteTool, SchemaTool};
@@ -34,13 +34,9 @@ fn arb_datasource_config() -> impl Strategy<Value = DataSourceConfig> {
     arb_valid_key().prop_map(|key| DataSourceConfig {
         key,
         name: "Test Database".to_string(),
-        host: "localhost".to_string(),
-        port: 3306,
         username: "test".to_string(),
         password: "test".to_string(),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+        ..Default::default()
     })
 }
 
@@ -256,13 +252,9 @@ async fn test_empty_key_is_rejected() {
     let config = DataSourceConfig {
         key: "test-db".to_string(),
         name: "Test Database".to_string(),
-        host: "localhost".to_string(),
-        port: 3306,
         username: "test".to_string(),
         password: "test".to_string(),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+        ..Default::default()
     };
     
     let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
@@ -294,13 +286,9 @@ async fn test_valid_key_is_accepted() {
     let config = DataSourceConfig {
         key: "test-db".to_string(),
         name: "Test Database".to_string(),
-        host: "localhost".to_string(),
-        port: 3306,
         username: "test".to_string(),
         password: "test".to_string(),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+        ..Default::default()
     };
     
     let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
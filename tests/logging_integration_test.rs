@@ -87,7 +87,7 @@ fn test_non_sensitive_messages_unchanged() {
 
 #[tokio::test]
 async fn test_monitoring_service_integration() {
-    use mysql_mcp_server::config::{DataSourceConfig, PoolConfig};
+    use mysql_mcp_server::config::DataSourceConfig;
     use mysql_mcp_server::manager::DataSourceManager;
     use mysql_mcp_server::monitoring::MonitoringService;
     use std::collections::HashMap;
@@ -98,13 +98,9 @@ async fn test_monitoring_service_integration() {
     let config = DataSourceConfig {
         key: "test".to_string(),
         name: "Test DB".to_string(),
-        host: "localhost".to_string(),
-        port: 3306,
         username: "test".to_string(),
         password: "test".to_string(),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+        ..Default::default()
     };
     
     let manager = DataSourceManager::new(vec![config]).await.unwrap();
@@ -4,7 +4,7 @@
 // Feature: mysql-mcp-multi-datasource, Property 20: Key-to-credentials mapping correctness
 // Validates: Requirements 8.5
 
-use mysql_mcp_server::config::{DataSourceConfig, PoolConfig};
+use mysql_mcp_server::config::DataSourceConfig;
 use mysql_mcp_server::manager::DataSourceManager;
 use proptest::prelude::*;
 
@@ -40,9 +40,7 @@ fn valid_datasource_config() -> impl Strategy<Value = DataSourceConfig> {
             port,
             username,
             password,
-            databases: vec![],
-            pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+            ..Default::default()
         })
 }
 
@@ -62,9 +60,7 @@ fn configs_with_duplicate_keys() -> impl Strategy<Value = Vec<DataSourceConfig>>
                 port: base_config.port,
                 username: base_config.username.clone(),
                 password: base_config.password.clone(),
-                databases: vec![],
-                pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+                ..Default::default()
             };
 
             // Insert the duplicate at a random position
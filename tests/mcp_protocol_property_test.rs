@@ -1,7 +1,11 @@
 // Feature: mysql-mcp-multi-datasource, Property 31: MCP protocol compliance
 // Validates: Requirements 14.2, 14.3, 14.4, 14.5
 
-use mysql_mcp_server::config::{DataSourceConfig, PoolConfig, ServerConfig};
+use mysql_mcp_server::config::{
+    DataSourceConfig, ExportConfig, HealthConfig, ImportConfig, ServerConfig,
+    StreamConfig, TransactionConfig, TypeConfig,
+};
+use mysql_mcp_server::error::RetryConfig;
 use mysql_mcp_server::mcp_server::MySqlMcpServerHandler;
 use proptest::prelude::*;
 use rmcp::handler::server::ServerHandler;
@@ -27,8 +31,7 @@ fn valid_datasource_config() -> impl Strategy<Value = DataSourceConfig> {
                 username,
                 password,
                 databases,
-                pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+                ..Default::default()
             },
         )
 }
@@ -38,7 +41,17 @@ fn server_config_strategy() -> impl Strategy<Value = ServerConfig> {
     prop::collection::vec(valid_datasource_config(), 1..4).prop_map(|data_sources| ServerConfig {
         data_sources,
         query_timeout_secs: 30,
+        max_query_timeout_secs: 300,
+        max_rows: 10_000,
+        max_result_bytes: 5_242_880,
         stream_chunk_size: 1000,
+        transactions: TransactionConfig::default(),
+        streams: StreamConfig::default(),
+        types: TypeConfig::default(),
+        export: ExportConfig::default(),
+        import: ImportConfig::default(),
+        health: HealthConfig::default(),
+        retry: RetryConfig::default(),
     })
 }
 
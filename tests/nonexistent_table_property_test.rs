@@ -1,7 +1,7 @@
 // Feature: mysql-mcp-multi-datasource, Property 13: Non-existent table error handling
 // Validates: Requirements 4.3
 
-use mysql_mcp_server::config::{DataSourceConfig, PoolConfig};
+use mysql_mcp_server::config::DataSourceConfig;
 use mysql_mcp_server::manager::DataSourceManager;
 use mysql_mcp_server::tools::SchemaTool;
 use proptest::prelude::*;
@@ -19,13 +19,9 @@ fn valid_datasource_config() -> impl Strategy<Value = DataSourceConfig> {
     arbitrary_non_empty_string().prop_map(|key| DataSourceConfig {
         key,
         name: "Test Database".to_string(),
-        host: "localhost".to_string(),
-        port: 3306,
         username: "test".to_string(),
         password: "test".to_string(),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+        ..Default::default()
     })
 }
 
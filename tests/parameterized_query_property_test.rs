@@ -0,0 +1,106 @@
+// Feature: mysql-mcp-multi-datasource, Property 34: Bind parameter validation
+// Validates: Requirements 3.1, 11.1
+
+use mysql_mcp_server::error::McpError;
+use mysql_mcp_server::params::{SqlParam, StatementParams};
+use proptest::prelude::*;
+use serde_json::{json, Value};
+
+// Strategy to generate JSON values accepted as bind parameters
+fn param_value() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        "[a-zA-Z0-9 '?:;-]{0,20}".prop_map(Value::from),
+        "-?[0-9]{1,10}\\.[0-9]{1,4}".prop_map(|v| json!({"type": "decimal", "value": v})),
+    ]
+}
+
+// Build a statement with `count` positional placeholders, plus decoys inside
+// string literals and comments that must not be counted
+fn positional_statement(count: usize) -> String {
+    let conditions: Vec<String> = (0..count).map(|i| format!("c{} = ?", i)).collect();
+    let filter = if conditions.is_empty() {
+        "1 = 1".to_string()
+    } else {
+        conditions.join(" AND ")
+    };
+    format!(
+        "SELECT '?', \"?\" FROM users /* ? */ WHERE {} -- ?\n",
+        filter
+    )
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(50))]
+
+    /// Property 34: Bind parameter validation
+    /// For any statement with N ? placeholders and M params, binding should succeed
+    /// exactly when N == M and otherwise fail with InvalidStatement
+    #[test]
+    fn test_positional_param_count_must_match(
+        placeholders in 0usize..8,
+        values in prop::collection::vec(param_value(), 0..8),
+    ) {
+        let statement = positional_statement(placeholders);
+        let params = StatementParams::from_json(Some(&Value::Array(values.clone())), None).unwrap();
+
+        match params.bind(&statement) {
+            Ok(bound) => {
+                prop_assert_eq!(placeholders, values.len());
+                prop_assert_eq!(bound.args.len(), values.len());
+                prop_assert_eq!(bound.sql, statement);
+            }
+            Err(e) => {
+                prop_assert_ne!(placeholders, values.len());
+                prop_assert!(matches!(e, McpError::InvalidStatement(_)), "got: {:?}", e);
+            }
+        }
+    }
+
+    /// Property 34a: Named placeholders bind by occurrence
+    /// For any set of names used in any order (with repeats), every :name is rewritten
+    /// to ? and bound to its value, and values never end up in the SQL text
+    #[test]
+    fn test_named_params_bind_by_occurrence(
+        values in prop::collection::btree_map("[a-z_][a-z0-9_]{0,10}", any::<i64>(), 1..5),
+        order in prop::collection::vec(any::<prop::sample::Index>(), 1..10),
+    ) {
+        let names: Vec<&String> = values.keys().collect();
+        let mut used: Vec<&String> = order.iter().map(|i| *i.get(&names)).collect();
+        // Every supplied name must appear at least once
+        used.extend(names.iter().copied());
+
+        let conditions: Vec<String> = used.iter().map(|name| format!("c = :{}", name)).collect();
+        let statement = format!("SELECT * FROM t WHERE {}", conditions.join(" OR "));
+        let named_params = Value::Object(
+            values.iter().map(|(k, v)| (k.clone(), Value::from(*v))).collect(),
+        );
+        let params = StatementParams::from_json(None, Some(&named_params)).unwrap();
+
+        let bound = params.bind(&statement).unwrap();
+        prop_assert_eq!(bound.sql.matches('?').count(), used.len());
+        prop_assert!(!bound.sql.contains(':'));
+        let expected: Vec<SqlParam> = used.iter().map(|name| SqlParam::Int(values[*name])).collect();
+        prop_assert_eq!(bound.args, expected);
+    }
+
+    /// Property 34b: Missing and unused names are rejected
+    /// For any statement, a named param that is not referenced, or a placeholder without
+    /// a value, should fail with InvalidStatement
+    #[test]
+    fn test_named_params_must_match_placeholders(
+        used in "[a-z][a-z0-9_]{0,10}",
+        other in "[a-z][a-z0-9_]{0,10}",
+    ) {
+        prop_assume!(used != other);
+        let statement = format!("DELETE FROM t WHERE id = :{}", used);
+
+        let unused = StatementParams::from_json(None, Some(&json!({ used.clone(): 1, other.clone(): 2 }))).unwrap();
+        prop_assert!(matches!(unused.bind(&statement), Err(McpError::InvalidStatement(_))));
+
+        let missing = StatementParams::from_json(None, Some(&json!({ other.clone(): 1 }))).unwrap();
+        prop_assert!(matches!(missing.bind(&statement), Err(McpError::InvalidStatement(_))));
+    }
+}
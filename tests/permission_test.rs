@@ -1,7 +1,7 @@
 // Permission control tests
 // Tests that different permission levels correctly restrict operations
 
-use mysql_mcp_server::config::{DataSourceConfig, Permission, PoolConfig};
+use mysql_mcp_server::config::{DataSourceConfig, Permission};
 use mysql_mcp_server::error::McpError;
 use mysql_mcp_server::manager::DataSourceManager;
 use mysql_mcp_server::tools::{ExecuteTool, QueryTool};
@@ -13,13 +13,10 @@ fn create_config_with_permission(key: &str, permission: Permission) -> DataSourc
     DataSourceConfig {
         key: key.to_string(),
         name: format!("Test Database - {:?}", permission),
-        host: "localhost".to_string(),
-        port: 3306,
         username: "test".to_string(),
         password: "test".to_string(),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
         permission,
+        ..Default::default()
     }
 }
 
@@ -101,6 +98,25 @@ async fn test_ddl_permission_allows_all() {
     assert!(manager.check_ddl_permission("ddl-allowed").is_ok());
 }
 
+#[tokio::test]
+async fn test_admin_is_independent_of_permission_level() {
+    let mut admin = create_config_with_permission("query-admin", Permission::Query);
+    admin.admin = true;
+    let configs = vec![
+        create_config_with_permission("ddl-allowed", Permission::Ddl),
+        admin,
+    ];
+    let manager = Arc::new(DataSourceManager::new(configs).await.unwrap());
+
+    // DDL permission does not imply admin
+    let result = manager.check_admin_permission("ddl-allowed");
+    assert!(matches!(result.unwrap_err(), McpError::PermissionDenied(_)));
+
+    // Admin does not imply updates
+    assert!(manager.check_admin_permission("query-admin").is_ok());
+    assert!(manager.check_update_permission("query-admin").is_err());
+}
+
 #[tokio::test]
 async fn test_execute_tool_respects_query_permission() {
     let configs = vec![create_config_with_permission("query-only", Permission::Query)];
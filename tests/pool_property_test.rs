@@ -52,7 +52,7 @@ fn valid_datasource_config() -> impl Strategy<Value = DataSourceConfig> {
                     password,
                     databases,
                     pool_config,
-                    permission: mysql_mcp_server::config::Permission::default(),
+                    ..Default::default()
                 }
             },
         )
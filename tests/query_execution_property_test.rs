@@ -1,7 +1,7 @@
 // Feature: mysql-mcp-multi-datasource, Property 7: Query execution correctness
 // Validates: Requirements 3.1
 
-use mysql_mcp_server::config::{DataSourceConfig, PoolConfig};
+use mysql_mcp_server::config::DataSourceConfig;
 use mysql_mcp_server::error::McpError;
 use mysql_mcp_server::manager::DataSourceManager;
 use mysql_mcp_server::tools::QueryTool;
@@ -41,13 +41,9 @@ fn valid_datasource_config() -> impl Strategy<Value = DataSourceConfig> {
     arbitrary_non_empty_string().prop_map(|key| DataSourceConfig {
         key,
         name: "Test Database".to_string(),
-        host: "localhost".to_string(),
-        port: 3306,
         username: "test".to_string(),
         password: "test".to_string(),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+        ..Default::default()
     })
 }
 
@@ -115,7 +111,7 @@ proptest! {
                             McpError::DatabaseNotFound(_)
                                 | McpError::QueryExecutionError(_)
                                 | McpError::ConnectionFailed(_)
-                                | McpError::QueryTimeout
+                                | McpError::QueryTimeout { .. }
                                 | McpError::DataSourceUnavailable(_)
                                 | McpError::NetworkError(_)
                                 | McpError::PoolError(_)
@@ -0,0 +1,84 @@
+// Feature: mysql-mcp-multi-datasource, Property 37: Query timeout resolution
+// Validates: Requirements 3.4, 5.4
+
+use mysql_mcp_server::config::{DataSourceConfig, Permission};
+use mysql_mcp_server::manager::DataSourceManager;
+use mysql_mcp_server::sql;
+use proptest::prelude::*;
+use std::time::Duration;
+
+// Strategy to generate a data source with an optional timeout override
+fn datasource_config() -> impl Strategy<Value = DataSourceConfig> {
+    ("[a-zA-Z0-9_-]{1,30}", prop::option::of(1u64..600)).prop_map(|(key, query_timeout_secs)| {
+        DataSourceConfig {
+            key,
+            name: "Test Database".to_string(),
+            username: "test".to_string(),
+            password: "test".to_string(),
+            permission: Permission::Query,
+            query_timeout_secs,
+            ..Default::default()
+        }
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(50))]
+
+    /// Property 37: Query timeout resolution
+    /// For any server default, maximum, data source override and per-call request,
+    /// the effective timeout is the most specific one, capped at the maximum
+    #[test]
+    fn test_query_timeout_is_most_specific_and_capped(
+        config in datasource_config(),
+        default_secs in 1u64..600,
+        max_secs in 1u64..600,
+        requested_ms in prop::option::of(1u64..1_000_000),
+    ) {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async {
+            let datasource_key = config.key.clone();
+            let override_secs = config.query_timeout_secs;
+            let max = Duration::from_secs(max_secs);
+            let manager = DataSourceManager::new(vec![config])
+                .await
+                .unwrap()
+                .with_query_timeouts(Duration::from_secs(default_secs), max);
+
+            let requested = requested_ms.map(Duration::from_millis);
+            let timeout = manager.query_timeout(&datasource_key, requested);
+
+            let expected = requested
+                .or(override_secs.map(Duration::from_secs))
+                .unwrap_or(Duration::from_secs(default_secs))
+                .min(max);
+            prop_assert_eq!(timeout, expected);
+            prop_assert!(timeout <= max);
+
+            Ok(())
+        })?;
+    }
+
+    /// Property 37a: Server-side enforcement of SELECT timeouts
+    /// For any SELECT and timeout, the statement sent to MySQL carries exactly one
+    /// MAX_EXECUTION_TIME hint with the timeout right after the SELECT keyword
+    #[test]
+    fn test_select_carries_max_execution_time(
+        columns in "[a-z_]{1,10}(, [a-z_]{1,10}){0,3}",
+        table in "[a-z_]{1,10}",
+        keyword in prop_oneof![Just("SELECT"), Just("select"), Just("Select")],
+        timeout_ms in 1u64..10_000_000,
+    ) {
+        let statement = format!("{} {} FROM {} WHERE id = ?", keyword, columns, table);
+        let hinted = sql::with_max_execution_time(&statement, timeout_ms);
+
+        let hint = format!("/*+ MAX_EXECUTION_TIME({}) */", timeout_ms);
+        prop_assert_eq!(hinted.matches("MAX_EXECUTION_TIME").count(), 1);
+        prop_assert!(hinted.starts_with(&format!("{} {}", keyword, hint)), "got: {}", hinted);
+        prop_assert_eq!(hinted.replace(&format!(" {}", hint), ""), statement);
+
+        // Hinting is idempotent
+        prop_assert_eq!(sql::with_max_execution_time(&hinted, timeout_ms), hinted);
+    }
+}
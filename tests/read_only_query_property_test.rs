@@ -0,0 +1,119 @@
+// Feature: mysql-mcp-multi-datasource, Property 32: Read-only query enforcement
+// Validates: Requirements 3.1, 11.3
+
+use mysql_mcp_server::config::{DataSourceConfig, Permission};
+use mysql_mcp_server::error::McpError;
+use mysql_mcp_server::manager::DataSourceManager;
+use mysql_mcp_server::sql::{classify, StatementKind};
+use mysql_mcp_server::tools::QueryTool;
+use proptest::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+// Strategy to generate a valid DataSourceConfig with full permissions
+fn ddl_datasource_config() -> impl Strategy<Value = DataSourceConfig> {
+    "[a-zA-Z0-9_-]{1,30}".prop_map(|key| DataSourceConfig {
+        key,
+        name: "Test Database".to_string(),
+        username: "test".to_string(),
+        password: "test".to_string(),
+        permission: Permission::Ddl,
+        ..Default::default()
+    })
+}
+
+// Strategy to generate statements that modify state
+fn arbitrary_write_statement() -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just("DELETE FROM users".to_string()),
+        Just("UPDATE users SET name = 'x'".to_string()),
+        Just("INSERT INTO users VALUES (1)".to_string()),
+        Just("DROP TABLE users".to_string()),
+        Just("TRUNCATE TABLE users".to_string()),
+        Just("WITH t AS (SELECT 1) DELETE FROM users".to_string()),
+        Just("SELECT * FROM users INTO OUTFILE '/tmp/users'".to_string()),
+        Just("SELECT * FROM users FOR UPDATE".to_string()),
+        Just("LOCK TABLES users WRITE".to_string()),
+        Just("SET @a = 1".to_string()),
+        Just("CALL cleanup()".to_string()),
+        Just("HANDLER users OPEN".to_string()),
+    ]
+}
+
+// Strategy to generate comment prefixes that must not hide the statement kind
+fn arbitrary_comment_prefix() -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just("".to_string()),
+        Just("/* SELECT */ ".to_string()),
+        Just("-- SELECT\n".to_string()),
+        Just("# SELECT\n".to_string()),
+        Just("  \n\t".to_string()),
+    ]
+}
+
+// Strategy to generate string literal contents, including SQL keywords
+fn arbitrary_literal() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[a-zA-Z0-9 ]{0,20}",
+        Just("DELETE FROM users".to_string()),
+        Just("; DROP TABLE users; --".to_string()),
+        Just("INTO OUTFILE".to_string()),
+        Just("FOR UPDATE".to_string()),
+    ]
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(20))]
+
+    /// Property 32: Read-only query enforcement
+    /// For any statement that is not provably read-only, the query tool should reject it
+    /// before touching the database, even when the data source has DDL permission
+    #[test]
+    fn test_query_tool_rejects_write_statements(
+        config in ddl_datasource_config(),
+        prefix in arbitrary_comment_prefix(),
+        statement in arbitrary_write_statement(),
+    ) {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async {
+            let datasource_key = config.key.clone();
+            let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
+            let pool_managers = Arc::new(RwLock::new(HashMap::new()));
+            let tool = QueryTool::new(manager, pool_managers);
+
+            let query = format!("{}{}", prefix, statement);
+            let result = tool.execute(&datasource_key, "testdb", &query).await;
+
+            prop_assert!(
+                matches!(result, Err(McpError::InvalidStatement(_))),
+                "Statement '{}' should be rejected on the query path, got: {:?}",
+                query, result
+            );
+
+            Ok(())
+        })?;
+    }
+
+    /// Property 32a: String literals never change the classification
+    /// For any SELECT whose literals contain SQL keywords, the statement stays read-only
+    #[test]
+    fn test_literals_do_not_affect_classification(
+        literal in arbitrary_literal(),
+    ) {
+        let query = format!("SELECT '{}' AS a, \"{}\" AS b FROM users", literal, literal);
+        prop_assert_eq!(classify(&query), StatementKind::ReadOnly);
+    }
+
+    /// Property 32b: Comment prefixes never hide the statement kind
+    /// For any write statement, prefixing it with comments keeps it non read-only
+    #[test]
+    fn test_comments_do_not_hide_write_statements(
+        prefix in arbitrary_comment_prefix(),
+        statement in arbitrary_write_statement(),
+    ) {
+        let query = format!("{}{}", prefix, statement);
+        prop_assert!(!classify(&query).is_read_only(), "'{}' classified as read-only", query);
+    }
+}
@@ -4,7 +4,7 @@
 // Property 29: Resource URI validation
 // *For any* resource request, if the URI format is invalid, the server should return an error
 
-use mysql_mcp_server::config::{DataSourceConfig, PoolConfig};
+use mysql_mcp_server::config::DataSourceConfig;
 use mysql_mcp_server::error::McpError;
 use mysql_mcp_server::manager::DataSourceManager;
 use mysql_mcp_server::resources::ResourceProvider;
@@ -17,13 +17,9 @@ fn create_test_config(key: &str) -> DataSourceConfig {
     DataSourceConfig {
         key: key.to_string(),
         name: "Test Database".to_string(),
-        host: "localhost".to_string(),
-        port: 3306,
         username: "test".to_string(),
         password: "test".to_string(),
-        databases: vec![],
-        pool_config: PoolConfig::default(),
-            permission: mysql_mcp_server::config::Permission::default(),
+        ..Default::default()
     }
 }
 
@@ -0,0 +1,200 @@
+// Feature: mysql-mcp-multi-datasource, Property 42: Result encodings
+// Validates: Requirements 3.2, 13.1
+
+use mysql_mcp_server::config::DataSourceConfig;
+use mysql_mcp_server::error::McpError;
+use mysql_mcp_server::format::ResultFormat;
+use mysql_mcp_server::manager::DataSourceManager;
+use mysql_mcp_server::resources::ResourceProvider;
+use mysql_mcp_server::tools::{ColumnMetadata, QueryRow};
+use proptest::prelude::*;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+// Strategy to generate values that need quoting or escaping in text formats
+fn arbitrary_value() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(|n| json!(n)),
+        "[a-z ,;\"|\t\n\r]{0,12}".prop_map(Value::String),
+        prop::collection::vec("[a-z]{1,5}", 0..3).prop_map(|set| json!(set)),
+    ]
+}
+
+// Strategy to generate a result with 1-5 columns, possibly with repeated names
+fn arbitrary_result() -> impl Strategy<Value = (Vec<ColumnMetadata>, Vec<QueryRow>)> {
+    prop::collection::vec("[a-c]{1,2}", 1..=5).prop_flat_map(|names| {
+        let width = names.len();
+        let columns = names
+            .into_iter()
+            .map(|name| ColumnMetadata {
+                name,
+                data_type: "VARCHAR".to_string(),
+                nullable: true,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let rows = prop::collection::vec(
+            prop::collection::vec(arbitrary_value(), width).prop_map(|values| QueryRow { values }),
+            0..20,
+        );
+        (Just(columns), rows)
+    })
+}
+
+fn create_test_config(key: &str) -> DataSourceConfig {
+    DataSourceConfig {
+        key: key.to_string(),
+        name: "Test Database".to_string(),
+        username: "test".to_string(),
+        password: "test".to_string(),
+        ..Default::default()
+    }
+}
+
+/// Parse delimited text per RFC 4180, keeping empty unquoted fields as `None`
+fn parse_delimited(text: &str, delimiter: char) -> Vec<Vec<Option<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                c => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                quoted = true;
+                in_quotes = true;
+            }
+            c if c == delimiter || c == '\n' => {
+                let value = std::mem::take(&mut field);
+                record.push((quoted || !value.is_empty()).then_some(value));
+                quoted = false;
+                if c == '\n' {
+                    records.push(std::mem::take(&mut record));
+                }
+            }
+            c => field.push(c),
+        }
+    }
+    records
+}
+
+/// Text a tabular format shows for a value, `None` for `NULL`
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(100))]
+
+    /// Property 42: Result encodings
+    /// For any result, CSV and TSV parse back to the column names and the text
+    /// of every value, with NULL distinct from an empty string
+    #[test]
+    fn test_delimited_round_trip((columns, rows) in arbitrary_result(), tsv in any::<bool>()) {
+        let (format, delimiter) = if tsv { (ResultFormat::Tsv, '\t') } else { (ResultFormat::Csv, ',') };
+        let out = format.formatter().unwrap().format(&columns, &rows, true);
+        let records = parse_delimited(&out, delimiter);
+
+        prop_assert_eq!(records.len(), rows.len() + 1);
+        let header: Vec<Option<String>> = columns.iter().map(|c| Some(c.name.clone())).collect();
+        prop_assert_eq!(&records[0], &header);
+        for (record, row) in records[1..].iter().zip(&rows) {
+            let expected: Vec<Option<String>> = row.values.iter().map(text).collect();
+            prop_assert_eq!(record, &expected);
+        }
+
+        // Continuation chunks leave out the header
+        let out = format.formatter().unwrap().format(&columns, &rows, false);
+        prop_assert_eq!(parse_delimited(&out, delimiter).len(), rows.len());
+    }
+
+    /// Property 42a: JSON encodings keep every row and column
+    /// For any result, the JSON formats parse as JSON with one object per row,
+    /// or one array per column, holding the values in column order
+    #[test]
+    fn test_json_encodings((columns, rows) in arbitrary_result()) {
+        let compact: Value = serde_json::from_str(
+            &ResultFormat::JsonCompact.formatter().unwrap().format(&columns, &rows, true),
+        ).unwrap();
+        let objects = compact.as_array().unwrap();
+        prop_assert_eq!(objects.len(), rows.len());
+        for (object, row) in objects.iter().zip(&rows) {
+            let object = object.as_object().unwrap();
+            prop_assert_eq!(object.len(), columns.len());
+            prop_assert_eq!(&object[&columns[0].name], &row.values[0]);
+        }
+
+        let ndjson = ResultFormat::Ndjson.formatter().unwrap().format(&columns, &rows, true);
+        let lines: Vec<Value> = ndjson.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        prop_assert_eq!(&lines, objects);
+
+        let columnar: Value = serde_json::from_str(
+            &ResultFormat::JsonColumnar.formatter().unwrap().format(&columns, &rows, true),
+        ).unwrap();
+        let columnar = columnar.as_object().unwrap();
+        prop_assert_eq!(columnar.len(), columns.len());
+        let first: Vec<Value> = rows.iter().map(|row| row.values[0].clone()).collect();
+        prop_assert_eq!(&columnar[&columns[0].name], &Value::Array(first));
+    }
+
+    /// Property 42b: Markdown tables have one line per row
+    /// For any result, the table has a header, a separator and one line per
+    /// row, each with a cell per column
+    #[test]
+    fn test_markdown_table((columns, rows) in arbitrary_result()) {
+        let out = ResultFormat::Markdown.formatter().unwrap().format(&columns, &rows, false);
+        let lines: Vec<&str> = out.lines().collect();
+        prop_assert_eq!(lines.len(), rows.len() + 2);
+        for line in lines {
+            let unescaped_pipes = line.len() - line.replace('|', "").len() - line.matches("\\|").count();
+            prop_assert_eq!(unescaped_pipes, columns.len() + 1);
+        }
+    }
+
+    /// Property 42c: Resources accept a format query
+    /// For any tabular format, the data source list resource is encoded in it,
+    /// and unknown formats are rejected as invalid URIs
+    #[test]
+    fn test_resource_format(keys in prop::collection::hash_set("[a-z]{3,10}", 1..5)) {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let configs = keys.iter().map(|key| create_test_config(key)).collect();
+            let manager = Arc::new(DataSourceManager::new(configs).await.unwrap());
+            let provider = ResourceProvider::new(manager, Arc::new(RwLock::new(HashMap::new())));
+
+            let content = provider.get_resource("mysql://datasources?format=csv").await.unwrap();
+            prop_assert_eq!(content.mime_type, "text/csv");
+            prop_assert_eq!(content.uri, "mysql://datasources?format=csv");
+            let records = parse_delimited(&content.content, ',');
+            prop_assert_eq!(records.len(), keys.len() + 1);
+
+            let content = provider.get_resource("mysql://datasources?format=json").await.unwrap();
+            prop_assert_eq!(content.mime_type, "application/json");
+
+            let result = provider.get_resource("mysql://datasources?format=xml").await;
+            prop_assert!(matches!(result, Err(McpError::InvalidResourceUri(_))));
+            Ok(())
+        })?;
+    }
+}
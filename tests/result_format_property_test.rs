@@ -16,6 +16,7 @@ fn arbitrary_column_metadata() -> impl Strategy<Value = ColumnMetadata> {
             name,
             data_type,
             nullable: true,
+            ..Default::default()
         }
     })
 }
@@ -52,8 +53,11 @@ fn arbitrary_query_result() -> impl Strategy<Value = QueryResult> {
         let num_columns = columns.len();
         arbitrary_query_rows(num_columns, num_rows).prop_map(move |rows| QueryResult {
             columns: columns.clone(),
+            rows_returned: rows.len(),
             rows,
             affected_rows: 0,
+            truncated: false,
+            hint: None,
         })
     })
 }
@@ -135,6 +139,9 @@ proptest! {
             columns: columns.clone(),
             rows: vec![],
             affected_rows: 0,
+            truncated: false,
+            rows_returned: 0,
+            hint: None,
         };
 
         // Empty results should still have column metadata
@@ -0,0 +1,70 @@
+// Feature: mysql-mcp-multi-datasource, Property 39: Result limit resolution
+// Validates: Requirements 3.5, 5.5
+
+use mysql_mcp_server::config::{DataSourceConfig, Permission, ResultLimits};
+use mysql_mcp_server::manager::DataSourceManager;
+use proptest::prelude::*;
+
+// Strategy to generate a data source with optional result limit overrides
+fn datasource_config() -> impl Strategy<Value = DataSourceConfig> {
+    (
+        "[a-zA-Z0-9_-]{1,30}",
+        prop::option::of(1usize..100_000),
+        prop::option::of(1usize..10_000_000),
+    )
+        .prop_map(|(key, max_rows, max_result_bytes)| DataSourceConfig {
+            key,
+            name: "Test Database".to_string(),
+            username: "test".to_string(),
+            password: "test".to_string(),
+            permission: Permission::Query,
+            max_rows,
+            max_result_bytes,
+            ..Default::default()
+        })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(50))]
+
+    /// Property 39: Result limit resolution
+    /// For any server limits, data source overrides and per-call requests, the
+    /// effective limits are the most specific ones, capped at the server limits
+    #[test]
+    fn test_result_limits_are_most_specific_and_capped(
+        config in datasource_config(),
+        server_rows in 1usize..100_000,
+        server_bytes in 1usize..10_000_000,
+        requested_rows in prop::option::of(1usize..1_000_000),
+        requested_bytes in prop::option::of(1usize..100_000_000),
+    ) {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async {
+            let datasource_key = config.key.clone();
+            let override_rows = config.max_rows;
+            let override_bytes = config.max_result_bytes;
+            let manager = DataSourceManager::new(vec![config])
+                .await
+                .unwrap()
+                .with_result_limits(ResultLimits {
+                    max_rows: server_rows,
+                    max_result_bytes: server_bytes,
+                });
+
+            let limits = manager.result_limits(&datasource_key, requested_rows, requested_bytes);
+
+            let expected_rows = requested_rows.or(override_rows).unwrap_or(server_rows).min(server_rows);
+            let expected_bytes = requested_bytes
+                .or(override_bytes)
+                .unwrap_or(server_bytes)
+                .min(server_bytes);
+            prop_assert_eq!(limits.max_rows, expected_rows);
+            prop_assert_eq!(limits.max_result_bytes, expected_bytes);
+            prop_assert!(limits.max_rows <= server_rows);
+            prop_assert!(limits.max_result_bytes <= server_bytes);
+
+            Ok(())
+        })?;
+    }
+}
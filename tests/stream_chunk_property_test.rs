@@ -11,6 +11,7 @@ fn arbitrary_column_metadata() -> impl Strategy<Value = ColumnMetadata> {
             name,
             data_type,
             nullable,
+            ..Default::default()
         })
 }
 
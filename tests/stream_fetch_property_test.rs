@@ -0,0 +1,102 @@
+// Feature: mysql-mcp-multi-datasource, Property 38: Stream fetch lifecycle
+// Validates: Requirements 9.1, 9.3
+
+use mysql_mcp_server::config::StreamConfig;
+use mysql_mcp_server::error::McpError;
+use mysql_mcp_server::tools::{ColumnMetadata, QueryResultStream, QueryRow, StreamManager};
+use proptest::prelude::*;
+
+fn columns() -> Vec<ColumnMetadata> {
+    vec![ColumnMetadata {
+        name: "id".to_string(),
+        data_type: "BIGINT".to_string(),
+        nullable: false,
+        ..Default::default()
+    }]
+}
+
+fn rows(count: usize) -> Vec<QueryRow> {
+    (0..count)
+        .map(|i| QueryRow {
+            values: vec![serde_json::json!(i)],
+        })
+        .collect()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(100))]
+
+    /// Property 38: Stream fetch lifecycle
+    /// For any result set and chunk size, fetching a registered stream returns every
+    /// row exactly once and in order, marks only the final chunk as last, and
+    /// removes the stream once that chunk has been returned
+    #[test]
+    fn test_fetch_chunk_returns_every_row_then_ends(
+        row_count in 0usize..500,
+        chunk_size in 1usize..100,
+    ) {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async {
+            let manager = StreamManager::new();
+            let stream = QueryResultStream::new(columns(), rows(row_count), chunk_size);
+            let stream_id = manager.register_stream(stream).await;
+
+            let mut values = Vec::new();
+            let mut chunks = 0;
+            loop {
+                let chunk = manager.fetch_chunk(&stream_id).await.unwrap();
+                prop_assert_eq!(&chunk.stream_id, &stream_id);
+                prop_assert_eq!(chunk.chunk.chunk_number, chunks);
+                prop_assert!(chunk.chunk.rows.len() <= chunk_size);
+                values.extend(chunk.chunk.rows.into_iter().map(|row| row.values[0].clone()));
+                chunks += 1;
+
+                if chunk.chunk.is_last {
+                    prop_assert_eq!(chunk.chunk.total_rows, row_count);
+                    break;
+                }
+                prop_assert_eq!(manager.active_stream_count().await, 1);
+            }
+
+            let expected: Vec<serde_json::Value> =
+                (0..row_count).map(|i| serde_json::json!(i)).collect();
+            prop_assert_eq!(values, expected);
+            prop_assert_eq!(chunks, row_count.div_ceil(chunk_size).max(1));
+
+            prop_assert_eq!(manager.active_stream_count().await, 0);
+            let result = manager.fetch_chunk(&stream_id).await;
+            prop_assert!(matches!(result, Err(McpError::InvalidStatement(_))), "got: {:?}", result);
+
+            Ok(())
+        })?;
+    }
+
+    /// Property 38a: Idle streams expire
+    /// For any number of streams left unread past the idle timeout, closing idle
+    /// streams removes every one of them
+    #[test]
+    fn test_idle_streams_are_closed(stream_count in 1usize..10) {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async {
+            let manager = StreamManager::with_config(StreamConfig {
+                idle_timeout_secs: 0,
+                ..Default::default()
+            });
+            let mut streams = Vec::new();
+            for _ in 0..stream_count {
+                let stream_id = manager.register_stream(QueryResultStream::new(columns(), rows(10), 3)).await;
+                streams.push(manager.get_stream(&stream_id).await.unwrap());
+            }
+
+            prop_assert_eq!(manager.close_idle().await, stream_count);
+            prop_assert_eq!(manager.active_stream_count().await, 0);
+            for stream in streams {
+                prop_assert!(stream.is_cancelled().await);
+            }
+
+            Ok(())
+        })?;
+    }
+}
@@ -0,0 +1,127 @@
+// Feature: mysql-mcp-multi-datasource, Property 35: Transaction statement validation
+// Validates: Requirements 11.1, 11.2, 11.3
+
+use mysql_mcp_server::config::{DataSourceConfig, Permission};
+use mysql_mcp_server::error::McpError;
+use mysql_mcp_server::manager::DataSourceManager;
+use mysql_mcp_server::params::StatementParams;
+use mysql_mcp_server::tools::{TransactionStatement, TransactionTool};
+use proptest::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+// Strategy to generate a data source with the given permission
+fn datasource_config(permission: Permission) -> impl Strategy<Value = DataSourceConfig> {
+    "[a-zA-Z0-9_-]{1,30}".prop_map(move |key| DataSourceConfig {
+        key,
+        name: "Test Database".to_string(),
+        username: "test".to_string(),
+        password: "test".to_string(),
+        permission,
+        ..Default::default()
+    })
+}
+
+// Strategy to generate statements that are valid inside a transaction
+fn valid_statement() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[a-z]{1,10}".prop_map(|name| format!("INSERT INTO users (name) VALUES ('{}')", name)),
+        (1u32..1000).prop_map(|id| format!("UPDATE users SET active = 0 WHERE id = {}", id)),
+        (1u32..1000).prop_map(|id| format!("DELETE FROM sessions WHERE user_id = {}", id)),
+    ]
+}
+
+// Strategy to generate statements that must never run inside a transaction
+fn rejected_statement() -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just("CREATE TABLE audit (id INT)".to_string()),
+        Just("DROP TABLE users".to_string()),
+        Just("TRUNCATE TABLE sessions".to_string()),
+        Just("COMMIT".to_string()),
+        Just("ROLLBACK".to_string()),
+        Just("START TRANSACTION".to_string()),
+        Just("SET autocommit = 1".to_string()),
+        Just("LOCK TABLES users WRITE".to_string()),
+        Just("CALL archive_users()".to_string()),
+        Just("DELETE FROM a; DROP TABLE b".to_string()),
+    ]
+}
+
+fn to_transaction_statements(statements: &[String]) -> Vec<TransactionStatement> {
+    statements
+        .iter()
+        .map(|statement| TransactionStatement {
+            statement: statement.clone(),
+            params: StatementParams::default(),
+        })
+        .collect()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(20))]
+
+    /// Property 35: Transaction statement validation
+    /// For any list of statements containing one that cannot run inside a transaction,
+    /// the tool should reject the whole list, name the offending statement and never connect
+    #[test]
+    fn test_invalid_statement_rejects_whole_transaction(
+        config in datasource_config(Permission::Ddl),
+        mut statements in prop::collection::vec(valid_statement(), 0..6),
+        rejected in rejected_statement(),
+        position in any::<prop::sample::Index>(),
+    ) {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async {
+            let index = position.index(statements.len() + 1);
+            statements.insert(index, rejected.clone());
+
+            let datasource_key = config.key.clone();
+            let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
+            let pool_managers = Arc::new(RwLock::new(HashMap::new()));
+            let tool = TransactionTool::new(manager, pool_managers.clone());
+
+            let result = tool
+                .execute(&datasource_key, "testdb", &to_transaction_statements(&statements), None)
+                .await;
+
+            let expected_prefix = format!("statements[{}]:", index);
+            prop_assert!(
+                matches!(&result, Err(McpError::InvalidStatement(msg)) if msg.starts_with(&expected_prefix)),
+                "'{}' at {} should be rejected, got: {:?}",
+                rejected, index, result
+            );
+            prop_assert!(pool_managers.read().await.is_empty());
+
+            Ok(())
+        })?;
+    }
+
+    /// Property 35a: Transactions need update permission
+    /// For any list of DML statements on a read-only data source, the tool should
+    /// reject the transaction with PermissionDenied
+    #[test]
+    fn test_read_only_datasource_rejects_transactions(
+        config in datasource_config(Permission::Query),
+        statements in prop::collection::vec(valid_statement(), 1..6),
+    ) {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async {
+            let datasource_key = config.key.clone();
+            let manager = Arc::new(DataSourceManager::new(vec![config]).await.unwrap());
+            let pool_managers = Arc::new(RwLock::new(HashMap::new()));
+            let tool = TransactionTool::new(manager, pool_managers.clone());
+
+            let result = tool
+                .execute(&datasource_key, "testdb", &to_transaction_statements(&statements), None)
+                .await;
+
+            prop_assert!(matches!(result, Err(McpError::PermissionDenied(_))), "got: {:?}", result);
+            prop_assert!(pool_managers.read().await.is_empty());
+
+            Ok(())
+        })?;
+    }
+}
@@ -0,0 +1,236 @@
+// Feature: mysql-mcp-multi-datasource, Property 40: Lossless type mapping
+// Validates: Requirements 2.2, 2.4
+
+use base64::Engine;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+use mysql_mcp_server::params::SqlParam;
+use mysql_mcp_server::types::{ColumnKind, MySqlValue, TypeMapping};
+use proptest::prelude::*;
+use serde_json::Value;
+
+// Strategy to generate JSON documents as stored in JSON columns
+fn arbitrary_json() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::from),
+        "[a-zA-Z0-9 _\"\\\\-]{0,20}".prop_map(Value::String),
+    ];
+    leaf.prop_recursive(3, 32, 5, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..5).prop_map(Value::Array),
+            prop::collection::btree_map("[a-z]{1,8}", inner, 0..5)
+                .prop_map(|map| Value::Object(map.into_iter().collect())),
+        ]
+    })
+}
+
+// Strategy to generate DATETIME / TIMESTAMP values with microsecond precision
+fn arbitrary_datetime() -> impl Strategy<Value = NaiveDateTime> {
+    (0i64..253_402_300_799, 0u32..1_000_000).prop_map(|(seconds, micros)| {
+        DateTime::from_timestamp(seconds, micros * 1000)
+            .unwrap()
+            .naive_utc()
+    })
+}
+
+// Strategy to generate UTC offsets between -14:00 and +14:00
+fn arbitrary_offset() -> impl Strategy<Value = FixedOffset> {
+    (-14 * 4..=14 * 4).prop_map(|quarters: i32| FixedOffset::east_opt(quarters * 15 * 60).unwrap())
+}
+
+fn mapping() -> TypeMapping {
+    TypeMapping::default()
+}
+
+/// Parse a `[-]HH:MM:SS[.ffffff]` TIME value back to an interval
+fn parse_time(text: &str) -> TimeDelta {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let (clock, fraction) = text.split_once('.').unwrap_or((text, ""));
+    let parts: Vec<i64> = clock.split(':').map(|part| part.parse().unwrap()).collect();
+    let micros = if fraction.is_empty() {
+        0
+    } else {
+        format!("{:0<6}", fraction).parse().unwrap()
+    };
+    let value =
+        TimeDelta::microseconds(((parts[0] * 60 + parts[1]) * 60 + parts[2]) * 1_000_000 + micros);
+    if negative {
+        -value
+    } else {
+        value
+    }
+}
+
+#[test]
+fn test_every_column_type_has_a_mapping() {
+    let cases = [
+        ("BOOLEAN", ColumnKind::Boolean),
+        ("TINYINT", ColumnKind::Int),
+        ("SMALLINT", ColumnKind::Int),
+        ("MEDIUMINT", ColumnKind::Int),
+        ("INT", ColumnKind::Int),
+        ("BIGINT", ColumnKind::Int),
+        ("TINYINT UNSIGNED", ColumnKind::UnsignedInt),
+        ("SMALLINT UNSIGNED", ColumnKind::UnsignedInt),
+        ("MEDIUMINT UNSIGNED", ColumnKind::UnsignedInt),
+        ("INT UNSIGNED", ColumnKind::UnsignedInt),
+        ("BIGINT UNSIGNED", ColumnKind::UnsignedInt),
+        ("FLOAT", ColumnKind::Float),
+        ("DOUBLE", ColumnKind::Double),
+        ("DECIMAL", ColumnKind::Decimal),
+        ("DATE", ColumnKind::Date),
+        ("DATETIME", ColumnKind::DateTime),
+        ("TIMESTAMP", ColumnKind::Timestamp),
+        ("TIME", ColumnKind::Time),
+        ("YEAR", ColumnKind::Year),
+        ("BIT", ColumnKind::Bit),
+        ("ENUM", ColumnKind::Enum),
+        ("SET", ColumnKind::Set),
+        ("JSON", ColumnKind::Json),
+        ("CHAR", ColumnKind::Text),
+        ("VARCHAR", ColumnKind::Text),
+        ("TINYTEXT", ColumnKind::Text),
+        ("TEXT", ColumnKind::Text),
+        ("MEDIUMTEXT", ColumnKind::Text),
+        ("LONGTEXT", ColumnKind::Text),
+        ("BINARY", ColumnKind::BinaryString),
+        ("VARBINARY", ColumnKind::BinaryString),
+        ("TINYBLOB", ColumnKind::BinaryString),
+        ("BLOB", ColumnKind::BinaryString),
+        ("MEDIUMBLOB", ColumnKind::BinaryString),
+        ("LONGBLOB", ColumnKind::BinaryString),
+        ("GEOMETRY", ColumnKind::Geometry),
+        ("NULL", ColumnKind::Null),
+    ];
+
+    for (type_name, kind) in cases {
+        assert_eq!(ColumnKind::from_type_name(type_name), kind, "{}", type_name);
+        assert_eq!(
+            ColumnKind::from_type_name(&type_name.to_lowercase()),
+            kind,
+            "{}",
+            type_name
+        );
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(100))]
+
+    /// Property 40: Lossless type mapping
+    /// For any signed or unsigned integer, the JSON number holds exactly the stored value
+    #[test]
+    fn test_integers_are_exact(signed in any::<i64>(), unsigned in any::<u64>(), year in 1901u16..=2155, bits in any::<u64>()) {
+        prop_assert_eq!(MySqlValue::Int(signed).to_json(&mapping()).as_i64(), Some(signed));
+        prop_assert_eq!(MySqlValue::UInt(unsigned).to_json(&mapping()).as_u64(), Some(unsigned));
+        prop_assert_eq!(MySqlValue::Year(year).to_json(&mapping()).as_u64(), Some(year as u64));
+        prop_assert_eq!(MySqlValue::Bit(bits).to_json(&mapping()).as_u64(), Some(bits));
+        prop_assert_eq!(MySqlValue::Bool(signed % 2 == 0).to_json(&mapping()), Value::Bool(signed % 2 == 0));
+    }
+
+    /// Property 40a: Floating point values keep their precision
+    /// For any FLOAT or DOUBLE, parsing the JSON number yields the stored value
+    #[test]
+    fn test_floats_round_trip(single in any::<f32>(), double in any::<f64>()) {
+        prop_assume!(single.is_finite() && double.is_finite());
+
+        let json = serde_json::to_string(&MySqlValue::Float(single).to_json(&mapping())).unwrap();
+        prop_assert_eq!(json.parse::<f32>().unwrap(), single);
+        let json = serde_json::to_string(&MySqlValue::Double(double).to_json(&mapping())).unwrap();
+        prop_assert_eq!(json.parse::<f64>().unwrap(), double);
+    }
+
+    /// Property 40b: Decimals are exact strings
+    /// For any DECIMAL, the JSON value is MySQL's textual form, digit for digit
+    #[test]
+    fn test_decimals_are_exact_strings(decimal in "-?[0-9]{1,40}(\\.[0-9]{1,30})?") {
+        let json = MySqlValue::Decimal(decimal.clone()).to_json(&mapping());
+        prop_assert_eq!(json, Value::String(decimal.clone()));
+        prop_assert_eq!(
+            SqlParam::from_json(&serde_json::json!({"type": "decimal", "value": decimal.clone()})),
+            Ok(SqlParam::Decimal(decimal))
+        );
+    }
+
+    /// Property 40c: Temporal values round trip
+    /// For any DATE, DATETIME, TIMESTAMP (at any offset) or TIME, the JSON string
+    /// parses back to the stored value
+    #[test]
+    fn test_temporal_values_round_trip(
+        datetime in arbitrary_datetime(),
+        offset in arbitrary_offset(),
+        time_micros in -3_020_399_999_999i64..=3_020_399_999_999,
+    ) {
+        let date = datetime.date();
+        let json = MySqlValue::Date(date).to_json(&mapping());
+        prop_assert_eq!(NaiveDate::parse_from_str(json.as_str().unwrap(), "%Y-%m-%d").unwrap(), date);
+
+        let json = MySqlValue::DateTime(datetime).to_json(&mapping());
+        prop_assert_eq!(
+            NaiveDateTime::parse_from_str(json.as_str().unwrap(), "%Y-%m-%d %H:%M:%S%.f").unwrap(),
+            datetime
+        );
+
+        let timestamp = datetime.and_utc();
+        let json = MySqlValue::Timestamp(timestamp).to_json(&TypeMapping::new(offset));
+        let parsed = DateTime::parse_from_rfc3339(json.as_str().unwrap()).unwrap();
+        prop_assert_eq!(parsed.offset(), &offset);
+        prop_assert_eq!(parsed.with_timezone(&Utc), timestamp);
+
+        let time = TimeDelta::microseconds(time_micros);
+        let json = MySqlValue::Time(time).to_json(&mapping());
+        prop_assert_eq!(parse_time(json.as_str().unwrap()), time);
+    }
+
+    /// Property 40d: Strings, ENUM and SET members are preserved
+    /// For any text, ENUM value or SET of members, the JSON holds the same strings
+    #[test]
+    fn test_strings_enums_and_sets(
+        text in any::<String>(),
+        members in prop::collection::btree_set("[a-z_ ]{1,10}", 0..8),
+    ) {
+        prop_assert_eq!(MySqlValue::Text(text.clone()).to_json(&mapping()), Value::String(text.clone()));
+        prop_assert_eq!(MySqlValue::Enum(text.clone()).to_json(&mapping()), Value::String(text));
+
+        let members: Vec<String> = members.into_iter().collect();
+        let json = MySqlValue::Set(members.join(",")).to_json(&mapping());
+        let expected: Vec<Value> = members.into_iter().map(Value::String).collect();
+        prop_assert_eq!(json, Value::Array(expected));
+    }
+
+    /// Property 40e: JSON columns are embedded
+    /// For any JSON document, the value is the parsed document rather than its text
+    #[test]
+    fn test_json_documents_are_embedded(document in arbitrary_json()) {
+        let text = serde_json::to_string(&document).unwrap();
+        prop_assert_eq!(MySqlValue::Json(text).to_json(&mapping()), document);
+    }
+
+    /// Property 40f: Binary values round trip
+    /// For any bytes in a binary string column, the JSON is the same text when it is
+    /// valid UTF-8 and otherwise a tagged base64 value that binds back to the bytes
+    #[test]
+    fn test_binary_values_round_trip(bytes in prop::collection::vec(any::<u8>(), 0..64)) {
+        let json = MySqlValue::from_binary_string(bytes.clone()).to_json(&mapping());
+        match std::str::from_utf8(&bytes) {
+            Ok(text) => prop_assert_eq!(json, Value::String(text.to_string())),
+            Err(_) => {
+                prop_assert_eq!(&json["type"], "binary");
+                let decoded = base64::engine::general_purpose::STANDARD
+                    .decode(json["value"].as_str().unwrap())
+                    .unwrap();
+                prop_assert_eq!(&decoded, &bytes);
+                prop_assert_eq!(SqlParam::from_json(&json), Ok(SqlParam::Binary(bytes.clone())));
+            }
+        }
+
+        // Geometry values are always tagged
+        let json = MySqlValue::Binary(bytes.clone()).to_json(&mapping());
+        prop_assert_eq!(&json["type"], "binary");
+        prop_assert_eq!(SqlParam::from_json(&json), Ok(SqlParam::Binary(bytes)));
+    }
+}